@@ -0,0 +1,108 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use daifugo::card::{create_deck, Card, Rank, Suit};
+use daifugo::comb::Comb;
+use daifugo::field::Field;
+use daifugo::moves::{get_all_combs, get_legal_combs};
+use daifugo::observer::Observers;
+use daifugo::simulate::play_game;
+use daifugo::strategy::Strategy;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::hint::black_box;
+
+// 固定のシードで配った13枚ずつの手札
+fn create_hands() -> Vec<Vec<Card>> {
+    let mut deck = create_deck();
+    deck.shuffle(&mut ChaCha8Rng::seed_from_u64(0));
+    deck.chunks(13).map(|hands| hands.to_vec()).collect()
+}
+
+fn bench_moves(c: &mut Criterion) {
+    let hands = create_hands();
+    c.bench_function("get_all_combs", |b| {
+        b.iter(|| {
+            for cards in &hands {
+                black_box(get_all_combs(black_box(cards)));
+            }
+        })
+    });
+    let mut field = Field::new(4, 0);
+    field
+        .put(Some(&Comb::Single(Card::Normal(Suit::Club, Rank::Six))), 13)
+        .unwrap();
+    c.bench_function("get_legal_combs", |b| {
+        b.iter(|| {
+            for cards in &hands {
+                black_box(get_legal_combs(black_box(cards), &field));
+            }
+        })
+    });
+}
+
+fn bench_comb(c: &mut Criterion) {
+    let cards_list = [
+        vec![Card::Normal(Suit::Heart, Rank::Nine)],
+        vec![
+            Card::Normal(Suit::Club, Rank::Five),
+            Card::Normal(Suit::Spade, Rank::Five),
+            Card::Joker,
+        ],
+        vec![
+            Card::Normal(Suit::Diamond, Rank::Ten),
+            Card::Joker,
+            Card::Normal(Suit::Diamond, Rank::Queen),
+            Card::Normal(Suit::Diamond, Rank::King),
+        ],
+        vec![
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Heart, Rank::Four),
+        ],
+    ];
+    c.bench_function("Comb::try_from", |b| {
+        b.iter_batched(
+            || cards_list.clone(),
+            |cards_list| {
+                for cards in cards_list {
+                    black_box(Comb::try_from(cards).ok());
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_field(c: &mut Criterion) {
+    let combs = [
+        Comb::Single(Card::Normal(Suit::Club, Rank::Four)),
+        Comb::Single(Card::Normal(Suit::Club, Rank::Seven)),
+        Comb::Single(Card::Normal(Suit::Heart, Rank::Jack)),
+        Comb::Single(Card::Joker),
+    ];
+    c.bench_function("Field::put", |b| {
+        b.iter_batched_ref(
+            || Field::new(4, 0),
+            |field| {
+                for comb in &combs {
+                    black_box(field.put(Some(comb), 10).ok());
+                }
+                black_box(field.put(None, 10).ok());
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_game(c: &mut Criterion) {
+    let seats = [Strategy::Min; 4];
+    let mut seed = 0;
+    c.bench_function("play_game", |b| {
+        b.iter(|| {
+            seed += 1;
+            black_box(play_game(&seats, 0, seed, &mut Observers(vec![])))
+        })
+    });
+}
+
+criterion_group!(benches, bench_moves, bench_comb, bench_field, bench_game);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+// ffiフィーチャーではC言語のヘッダーを生成する
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        use cbindgen::{Builder, Config, Language};
+        use std::path::Path;
+
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-env-changed=DAIFUGO_HEADER");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let config = Config {
+            language: Language::C,
+            include_guard: Some("DAIFUGO_H".to_owned()),
+            ..Default::default()
+        };
+        // ffi.rsだけを読み、extern "C"の関数とそれが使う型だけを書き出す
+        let bindings = Builder::new()
+            .with_config(config)
+            .with_src(Path::new(&crate_dir).join("src/ffi.rs"))
+            .generate()
+            .expect("ヘッダーを生成できません");
+        bindings.write_to_file(Path::new(&out_dir).join("daifugo.h"));
+        // ソースツリーのヘッダーは出力先を指定したときだけ書き換える
+        if let Ok(path) = std::env::var("DAIFUGO_HEADER") {
+            bindings.write_to_file(Path::new(&crate_dir).join(path));
+        }
+    }
+}
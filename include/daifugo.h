@@ -0,0 +1,66 @@
+#ifndef DAIFUGO_H
+#define DAIFUGO_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct DaifugoGame DaifugoGame;
+
+/**
+ * seedで配り方と席を決め、npc(min、heuristic、mcts)と対戦するゲームを作る。
+ * npcが不明ならNULLを返す。
+ *
+ * # Safety
+ * npcはNUL終端の文字列でなければならない。
+ */
+struct DaifugoGame *daifugo_new_game(uint64_t seed, const char *npc);
+
+/**
+ * # Safety
+ * gameはdaifugo_new_gameで作り、まだ解放していないものでなければならない。
+ */
+void daifugo_free_game(struct DaifugoGame *game);
+
+/**
+ * 人間の席から見える状態をJSONで返す(daifugo_free_stringで解放する)。
+ *
+ * # Safety
+ * gameはdaifugo_new_gameで作ったものでなければならない。
+ */
+char *daifugo_state_json(struct DaifugoGame *game);
+
+/**
+ * 人間の手番なら出せる手の一覧をJSONで返す(nullはパス、daifugo_free_stringで解放する)。
+ *
+ * # Safety
+ * gameはdaifugo_new_gameで作ったものでなければならない。
+ */
+char *daifugo_legal_moves(struct DaifugoGame *game);
+
+/**
+ * JSONの手(nullならパス)を出し、次の人間の手番までNPCの手番を進める。
+ * 受け付ければNULLを、受け付けなければ理由を返す(daifugo_free_stringで解放する)。
+ *
+ * # Safety
+ * gameはdaifugo_new_gameで作ったもの、comb_jsonはNUL終端の文字列でなければならない。
+ */
+char *daifugo_play(struct DaifugoGame *game,
+                   const char *comb_json);
+
+/**
+ * ラウンドが終わっていれば次のラウンドを始めてtrueを返す。
+ *
+ * # Safety
+ * gameはdaifugo_new_gameで作ったものでなければならない。
+ */
+bool daifugo_next_round(struct DaifugoGame *game);
+
+/**
+ * # Safety
+ * sはこのライブラリが返した文字列で、まだ解放していないものでなければならない。
+ */
+void daifugo_free_string(char *s);
+
+#endif  /* DAIFUGO_H */
@@ -0,0 +1,192 @@
+use crate::card::{cmp_order, Card};
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::field::Field;
+use crate::i18n::{tr, trf};
+use crate::kifu::KifuRound;
+use crate::mcts::{get_candidates, MctsNpc};
+use crate::observer::Observer;
+use crate::player::Player;
+use crate::render::Style;
+use crate::replay::get_snapshots;
+use crate::rules::RuleSet;
+
+// 1つの候補あたりのプレイアウトの回数
+const ANALYSIS_PLAYOUTS: usize = 100;
+// 最善の手との評価の差がこれ以上なら悪手とする
+const BLUNDER_THRESHOLD: f64 = 0.15;
+
+// 最善の手より明らかに評価が低かった手
+#[derive(Debug, PartialEq)]
+pub struct Blunder {
+    // ラウンドの何手目か(1から数える)
+    pub turn: usize,
+    pub idx: usize,
+    pub played: Option<Comb>,
+    pub best: Option<Comb>,
+    pub played_value: f64,
+    pub best_value: f64,
+}
+
+impl Blunder {
+    pub fn get_line(&self, style: &Style) -> String {
+        let comb = |comb: &Option<Comb>| match comb {
+            Some(comb) => style.comb(comb),
+            None => tr("pass").to_owned(),
+        };
+        trf(
+            "blunder",
+            &[
+                ("turn", &self.turn),
+                ("played", &comb(&self.played)),
+                ("best", &comb(&self.best)),
+                ("played_value", &format!("{:.0}", self.played_value * 100.0)),
+                ("best_value", &format!("{:.0}", self.best_value * 100.0)),
+            ],
+        )
+    }
+}
+
+// 指定した席のプレイヤーの手をMCTSで評価し直して悪手を探す
+pub fn find_blunders(
+    round: &KifuRound,
+    seats: &[usize],
+    seed: u64,
+) -> Result<Vec<Blunder>, String> {
+    let snapshots = get_snapshots(round)?;
+    let mut evaluator = MctsNpc::new(String::new(), seed);
+    let mut blunders = vec![];
+    for (i, (idx, played)) in round.moves.iter().enumerate() {
+        if !seats.contains(idx) {
+            continue;
+        }
+        let snapshot = &snapshots[i];
+        let hands = &snapshot.hands[*idx];
+        let candidates = get_candidates(hands, &Field::from_view(&snapshot.view));
+        if candidates.len() < 2 {
+            continue;
+        }
+        evaluator.init(hands.clone());
+        let values = evaluator.evaluate(&snapshot.view, &candidates, ANALYSIS_PLAYOUTS);
+        let played_value = candidates
+            .iter()
+            .position(|candidate| {
+                let comb = candidate.as_ref().map(|(comb, _)| comb);
+                get_sorted_cards(comb) == get_sorted_cards(played.as_ref())
+            })
+            .map_or(0.0, |pos| values[pos]);
+        let Some(best) = (0..values.len()).max_by(|a, b| values[*a].total_cmp(&values[*b])) else {
+            continue;
+        };
+        if values[best] - played_value >= BLUNDER_THRESHOLD {
+            blunders.push(Blunder {
+                turn: i + 1,
+                idx: *idx,
+                played: played.clone(),
+                best: candidates[best].as_ref().map(|(comb, _)| comb.clone()),
+                played_value,
+                best_value: values[best],
+            });
+        }
+    }
+    Ok(blunders)
+}
+
+// 並び順に関係なく比べるためにカードを並べ替える
+fn get_sorted_cards(comb: Option<&Comb>) -> Vec<Card> {
+    let mut cards = match comb {
+        Some(Comb::Single(card)) => vec![*card],
+        Some(Comb::Multi(cards)) | Some(Comb::Seq(cards)) => cards.clone(),
+        None => vec![],
+    };
+    cards.sort_by(cmp_order);
+    cards
+}
+
+// ラウンドが終わる度に人間のプレイヤーの手を分析して表示する
+pub struct Analyzer {
+    seats: Vec<usize>,
+    style: Style,
+    round: KifuRound,
+}
+
+impl Analyzer {
+    pub fn new(seats: Vec<usize>, style: Style) -> Self {
+        Self {
+            seats,
+            style,
+            round: KifuRound::default(),
+        }
+    }
+
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.round.rules = rules;
+        self
+    }
+}
+
+impl Observer for Analyzer {
+    fn notify(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Start { names, hands } => {
+                // 前のラウンドの1位が大富豪になる
+                self.round.capital = self.round.player_rank.first().copied();
+                self.round.names = names.clone();
+                self.round.deals = hands.clone();
+                self.round.moves.clear();
+            }
+            GameEvent::Turn { idx, comb, .. } => self.round.moves.push((*idx, comb.clone())),
+            GameEvent::End { player_rank } => {
+                self.round.player_rank = player_rank.clone();
+                println!("{}", self.style.bold(tr("analysis")));
+                match find_blunders(&self.round, &self.seats, rand::random()) {
+                    Ok(blunders) if blunders.is_empty() => println!("{}", tr("no_blunders")),
+                    Ok(blunders) => {
+                        for blunder in blunders {
+                            let name = &self.round.names[blunder.idx];
+                            println!("{name} {}", blunder.get_line(&self.style));
+                        }
+                    }
+                    // 途中から再開したラウンドは最初の状態がわからない
+                    Err(_) => println!("{}", tr("cannot_analyze")),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+
+    #[test]
+    fn test_find_blunders() {
+        let c = |suit, rank| Card::Normal(suit, rank);
+        // P0がジョーカーを出さずにパスすると、P1に出し切られてしまう
+        let round = KifuRound {
+            names: vec!["P0".to_owned(), "P1".to_owned()],
+            deals: vec![
+                vec![c(Suit::Club, Rank::Four), Card::Joker],
+                vec![c(Suit::Club, Rank::Three), c(Suit::Heart, Rank::Five)],
+            ],
+            moves: vec![
+                (1, Some(Comb::Single(c(Suit::Club, Rank::Three)))),
+                (0, None),
+                (1, Some(Comb::Single(c(Suit::Heart, Rank::Five)))),
+            ],
+            player_rank: vec![1, 0],
+            ..Default::default()
+        };
+        let blunders = find_blunders(&round, &[0], 0).unwrap();
+        assert_eq!(blunders.len(), 1);
+        assert_eq!(blunders[0].turn, 2);
+        assert_eq!(blunders[0].played, None);
+        assert!(blunders[0].best.is_some());
+        // P1の手は分析しない
+        assert!(find_blunders(&round, &[], 0).unwrap().is_empty());
+        let line = blunders[0].get_line(&Style::new(false, true));
+        assert!(line.starts_with("2手目: パス → "));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::record::GameRecord;
+use crate::simulate::play_game;
+use crate::strategy::Strategy;
+use itertools::Itertools;
+use std::path::PathBuf;
+
+// 95%信頼区間のz値
+const Z_95: f64 = 1.96;
+
+pub struct ArenaOptions {
+    pub games: usize,
+    pub seed: u64,
+    pub duplicate: bool,
+    // ゲーム毎の結果を書き出すCSVファイル
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Matchup {
+    pub first: Strategy,
+    pub second: Strategy,
+    // firstの戦略が1位になったゲーム数
+    pub wins: usize,
+    pub games: usize,
+    pub records: Vec<GameRecord>,
+}
+
+impl Matchup {
+    pub fn get_win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+
+    pub fn get_line(&self) -> String {
+        let (low, high) = get_wilson_interval(self.wins, self.games, Z_95);
+        format!(
+            "{:<22} {:>8.1}%  [{:>5.1}%, {:>5.1}%]",
+            format!("{} vs {}", self.first.get_name(), self.second.get_name()),
+            self.get_win_rate() * 100.0,
+            low * 100.0,
+            high * 100.0
+        )
+    }
+}
+
+// 全ての戦略の組み合わせで2人ずつ交互に座って対戦する
+pub fn run_arena(options: &ArenaOptions) -> Vec<Matchup> {
+    Strategy::ALL
+        .into_iter()
+        .tuple_combinations()
+        .map(|(first, second)| {
+            let lineup = [first, second, first, second];
+            let records: Vec<GameRecord> = get_boards(options)
+                .into_iter()
+                .map(|(seed, rotation)| {
+                    let mut seats = lineup.to_vec();
+                    seats.rotate_left(rotation);
+                    let mut record = GameRecord::new(seed, seats.clone());
+                    play_game(&seats, 0, seed, &mut record);
+                    record
+                })
+                .collect();
+            let wins = records
+                .iter()
+                .filter(|record| record.seats[record.player_rank[0]] == first)
+                .count();
+            Matchup {
+                first,
+                second,
+                wins,
+                games: records.len(),
+                records,
+            }
+        })
+        .collect()
+}
+
+// 対戦するゲームのシードと席順のずらし幅を取得する
+fn get_boards(options: &ArenaOptions) -> Vec<(u64, usize)> {
+    match options.duplicate {
+        // 同じ配り方で席を入れ替えて2回ずつ対戦する
+        true => (0..options.games)
+            .flat_map(|deal| {
+                [0, 1].map(|rotation| (options.seed.wrapping_add(deal as u64), rotation))
+            })
+            .collect(),
+        // ゲーム毎に配り直し、席順をずらす
+        false => (0..options.games)
+            .map(|game| (options.seed.wrapping_add(game as u64), game % 4))
+            .collect(),
+    }
+}
+
+// 勝率のWilsonスコア信頼区間を取得する
+pub fn get_wilson_interval(wins: usize, games: usize, z: f64) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let n = games as f64;
+    let p = wins as f64 / n;
+    let denominator = 1.0 + z * z / n;
+    let center = (p + z * z / (2.0 * n)) / denominator;
+    let margin = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_wilson_interval() {
+        for (wins, games, expected) in [
+            (0, 0, (0.0, 1.0)),
+            (50, 100, (0.4038, 0.5962)),
+            (0, 10, (0.0, 0.2775)),
+            (10, 10, (0.7225, 1.0)),
+        ] {
+            let (low, high) = get_wilson_interval(wins, games, Z_95);
+            assert!((low - expected.0).abs() < 1e-4);
+            assert!((high - expected.1).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_get_boards() {
+        for (duplicate, expected) in [
+            (false, vec![(5, 0), (6, 1), (7, 2)]),
+            (true, vec![(5, 0), (5, 1), (6, 0), (6, 1), (7, 0), (7, 1)]),
+        ] {
+            let options = ArenaOptions {
+                games: 3,
+                seed: 5,
+                duplicate,
+                output: None,
+            };
+            assert_eq!(get_boards(&options), expected);
+        }
+    }
+
+    #[test]
+    fn test_run_arena() {
+        let options = ArenaOptions {
+            games: 2,
+            seed: 0,
+            duplicate: true,
+            output: None,
+        };
+        let matchups = run_arena(&options);
+        assert_eq!(matchups.len(), 3);
+        assert!(matchups
+            .iter()
+            .all(|m| m.games == 4 && m.wins <= 4 && m.records.len() == 4));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::i18n::trf;
+use crate::observer::Observers;
+use crate::simulate::play_game;
+use crate::strategy::Strategy;
+use std::time::{Duration, Instant};
+
+pub struct BenchOptions {
+    pub games: usize,
+    pub seats: Vec<Strategy>,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub games: usize,
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    pub fn get_games_per_sec(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => self.games as f64 / secs,
+            _ => 0.0,
+        }
+    }
+}
+
+impl From<&BenchResult> for String {
+    fn from(result: &BenchResult) -> Self {
+        trf(
+            "bench_result",
+            &[
+                ("games", &result.games),
+                ("secs", &format!("{:.3}", result.elapsed.as_secs_f64())),
+                ("rate", &format!("{:.1}", result.get_games_per_sec())),
+            ],
+        )
+    }
+}
+
+// 結果を記録せずにゲームだけを繰り返し、かかった時間を計る
+pub fn run_bench(options: &BenchOptions) -> BenchResult {
+    let start = Instant::now();
+    for game in 0..options.games {
+        let seed = options.seed.wrapping_add(game as u64);
+        play_game(
+            &options.seats,
+            game % options.seats.len(),
+            seed,
+            &mut Observers(vec![]),
+        );
+    }
+    BenchResult {
+        games: options.games,
+        elapsed: start.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_run_bench() {
+        let options = BenchOptions {
+            games: 3,
+            seats: vec![Strategy::Min; 4],
+            seed: 0,
+        };
+        let result = run_bench(&options);
+        assert_eq!(result.games, 3);
+        assert!(result.get_games_per_sec() > 0.0);
+        for (games, millis, expected) in [(100, 500, 200.0), (10, 0, 0.0)] {
+            let result = BenchResult {
+                games,
+                elapsed: Duration::from_millis(millis),
+            };
+            assert_eq!(result.get_games_per_sec(), expected);
+        }
+        let result = BenchResult {
+            games: 100,
+            elapsed: Duration::from_millis(500),
+        };
+        assert_eq!(
+            String::from(&result),
+            "100ゲーム / 0.500秒 = 200.0ゲーム/秒"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::card::{Card, Rank, Suit, RANKS, SUITS};
+
+// 1スートあたりのビット数(3から2まで)
+const RANKS_LEN: u32 = 13;
+// スート毎の13ビットの上に置くジョーカーのビット
+const JOKER_SHIFT: u32 = RANKS_LEN * 4;
+const JOKER_BITS: u64 = 0b11 << JOKER_SHIFT;
+
+// 手札の各カードを1ビットで表す(スート順に3から2まで並べ、最後の2ビットをジョーカーとする)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct HandMask(u64);
+
+fn get_card_bit(suit: &Suit, rank: &Rank) -> u64 {
+    1 << (*suit as u32 * RANKS_LEN + i32::from(rank) as u32)
+}
+
+impl HandMask {
+    pub fn get_bits(&self) -> u64 {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn count_jokers(&self) -> usize {
+        (self.0 & JOKER_BITS).count_ones() as usize
+    }
+
+    pub fn contains(&self, card: &Card) -> bool {
+        match card {
+            Card::Normal(suit, rank) => self.0 & get_card_bit(suit, rank) != 0,
+            Card::Joker => self.count_jokers() > 0,
+        }
+    }
+
+    pub fn insert(&mut self, card: &Card) {
+        match card {
+            Card::Normal(suit, rank) => self.0 |= get_card_bit(suit, rank),
+            // 空いているジョーカーのビットを立てる
+            Card::Joker => {
+                self.0 |= (self.0 & JOKER_BITS).wrapping_add(1 << JOKER_SHIFT) & JOKER_BITS
+            }
+        }
+    }
+
+    pub fn remove(&mut self, card: &Card) {
+        match card {
+            Card::Normal(suit, rank) => self.0 &= !get_card_bit(suit, rank),
+            // 最も上のジョーカーのビットを消す
+            Card::Joker => {
+                let jokers = self.0 & JOKER_BITS;
+                if jokers != 0 {
+                    self.0 &= !(1 << (63 - jokers.leading_zeros()));
+                }
+            }
+        }
+    }
+
+    // 数字が同じカードのスート(ビットの位置はSUITSの順)
+    pub fn get_rank_suits(&self, rank: &Rank) -> u8 {
+        SUITS.iter().enumerate().fold(0, |suits, (i, suit)| {
+            suits | (u8::from(self.0 & get_card_bit(suit, rank) != 0) << i)
+        })
+    }
+
+    // スートが同じカードの数字(ビットの位置はRANKSの順)
+    pub fn get_suit_ranks(&self, suit: &Suit) -> u16 {
+        ((self.0 >> (*suit as u32 * RANKS_LEN)) & ((1 << RANKS_LEN) - 1)) as u16
+    }
+
+    pub fn get_cards(&self) -> Vec<Card> {
+        let mut cards: Vec<Card> = SUITS
+            .iter()
+            .flat_map(|suit| {
+                let ranks = self.get_suit_ranks(suit);
+                RANKS
+                    .iter()
+                    .enumerate()
+                    .filter(move |(i, _)| ranks & (1 << i) != 0)
+                    .map(|(_, rank)| Card::Normal(*suit, *rank))
+            })
+            .collect();
+        cards.extend((0..self.count_jokers()).map(|_| Card::Joker));
+        cards
+    }
+}
+
+impl From<&[Card]> for HandMask {
+    fn from(cards: &[Card]) -> Self {
+        let mut mask = HandMask::default();
+        for card in cards {
+            mask.insert(card);
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hand_mask() {
+        let cards = [
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Heart, Rank::Three),
+            Card::Normal(Suit::Heart, Rank::Five),
+            Card::Normal(Suit::Spade, Rank::Two),
+            Card::Joker,
+        ];
+        let mut mask = HandMask::from(&cards[..]);
+        assert_eq!((mask.count(), mask.count_jokers()), (5, 1));
+        for (rank, expected) in [
+            (Rank::Three, 0b0101),
+            (Rank::Five, 0b0100),
+            (Rank::Two, 0b1000),
+        ] {
+            assert_eq!(mask.get_rank_suits(&rank), expected);
+        }
+        for (suit, expected) in [
+            (Suit::Club, 0b1),
+            (Suit::Diamond, 0),
+            (Suit::Heart, 0b101),
+            (Suit::Spade, 1 << 12),
+        ] {
+            assert_eq!(mask.get_suit_ranks(&suit), expected);
+        }
+        assert_eq!(mask.get_cards(), cards);
+        mask.insert(&Card::Joker);
+        assert_eq!(mask.count_jokers(), 2);
+        for card in cards {
+            assert!(mask.contains(&card));
+            mask.remove(&card);
+        }
+        assert!(!mask.contains(&Card::Normal(Suit::Club, Rank::Three)));
+        assert_eq!(mask.get_cards(), vec![Card::Joker]);
+        mask.remove(&Card::Joker);
+        assert!(mask.is_empty());
+    }
+}
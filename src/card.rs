@@ -1,4 +1,11 @@
+use crate::i18n::trf;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Suit {
     Club,
     Diamond,
@@ -7,6 +14,7 @@ pub enum Suit {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Rank {
     Three,
     Four,
@@ -23,6 +31,24 @@ pub enum Rank {
     Two,
 }
 
+pub const SUITS: [Suit; 4] = [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade];
+
+pub const RANKS: [Rank; 13] = [
+    Rank::Three,
+    Rank::Four,
+    Rank::Five,
+    Rank::Six,
+    Rank::Seven,
+    Rank::Eight,
+    Rank::Nine,
+    Rank::Ten,
+    Rank::Jack,
+    Rank::Queen,
+    Rank::King,
+    Rank::Ace,
+    Rank::Two,
+];
+
 impl From<&Rank> for i32 {
     fn from(rank: &Rank) -> Self {
         match rank {
@@ -44,61 +70,167 @@ impl From<&Rank> for i32 {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Card {
     Normal(Suit, Rank),
     Joker,
 }
 
-impl From<&Card> for String {
-    fn from(card: &Card) -> Self {
-        match card {
-            Card::Normal(suit, rank) => {
-                let s = match suit {
-                    Suit::Spade => "♠️",
-                    Suit::Club => "♣️",
-                    Suit::Diamond => "♦︎",
-                    Suit::Heart => "♥",
-                };
-                let r = match rank {
-                    Rank::Three => "3",
-                    Rank::Four => "4",
-                    Rank::Five => "5",
-                    Rank::Six => "6",
-                    Rank::Seven => "7",
-                    Rank::Eight => "8",
-                    Rank::Nine => "9",
-                    Rank::Ten => "10",
-                    Rank::Jack => "J",
-                    Rank::Queen => "Q",
-                    Rank::King => "K",
-                    Rank::Ace => "A",
-                    Rank::Two => "2",
-                };
-                format!("{s}{r}")
-            }
-            Card::Joker => "Joker".to_owned(),
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Suit::Spade => "♠️",
+            Suit::Club => "♣️",
+            Suit::Diamond => "♦︎",
+            Suit::Heart => "♥",
+        })
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(get_rank_symbol(self))
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Card::Normal(suit, rank) => write!(f, "{suit}{rank}"),
+            Card::Joker => f.write_str("Joker"),
+        }
+    }
+}
+
+impl FromStr for Suit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_suit(s) {
+            Some((suit, "")) => Ok(suit),
+            _ => Err(trf("suit_parse_failed", &[("text", &s)])),
         }
     }
 }
 
+impl FromStr for Rank {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RANKS
+            .into_iter()
+            .find(|rank| get_rank_symbol(rank) == s)
+            .ok_or_else(|| trf("rank_parse_failed", &[("text", &s)]))
+    }
+}
+
+impl FromStr for Card {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_card(s.trim()) {
+            Some((card, "")) => Ok(card),
+            _ => Err(trf("card_parse_failed", &[("text", &s)])),
+        }
+    }
+}
+
+fn get_rank_symbol(rank: &Rank) -> &'static str {
+    match rank {
+        Rank::Three => "3",
+        Rank::Four => "4",
+        Rank::Five => "5",
+        Rank::Six => "6",
+        Rank::Seven => "7",
+        Rank::Eight => "8",
+        Rank::Nine => "9",
+        Rank::Ten => "10",
+        Rank::Jack => "J",
+        Rank::Queen => "Q",
+        Rank::King => "K",
+        Rank::Ace => "A",
+        Rank::Two => "2",
+    }
+}
+
+// 先頭のスート(記号かASCII文字)を読み取り、残りの文字列と返す
+fn split_suit(text: &str) -> Option<(Suit, &str)> {
+    SUITS.iter().find_map(|suit| {
+        let symbols = match suit {
+            Suit::Spade => ["♠", "S"],
+            Suit::Club => ["♣", "C"],
+            Suit::Diamond => ["♦", "D"],
+            Suit::Heart => ["♥", "H"],
+        };
+        let rest = symbols.iter().find_map(|s| text.strip_prefix(s))?;
+        // 絵文字や文字として表示させる異体字セレクタは読み飛ばす
+        Some((*suit, rest.trim_start_matches(['\u{fe0e}', '\u{fe0f}'])))
+    })
+}
+
+// 先頭の1枚のカードを読み取り、残りの文字列と返す
+fn split_card(text: &str) -> Option<(Card, &str)> {
+    if let Some(rest) = text
+        .strip_prefix("Joker")
+        .or_else(|| text.strip_prefix("JK"))
+    {
+        return Some((Card::Joker, rest));
+    }
+    let (suit, rest) = split_suit(text)?;
+    RANKS.iter().find_map(|rank| {
+        let rest = rest.strip_prefix(get_rank_symbol(rank))?;
+        Some((Card::Normal(suit, *rank), rest))
+    })
+}
+
+// 空白で区切るか続けて書いたカードを読み取る(例: ♠7 ♥7、♠7♥7、S7 JK)
+pub fn parse_cards(text: &str) -> Result<Vec<Card>, String> {
+    let mut cards = vec![];
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let (card, r) =
+            split_card(rest).ok_or_else(|| trf("card_parse_failed", &[("text", &rest)]))?;
+        cards.push(card);
+        rest = r.trim_start();
+    }
+    Ok(cards)
+}
+
+// ASCII文字のみでスートを表す
+pub fn suit_to_ascii(suit: &Suit) -> String {
+    match suit {
+        Suit::Spade => "S",
+        Suit::Club => "C",
+        Suit::Diamond => "D",
+        Suit::Heart => "H",
+    }
+    .to_owned()
+}
+
+// ASCII文字のみでカードを表す(例: S3, H10, JK)
+pub fn card_to_ascii(card: &Card) -> String {
+    match card {
+        Card::Normal(suit, rank) => format!("{}{rank}", suit_to_ascii(suit)),
+        Card::Joker => "JK".to_owned(),
+    }
+}
+
+// ASCII文字のみでカードを空白区切りで並べる
+pub fn cards_to_ascii(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(card_to_ascii)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// 52枚のカードとジョーカー1枚
+pub const DECK_LEN: usize = 53;
+
 pub fn create_deck() -> Vec<Card> {
     let mut deck = Vec::<Card>::new();
-    for suit in [Suit::Spade, Suit::Club, Suit::Diamond, Suit::Heart] {
-        for rank in [
-            Rank::Three,
-            Rank::Four,
-            Rank::Five,
-            Rank::Six,
-            Rank::Seven,
-            Rank::Eight,
-            Rank::Nine,
-            Rank::Ten,
-            Rank::Jack,
-            Rank::Queen,
-            Rank::King,
-            Rank::Ace,
-            Rank::Two,
-        ] {
+    for suit in SUITS {
+        for rank in RANKS {
             deck.push(Card::Normal(suit, rank));
         }
     }
@@ -106,6 +238,22 @@ pub fn create_deck() -> Vec<Card> {
     deck
 }
 
+// 手札の並び順(革命中は数字の強さが逆になる)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Normal,
+    Reversed,
+}
+
+impl Order {
+    pub fn get_comparator(&self) -> fn(&Card, &Card) -> std::cmp::Ordering {
+        match self {
+            Order::Normal => cmp_order,
+            Order::Reversed => cmp_order_reversely,
+        }
+    }
+}
+
 pub fn cmp_order(c1: &Card, c2: &Card) -> std::cmp::Ordering {
     match (c1, c2) {
         (Card::Normal(s1, r1), Card::Normal(s2, r2)) => r1.cmp(r2).then(s1.cmp(s2)),
@@ -120,6 +268,20 @@ pub fn cmp_order_reversely(c1: &Card, c2: &Card) -> std::cmp::Ordering {
     }
 }
 
+pub fn cmp_suit_order(c1: &Card, c2: &Card) -> std::cmp::Ordering {
+    match (c1, c2) {
+        (Card::Normal(s1, r1), Card::Normal(s2, r2)) => s1.cmp(s2).then(r1.cmp(r2)),
+        (_, _) => c1.cmp(c2),
+    }
+}
+
+pub fn cmp_suit_order_reversely(c1: &Card, c2: &Card) -> std::cmp::Ordering {
+    match (c1, c2) {
+        (Card::Normal(s1, r1), Card::Normal(s2, r2)) => s1.cmp(s2).then(r2.cmp(r1)),
+        (_, _) => c1.cmp(c2),
+    }
+}
+
 pub fn cmp_rank(c1: &Card, c2: &Card) -> std::cmp::Ordering {
     match (c1, c2) {
         (Card::Normal(_, r1), Card::Normal(_, r2)) => r1.cmp(r2),
@@ -138,6 +300,76 @@ pub fn cmp_rank_reversely(c1: &Card, c2: &Card) -> std::cmp::Ordering {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_card_to_ascii() {
+        for (card, expected) in [
+            (Card::Normal(Suit::Spade, Rank::Three), "S3"),
+            (Card::Normal(Suit::Heart, Rank::Ten), "H10"),
+            (Card::Normal(Suit::Diamond, Rank::Queen), "DQ"),
+            (Card::Normal(Suit::Club, Rank::Two), "C2"),
+            (Card::Joker, "JK"),
+        ] {
+            assert_eq!(card_to_ascii(&card), expected);
+        }
+        assert_eq!(
+            cards_to_ascii(&parse_cards("♠3 ♥10 Joker").unwrap()),
+            "S3 H10 JK"
+        );
+    }
+
+    #[test]
+    fn test_display_and_from_str() {
+        for (card, expected) in [
+            (Card::Normal(Suit::Spade, Rank::Three), "♠️3"),
+            (Card::Normal(Suit::Diamond, Rank::Ten), "♦︎10"),
+            (Card::Normal(Suit::Heart, Rank::Queen), "♥Q"),
+            (Card::Joker, "Joker"),
+        ] {
+            assert_eq!(card.to_string(), expected);
+            // 表示した文字列から元のカードに戻せる
+            assert_eq!(expected.parse::<Card>(), Ok(card));
+        }
+        for (text, expected) in [
+            ("♠3", Ok(Card::Normal(Suit::Spade, Rank::Three))),
+            ("S3", Ok(Card::Normal(Suit::Spade, Rank::Three))),
+            ("H10", Ok(Card::Normal(Suit::Heart, Rank::Ten))),
+            (" ♣A ", Ok(Card::Normal(Suit::Club, Rank::Ace))),
+            ("JK", Ok(Card::Joker)),
+            ("S1", Err("カードを読み取れません: S1".to_owned())),
+            ("♠3♠4", Err("カードを読み取れません: ♠3♠4".to_owned())),
+            ("X3", Err("カードを読み取れません: X3".to_owned())),
+        ] {
+            assert_eq!(text.parse::<Card>(), expected);
+        }
+        assert_eq!("♦️".parse::<Suit>(), Ok(Suit::Diamond));
+        assert!("♦3".parse::<Suit>().is_err());
+        assert_eq!("10".parse::<Rank>(), Ok(Rank::Ten));
+        assert!("1".parse::<Rank>().is_err());
+    }
+
+    #[test]
+    fn test_parse_cards() {
+        let cards = vec![
+            Card::Normal(Suit::Spade, Rank::Seven),
+            Card::Normal(Suit::Heart, Rank::Seven),
+            Card::Joker,
+        ];
+        for text in [
+            "♠7♥7JK",
+            "♠7 ♥7 JK",
+            "S7 H7 JK",
+            "♠️7 ♥7 Joker",
+            " S7♥7  JK ",
+        ] {
+            assert_eq!(parse_cards(text), Ok(cards.clone()));
+        }
+        assert_eq!(parse_cards(""), Ok(vec![]));
+        assert_eq!(
+            parse_cards("♠7 ?7"),
+            Err("カードを読み取れません: ?7".to_owned())
+        );
+    }
+
     #[test]
     fn test_cmp_order() {
         for (c1, c2, expected) in [
@@ -256,6 +488,38 @@ mod test {
         assert_eq!(cards, expected);
     }
 
+    #[test]
+    fn test_cmp_suit_order() {
+        let mut cards = vec![
+            Card::Normal(Suit::Heart, Rank::Jack),
+            Card::Normal(Suit::Spade, Rank::Four),
+            Card::Joker,
+            Card::Normal(Suit::Heart, Rank::Three),
+            Card::Normal(Suit::Club, Rank::Four),
+            Card::Normal(Suit::Spade, Rank::Two),
+        ];
+        cards.sort_by(cmp_suit_order);
+        let expected = vec![
+            Card::Normal(Suit::Club, Rank::Four),
+            Card::Normal(Suit::Heart, Rank::Three),
+            Card::Normal(Suit::Heart, Rank::Jack),
+            Card::Normal(Suit::Spade, Rank::Four),
+            Card::Normal(Suit::Spade, Rank::Two),
+            Card::Joker,
+        ];
+        assert_eq!(cards, expected);
+        cards.sort_by(cmp_suit_order_reversely);
+        let expected = vec![
+            Card::Normal(Suit::Club, Rank::Four),
+            Card::Normal(Suit::Heart, Rank::Jack),
+            Card::Normal(Suit::Heart, Rank::Three),
+            Card::Normal(Suit::Spade, Rank::Two),
+            Card::Normal(Suit::Spade, Rank::Four),
+            Card::Joker,
+        ];
+        assert_eq!(cards, expected);
+    }
+
     #[test]
     fn test_cmp_rank() {
         for (c1, c2, expected) in [
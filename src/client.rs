@@ -0,0 +1,140 @@
+use crate::field::Field;
+use crate::i18n::{tr, trf};
+use crate::kifu::rules_to_kifu;
+use crate::observer::Observer;
+use crate::options::JoinOptions;
+use crate::pc::Pc;
+use crate::player::{Action, Player};
+use crate::protocol::{read_message, write_message, ClientMessage, ServerMessage, TableSettings};
+use crate::render::{Renderer, Style};
+use std::io;
+use std::net::TcpStream;
+
+// ホストとしてゲームを開始するか、テーブルの一覧かルールを確かめる
+fn prompt_start(stream: &mut TcpStream) -> io::Result<()> {
+    println!("{}", tr("client_help"));
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    let request = match line.trim() {
+        "l" => ClientMessage::ListTables,
+        "r" => ClientMessage::GetRules,
+        _ => ClientMessage::StartGame,
+    };
+    write_message(stream, &request)
+}
+
+// サーバーのテーブルに参加し、手番ではローカルと同じ画面で手を選んで送る
+pub fn run_client(options: &JoinOptions, style: Style) -> Result<(), String> {
+    let mut stream = TcpStream::connect(&options.addr)
+        .map_err(|e| trf("connect_failed", &[("addr", &options.addr), ("error", &e)]))?;
+    let request = if let Some(token) = &options.token {
+        ClientMessage::Rejoin {
+            token: token.clone(),
+        }
+    } else if options.spectate {
+        ClientMessage::Spectate {
+            table: options.table.clone(),
+        }
+    } else if let Some(table) = &options.create {
+        ClientMessage::CreateTable {
+            table: table.clone(),
+            name: options.name.clone(),
+            settings: TableSettings {
+                preset: options.preset.clone(),
+                npc: options.npc,
+            },
+        }
+    } else if let Some(table) = &options.table {
+        ClientMessage::JoinTable {
+            table: table.clone(),
+            name: options.name.clone(),
+        }
+    } else if options.list {
+        ClientMessage::ListTables
+    } else {
+        ClientMessage::Join {
+            name: options.name.clone(),
+        }
+    };
+    write_message(&mut stream, &request).map_err(|e| e.to_string())?;
+    println!("{}", trf("connected", &[("addr", &options.addr)]));
+    let mut pc = Pc::new(options.name.clone(), style, false, None).with_notice(options.notice);
+    pc.enable_chat();
+    let mut renderer = Renderer::new(style, options.speed, false);
+    let mut seated = false;
+    loop {
+        let message = match read_message(&mut stream) {
+            Ok(message) => message,
+            // サーバーが接続を閉じたら終了する
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(trf("disconnected", &[("error", &e)])),
+        };
+        match message {
+            ServerMessage::Welcome { seat, token } => {
+                seated = true;
+                println!("{}", trf("seated", &[("seat", &seat), ("token", &token)]));
+            }
+            ServerMessage::Deal { .. } => {}
+            ServerMessage::TurnRequest { view, hands } => {
+                pc.init(hands);
+                let field = Field::from_view(&view);
+                let comb = loop {
+                    match pc.play(&field) {
+                        Action::Move(comb) | Action::Away(comb) => break comb,
+                        Action::Chat(text) => {
+                            let chat = ClientMessage::Chat { text };
+                            write_message(&mut stream, &chat).map_err(|e| e.to_string())?;
+                        }
+                        Action::Save(_) => println!("{}", tr("network_save")),
+                        Action::Undo => println!("{}", tr("network_undo")),
+                        // 中断すると接続を閉じて席はサーバーのNPCに任せる
+                        Action::Quit(_) => return Ok(()),
+                    }
+                };
+                let reply = match comb {
+                    Some(comb) => ClientMessage::Play { comb },
+                    None => ClientMessage::Pass,
+                };
+                write_message(&mut stream, &reply).map_err(|e| e.to_string())?;
+            }
+            // 席に着く前に断られたら終了する
+            ServerMessage::Reject { message } if !seated => return Err(message),
+            ServerMessage::Reject { message } => println!("{message}"),
+            ServerMessage::Event { event } => renderer.notify(&event),
+            ServerMessage::Chat { name, text } => {
+                println!("{} {text}", style.bold(&format!("{name}:")));
+            }
+            ServerMessage::Tables { tables } => {
+                if tables.is_empty() {
+                    println!("{}", tr("no_tables"));
+                }
+                for table in &tables {
+                    println!("{}", String::from(table));
+                }
+                if options.list {
+                    break;
+                }
+                if options.create.is_some() {
+                    prompt_start(&mut stream).map_err(|e| e.to_string())?;
+                }
+            }
+            ServerMessage::Rules { rules } => {
+                println!(
+                    "{}",
+                    trf("client_rules", &[("rules", &rules_to_kifu(&rules))])
+                );
+                if options.create.is_some() && !seated {
+                    prompt_start(&mut stream).map_err(|e| e.to_string())?;
+                }
+            }
+            ServerMessage::TableJoined { table } => {
+                println!("{}", String::from(&table));
+                // 開始はホストが決める
+                if options.create.is_some() && table.players.len() == 1 {
+                    prompt_start(&mut stream).map_err(|e| e.to_string())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,11 +1,16 @@
-use crate::card::Card;
+use crate::card::{parse_cards, Card, Rank, Suit, RANKS};
+use crate::i18n::trf;
 use itertools::Itertools;
-use std::{cmp::Ordering, collections::HashSet};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::{cmp::Ordering, collections::HashSet, fmt};
 
 pub const MIN_MULTI: usize = 2;
 pub const MIN_SEQ: usize = 3;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Comb {
     Single(Card),
     Multi(Vec<Card>),
@@ -13,6 +18,13 @@ pub enum Comb {
 }
 
 impl Comb {
+    pub fn get_cards(&self) -> &[Card] {
+        match self {
+            Comb::Single(card) => std::slice::from_ref(card),
+            Comb::Multi(cards) | Comb::Seq(cards) => cards,
+        }
+    }
+
     pub fn is_greater<F>(&self, comb: &Comb, comparator: F) -> bool
     where
         F: Fn(&Card, &Card) -> Ordering,
@@ -41,26 +53,240 @@ impl Comb {
             (_, _) => false,
         }
     }
+
+    // 階段のジョーカーが表す数字(階段でなければ空)
+    pub fn get_joker_ranks(&self) -> Vec<Rank> {
+        let Comb::Seq(cards) = self else {
+            return vec![];
+        };
+        let ranks = get_seq_ranks(cards).unwrap_or_default();
+        cards
+            .iter()
+            .zip(ranks)
+            .filter(|(card, _)| matches!(card, Card::Joker))
+            .map(|(_, r)| RANKS[r])
+            .collect()
+    }
+
+    // ジョーカーがrankを表すように並べ直した階段を作る(表せなければNone)
+    pub fn declare_joker(&self, rank: &Rank) -> Option<Comb> {
+        let Comb::Seq(cards) = self else {
+            return None;
+        };
+        let suit = get_suit(cards)?;
+        let ranks = get_seq_ranks(cards)?;
+        let normals: Vec<usize> = cards
+            .iter()
+            .zip(&ranks)
+            .filter(|(card, _)| matches!(card, Card::Normal(..)))
+            .map(|(_, r)| *r)
+            .sorted()
+            .collect();
+        let rank = i32::from(rank) as usize;
+        if !cards.contains(&Card::Joker) || normals.binary_search(&rank).is_ok() {
+            return None;
+        }
+        let (first, last) = (normals[0], normals[normals.len() - 1]);
+        // 階段の外の数字なら、その数字が端になるように階段をずらす
+        let low = match rank {
+            _ if rank < first => rank,
+            _ if rank > last => (rank + 1).checked_sub(cards.len())?,
+            _ => *ranks.iter().min()?,
+        };
+        let high = low + cards.len() - 1;
+        if high >= RANKS.len() || low > first || high < last || !(low..=high).contains(&rank) {
+            return None;
+        }
+        Some(Comb::Seq(
+            (low..=high)
+                .map(|r| match normals.binary_search(&r) {
+                    Ok(_) => Card::Normal(suit, RANKS[r]),
+                    Err(_) => Card::Joker,
+                })
+                .collect(),
+        ))
+    }
+
+    // 階段のジョーカーが表せる数字
+    pub fn get_joker_choices(&self) -> Vec<Rank> {
+        RANKS
+            .into_iter()
+            .filter(|rank| self.declare_joker(rank).is_some())
+            .collect()
+    }
+
+    // 階段はジョーカーを補った数字で最も弱いカード同士を比べる(それ以外はis_greaterと同じ)
+    pub fn is_greater_by_extreme<F>(&self, comb: &Comb, comparator: F) -> bool
+    where
+        F: Fn(&Card, &Card) -> Ordering,
+    {
+        match (self, comb) {
+            (Comb::Seq(cards1), Comb::Seq(cards2)) => {
+                if cards1.len() != cards2.len() {
+                    return false;
+                }
+                match (
+                    get_weakest(cards1, &comparator),
+                    get_weakest(cards2, &comparator),
+                ) {
+                    (Some(c1), Some(c2)) => comparator(&c1, &c2) == Ordering::Greater,
+                    _ => false,
+                }
+            }
+            (_, _) => self.is_greater(comb, comparator),
+        }
+    }
+}
+
+// 階段の各位置のカードが表す数字(ジョーカーは並びの位置で決まる)
+fn get_seq_ranks(cards: &[Card]) -> Option<Vec<usize>> {
+    let mut normals = cards.iter().enumerate().filter_map(|(i, c)| match c {
+        Card::Normal(_, r) => Some((i, i32::from(r) as usize)),
+        Card::Joker => None,
+    });
+    let (i1, r1) = normals.next()?;
+    // 並びの向きは2枚の数字から決める(1枚なら昇順とみなす)
+    let is_desc = matches!(normals.next(), Some((_, r2)) if r2 < r1);
+    (0..cards.len())
+        .map(|i| {
+            match is_desc {
+                true => (r1 + i1).checked_sub(i),
+                false => (r1 + i).checked_sub(i1),
+            }
+            .filter(|r| *r < RANKS.len())
+        })
+        .collect()
+}
+
+fn get_suit(cards: &[Card]) -> Option<Suit> {
+    cards.iter().find_map(|c| match c {
+        Card::Normal(s, _) => Some(*s),
+        Card::Joker => None,
+    })
+}
+
+// 階段の両端のカードのうち弱い方をジョーカーを補った数字で取得する
+fn get_weakest<F>(cards: &[Card], comparator: &F) -> Option<Card>
+where
+    F: Fn(&Card, &Card) -> Ordering,
+{
+    let suit = get_suit(cards)?;
+    let ranks = get_seq_ranks(cards)?;
+    let (low, high) = (
+        Card::Normal(suit, RANKS[*ranks.iter().min()?]),
+        Card::Normal(suit, RANKS[*ranks.iter().max()?]),
+    );
+    Some(match comparator(&low, &high) {
+        Ordering::Greater => high,
+        _ => low,
+    })
+}
+
+impl fmt::Display for Comb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Comb::Single(card) => write!(f, "{card}"),
+            Comb::Multi(cards) | Comb::Seq(cards) => write!(f, "{}", cards.iter().join(" ")),
+        }
+    }
+}
+
+// 1枚のカードか、組み合わせになる複数のカードを読み取る(例: ♠7、♠7 ♥7、S3 S4 JK)
+impl FromStr for Comb {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = parse_cards(s)?;
+        match cards[..] {
+            [card] => Ok(Comb::Single(card)),
+            _ => {
+                Comb::try_from(cards).map_err(|_| trf("comb_parse_failed", &[("text", &s.trim())]))
+            }
+        }
+    }
 }
 
 impl TryFrom<Vec<Card>> for Comb {
     type Error = ();
 
     fn try_from(cards: Vec<Card>) -> Result<Self, Self::Error> {
+        Comb::try_from_cards(cards, MIN_SEQ).ok_or(())
+    }
+}
+
+impl Comb {
+    // 入力の順に関わらず、同じ組み合わせは同じ並びにする(連続して並んだ階段の端のジョーカーは位置を保つ)
+    // 階段はmin_seq枚以上の場合だけ組み合わせになる(0なら階段にしない)
+    pub fn try_from_cards(mut cards: Vec<Card>, min_seq: usize) -> Option<Self> {
         let len = cards.len();
         if len < MIN_MULTI {
-            return Err(());
+            return None;
         }
         if is_same_ranks(&cards) {
-            return Ok(Comb::Multi(cards));
+            // スートの順に並べ、ジョーカーを最後に置く
+            cards.sort();
+            return Some(Comb::Multi(cards));
         }
-        if len >= MIN_SEQ && is_same_suits(&cards) && is_seq(&cards) {
-            return Ok(Comb::Seq(cards));
+        if min_seq != 0 && len >= min_seq.max(MIN_SEQ) && is_same_suits(&cards) {
+            return sort_seq(cards).map(Comb::Seq);
         }
-        Err(())
+        None
     }
 }
 
+// 階段のカードを数字の昇順に並べる
+// 連続して並んでいればジョーカーの位置を保ち、そうでなければジョーカーで足りない数字を補う
+fn sort_seq(mut cards: Vec<Card>) -> Option<Vec<Card>> {
+    let jokers = cards.iter().filter(|c| matches!(c, Card::Joker)).count();
+    // is_seqはジョーカーを1枚まで扱える
+    if jokers <= 1 && is_seq(&cards) {
+        let mut ranks = cards.iter().filter_map(|c| match c {
+            Card::Normal(_, r) => Some(r),
+            Card::Joker => None,
+        });
+        if ranks.next() > ranks.next() {
+            cards.reverse();
+        }
+        // 3より下や2より上に入力されたジョーカーは置き直す
+        let fits = match cards.as_slice() {
+            [Card::Joker, Card::Normal(_, r), ..] => i32::from(r) > 0,
+            [.., Card::Normal(_, r), Card::Joker] => (i32::from(r) as usize) < RANKS.len() - 1,
+            _ => true,
+        };
+        if fits {
+            return Some(cards);
+        }
+    }
+    let suit = cards.iter().find_map(|c| match c {
+        Card::Normal(s, _) => Some(*s),
+        Card::Joker => None,
+    })?;
+    let mut ranks: Vec<usize> = cards
+        .iter()
+        .filter_map(|c| match c {
+            Card::Normal(_, r) => Some(i32::from(r) as usize),
+            Card::Joker => None,
+        })
+        .collect();
+    ranks.sort();
+    if ranks.iter().tuple_windows().any(|(r1, r2)| r1 == r2) {
+        return None;
+    }
+    let (first, last) = (*ranks.first()?, *ranks.last()?);
+    // 間の数字をジョーカーで埋め、余ったジョーカーは上に、上に置けなければ下に置く
+    let extra = jokers.checked_sub(last - first + 1 - ranks.len())?;
+    let high = extra.min(RANKS.len() - 1 - last);
+    let low = first.checked_sub(extra - high)?;
+    Some(
+        (low..=last + high)
+            .map(|r| match ranks.binary_search(&r) {
+                Ok(_) => Card::Normal(suit, RANKS[r]),
+                Err(_) => Card::Joker,
+            })
+            .collect(),
+    )
+}
+
 // 全てのカードが同じ数字か判定する
 fn is_same_ranks(cards: &[Card]) -> bool {
     cards
@@ -191,36 +417,153 @@ mod test {
             Card::Normal(Suit::Spade, Rank::Six),
         ];
         let joker = Card::Joker;
-        for cds in [
-            vec![cards[0], cards[1], cards[2]],
-            vec![cards[2], cards[1], cards[0]],
-            vec![cards[0], cards[1], cards[2], cards[3]],
-            vec![cards[3], cards[2], cards[1], cards[0]],
-            vec![joker, cards[1], cards[2], cards[3]],
-            vec![cards[0], joker, cards[2], cards[3]],
-            vec![cards[0], cards[1], joker, cards[3]],
-            vec![cards[0], cards[1], cards[2], joker],
-            vec![joker, cards[2], cards[1], cards[0]],
-            vec![cards[3], joker, cards[1], cards[0]],
-            vec![cards[3], cards[2], joker, cards[0]],
-            vec![cards[3], cards[2], cards[1], joker],
+        for (cds, expected) in [
+            (
+                vec![cards[0], cards[1], cards[2]],
+                vec![cards[0], cards[1], cards[2]],
+            ),
+            (
+                vec![cards[2], cards[1], cards[0]],
+                vec![cards[0], cards[1], cards[2]],
+            ),
+            (
+                vec![cards[3], cards[2], cards[1], cards[0]],
+                vec![cards[0], cards[1], cards[2], cards[3]],
+            ),
+            // 連続して並んでいればジョーカーの位置を保つ
+            (
+                vec![joker, cards[1], cards[2], cards[3]],
+                vec![joker, cards[1], cards[2], cards[3]],
+            ),
+            (
+                vec![cards[3], cards[2], joker, cards[0]],
+                vec![cards[0], joker, cards[2], cards[3]],
+            ),
+            (
+                vec![cards[3], cards[2], cards[1], joker],
+                vec![joker, cards[1], cards[2], cards[3]],
+            ),
+            (
+                vec![cards[1], cards[2], joker],
+                vec![cards[1], cards[2], joker],
+            ),
+            // 並んでいなければ並べ替え、ジョーカーで足りない数字を補う
+            (
+                vec![cards[1], cards[0], cards[2]],
+                vec![cards[0], cards[1], cards[2]],
+            ),
+            (
+                vec![cards[3], cards[1], cards[2], cards[0]],
+                vec![cards[0], cards[1], cards[2], cards[3]],
+            ),
+            (
+                vec![joker, cards[3], cards[0], cards[1]],
+                vec![cards[0], cards[1], joker, cards[3]],
+            ),
+            (
+                vec![cards[2], joker, cards[1], cards[0]],
+                vec![cards[0], cards[1], cards[2], joker],
+            ),
+            (
+                vec![cards[3], joker, cards[1]],
+                vec![cards[1], joker, cards[3]],
+            ),
+            (
+                vec![cards[1], joker, cards[2]],
+                vec![cards[1], cards[2], joker],
+            ),
         ] {
-            let expected = Ok::<Comb, ()>(Comb::Seq(cds.clone()));
-            assert_eq!(Comb::try_from(cds), expected);
+            assert_eq!(Comb::try_from(cds), Ok(Comb::Seq(expected)));
         }
+        let (two, ace, king) = (
+            Card::Normal(Suit::Spade, Rank::Two),
+            Card::Normal(Suit::Spade, Rank::Ace),
+            Card::Normal(Suit::Spade, Rank::King),
+        );
+        // 2より上には置けないので下に置く
+        assert_eq!(
+            Comb::try_from(vec![two, joker, ace]),
+            Ok(Comb::Seq(vec![joker, ace, two]))
+        );
+        assert_eq!(
+            Comb::try_from(vec![joker, two, king, joker]),
+            Ok(Comb::Seq(vec![joker, king, joker, two]))
+        );
+        assert_eq!(
+            Comb::try_from(vec![ace, two, joker]),
+            Ok(Comb::Seq(vec![joker, ace, two]))
+        );
+        // 3より下には置けないので上に置く
+        assert_eq!(
+            Comb::try_from(vec![joker, cards[0], cards[1]]),
+            Ok(Comb::Seq(vec![cards[0], cards[1], joker]))
+        );
         for cds in [
             vec![],
             vec![cards[0]],
             vec![cards[0], cards[1]],
-            vec![cards[1], cards[0], cards[2]],
-            vec![cards[3], cards[1], cards[2], cards[0]],
-            vec![joker, cards[1], cards[2], cards[0]],
-            vec![cards[3], joker, cards[2], cards[0]],
-            vec![cards[3], cards[1], joker, cards[0]],
-            vec![cards[3], cards[1], cards[2], joker],
+            vec![cards[0], cards[3], cards[1]],
+            vec![cards[0], cards[0], cards[1]],
+            vec![joker, cards[0], cards[3]],
+            vec![cards[0], Card::Normal(Suit::Heart, Rank::Four), joker],
         ] {
             assert_eq!(Comb::try_from(cds), Err::<Comb, ()>(()));
         }
+        // 階段に必要な枚数を変える
+        for (cds, min_seq, expected) in [
+            (cards[..3].to_vec(), 4, None),
+            (cards.to_vec(), 4, Some(Comb::Seq(cards.to_vec()))),
+            (cards.to_vec(), 0, None),
+        ] {
+            assert_eq!(Comb::try_from_cards(cds, min_seq), expected);
+        }
+    }
+
+    #[test]
+    fn test_get_cards() {
+        let card = Card::Normal(Suit::Club, Rank::Three);
+        for (comb, expected) in [
+            (Comb::Single(card), vec![card]),
+            (
+                Comb::Multi(vec![card, Card::Joker]),
+                vec![card, Card::Joker],
+            ),
+        ] {
+            assert_eq!(comb.get_cards(), expected);
+        }
+    }
+
+    #[test]
+    fn test_display_and_from_str() {
+        for (comb, expected) in [
+            (Comb::Single(Card::Joker), "Joker"),
+            (
+                Comb::Multi(vec![
+                    Card::Normal(Suit::Heart, Rank::Seven),
+                    Card::Normal(Suit::Spade, Rank::Seven),
+                ]),
+                "♥7 ♠️7",
+            ),
+        ] {
+            assert_eq!(comb.to_string(), expected);
+            assert_eq!(expected.parse::<Comb>(), Ok(comb));
+        }
+        for (text, expected) in [
+            (
+                "S5 JK S3",
+                Ok(Comb::Seq(vec![
+                    Card::Normal(Suit::Spade, Rank::Three),
+                    Card::Joker,
+                    Card::Normal(Suit::Spade, Rank::Five),
+                ])),
+            ),
+            ("♣9", Ok(Comb::Single(Card::Normal(Suit::Club, Rank::Nine)))),
+            ("", Err("組み合わせになっていません: ".to_owned())),
+            ("♠7 ♥8", Err("組み合わせになっていません: ♠7 ♥8".to_owned())),
+            ("♠7 X8", Err("カードを読み取れません: X8".to_owned())),
+        ] {
+            assert_eq!(text.parse::<Comb>(), expected);
+        }
     }
 
     #[test]
@@ -258,6 +601,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_is_greater_multi() {
         let comb1 = Comb::Multi(vec![
             Card::Normal(Suit::Spade, Rank::Nine),
@@ -404,6 +748,125 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_is_greater_by_extreme() {
+        let seq = |suit, ranks: [Option<Rank>; 3]| {
+            Comb::Seq(
+                ranks
+                    .iter()
+                    .map(|r| match r {
+                        Some(r) => Card::Normal(suit, *r),
+                        None => Card::Joker,
+                    })
+                    .collect(),
+            )
+        };
+        let comb1 = seq(
+            Suit::Spade,
+            [Some(Rank::Nine), Some(Rank::Ten), Some(Rank::Jack)],
+        );
+        for (comb2, expected) in [
+            (
+                seq(
+                    Suit::Club,
+                    [Some(Rank::Three), Some(Rank::Four), Some(Rank::Five)],
+                ),
+                true,
+            ),
+            // 降順に並んでいても最も弱いカードで比べる
+            (
+                seq(
+                    Suit::Club,
+                    [Some(Rank::Ten), Some(Rank::Nine), Some(Rank::Eight)],
+                ),
+                true,
+            ),
+            (
+                seq(Suit::Heart, [None, Some(Rank::Nine), Some(Rank::Ten)]),
+                true,
+            ),
+            (
+                seq(Suit::Heart, [Some(Rank::Nine), Some(Rank::Ten), None]),
+                false,
+            ),
+            (
+                seq(Suit::Diamond, [Some(Rank::Queen), None, Some(Rank::Ten)]),
+                false,
+            ),
+        ] {
+            assert_eq!(comb1.is_greater_by_extreme(&comb2, cmp_rank), expected);
+            assert_eq!(
+                comb2.is_greater_by_extreme(&comb1, cmp_rank_reversely),
+                expected
+            );
+        }
+        // 同じ位置のカードで比べると向きが違う階段を比べられない
+        let desc = seq(
+            Suit::Club,
+            [Some(Rank::Ten), Some(Rank::Nine), Some(Rank::Eight)],
+        );
+        assert!(!comb1.is_greater(&desc, cmp_rank));
+        let four = Comb::Seq(vec![
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Club, Rank::Four),
+            Card::Normal(Suit::Club, Rank::Five),
+            Card::Normal(Suit::Club, Rank::Six),
+        ]);
+        assert!(!comb1.is_greater_by_extreme(&four, cmp_rank));
+        let single = Comb::Single(Card::Normal(Suit::Club, Rank::Three));
+        assert!(Comb::Single(Card::Joker).is_greater_by_extreme(&single, cmp_rank));
+    }
+
+    #[test]
+    fn test_declare_joker() {
+        let (four, five, six, joker) = (
+            Card::Normal(Suit::Heart, Rank::Four),
+            Card::Normal(Suit::Heart, Rank::Five),
+            Card::Normal(Suit::Heart, Rank::Six),
+            Card::Joker,
+        );
+        let comb = Comb::Seq(vec![four, five, joker]);
+        assert_eq!(comb.get_joker_ranks(), vec![Rank::Six]);
+        assert_eq!(comb.get_joker_choices(), vec![Rank::Three, Rank::Six]);
+        for (rank, expected) in [
+            (Rank::Three, Some(Comb::Seq(vec![joker, four, five]))),
+            (Rank::Six, Some(comb.clone())),
+            (Rank::Four, None),
+            (Rank::Seven, None),
+        ] {
+            let declared = comb.declare_joker(&rank);
+            assert_eq!(declared, expected);
+            if let Some(declared) = declared {
+                assert_eq!(declared.get_joker_ranks(), vec![rank]);
+            }
+        }
+        // 間を埋めるジョーカーは他の数字を表せない
+        let comb = Comb::Seq(vec![four, joker, six]);
+        assert_eq!(comb.get_joker_choices(), vec![Rank::Five]);
+        // 表した数字で比べる
+        let prev_comb = Comb::Seq(vec![
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Club, Rank::Four),
+            Card::Normal(Suit::Club, Rank::Five),
+        ]);
+        let comb = Comb::Seq(vec![four, five, joker]);
+        for (rank, expected) in [(Rank::Six, true), (Rank::Three, false)] {
+            let declared = comb.declare_joker(&rank).unwrap();
+            assert_eq!(
+                declared.is_greater_by_extreme(&prev_comb, cmp_rank),
+                expected
+            );
+        }
+        for comb in [
+            Comb::Single(joker),
+            Comb::Multi(vec![four, joker]),
+            Comb::Seq(vec![four, five, six]),
+        ] {
+            assert!(comb.get_joker_ranks().is_empty());
+            assert!(comb.get_joker_choices().is_empty());
+        }
+    }
+
     #[test]
     fn test_is_same_ranks() {
         for (cards, expected) in [
@@ -0,0 +1,99 @@
+use crate::heuristic::Weights;
+use crate::i18n::Lang;
+use crate::rules::RuleSet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    // tuneサブコマンドで調整したHeuristicNpcの重み
+    pub heuristic: Option<Weights>,
+    // 画面に表示する言語(--langを指定すればそちらを優先する)
+    pub lang: Option<Lang>,
+    // 人間のプレイヤーの名前とNPCに付ける名前(--nameと--npc-namesを指定すればそちらを優先する)
+    pub name: Option<String>,
+    pub npc_names: Option<Vec<String>>,
+    // NPCの名前を候補からランダムに選ぶ
+    pub random_names: Option<bool>,
+    // --presetで選べる自分で定義したプリセット(省略したルールはstandardと同じ)
+    pub presets: Option<BTreeMap<String, RuleSet>>,
+}
+
+// ユーザーの設定ディレクトリの設定ファイルのパス
+pub fn get_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("daifugo").join("config.toml"))
+}
+
+pub fn load_config(path: &Path) -> Result<Config, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => toml::from_str(&text).map_err(|e| e.to_string()),
+        Err(_) => Ok(Config::default()),
+    }
+}
+
+pub fn save_config(path: &Path, config: &Config) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = toml::to_string(config).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let path = std::env::temp_dir()
+            .join("daifugo_test_config")
+            .join("config.toml");
+        let _ = fs::remove_file(&path);
+        assert_eq!(load_config(&path), Ok(Config::default()));
+        let config = Config {
+            heuristic: Some(Weights {
+                pass: -0.5,
+                ..Default::default()
+            }),
+            lang: Some(Lang::En),
+            name: Some("Alice".to_owned()),
+            npc_names: Some(vec!["Bob".to_owned(), "Carol".to_owned()]),
+            random_names: Some(true),
+            presets: Some(BTreeMap::from([(
+                "house".to_owned(),
+                RuleSet {
+                    five_skip: true,
+                    ..RuleSet::default()
+                },
+            )])),
+        };
+        save_config(&path, &config).unwrap();
+        assert_eq!(load_config(&path), Ok(config));
+        fs::write(&path, "lang = \"en\"").unwrap();
+        assert_eq!(load_config(&path).unwrap().lang, Some(Lang::En));
+        fs::write(&path, "npc_names = [\"Bob\"]").unwrap();
+        assert_eq!(
+            load_config(&path).unwrap().npc_names,
+            Some(vec!["Bob".to_owned()])
+        );
+        fs::write(
+            &path,
+            "[presets.house]\njack_back = true\nbind = false\nlead = \"winner\"",
+        )
+        .unwrap();
+        let presets = load_config(&path).unwrap().presets.unwrap();
+        assert_eq!(
+            presets["house"],
+            RuleSet {
+                jack_back: true,
+                bind: false,
+                lead: crate::rules::LeadRule::Winner,
+                ..RuleSet::default()
+            }
+        );
+        fs::write(&path, "heuristic = 1").unwrap();
+        assert!(load_config(&path).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::card::{Card, Order};
+use crate::encoding::{encode_comb, encode_state, CARDS_LEN, STATE_LEN};
+use crate::i18n::tr;
+use crate::player::{Action, Player};
+use crate::strategy::Strategy;
+use crate::validator::Validator;
+use itertools::Itertools;
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+// 1手分の学習データ
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub state: Vec<f32>,
+    pub action: Vec<f32>,
+    pub seat: usize,
+    // 手を出したプレイヤーの最終順位(0が大富豪)
+    pub place: usize,
+}
+
+// 手を出す前の状態と出した手を記録するプレイヤー
+struct RecordingPlayer {
+    player: Box<dyn Player>,
+    seat: usize,
+    samples: Rc<RefCell<Vec<Sample>>>,
+}
+
+impl Player for RecordingPlayer {
+    fn init(&mut self, hands: Vec<Card>) {
+        self.player.init(hands);
+    }
+
+    fn count_hands(&self) -> usize {
+        self.player.count_hands()
+    }
+
+    fn get_name(&self) -> &str {
+        self.player.get_name()
+    }
+
+    fn get_hands(&self) -> &[Card] {
+        self.player.get_hands()
+    }
+
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+        self.player.take_cards(cards)
+    }
+
+    fn receive_cards(&mut self, cards: Vec<Card>) {
+        self.player.receive_cards(cards);
+    }
+
+    fn sort_hands(&mut self, order: Order) {
+        self.player.sort_hands(order);
+    }
+
+    fn play(&mut self, validator: &dyn Validator) -> Action {
+        let state = encode_state(&validator.get_view(), self.player.get_hands());
+        let action = self.player.play(validator);
+        // 手を出した操作だけを記録する
+        if let Action::Move(comb) = &action {
+            self.samples.borrow_mut().push(Sample {
+                state,
+                action: encode_comb(comb.as_ref()),
+                seat: self.seat,
+                place: 0,
+            });
+        }
+        action
+    }
+
+    fn confirm_tribute(&mut self, cards: &[Card]) {
+        self.player.confirm_tribute(cards);
+    }
+
+    fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
+        self.player.get_needless_cards(cards_count)
+    }
+
+    fn get_strategy(&self) -> Option<Strategy> {
+        self.player.get_strategy()
+    }
+}
+
+// 全てのプレイヤーの手をsamplesに記録する
+pub fn record_samples(
+    players: Vec<Box<dyn Player>>,
+    samples: &Rc<RefCell<Vec<Sample>>>,
+) -> Vec<Box<dyn Player>> {
+    players
+        .into_iter()
+        .enumerate()
+        .map(|(seat, player)| {
+            Box::new(RecordingPlayer {
+                player,
+                seat,
+                samples: samples.clone(),
+            }) as Box<dyn Player>
+        })
+        .collect()
+}
+
+// ゲームが終わったら最終順位を書き込む
+pub fn set_places(samples: &mut [Sample], player_rank: &[usize]) {
+    for sample in samples {
+        sample.place = player_rank
+            .iter()
+            .position(|seat| *seat == sample.seat)
+            .unwrap_or_default();
+    }
+}
+
+// NumPyのnpy形式(符号なし8ビット整数)の配列
+fn to_npy(shape: &[usize], data: Vec<u8>) -> Vec<u8> {
+    let shape = match shape {
+        [len] => format!("({len},)"),
+        _ => format!("({})", shape.iter().join(", ")),
+    };
+    let mut header = format!("{{'descr': '|u1', 'fortran_order': False, 'shape': {shape}, }}");
+    // マジックナンバーを含めたヘッダーの長さを64バイトの倍数にする
+    while (10 + header.len() + 1) % 64 != 0 {
+        header.push(' ');
+    }
+    header.push('\n');
+    let mut npy = b"\x93NUMPY\x01\x00".to_vec();
+    npy.extend((header.len() as u16).to_le_bytes());
+    npy.extend(header.as_bytes());
+    npy.extend(data);
+    npy
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xedb8_8320,
+                _ => crc >> 1,
+            };
+        }
+    }
+    !crc
+}
+
+// 圧縮せずにZIPにまとめる(numpy.loadで読めるnpz形式)
+fn to_npz(files: &[(&str, Vec<u8>)]) -> Result<Vec<u8>, String> {
+    let mut zip = vec![];
+    let mut central = vec![];
+    for (name, data) in files {
+        let offset = u32::try_from(zip.len()).map_err(|_| tr("data_too_large"))?;
+        let size = u32::try_from(data.len()).map_err(|_| tr("data_too_large"))?;
+        // バージョン、フラグ、圧縮方式、時刻、日付、CRC、圧縮前後のサイズ、名前の長さ、拡張フィールドの長さ
+        let mut fields = vec![];
+        fields.extend(20u16.to_le_bytes());
+        fields.extend([0; 8]);
+        fields.extend(crc32(data).to_le_bytes());
+        fields.extend(size.to_le_bytes());
+        fields.extend(size.to_le_bytes());
+        fields.extend((name.len() as u16).to_le_bytes());
+        fields.extend([0; 2]);
+        zip.extend(0x0403_4b50u32.to_le_bytes());
+        zip.extend(&fields);
+        zip.extend(name.as_bytes());
+        zip.extend(data);
+        central.extend(0x0201_4b50u32.to_le_bytes());
+        central.extend(20u16.to_le_bytes());
+        central.extend(&fields);
+        // コメントの長さ、ディスク番号、属性
+        central.extend([0; 10]);
+        central.extend(offset.to_le_bytes());
+        central.extend(name.as_bytes());
+    }
+    let offset = u32::try_from(zip.len()).map_err(|_| tr("data_too_large"))?;
+    let count = files.len() as u16;
+    zip.extend(central.iter());
+    zip.extend(0x0605_4b50u32.to_le_bytes());
+    zip.extend([0; 4]);
+    zip.extend(count.to_le_bytes());
+    zip.extend(count.to_le_bytes());
+    zip.extend((central.len() as u32).to_le_bytes());
+    zip.extend(offset.to_le_bytes());
+    zip.extend([0; 2]);
+    Ok(zip)
+}
+
+// states、actions、places、seatsの配列をnpz形式で書き出す
+pub fn write_npz(path: &Path, samples: &[Sample]) -> Result<(), String> {
+    let len = samples.len();
+    let flatten = |get: fn(&Sample) -> &[f32]| -> Vec<u8> {
+        samples
+            .iter()
+            .flat_map(|sample| get(sample).iter().map(|v| *v as u8))
+            .collect()
+    };
+    let files = [
+        (
+            "states.npy",
+            to_npy(&[len, STATE_LEN], flatten(|s| &s.state)),
+        ),
+        (
+            "actions.npy",
+            to_npy(&[len, CARDS_LEN], flatten(|s| &s.action)),
+        ),
+        (
+            "places.npy",
+            to_npy(&[len], samples.iter().map(|s| s.place as u8).collect()),
+        ),
+        (
+            "seats.npy",
+            to_npy(&[len], samples.iter().map(|s| s.seat as u8).collect()),
+        ),
+    ];
+    std::fs::write(path, to_npz(&files)?).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_npy() {
+        for (shape, data, expected) in [
+            (vec![3], vec![1, 2, 3], "(3,)"),
+            (vec![2, 2], vec![0, 1, 1, 0], "(2, 2)"),
+        ] {
+            let npy = to_npy(&shape, data.clone());
+            let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+            assert_eq!((10 + header_len) % 64, 0);
+            let header = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+            assert!(header.contains(&format!("'shape': {expected}")));
+            assert_eq!(&npy[10 + header_len..], &data);
+        }
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+}
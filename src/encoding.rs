@@ -0,0 +1,107 @@
+use crate::card::{Card, Suit};
+use crate::comb::Comb;
+use crate::view::GameView;
+use crate::PLAYERS_COUNT;
+
+// 52枚のカードとジョーカー
+pub const CARDS_LEN: usize = 53;
+// 手札、場のカード、場の組み合わせの種類、縛り、革命、自分から見た順の枚数、パスの回数
+pub const STATE_LEN: usize = CARDS_LEN * 2 + 3 + 4 + 1 + PLAYERS_COUNT + 1;
+
+// スート順に3から2まで並べ、最後をジョーカーとする
+pub fn get_card_index(card: &Card) -> usize {
+    match card {
+        Card::Normal(suit, rank) => *suit as usize * 13 + i32::from(rank) as usize,
+        Card::Joker => CARDS_LEN - 1,
+    }
+}
+
+// 含まれるカードを1とする
+pub fn encode_cards(cards: &[Card]) -> Vec<f32> {
+    let mut encoded = vec![0.0; CARDS_LEN];
+    for card in cards {
+        encoded[get_card_index(card)] = 1.0;
+    }
+    encoded
+}
+
+// 出したカードを1とする(パスは全て0)
+pub fn encode_comb(comb: Option<&Comb>) -> Vec<f32> {
+    match comb {
+        Some(Comb::Single(card)) => encode_cards(&[*card]),
+        Some(Comb::Multi(cards)) | Some(Comb::Seq(cards)) => encode_cards(cards),
+        None => encode_cards(&[]),
+    }
+}
+
+// 手番のプレイヤーから見た場の状態と手札を符号化する(全て0以上の整数)
+pub fn encode_state(view: &GameView, hands: &[Card]) -> Vec<f32> {
+    let mut state = encode_cards(hands);
+    state.extend(encode_comb(view.prev_comb.as_ref()));
+    state.extend(match &view.prev_comb {
+        Some(Comb::Single(_)) => [1.0, 0.0, 0.0],
+        Some(Comb::Multi(_)) => [0.0, 1.0, 0.0],
+        Some(Comb::Seq(_)) => [0.0, 0.0, 1.0],
+        None => [0.0; 3],
+    });
+    let bind_suits = view.bind_suits.as_deref().unwrap_or_default();
+    state.extend(
+        [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade]
+            .map(|suit| f32::from(u8::from(bind_suits.contains(&suit)))),
+    );
+    state.push(f32::from(u8::from(view.is_rev)));
+    let players_count = view.players.len();
+    state.extend((0..PLAYERS_COUNT).map(|i| match i < players_count {
+        true => view.players[(view.idx + i) % players_count].1 as f32,
+        false => 0.0,
+    }));
+    state.push(view.pass_count as f32);
+    state
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{create_deck, Rank};
+
+    #[test]
+    fn test_encode_state() {
+        let mut indices: Vec<usize> = create_deck().iter().map(get_card_index).collect();
+        indices.sort();
+        assert_eq!(indices, (0..CARDS_LEN).collect::<Vec<_>>());
+        let view = GameView {
+            idx: 1,
+            prev_comb: Some(Comb::Multi(vec![
+                Card::Normal(Suit::Heart, Rank::Five),
+                Card::Joker,
+            ])),
+            bind_suits: Some(vec![Suit::Heart]),
+            is_rev: true,
+            players: vec![
+                ("A".to_owned(), 3),
+                ("B".to_owned(), 5),
+                ("C".to_owned(), 0),
+                ("D".to_owned(), 8),
+            ],
+            pass_count: 1,
+            ..Default::default()
+        };
+        let hands = [Card::Normal(Suit::Club, Rank::Three)];
+        let state = encode_state(&view, &hands);
+        assert_eq!(state.len(), STATE_LEN);
+        for (i, expected) in [
+            (0, 1.0),
+            (CARDS_LEN + 28, 1.0),
+            (CARDS_LEN * 2 - 1, 1.0),
+            (CARDS_LEN * 2 + 1, 1.0),
+            (CARDS_LEN * 2 + 5, 1.0),
+            (CARDS_LEN * 2 + 7, 1.0),
+            (CARDS_LEN * 2 + 8, 5.0),
+            (CARDS_LEN * 2 + 11, 3.0),
+            (STATE_LEN - 1, 1.0),
+        ] {
+            assert_eq!(state[i], expected, "{i}");
+        }
+        assert_eq!(state.iter().sum::<f32>(), 6.0 + 16.0 + 1.0);
+    }
+}
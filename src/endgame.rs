@@ -0,0 +1,220 @@
+use crate::card::{self, Card};
+use crate::comb::Comb;
+use crate::field::Field;
+use crate::moves::{get_all_combs, get_legal_combs, remove_cards};
+use crate::validator::Validator;
+
+// この枚数以下の手札なら上がり方を探す
+pub const PLAN_HANDS: usize = 6;
+
+// 誰にも返されずに手札を出し切れる組み合わせの順番を探す(最後の組み合わせは返されてもよい)
+// 見えていないカードを全て1人が持っているとみなすので、見つかれば他のプレイヤーの手札に関わらず上がれる
+pub fn find_out(hands: &[Card], validator: &dyn Validator) -> Option<Vec<Comb>> {
+    if hands.is_empty() || hands.len() > PLAN_HANDS {
+        return None;
+    }
+    let mut view = validator.get_view();
+    let me = view.idx;
+    view.players[me].1 = hands.len();
+    // 他に手札の残っているプレイヤーがいなければ探さない
+    if view.players.iter().filter(|(_, count)| *count > 0).count() < 2 {
+        return None;
+    }
+    let played: Vec<Card> = validator
+        .get_history()
+        .iter()
+        .flat_map(|(_, comb)| comb.get_cards().to_vec())
+        .collect();
+    let unseen: Vec<Card> = card::create_deck()
+        .into_iter()
+        .filter(|card| !hands.contains(card) && !played.contains(card))
+        .collect();
+    let responses: Vec<Comb> = get_all_combs(&unseen)
+        .into_iter()
+        .map(|(comb, _)| comb)
+        .collect();
+    search(hands, &Field::from_view(&view), me, &responses)
+}
+
+fn search(hands: &[Card], field: &Field, me: usize, responses: &[Comb]) -> Option<Vec<Comb>> {
+    for (comb, indices) in get_legal_combs(hands, field) {
+        let rest: Vec<Card> = (0..hands.len())
+            .filter(|i| !indices.contains(i))
+            .map(|i| hands[i])
+            .collect();
+        let mut next = field.clone();
+        let Ok(outcome) = next.put(Some(&comb), rest.len()) else {
+            continue;
+        };
+        if rest.is_empty() {
+            // 反則上がりになる組み合わせでは上がらない
+            if outcome.finish.is_some_and(|finish| !finish.foul) {
+                return Some(vec![comb]);
+            }
+            continue;
+        }
+        // 8切りなどで場が流れなければ、誰も返せずに全員がパスして親に戻る必要がある
+        let is_lead = next.get_idx() == me && next.get_prev_comb().is_none();
+        if !is_lead
+            && (responses.iter().any(|response| next.is_valid(response))
+                || !pass_around(&mut next, me))
+        {
+            continue;
+        }
+        if let Some(mut plan) = search(&rest, &next, me, responses) {
+            plan.insert(0, comb);
+            return Some(plan);
+        }
+    }
+    None
+}
+
+// 他のプレイヤーがパスを続けて、場が流れてmeが親になるまで進める
+fn pass_around(field: &mut Field, me: usize) -> bool {
+    for _ in 0..field.get_view().players.len() {
+        if field.get_idx() == me && field.get_prev_comb().is_none() {
+            return true;
+        }
+        let idx = field.get_idx();
+        let count = field.get_view().players[idx].1;
+        if field.put(None, count).is_err() {
+            return false;
+        }
+    }
+    field.get_idx() == me && field.get_prev_comb().is_none()
+}
+
+// 見つけた上がり方を覚え、以降の手番で順に出す
+#[derive(Debug, Default)]
+pub struct Planner {
+    plan: Vec<Comb>,
+}
+
+impl Planner {
+    // ラウンドが変わったら覚えた上がり方を捨てる
+    pub fn clear(&mut self) {
+        self.plan.clear();
+    }
+
+    // 上がり方が見つかっていれば次に出す組み合わせ
+    // (覚えた組み合わせを出せなくなっていれば探し直す)
+    pub fn next(&mut self, hands: &[Card], validator: &dyn Validator) -> Option<Comb> {
+        let playable = self
+            .plan
+            .first()
+            .is_some_and(|comb| validator.is_valid(comb) && remove_cards(hands, comb).is_some());
+        if !playable {
+            self.plan = find_out(hands, validator).unwrap_or_default();
+        }
+        (!self.plan.is_empty()).then(|| self.plan.remove(0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+
+    // meの席の手札と、meが親になる前に場に出された組み合わせ
+    fn create_field(hands: &[Card], played: &[Comb]) -> Field {
+        let me = 0;
+        let mut field = Field::new(4, me);
+        let counts = vec![hands.len() + played.len(), 10, 10, 10];
+        field.set_players(vec![String::new(); 4], counts);
+        for comb in played {
+            let count = field.get_view().players[me].1 - 1;
+            field.put(Some(comb), count).unwrap();
+            assert!(pass_around(&mut field, me));
+        }
+        field
+    }
+
+    #[test]
+    fn test_find_out() {
+        let c = Card::Normal;
+        let cases = [
+            // ジョーカーは誰にも返されないので先に出す(ジョーカーで上がると反則上がり)
+            (
+                vec![Card::Joker, c(Suit::Club, Rank::Three)],
+                vec![],
+                Some(vec![
+                    Comb::Single(Card::Joker),
+                    Comb::Single(c(Suit::Club, Rank::Three)),
+                ]),
+            ),
+            // 見えていないジョーカーに返される
+            (
+                vec![c(Suit::Spade, Rank::Two), c(Suit::Club, Rank::Three)],
+                vec![],
+                None,
+            ),
+            // ジョーカーが出た後なら2は返されない
+            (
+                vec![c(Suit::Spade, Rank::Two), c(Suit::Club, Rank::Three)],
+                vec![Comb::Single(Card::Joker)],
+                Some(vec![
+                    Comb::Single(c(Suit::Spade, Rank::Two)),
+                    Comb::Single(c(Suit::Club, Rank::Three)),
+                ]),
+            ),
+            // 8切りで場を流してから出す
+            (
+                vec![c(Suit::Diamond, Rank::Eight), c(Suit::Club, Rank::Five)],
+                vec![],
+                Some(vec![
+                    Comb::Single(c(Suit::Diamond, Rank::Eight)),
+                    Comb::Single(c(Suit::Club, Rank::Five)),
+                ]),
+            ),
+            // 1回で出し切れる
+            (
+                vec![c(Suit::Club, Rank::Five), c(Suit::Heart, Rank::Five)],
+                vec![],
+                Some(vec![Comb::Multi(vec![
+                    c(Suit::Club, Rank::Five),
+                    c(Suit::Heart, Rank::Five),
+                ])]),
+            ),
+            // 返されない組み合わせが足りない
+            (
+                vec![c(Suit::Club, Rank::Five), c(Suit::Heart, Rank::Six)],
+                vec![],
+                None,
+            ),
+        ];
+        for (hands, played, expected) in cases {
+            let field = create_field(&hands, &played);
+            assert_eq!(find_out(&hands, &field), expected, "{hands:?}");
+        }
+        // 手札が多ければ探さない
+        let hands: Vec<Card> = card::create_deck()
+            .into_iter()
+            .take(PLAN_HANDS + 1)
+            .collect();
+        assert_eq!(find_out(&hands, &create_field(&hands, &[])), None);
+    }
+
+    #[test]
+    fn test_planner() {
+        let c = Card::Normal;
+        let hands = vec![Card::Joker, c(Suit::Club, Rank::Three)];
+        let mut field = create_field(&hands, &[]);
+        let mut planner = Planner::default();
+        assert_eq!(
+            planner.next(&hands, &field),
+            Some(Comb::Single(Card::Joker))
+        );
+        field.put(Some(&Comb::Single(Card::Joker)), 1).unwrap();
+        assert!(pass_around(&mut field, 0));
+        // 覚えた順に出す
+        let three = Comb::Single(c(Suit::Club, Rank::Three));
+        assert_eq!(planner.next(&hands[1..], &field), Some(three));
+        assert_eq!(planner.next(&[], &field), None);
+        // 覚えた組み合わせが手札になければ探し直す
+        planner.plan = vec![Comb::Single(c(Suit::Spade, Rank::Two))];
+        let hands = [c(Suit::Club, Rank::Three), c(Suit::Heart, Rank::Four)];
+        assert_eq!(planner.next(&hands, &field), None);
+        planner.clear();
+        assert!(planner.plan.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::card::Card;
+use crate::comb::Comb;
+use crate::field::{PlayError, TurnOutcome};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(tag = "type"))]
+pub enum GameEvent {
+    // ラウンド開始
+    Start {
+        names: Vec<String>,
+        hands: Vec<Vec<Card>>,
+    },
+    // カードを場に出すかパス
+    Turn {
+        idx: usize,
+        comb: Option<Comb>,
+        hands_count: usize,
+        outcome: TurnOutcome,
+    },
+    // 場に出せなかった手(同じ席の手番をやり直す)
+    Reject {
+        idx: usize,
+        error: PlayError,
+    },
+    // NPCが手を選んだ理由(--explain-aiを指定した場合のみ)
+    Reason {
+        idx: usize,
+        reason: String,
+    },
+    // 場が流れて親になったプレイヤー
+    Lead {
+        idx: usize,
+    },
+    // ラウンド終了
+    End {
+        player_rank: Vec<usize>,
+    },
+    // 大貧民(貧民)から大富豪(富豪)へ最も強いカードを渡す
+    Tribute {
+        from: usize,
+        to: usize,
+        cards: Vec<Card>,
+    },
+    // 大富豪(富豪)が選んだカードを返す
+    Return {
+        from: usize,
+        to: usize,
+        cards: Vec<Card>,
+    },
+    // カードの交換の終わり
+    Exchange,
+    // ラウンドの途中でゲームを中断
+    Quit {
+        // 席毎の順位(まだ上がっていなければNone)と手札の枚数
+        places: Vec<Option<usize>>,
+        hands_counts: Vec<usize>,
+    },
+    // 抜けたプレイヤーの席をNPCが引き継いだ
+    Substitute {
+        idx: usize,
+    },
+    // 人間のプレイヤーの前の手番の始めに戻した(戻せなければturnsは0)
+    Undo {
+        // 取り消した手の数
+        turns: usize,
+    },
+    // ゲームの保存
+    Save {
+        path: PathBuf,
+        result: Result<(), String>,
+    },
+}
+
+impl GameEvent {
+    // 指定した席以外の手札を隠す
+    pub fn censor(&self, seat: Option<usize>) -> GameEvent {
+        match self {
+            GameEvent::Start { names, hands } => GameEvent::Start {
+                names: names.clone(),
+                hands: (0..hands.len())
+                    .map(|i| {
+                        if Some(i) == seat {
+                            hands[i].clone()
+                        } else {
+                            vec![]
+                        }
+                    })
+                    .collect(),
+            },
+            // 交換したカードは渡した側と受け取った側にだけ見せる
+            GameEvent::Tribute { from, to, cards } => GameEvent::Tribute {
+                from: *from,
+                to: *to,
+                cards: get_visible_cards(cards, seat, &[*from, *to]),
+            },
+            GameEvent::Return { from, to, cards } => GameEvent::Return {
+                from: *from,
+                to: *to,
+                cards: get_visible_cards(cards, seat, &[*from, *to]),
+            },
+            // 理由は手札の内容に触れるので本人にだけ見せる
+            GameEvent::Reason { idx, reason } => GameEvent::Reason {
+                idx: *idx,
+                reason: match seat == Some(*idx) {
+                    true => reason.clone(),
+                    false => String::new(),
+                },
+            },
+            event => event.clone(),
+        }
+    }
+}
+
+fn get_visible_cards(cards: &[Card], seat: Option<usize>, seats: &[usize]) -> Vec<Card> {
+    match seat {
+        Some(seat) if seats.contains(&seat) => cards.to_vec(),
+        _ => vec![],
+    }
+}
@@ -0,0 +1,80 @@
+use crate::event::GameEvent;
+use crate::i18n::trf;
+use crate::observer::Observer;
+use serde::Serialize;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize)]
+struct Record<'a> {
+    // UNIX時間(ミリ秒)
+    timestamp: u64,
+    #[serde(flatten)]
+    event: &'a GameEvent,
+}
+
+// 全てのイベントを1行ずつJSONで書き出す
+pub struct EventLog<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> EventLog<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> Observer for EventLog<W> {
+    fn notify(&mut self, event: &GameEvent) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let result = serde_json::to_string(&Record { timestamp, event })
+            .map_err(std::io::Error::from)
+            .and_then(|line| writeln!(self.writer, "{line}"))
+            .and_then(|_| self.writer.flush());
+        if let Err(e) = result {
+            eprintln!("{}", trf("log_write_failed", &[("error", &e)]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Card, Rank, Suit};
+    use crate::comb::Comb;
+    use crate::field::{Effect, TurnOutcome};
+
+    #[test]
+    fn test_event_log() {
+        let mut log = EventLog::new(vec![]);
+        for event in [
+            GameEvent::Turn {
+                idx: 1,
+                comb: Some(Comb::Single(Card::Normal(Suit::Heart, Rank::Eight))),
+                hands_count: 3,
+                outcome: TurnOutcome {
+                    effects: vec![Effect::EightCut],
+                    ..TurnOutcome::default()
+                },
+            },
+            GameEvent::Exchange,
+        ] {
+            log.notify(&event);
+        }
+        let text = String::from_utf8(log.writer).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0]["timestamp"].as_u64().unwrap() > 0);
+        assert_eq!(lines[0]["type"], "Turn");
+        assert_eq!(lines[0]["idx"], 1);
+        assert_eq!(lines[0]["outcome"]["effects"][0], "eight_cut");
+        assert_eq!(lines[0]["outcome"]["cleared"], false);
+        assert_eq!(lines[1]["type"], "Exchange");
+    }
+}
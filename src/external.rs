@@ -0,0 +1,282 @@
+use crate::card::{Card, Order};
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::game::Game;
+use crate::i18n::tr;
+use crate::mcts::get_candidates;
+use crate::moves::{check_move, remove_cards};
+use crate::observer::Observer;
+use crate::player::{take_from_hands, Action, Player};
+use crate::rules::RuleSet;
+use crate::strategy::Strategy;
+use crate::validator::Validator;
+use crate::view::GameView;
+use crate::{NPC_NAMES, PLAYERS_COUNT};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// 受け取って検証済みの人間の手(Noneならパス)
+type Pending = Rc<RefCell<Option<Option<Comb>>>>;
+
+// 1手ずつ渡された手を出すプレイヤー
+struct PendingPlayer {
+    name: String,
+    hands: Vec<Card>,
+    pending: Pending,
+}
+
+impl Player for PendingPlayer {
+    fn init(&mut self, hands: Vec<Card>) {
+        self.hands = hands;
+    }
+
+    fn count_hands(&self) -> usize {
+        self.hands.len()
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_hands(&self) -> &[Card] {
+        &self.hands
+    }
+
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+        take_from_hands(&mut self.hands, cards)
+    }
+
+    fn receive_cards(&mut self, cards: Vec<Card>) {
+        self.hands.extend(cards);
+    }
+
+    fn sort_hands(&mut self, order: Order) {
+        self.hands.sort_by(order.get_comparator());
+    }
+
+    fn play(&mut self, _validator: &dyn Validator) -> Action {
+        let comb = self.pending.borrow_mut().take().flatten();
+        if let Some(hands_left) = comb.as_ref().and_then(|c| remove_cards(&self.hands, c)) {
+            self.hands = hands_left;
+        }
+        Action::Move(comb)
+    }
+
+    fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
+        (0..cards_count).map(|_| self.hands.remove(0)).collect()
+    }
+}
+
+// ラウンドの進行を記録する
+struct Recorder(Vec<GameEvent>);
+
+impl Observer for Recorder {
+    fn notify(&mut self, event: &GameEvent) {
+        self.0.push(event.clone());
+    }
+}
+
+// get_state_jsonで返す状態
+#[derive(Serialize)]
+struct State<'a> {
+    seat: usize,
+    view: GameView,
+    hands: &'a [Card],
+    // その席の手番ならtrue
+    turn: bool,
+    events: Vec<GameEvent>,
+    // ラウンドが終わっていれば順位
+    player_rank: Option<&'a [usize]>,
+}
+
+// 人間とNPCが対戦し、外から人間の手を受け取るたびに進めるゲーム
+pub struct ExternalGame {
+    game: Game,
+    // 人間の席
+    seats: Vec<usize>,
+    pending: Pending,
+    recorder: Recorder,
+    player_rank: Option<Vec<usize>>,
+}
+
+impl ExternalGame {
+    // seedで配り方と人間の席を決め、残りの席をNPCで埋める
+    pub fn new(seed: u64, humans: usize, npc: Strategy) -> Self {
+        let humans = humans.clamp(1, PLAYERS_COUNT);
+        let names = match humans {
+            1 => vec!["User".to_owned()],
+            _ => (1..=humans).map(|n| format!("User{n}")).collect(),
+        };
+        Self::with_names(seed, names, npc, RuleSet::default())
+    }
+
+    // 人間のプレイヤーの名前とルールを指定してゲームを作る
+    pub fn with_names(seed: u64, names: Vec<String>, npc: Strategy, rules: RuleSet) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut seats: Vec<usize> = (0..PLAYERS_COUNT).collect();
+        seats.shuffle(&mut rng);
+        seats.truncate(names.len().clamp(1, PLAYERS_COUNT));
+        seats.sort();
+        let pending = Pending::default();
+        let mut npc_names = NPC_NAMES.iter();
+        let players = (0..PLAYERS_COUNT)
+            .map(|i| match seats.iter().position(|seat| *seat == i) {
+                Some(n) => Box::new(PendingPlayer {
+                    name: names
+                        .get(n)
+                        .cloned()
+                        .unwrap_or_else(|| format!("User{}", n + 1)),
+                    hands: vec![],
+                    pending: pending.clone(),
+                }) as Box<dyn Player>,
+                None => {
+                    let name = npc_names.next().map_or("Npc", |name| *name);
+                    npc.create_npc(name.to_owned(), rng.gen())
+                }
+            })
+            .collect();
+        let mut game = Self {
+            game: Game::new(players, 0, rng.gen()).with_rules(rules),
+            seats,
+            pending,
+            recorder: Recorder(vec![]),
+            player_rank: None,
+        };
+        game.game.start_round(&mut game.recorder);
+        game.advance();
+        game
+    }
+
+    pub fn get_seats(&self) -> &[usize] {
+        &self.seats
+    }
+
+    // 人間の手番ならその席
+    pub fn get_turn(&self) -> Option<usize> {
+        if self.player_rank.is_some() {
+            return None;
+        }
+        let idx = self.game.get_field().get_idx();
+        self.seats.contains(&idx).then_some(idx)
+    }
+
+    pub fn get_hands(&self, seat: usize) -> &[Card] {
+        self.game.get_hands(seat)
+    }
+
+    pub fn get_view(&self) -> GameView {
+        self.game.get_field().get_view()
+    }
+
+    // 人間の手番かラウンドの終わりまでNPCの手番を進める
+    fn advance(&mut self) {
+        while self.player_rank.is_none() {
+            if self.game.is_over() {
+                self.player_rank = Some(self.game.end_round(&mut self.recorder));
+            } else if self.get_turn().is_some() && self.pending.borrow().is_none() {
+                break;
+            } else {
+                self.game.step(&mut self.recorder);
+            }
+        }
+    }
+
+    // 手(Noneならパス)を出し、次の人間の手番までNPCの手番を進める
+    pub fn play(&mut self, comb: Option<Comb>) -> Result<(), String> {
+        let Some(seat) = self.get_turn() else {
+            return Err(tr("not_human_turn").to_owned());
+        };
+        let hands = self.game.get_hands(seat).to_vec();
+        check_move(self.game.get_field(), &hands, comb.as_ref())?;
+        *self.pending.borrow_mut() = Some(comb);
+        self.advance();
+        Ok(())
+    }
+
+    // 人間の手番なら出せる手の一覧(Noneはパス)
+    pub fn get_legal_moves(&mut self) -> Vec<Option<Comb>> {
+        let Some(seat) = self.get_turn() else {
+            return vec![];
+        };
+        let hands = self.game.get_hands(seat).to_vec();
+        get_candidates(&hands, self.game.get_field())
+            .into_iter()
+            .map(|candidate| candidate.map(|(comb, _)| comb))
+            .collect()
+    }
+
+    pub fn get_player_rank(&self) -> Option<&[usize]> {
+        self.player_rank.as_deref()
+    }
+
+    // 席から見えるこのラウンドの進行
+    pub fn get_events(&self, seat: usize) -> Vec<GameEvent> {
+        self.recorder
+            .0
+            .iter()
+            .map(|event| event.censor(Some(seat)))
+            .collect()
+    }
+
+    // 席から見える状態をJSONで取得する
+    pub fn get_state_json(&mut self, seat: usize) -> String {
+        let view = self.get_view();
+        let turn = self.get_turn() == Some(seat);
+        let state = State {
+            seat,
+            view,
+            hands: self.game.get_hands(seat),
+            turn,
+            events: self.get_events(seat),
+            player_rank: self.player_rank.as_deref(),
+        };
+        serde_json::to_string(&state).unwrap()
+    }
+
+    // ラウンドが終わっていればカードを配り直して次のラウンドを始める
+    pub fn next_round(&mut self) -> bool {
+        let Some(player_rank) = self.player_rank.take() else {
+            return false;
+        };
+        self.recorder.0.clear();
+        self.game.next_round(&player_rank, &mut self.recorder);
+        self.game.start_round(&mut self.recorder);
+        self.advance();
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_external_game() {
+        for (seed, humans) in (0..10).zip([1, 2, 4].into_iter().cycle()) {
+            let mut game = ExternalGame::new(seed, humans, Strategy::Min);
+            assert_eq!(game.get_seats().len(), humans);
+            assert!(!game.recorder.0.is_empty());
+            while game.player_rank.is_none() {
+                assert!(game.get_turn().is_some());
+                // 手札にないカードは出せない
+                let joker = Some(Comb::Multi(vec![Card::Joker, Card::Joker]));
+                assert!(game.play(joker).is_err());
+                let comb = game.get_legal_moves().into_iter().next().unwrap();
+                game.play(comb).unwrap();
+            }
+            assert!(game.get_legal_moves().is_empty());
+            assert!(game.play(None).is_err());
+            let state: serde_json::Value = serde_json::from_str(&game.get_state_json(0)).unwrap();
+            assert_eq!(
+                state["player_rank"].as_array().unwrap().len(),
+                PLAYERS_COUNT
+            );
+            assert!(game.next_round());
+            assert!(!game.next_round());
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::external::ExternalGame;
+use crate::i18n::tr;
+use crate::strategy::Strategy;
+use std::ffi::{c_char, CStr, CString};
+
+// C側からは中身の見えないゲーム
+pub struct DaifugoGame(ExternalGame);
+
+fn to_c_string(s: String) -> *mut c_char {
+    // JSONやメッセージにはNUL文字が含まれない
+    CString::new(s).unwrap().into_raw()
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    match s.is_null() {
+        true => None,
+        false => CStr::from_ptr(s).to_str().ok(),
+    }
+}
+
+/// seedで配り方と席を決め、npc(min、heuristic、mcts)と対戦するゲームを作る。
+/// npcが不明ならNULLを返す。
+///
+/// # Safety
+/// npcはNUL終端の文字列でなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn daifugo_new_game(seed: u64, npc: *const c_char) -> *mut DaifugoGame {
+    match to_str(npc).and_then(|npc| npc.parse::<Strategy>().ok()) {
+        Some(npc) => Box::into_raw(Box::new(DaifugoGame(ExternalGame::new(seed, 1, npc)))),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// gameはdaifugo_new_gameで作り、まだ解放していないものでなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn daifugo_free_game(game: *mut DaifugoGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// 人間の席から見える状態をJSONで返す(daifugo_free_stringで解放する)。
+///
+/// # Safety
+/// gameはdaifugo_new_gameで作ったものでなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn daifugo_state_json(game: *mut DaifugoGame) -> *mut c_char {
+    let seat = (*game).0.get_seats()[0];
+    to_c_string((*game).0.get_state_json(seat))
+}
+
+/// 人間の手番なら出せる手の一覧をJSONで返す(nullはパス、daifugo_free_stringで解放する)。
+///
+/// # Safety
+/// gameはdaifugo_new_gameで作ったものでなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn daifugo_legal_moves(game: *mut DaifugoGame) -> *mut c_char {
+    to_c_string(serde_json::to_string(&(*game).0.get_legal_moves()).unwrap())
+}
+
+/// JSONの手(nullならパス)を出し、次の人間の手番までNPCの手番を進める。
+/// 受け付ければNULLを、受け付けなければ理由を返す(daifugo_free_stringで解放する)。
+///
+/// # Safety
+/// gameはdaifugo_new_gameで作ったもの、comb_jsonはNUL終端の文字列でなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn daifugo_play(
+    game: *mut DaifugoGame,
+    comb_json: *const c_char,
+) -> *mut c_char {
+    let result = to_str(comb_json)
+        .ok_or_else(|| tr("not_utf8").to_owned())
+        .and_then(|json| serde_json::from_str(json).map_err(|e| e.to_string()))
+        .and_then(|comb| (*game).0.play(comb));
+    match result {
+        Ok(()) => std::ptr::null_mut(),
+        Err(message) => to_c_string(message),
+    }
+}
+
+/// ラウンドが終わっていれば次のラウンドを始めてtrueを返す。
+///
+/// # Safety
+/// gameはdaifugo_new_gameで作ったものでなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn daifugo_next_round(game: *mut DaifugoGame) -> bool {
+    (*game).0.next_round()
+}
+
+/// # Safety
+/// sはこのライブラリが返した文字列で、まだ解放していないものでなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn daifugo_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ffi() {
+        unsafe {
+            assert!(daifugo_new_game(0, c"unknown".as_ptr()).is_null());
+            let game = daifugo_new_game(0, c"min".as_ptr());
+            for (comb_json, accepted) in [
+                (c"[]", false),
+                (c"{\"Multi\":[\"Joker\",\"Joker\"]}", false),
+            ] {
+                let message = daifugo_play(game, comb_json.as_ptr());
+                assert_eq!(message.is_null(), accepted);
+                daifugo_free_string(message);
+            }
+            let moves = daifugo_legal_moves(game);
+            let json: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(moves).to_str().unwrap()).unwrap();
+            let comb_json = CString::new(json[0].to_string()).unwrap();
+            daifugo_free_string(moves);
+            assert!(daifugo_play(game, comb_json.as_ptr()).is_null());
+            let state = daifugo_state_json(game);
+            assert!(CStr::from_ptr(state).to_str().unwrap().contains("\"seat\""));
+            daifugo_free_string(state);
+            daifugo_free_game(game);
+        }
+    }
+}
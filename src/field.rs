@@ -1,27 +1,132 @@
-use crate::card::{cmp_order, cmp_order_reversely, cmp_rank, cmp_rank_reversely, Card, Rank};
+use crate::card::{
+    cards_to_ascii, cmp_rank, cmp_rank_reversely, suit_to_ascii, Card, Order, Rank, Suit, DECK_LEN,
+};
 use crate::comb::Comb;
 use crate::indexer::Indexer;
+use crate::rules::{FoulRule, LeadPassRule, RuleSet, SeqRule};
 use crate::suit_binder::SuitBinder;
 use crate::validator::Validator;
-use bitflags::bitflags;
+use crate::view::GameView;
+use itertools::Itertools;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
-bitflags! {
-    pub struct Flags: u32 {
-        const BIND  =  0b00000001;
-        const EIGHT =  0b00000010;
-        const REV   =  0b00000100;
-        const OUT   =  0b00001000;
-        const LOSE  =  0b00010000;
+// 場に出したカードで発動したルール
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Effect {
+    EightCut,
+    Bind,
+    Revolution,
+    JackBack,
+    FiveSkip,
+    // 都落ちで最下位になった前のラウンドの大富豪の席
+    CapitalFall(usize),
+    // 反則上がりで出したカードが無効になった(FoulRule::Void)
+    FoulVoid,
+    // 8返しで場の8に8を出した(8切りは返した後に全員がパスすると成立する)
+    EightReturn,
+    // 5枚の革命でカードの強さが逆転して場が流れた(大革命)
+    SuperRevolution,
+}
+
+// カードを出して上がったプレイヤー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Finish {
+    pub idx: usize,
+    // 決まった順位(0が1位)
+    pub place: usize,
+    // 反則上がりなら空いている最も下の順位になる
+    pub foul: bool,
+}
+
+// Field::putで場に起きたこと
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TurnOutcome {
+    // 発動した順に並べたルール
+    pub effects: Vec<Effect>,
+    pub finish: Option<Finish>,
+    // 場が流れて次の親が決まったか(8切りは出したプレイヤーが続けるので含めない)
+    pub cleared: bool,
+}
+
+impl TurnOutcome {
+    pub fn contains(&self, effect: Effect) -> bool {
+        self.effects.contains(&effect)
+    }
+
+    // 都落ちで最下位になったプレイヤー
+    pub fn get_fallen(&self) -> Option<usize> {
+        self.effects.iter().find_map(|effect| match effect {
+            Effect::CapitalFall(idx) => Some(*idx),
+            _ => None,
+        })
     }
 }
 
+// Field::putで受け付けない手
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PlayError {
+    // 場にカードがないときのパス(LeadPassRule::Rejectの場合)
+    LeadPass,
+    // 全員の順位が決まった後の手
+    RoundOver,
+}
+
+impl PlayError {
+    // 理由を表示する文字列のキー(i18n::trで表示する)
+    pub fn get_key(&self) -> &'static str {
+        match self {
+            PlayError::LeadPass => "cannot_pass",
+            PlayError::RoundOver => "round_over_move",
+        }
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Field {
     prev_comb: Option<Comb>,
     indexer: Indexer,
     binder: SuitBinder,
     pass_counter: usize,
+    pass_count: usize,
     is_rev: bool,
+    // 11バックで場が流れるまでカードの強さが逆転しているか
+    #[cfg_attr(feature = "serde", serde(default))]
+    is_jack_back: bool,
+    names: Vec<String>,
+    hands_counts: Vec<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    rules: RuleSet,
+    #[cfg_attr(feature = "serde", serde(default))]
+    history: Vec<(usize, Comb)>,
+    // 場のカードを出したプレイヤーの席(場が流れたら親になる)
+    #[cfg_attr(feature = "serde", serde(default))]
+    lead: Option<usize>,
+    // 前のラウンドの大富豪の席(都落ちの判定が済んだらNone)
+    #[cfg_attr(feature = "serde", serde(default))]
+    capital: Option<usize>,
+    // 場を流したときの組み合わせのカードの領域(次の組み合わせで使い回す)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    spare: Vec<Card>,
+    // 配られたカードの枚数(途中の状態から作った場合は0)
+    #[cfg_attr(feature = "serde", serde(default))]
+    cards_count: usize,
+    // 8返し: 場の8に同じ枚数の8しか出せない状態か(場が流れるまで続く)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pending_eight: bool,
+    // 場のカードに対してパスしたプレイヤーの席(パスした順、カードが出されるか場が流れたら空にする)
+    #[cfg_attr(feature = "serde", serde(default))]
+    passed: Vec<usize>,
 }
 
 impl Field {
@@ -31,10 +136,76 @@ impl Field {
             indexer: Indexer::new(players_count, start_idx),
             binder: SuitBinder::new(),
             pass_counter: 0,
+            pass_count: 0,
             is_rev: false,
+            is_jack_back: false,
+            names: (0..players_count).map(|_| String::new()).collect(),
+            hands_counts: vec![0; players_count],
+            rules: RuleSet::default(),
+            history: vec![],
+            lead: None,
+            capital: None,
+            spare: vec![],
+            cards_count: 0,
+            pending_eight: false,
+            passed: vec![],
+        }
+    }
+
+    pub fn from_view(view: &GameView) -> Self {
+        let hands_counts: Vec<usize> = view.players.iter().map(|(_, count)| *count).collect();
+        let indexer = Indexer::from_hands_counts(&hands_counts, view.idx);
+        let mut binder = SuitBinder::new();
+        binder.set_bind_suits(view.bind_suits.clone());
+        // 場を流すまでに必要なパスの回数を推定する
+        let pass_counter = match view.prev_comb {
+            Some(_) => (indexer.count_active_players() - 1)
+                .saturating_sub(view.pass_count)
+                .max(1),
+            None => 0,
+        };
+        Self {
+            prev_comb: view.prev_comb.clone(),
+            indexer,
+            binder,
+            pass_counter,
+            pass_count: view.pass_count,
+            is_rev: view.is_rev,
+            is_jack_back: view.is_jack_back,
+            names: view.players.iter().map(|(name, _)| name.clone()).collect(),
+            hands_counts,
+            rules: view.rules,
+            // 途中の状態からは場に出された組み合わせがわからない
+            history: vec![],
+            lead: view.lead,
+            // 途中の状態では都落ちを判定しない
+            capital: None,
+            spare: vec![],
+            // 途中の状態からは配られたカードの枚数がわからない
+            cards_count: 0,
+            pending_eight: view.pending_eight,
+            passed: view.passed.clone(),
         }
     }
 
+    pub fn set_players(&mut self, names: Vec<String>, hands_counts: Vec<usize>) {
+        self.names = names;
+        self.cards_count = hands_counts.iter().sum();
+        self.hands_counts = hands_counts;
+    }
+
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        self.rules = rules;
+    }
+
+    pub fn get_rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    pub fn set_capital(&mut self, capital: Option<usize>) {
+        self.capital = capital;
+    }
+
     pub fn get_idx(&self) -> usize {
         self.indexer.get_idx()
     }
@@ -47,60 +218,362 @@ impl Field {
         self.indexer.count_active_players()
     }
 
-    pub fn put(&mut self, new_comb: Option<Comb>, hands_count: usize) -> Flags {
-        let mut flags = Flags::empty();
+    pub fn get_places(&self) -> Vec<Option<usize>> {
+        self.indexer.get_places()
+    }
+
+    // 場のカードを出したプレイヤー(場にカードがなければNone)
+    pub fn get_lead_player(&self) -> Option<usize> {
+        self.lead
+    }
+
+    // 場のカードに対してパスしたプレイヤーの席(パスした順)
+    pub fn get_passed(&self) -> &[usize] {
+        &self.passed
+    }
+
+    // 場にカードを出すかパスする(パスできない場合や、ラウンドが終わった後は場の状態を変えない)
+    pub fn put(
+        &mut self,
+        new_comb: Option<&Comb>,
+        hands_count: usize,
+    ) -> Result<TurnOutcome, PlayError> {
+        if self.indexer.count_active_players() == 0 {
+            return Err(PlayError::RoundOver);
+        }
+        if new_comb.is_none() && self.prev_comb.is_none() {
+            if self.rules.lead_pass == LeadPassRule::Reject {
+                tracing::debug!(
+                    idx = self.indexer.get_idx(),
+                    "場にカードがないパスを受け付けない"
+                );
+                return Err(PlayError::LeadPass);
+            }
+            self.indexer.next();
+            return Ok(TurnOutcome::default());
+        }
+        let outcome = self.put_comb(new_comb, hands_count);
+        tracing::debug!(
+            comb = %new_comb.map_or("-".to_owned(), |comb| cards_to_ascii(comb.get_cards())),
+            hands_count,
+            ?outcome,
+            next = ?(self.indexer.count_active_players() > 0).then(|| self.indexer.get_idx()),
+            pass_counter = self.pass_counter,
+            "場を更新"
+        );
+        #[cfg(feature = "invariants")]
+        if let Err(e) = self.check_invariants() {
+            panic!(
+                "場の状態が矛盾しています: {e}\n{}",
+                self.get_debug_lines().join("\n")
+            );
+        }
+        Ok(outcome)
+    }
+
+    // 内部の状態が矛盾していないかを確かめる(invariantsフィーチャーではputの度に確かめる)
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.indexer.check_invariants()?;
+        let players_count = self.names.len();
+        if self.hands_counts.len() != players_count {
+            return Err(format!(
+                "手札の枚数が{}人分ではありません: {:?}",
+                players_count, self.hands_counts
+            ));
+        }
+        // 全てのカードを配った場合、手札と場に出されたカードの合計は変わらない
+        if self.cards_count == DECK_LEN {
+            let played: usize = self
+                .history
+                .iter()
+                .map(|(_, comb)| comb.get_cards().len())
+                .sum();
+            let total = self.hands_counts.iter().sum::<usize>() + played;
+            if total != self.cards_count {
+                return Err(format!(
+                    "カードの枚数が合いません: {total}枚 (配られたのは{}枚)",
+                    self.cards_count
+                ));
+            }
+            if let Some(player) = self
+                .indexer
+                .get_active_players()
+                .iter()
+                .find(|p| self.hands_counts[**p] == 0)
+            {
+                return Err(format!("席{player}は手札がないのに上がっていません"));
+            }
+        }
+        // 場が流れるまでのパスの数は残りのプレイヤーの数を超えない
+        let active = self.indexer.count_active_players();
+        if active > 0 && self.pass_counter > active {
+            return Err(format!(
+                "pass_counterが範囲外です: {} (残り{active}人)",
+                self.pass_counter
+            ));
+        }
+        if self.prev_comb.is_some() && self.pass_counter == 0 {
+            return Err("場にカードがあるのにpass_counterが0です".to_owned());
+        }
+        if self.pass_count >= players_count.max(1) {
+            return Err(format!("pass_countが範囲外です: {}", self.pass_count));
+        }
+        if self.lead.is_some() && self.prev_comb.is_none() {
+            return Err("場にカードがないのに親が決まっています".to_owned());
+        }
+        Ok(())
+    }
+
+    fn put_comb(&mut self, new_comb: Option<&Comb>, hands_count: usize) -> TurnOutcome {
+        let mut outcome = TurnOutcome::default();
+        self.hands_counts[self.indexer.get_idx()] = hands_count;
         match new_comb {
             Some(comb) => {
+                let idx = self.indexer.get_idx();
+                self.history.push((idx, comb.clone()));
                 self.pass_counter = self.indexer.count_active_players() - 1;
-                let eight_flag = contains_eight(&comb);
+                self.pass_count = 0;
+                self.passed.clear();
+                let eight_flag = self.rules.eight_cut && contains_eight(comb);
+                // 大革命は8切りと同じく場を流す
+                let super_rev =
+                    self.rules.revolution && self.rules.super_revolution && is_super_rev_comb(comb);
+                // 8返しでは8を出しても場を流さず、他のプレイヤーに返す機会を与える
+                let pending = eight_flag && self.rules.eight_return && !super_rev;
+                let clears = (eight_flag && !pending) || super_rev;
                 if hands_count > 0 {
                     if eight_flag {
                         // 8切り
-                        flags.insert(Flags::EIGHT);
+                        outcome.effects.push(match self.pending_eight {
+                            true => Effect::EightReturn,
+                            false => Effect::EightCut,
+                        });
+                    }
+                    if eight_flag || super_rev {
                         self.binder.clear();
-                    } else {
+                    }
+                    if !clears {
                         // 次のプレイヤーのターンに移る
                         self.indexer.next();
                     }
-                } else if contains_especial_card(&comb, self.is_rev) {
+                } else if self.is_foul(comb) {
                     // 反則上がり
                     self.indexer.set_rank_back();
-                    flags.insert(Flags::LOSE);
+                    outcome.finish = self.get_finish(idx, true);
+                    if self.rules.foul == FoulRule::Void {
+                        // 出したカードを無効にして場を流し、次のプレイヤーから出す
+                        self.clear_prev_comb();
+                        self.pass_counter = 0;
+                        self.pass_count = 0;
+                        self.passed.clear();
+                        self.binder.clear();
+                        outcome.effects.push(Effect::FoulVoid);
+                        outcome.cleared = true;
+                        return outcome;
+                    }
                 } else {
                     // 上がり
                     self.indexer.set_rank_front();
-                    flags.insert(Flags::OUT);
+                    outcome.finish = self.get_finish(idx, false);
+                    self.check_capital(idx, &mut outcome);
                 }
-                if !eight_flag && !self.binder.is_activate() && self.binder.push(&comb) {
-                    flags.insert(Flags::BIND);
+                if self.rules.bind
+                    && !self.is_heads_up()
+                    && !eight_flag
+                    && !super_rev
+                    && !self.binder.is_activate()
+                    && self.binder.push(comb)
+                {
+                    outcome.effects.push(Effect::Bind);
                 }
-                if is_rev_comb(&comb) {
+                let revolution = self.rules.revolution && is_rev_comb(comb);
+                if revolution {
                     // カードの強さが逆転する
                     self.is_rev = !self.is_rev;
-                    flags.insert(Flags::REV);
+                    outcome.effects.push(match super_rev {
+                        true => Effect::SuperRevolution,
+                        false => Effect::Revolution,
+                    });
                 }
-                // 8を含むなら場を流す
-                self.prev_comb = if eight_flag { None } else { Some(comb) }
-            }
-            None => {
-                // カウントが0なら場を流す
-                self.pass_counter -= 1;
-                if self.pass_counter == 0 {
-                    self.prev_comb = None;
-                    self.binder.clear();
+                // Jを含む革命は革命だけが効く(Jバックで打ち消さない)
+                if self.rules.jack_back && !revolution && contains_rank(comb, Rank::Jack) {
+                    // 場が流れるまでカードの強さが逆転する
+                    self.is_jack_back = !self.is_jack_back;
+                    outcome.effects.push(Effect::JackBack);
+                }
+                // 8を含むか大革命なら場を流す(8返しでは全員がパスするまで流さない)
+                match clears {
+                    true => self.clear_prev_comb(),
+                    false => {
+                        self.set_prev_comb(comb);
+                        self.lead = Some(idx);
+                    }
+                }
+                self.pending_eight = pending;
+                if self.rules.five_skip && !clears && contains_rank(comb, Rank::Five) {
+                    // 出した5の枚数だけ次のプレイヤーをパスさせる
+                    outcome.effects.push(Effect::FiveSkip);
+                    for _ in 0..count_rank(comb, Rank::Five) {
+                        if outcome.cleared || self.indexer.count_active_players() == 0 {
+                            break;
+                        }
+                        self.pass(&mut outcome);
+                    }
                 }
-                self.indexer.next();
             }
+            None => self.pass(&mut outcome),
         }
-        flags
+        outcome
     }
 
-    pub fn get_order_comparator(&self) -> impl Fn(&Card, &Card) -> Ordering {
-        match self.is_rev {
-            true => cmp_order_reversely,
-            false => cmp_order,
+    // 最後の2人で縛りがなくなっているか
+    fn is_heads_up(&self) -> bool {
+        self.rules.heads_up && self.indexer.count_active_players() == 2
+    }
+
+    fn get_finish(&self, idx: usize, foul: bool) -> Option<Finish> {
+        self.indexer.get_places()[idx].map(|place| Finish { idx, place, foul })
+    }
+
+    fn pass(&mut self, outcome: &mut TurnOutcome) {
+        // カウントが0なら場を流す
+        self.pass_counter -= 1;
+        self.pass_count += 1;
+        self.passed.push(self.indexer.get_idx());
+        if self.pass_counter == 0 {
+            let lead = self.lead;
+            self.clear_prev_comb();
+            self.pass_count = 0;
+            self.passed.clear();
+            self.binder.clear();
+            outcome.cleared = true;
+            tracing::debug!(?lead, "場が流れた");
+            // 最後にカードを出したプレイヤー(上がっていれば次の席のプレイヤー)が親になる
+            match lead {
+                Some(lead) => self.indexer.set_lead(lead),
+                None => self.indexer.next(),
+            }
+        } else {
+            self.indexer.next();
         }
     }
+
+    // 都落ち: 前のラウンドの大富豪より先に上がったら大富豪を最下位にする
+    fn check_capital(&mut self, idx: usize, outcome: &mut TurnOutcome) {
+        if !self.rules.capital_fall {
+            return;
+        }
+        let Some(capital) = self.capital.take() else {
+            return;
+        };
+        let places = self.indexer.get_places();
+        if capital != idx && places[idx] == Some(0) && places[capital].is_none() {
+            tracing::debug!(capital, "都落ち");
+            self.indexer.demote(capital);
+            self.pass_counter = self.pass_counter.saturating_sub(1).max(1);
+            outcome.effects.push(Effect::CapitalFall(capital));
+        }
+    }
+
+    // 前の組み合わせのカードの領域を使い回して場のカードを置き換える
+    fn set_prev_comb(&mut self, comb: &Comb) {
+        let mut cards = match self.prev_comb.take() {
+            Some(Comb::Multi(cards)) | Some(Comb::Seq(cards)) => cards,
+            _ => std::mem::take(&mut self.spare),
+        };
+        cards.clear();
+        self.prev_comb = Some(match comb {
+            Comb::Single(card) => {
+                self.spare = cards;
+                Comb::Single(*card)
+            }
+            Comb::Multi(new_cards) => {
+                cards.extend_from_slice(new_cards);
+                Comb::Multi(cards)
+            }
+            Comb::Seq(new_cards) => {
+                cards.extend_from_slice(new_cards);
+                Comb::Seq(cards)
+            }
+        });
+    }
+
+    fn clear_prev_comb(&mut self) {
+        self.lead = None;
+        self.pending_eight = false;
+        self.is_jack_back = false;
+        if let Some(Comb::Multi(cards)) | Some(Comb::Seq(cards)) = self.prev_comb.take() {
+            self.spare = cards;
+        }
+    }
+
+    // ルールの不具合を再現するための内部の状態(カードはASCII文字で表記する)
+    pub fn get_debug_lines(&self) -> Vec<String> {
+        let suits = |suits: Option<&[Suit]>| match suits {
+            Some(suits) => suits.iter().map(suit_to_ascii).join(""),
+            None => "-".to_owned(),
+        };
+        let prev_comb = match &self.prev_comb {
+            Some(comb) => cards_to_ascii(comb.get_cards()),
+            None => "-".to_owned(),
+        };
+        vec![
+            format!(
+                "prev_comb: {prev_comb} lead: {:?} pending_eight: {}",
+                self.lead, self.pending_eight
+            ),
+            format!(
+                "active: {:?} idx: {} places: {:?}",
+                self.indexer.get_active_players(),
+                self.indexer.get_idx(),
+                self.indexer.get_places()
+            ),
+            format!(
+                "pass_counter: {} pass_count: {} passed: {:?}",
+                self.pass_counter, self.pass_count, self.passed
+            ),
+            format!(
+                "bind: {} prev_suits: {}",
+                suits(self.binder.get_bind_suits()),
+                suits(self.binder.get_prev_suits())
+            ),
+            format!(
+                "rev: {} jack_back: {} capital: {:?}",
+                self.is_rev, self.is_jack_back, self.capital
+            ),
+        ]
+    }
+
+    // 最後に出すと反則上がりになる組み合わせか
+    fn is_foul(&self, comb: &Comb) -> bool {
+        if self.rules.joker_finish && *comb == Comb::Single(Card::Joker) {
+            return false;
+        }
+        contains_especial_card(comb, self.is_reversed())
+            && (self.rules.eight_cut || !contains_eight(comb))
+    }
+
+    // 手番のプレイヤーが手札を全て出す組み合わせか
+    fn is_last_comb(&self, comb: &Comb) -> bool {
+        self.indexer.count_active_players() > 0
+            && self.hands_counts.get(self.indexer.get_idx()) == Some(&comb.get_cards().len())
+    }
+
+    // 革命と11バックを合わせてカードの強さが逆転しているか
+    fn is_reversed(&self) -> bool {
+        self.is_rev != self.is_jack_back
+    }
+
+    pub fn get_order(&self) -> Order {
+        match self.is_reversed() {
+            true => Order::Reversed,
+            false => Order::Normal,
+        }
+    }
+
+    pub fn get_order_comparator(&self) -> impl Fn(&Card, &Card) -> Ordering {
+        self.get_order().get_comparator()
+    }
 }
 
 impl Validator for Field {
@@ -108,18 +581,89 @@ impl Validator for Field {
         self.prev_comb.as_ref()
     }
 
+    fn can_pass(&self) -> bool {
+        self.prev_comb.is_some() || self.rules.lead_pass == LeadPassRule::Skip
+    }
+
+    fn get_rules(&self) -> RuleSet {
+        self.rules
+    }
+
     fn is_valid(&self, comb: &Comb) -> bool {
+        // ルールの枚数に足りない階段は出せない
+        if let Comb::Seq(cards) = comb {
+            if !self.rules.is_seq_allowed(cards.len()) {
+                return false;
+            }
+        }
         match &self.prev_comb {
+            // 8返し: 場の8には同じ枚数の8しか出せない
+            Some(prev_comb) if self.pending_eight => {
+                contains_eight(comb) && comb.get_cards().len() == prev_comb.get_cards().len()
+            }
+            // スペ3: ジョーカー1枚には♠3を出せる
+            Some(Comb::Single(Card::Joker))
+                if self.rules.spade_three
+                    && *comb == Comb::Single(Card::Normal(Suit::Spade, Rank::Three)) =>
+            {
+                true
+            }
             Some(prev_comb) => {
-                let comparator = match self.is_rev {
+                let comparator = match self.is_reversed() {
                     true => cmp_rank_reversely,
                     false => cmp_rank,
                 };
-                self.binder.is_valid(comb) && comb.is_greater(prev_comb, comparator)
+                let has_joker = comb.get_cards().contains(&Card::Joker);
+                // 最後の2人では縛りを無視する
+                let binding = self.binder.is_activate() && !self.is_heads_up();
+                (!binding || self.binder.is_valid(comb))
+                    && !(self.rules.no_joker_bind && binding && has_joker)
+                    && match self.rules.seq {
+                        SeqRule::Pairwise => comb.is_greater(prev_comb, comparator),
+                        SeqRule::Extreme => comb.is_greater_by_extreme(prev_comb, comparator),
+                    }
+            }
+            // ジョーカーで親になれない場合も、最後の手札なら出せる
+            None => {
+                !self.rules.no_joker_lead
+                    || !comb.get_cards().contains(&Card::Joker)
+                    || self.is_last_comb(comb)
             }
-            None => true,
         }
     }
+
+    fn get_view(&self) -> GameView {
+        GameView {
+            // ゲームが終わった後はアクティブなプレイヤーがいない
+            idx: match self.indexer.count_active_players() {
+                0 => 0,
+                _ => self.indexer.get_idx(),
+            },
+            prev_comb: self.prev_comb.clone(),
+            bind_suits: match self.is_heads_up() {
+                true => None,
+                false => self.binder.get_bind_suits().map(<[Suit]>::to_vec),
+            },
+            is_rev: self.is_rev,
+            is_jack_back: self.is_jack_back,
+            players: self
+                .names
+                .iter()
+                .cloned()
+                .zip(self.hands_counts.iter().copied())
+                .collect(),
+            pass_count: self.pass_count,
+            rules: self.rules,
+            lead: self.lead,
+            pending_eight: self.pending_eight,
+            passed: self.passed.clone(),
+            places: self.indexer.get_places(),
+        }
+    }
+
+    fn get_history(&self) -> &[(usize, Comb)] {
+        &self.history
+    }
 }
 
 fn get_rank(cards: &[Card]) -> Option<&Rank> {
@@ -129,7 +673,7 @@ fn get_rank(cards: &[Card]) -> Option<&Rank> {
     })
 }
 
-fn contains_eight(comb: &Comb) -> bool {
+pub fn contains_eight(comb: &Comb) -> bool {
     // 組み合わせに8のカードを含むか
     // 階段の場合は無視する
     match comb {
@@ -158,6 +702,33 @@ fn contains_especial_card(comb: &Comb, is_rev: bool) -> bool {
     }
 }
 
+fn contains_rank(comb: &Comb, rank: Rank) -> bool {
+    count_rank(comb, rank) > 0
+}
+
+// 組み合わせに含まれる数字のカードの枚数(ジョーカーで補った数字も数える)
+fn count_rank(comb: &Comb, rank: Rank) -> usize {
+    match comb {
+        Comb::Multi(cards) => match get_rank(cards) {
+            Some(r) if *r == rank => cards.len(),
+            _ => 0,
+        },
+        _ => {
+            let normals = comb
+                .get_cards()
+                .iter()
+                .filter(|card| matches!(card, Card::Normal(_, r) if *r == rank))
+                .count();
+            normals
+                + comb
+                    .get_joker_ranks()
+                    .iter()
+                    .filter(|r| **r == rank)
+                    .count()
+        }
+    }
+}
+
 fn is_rev_comb(comb: &Comb) -> bool {
     match comb {
         Comb::Multi(cards) => cards.len() >= 4,
@@ -165,11 +736,89 @@ fn is_rev_comb(comb: &Comb) -> bool {
     }
 }
 
+// 4枚の同じ数字とジョーカーの5枚の組み合わせ
+fn is_super_rev_comb(comb: &Comb) -> bool {
+    match comb {
+        Comb::Multi(cards) => cards.len() >= 5,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::card::{Card, Rank, Suit};
 
+    #[test]
+    fn test_check_invariants() {
+        let mut field = Field::new(4, 0);
+        field.set_players(vec!["A".to_owned(); 4], vec![14, 13, 13, 13]);
+        field
+            .put(
+                Some(&Comb::Single(Card::Normal(Suit::Club, Rank::Three))),
+                13,
+            )
+            .unwrap();
+        field.put(None, 13).unwrap();
+        assert_eq!(field.check_invariants(), Ok(()));
+        // 手札の枚数と場に出されたカードが合わない
+        let mut broken = field.clone();
+        broken.hands_counts[0] = 14;
+        assert!(broken.check_invariants().is_err());
+        // 場にカードがあるのに流れている
+        let mut broken = field.clone();
+        broken.pass_counter = 0;
+        assert!(broken.check_invariants().is_err());
+        let mut broken = field.clone();
+        broken.pass_counter = 5;
+        assert!(broken.check_invariants().is_err());
+        // 途中の状態から作った場では枚数を確かめない
+        let mut field = Field::from_view(&field.get_view());
+        field.hands_counts[0] = 1;
+        assert_eq!(field.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_from_view() {
+        let mut field = Field::new(4, 0);
+        field.set_players(vec!["A".to_owned(); 4], vec![5, 5, 5, 5]);
+        for (comb, hands_count) in [
+            (Some(Comb::Single(Card::Normal(Suit::Club, Rank::Four))), 0),
+            (Some(Comb::Single(Card::Normal(Suit::Club, Rank::Six))), 4),
+            (None, 5),
+        ] {
+            field.put(comb.as_ref(), hands_count).unwrap();
+        }
+        let view = field.get_view();
+        let restored = Field::from_view(&view);
+        assert_eq!(restored.get_view(), view);
+        assert_eq!(restored.get_player_rank(), vec![0]);
+        assert_eq!(restored.pass_counter, field.pass_counter);
+    }
+
+    #[test]
+    fn test_get_debug_lines() {
+        let mut field = Field::new(3, 0);
+        field.set_players(vec!["A".to_owned(); 3], vec![5, 5, 5]);
+        for (comb, hands_count) in [
+            (Some(Comb::Single(Card::Normal(Suit::Club, Rank::Four))), 4),
+            (None, 5),
+            (Some(Comb::Single(Card::Normal(Suit::Club, Rank::Six))), 0),
+        ] {
+            field.put(comb.as_ref(), hands_count).unwrap();
+        }
+        assert_eq!(
+            field.get_debug_lines(),
+            vec![
+                "prev_comb: C6 lead: Some(2) pending_eight: false",
+                "active: [0, 1] idx: 0 places: [None, None, Some(0)]",
+                "pass_counter: 2 pass_count: 0 passed: []",
+                "bind: C prev_suits: -",
+                "rev: false jack_back: false capital: None",
+            ]
+        );
+    }
+
     #[test]
     fn test_is_valid_single() {
         let comb = Comb::Single(Card::Normal(Suit::Heart, Rank::Eight));
@@ -229,6 +878,263 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_is_valid_seq_rule() {
+        // 降順に並んだ階段
+        let prev_comb = Comb::Seq(vec![
+            Card::Normal(Suit::Club, Rank::Ten),
+            Card::Normal(Suit::Club, Rank::Nine),
+            Card::Normal(Suit::Club, Rank::Eight),
+        ]);
+        let comb = Comb::Seq(vec![
+            Card::Normal(Suit::Heart, Rank::Nine),
+            Card::Normal(Suit::Heart, Rank::Ten),
+            Card::Normal(Suit::Heart, Rank::Jack),
+        ]);
+        for (seq, expected) in [(SeqRule::Pairwise, false), (SeqRule::Extreme, true)] {
+            let mut field = Field::new(4, 0);
+            field.set_players(vec!["A".to_owned(); 4], vec![5; 4]);
+            field.set_rules(RuleSet {
+                seq,
+                ..RuleSet::default()
+            });
+            field.prev_comb = Some(prev_comb.clone());
+            assert_eq!(field.is_valid(&comb), expected);
+            // ルールは公開される状態から復元できる
+            assert_eq!(Field::from_view(&field.get_view()).get_rules().seq, seq);
+        }
+    }
+
+    fn create_field(players_count: usize, rules: RuleSet) -> Field {
+        let mut field = Field::new(players_count, 0);
+        field.set_players(vec!["A".to_owned(); players_count], vec![5; players_count]);
+        field.set_rules(rules);
+        field
+    }
+
+    #[test]
+    fn test_switch_rules() {
+        let c = |suit, rank| Card::Normal(suit, rank);
+        let quad = Comb::Multi(
+            [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade]
+                .map(|s| c(s, Rank::Six))
+                .to_vec(),
+        );
+        for (rules, comb, expected) in [
+            (
+                RuleSet::default(),
+                Comb::Single(c(Suit::Club, Rank::Eight)),
+                vec![Effect::EightCut],
+            ),
+            (
+                RuleSet {
+                    eight_cut: false,
+                    ..RuleSet::default()
+                },
+                Comb::Single(c(Suit::Club, Rank::Eight)),
+                vec![],
+            ),
+            (RuleSet::default(), quad.clone(), vec![Effect::Revolution]),
+            (
+                RuleSet {
+                    revolution: false,
+                    ..RuleSet::default()
+                },
+                quad,
+                vec![],
+            ),
+        ] {
+            let mut field = create_field(4, rules);
+            let outcome = field.put(Some(&comb), 4).unwrap();
+            // 8切りがなければ次のプレイヤーの手番になる
+            assert_eq!(
+                field.get_idx(),
+                usize::from(!outcome.contains(Effect::EightCut))
+            );
+            assert_eq!(outcome.effects, expected);
+        }
+        // 縛りがなければ同じスートが続いても縛らない
+        for (bind, expected) in [(true, vec![Effect::Bind]), (false, vec![])] {
+            let mut field = create_field(
+                4,
+                RuleSet {
+                    bind,
+                    ..RuleSet::default()
+                },
+            );
+            field
+                .put(Some(&Comb::Single(c(Suit::Heart, Rank::Four))), 4)
+                .unwrap();
+            assert_eq!(
+                field
+                    .put(Some(&Comb::Single(c(Suit::Heart, Rank::Five))), 4)
+                    .unwrap()
+                    .effects,
+                expected
+            );
+        }
+        // 8切りがなければ8で上がっても反則にならない
+        let mut field = create_field(
+            4,
+            RuleSet {
+                eight_cut: false,
+                ..RuleSet::default()
+            },
+        );
+        assert_eq!(
+            field
+                .put(Some(&Comb::Single(c(Suit::Club, Rank::Eight))), 0)
+                .unwrap(),
+            TurnOutcome {
+                finish: Some(Finish {
+                    idx: 0,
+                    place: 0,
+                    foul: false
+                }),
+                ..TurnOutcome::default()
+            }
+        );
+        // 8切りがあれば反則上がりで最下位になる(8切りとしては数えない)
+        let mut field = create_field(4, RuleSet::default());
+        let outcome = field
+            .put(Some(&Comb::Single(c(Suit::Club, Rank::Eight))), 0)
+            .unwrap();
+        assert_eq!(
+            outcome.finish,
+            Some(Finish {
+                idx: 0,
+                place: 3,
+                foul: true
+            })
+        );
+        assert!(outcome.effects.is_empty());
+    }
+
+    #[test]
+    fn test_jack_back() {
+        let rules = RuleSet {
+            jack_back: true,
+            ..RuleSet::default()
+        };
+        let mut field = create_field(2, rules);
+        let jack = Comb::Single(Card::Normal(Suit::Club, Rank::Jack));
+        assert_eq!(
+            field.put(Some(&jack), 4).unwrap().effects,
+            [Effect::JackBack]
+        );
+        assert_eq!(field.get_order(), Order::Reversed);
+        assert!(field.get_view().is_jack_back);
+        for (rank, expected) in [(Rank::Four, true), (Rank::Queen, false)] {
+            let comb = Comb::Single(Card::Normal(Suit::Heart, rank));
+            assert_eq!(field.is_valid(&comb), expected);
+        }
+        // 場が流れると元に戻る
+        assert!(field.put(None, 5).unwrap().cleared);
+        assert_eq!(field.get_order(), Order::Normal);
+    }
+
+    #[test]
+    fn test_jack_revolution() {
+        let rules = RuleSet {
+            jack_back: true,
+            ..RuleSet::default()
+        };
+        let mut field = create_field(2, rules);
+        let jacks = Comb::Multi(
+            [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade]
+                .map(|suit| Card::Normal(suit, Rank::Jack))
+                .to_vec(),
+        );
+        assert_eq!(
+            field.put(Some(&jacks), 4).unwrap().effects,
+            [Effect::Revolution]
+        );
+        assert_eq!(field.get_order(), Order::Reversed);
+        assert!(!field.get_view().is_jack_back);
+    }
+
+    #[test]
+    fn test_spade_three() {
+        let spade_three = Comb::Single(Card::Normal(Suit::Spade, Rank::Three));
+        let heart_three = Comb::Single(Card::Normal(Suit::Heart, Rank::Three));
+        for (spade_three_rule, expected) in [(true, true), (false, false)] {
+            let mut field = create_field(
+                4,
+                RuleSet {
+                    spade_three: spade_three_rule,
+                    ..RuleSet::default()
+                },
+            );
+            field.put(Some(&Comb::Single(Card::Joker)), 4).unwrap();
+            assert_eq!(field.is_valid(&spade_three), expected);
+            assert!(!field.is_valid(&heart_three));
+        }
+    }
+
+    #[test]
+    fn test_five_skip() {
+        let c = |suit| Card::Normal(suit, Rank::Five);
+        let rules = RuleSet {
+            five_skip: true,
+            ..RuleSet::default()
+        };
+        for (players_count, comb, expected_idx, expected_cleared) in [
+            (4, Comb::Single(c(Suit::Spade)), 2, false),
+            (
+                4,
+                Comb::Multi(vec![c(Suit::Heart), c(Suit::Spade)]),
+                3,
+                false,
+            ),
+            // 全員を飛ばしたら場が流れて出したプレイヤーが親になる
+            (
+                3,
+                Comb::Multi(vec![c(Suit::Diamond), c(Suit::Heart), c(Suit::Spade)]),
+                0,
+                true,
+            ),
+        ] {
+            let mut field = create_field(players_count, rules);
+            let outcome = field.put(Some(&comb), 3).unwrap();
+            assert_eq!(outcome.effects, [Effect::FiveSkip]);
+            assert_eq!(outcome.cleared, expected_cleared);
+            assert_eq!(field.get_idx(), expected_idx);
+        }
+    }
+
+    #[test]
+    fn test_capital_fall() {
+        let rules = RuleSet {
+            capital_fall: true,
+            ..RuleSet::default()
+        };
+        let comb = Comb::Single(Card::Normal(Suit::Club, Rank::Three));
+        for (capital, expected_effects, expected_places) in [
+            (
+                2,
+                vec![Effect::CapitalFall(2)],
+                vec![Some(0), None, Some(3), None],
+            ),
+            (0, vec![], vec![Some(0), None, None, None]),
+        ] {
+            let mut field = create_field(4, rules);
+            field.set_capital(Some(capital));
+            let outcome = field.put(Some(&comb), 0).unwrap();
+            assert_eq!(outcome.effects, expected_effects);
+            assert_eq!(outcome.get_fallen(), (capital != 0).then_some(capital));
+            assert_eq!(outcome.finish.map(|finish| finish.place), Some(0));
+            assert_eq!(field.get_places(), expected_places);
+            assert_eq!(field.get_idx(), 1);
+        }
+        // 都落ちした大富豪の分だけ少ないパスで場が流れる
+        let mut field = create_field(4, rules);
+        field.set_capital(Some(2));
+        field.put(Some(&comb), 0).unwrap();
+        assert!(!field.put(None, 5).unwrap().cleared);
+        assert!(field.put(None, 5).unwrap().cleared);
+        assert_eq!(field.get_idx(), 1);
+    }
+
     #[test]
     fn test_contains_eight() {
         for (comb, expected) in [
@@ -360,6 +1266,342 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_get_lead_player() {
+        let c = |rank| Comb::Single(Card::Normal(Suit::Club, rank));
+        for (hands_count, expected_idx) in [
+            // 最後に出したプレイヤーが親になる
+            (3, 0),
+            // 上がっていれば次の席のプレイヤーが親になる
+            (0, 1),
+        ] {
+            let mut field = Field::new(3, 0);
+            field.set_players(vec!["A".to_owned(); 3], vec![4, 4, 4]);
+            field.put(Some(&c(Rank::Three)), hands_count).unwrap();
+            assert_eq!(field.get_lead_player(), Some(0));
+            let mut cleared = false;
+            while !cleared {
+                let idx = field.get_idx();
+                cleared = field
+                    .put(None, field.get_view().players[idx].1)
+                    .unwrap()
+                    .cleared;
+            }
+            assert_eq!(field.get_lead_player(), None);
+            assert_eq!(field.get_idx(), expected_idx);
+        }
+        // 8切りでは親を記録しない
+        let mut field = Field::new(2, 0);
+        field.set_players(vec!["A".to_owned(); 2], vec![4, 4]);
+        field.put(Some(&c(Rank::Eight)), 3).unwrap();
+        assert_eq!(field.get_lead_player(), None);
+    }
+
+    #[test]
+    fn test_get_history() {
+        let mut field = Field::new(2, 0);
+        field.set_players(vec!["A".to_owned(), "B".to_owned()], vec![5, 5]);
+        let comb = Comb::Single(Card::Normal(Suit::Club, Rank::Three));
+        field.put(Some(&comb), 4).unwrap();
+        // 場が流れても記録は残る
+        field.put(None, 5).unwrap();
+        assert_eq!(field.get_prev_comb(), None);
+        assert_eq!(field.get_history(), [(0, comb)]);
+    }
+
+    #[test]
+    fn test_eight_return() {
+        let c = |suit, rank| Card::Normal(suit, rank);
+        let rules = RuleSet {
+            eight_return: true,
+            ..RuleSet::default()
+        };
+        let mut field = create_field(4, rules);
+        let outcome = field
+            .put(Some(&Comb::Single(c(Suit::Club, Rank::Eight))), 4)
+            .unwrap();
+        assert_eq!(outcome.effects, [Effect::EightCut]);
+        assert!(!outcome.cleared);
+        // 場は流れずに次のプレイヤーが同じ枚数の8で返せる
+        assert_eq!(field.get_idx(), 1);
+        assert!(field.get_view().pending_eight);
+        for (comb, expected) in [
+            (Comb::Single(c(Suit::Heart, Rank::Eight)), true),
+            (Comb::Single(c(Suit::Heart, Rank::Nine)), false),
+            (Comb::Single(Card::Joker), false),
+            (
+                Comb::Multi(vec![
+                    c(Suit::Heart, Rank::Eight),
+                    c(Suit::Spade, Rank::Eight),
+                ]),
+                false,
+            ),
+        ] {
+            assert_eq!(field.is_valid(&comb), expected);
+        }
+        let outcome = field
+            .put(Some(&Comb::Single(c(Suit::Heart, Rank::Eight))), 4)
+            .unwrap();
+        assert_eq!(outcome.effects, [Effect::EightReturn]);
+        assert_eq!(field.get_lead_player(), Some(1));
+        // 全員がパスすると最後に8を出したプレイヤーから出す
+        assert!(!field.put(None, 5).unwrap().cleared);
+        assert!(!field.put(None, 4).unwrap().cleared);
+        assert!(field.put(None, 5).unwrap().cleared);
+        assert_eq!(field.get_idx(), 1);
+        assert!(!field.get_view().pending_eight);
+        assert!(field.is_valid(&Comb::Single(c(Suit::Heart, Rank::Three))));
+        // 8切りがなければ8返しもない
+        let mut field = create_field(
+            4,
+            RuleSet {
+                eight_cut: false,
+                ..rules
+            },
+        );
+        let outcome = field
+            .put(Some(&Comb::Single(c(Suit::Club, Rank::Eight))), 4)
+            .unwrap();
+        assert!(outcome.effects.is_empty());
+        assert!(!field.get_view().pending_eight);
+    }
+
+    #[test]
+    fn test_joker_rules() {
+        let c = |suit, rank| Card::Normal(suit, rank);
+        let joker = Comb::Single(Card::Joker);
+        let pair = Comb::Multi(vec![c(Suit::Heart, Rank::Five), Card::Joker]);
+        for (no_joker_lead, expected) in [(false, true), (true, false)] {
+            let rules = RuleSet {
+                no_joker_lead,
+                ..RuleSet::default()
+            };
+            let mut field = create_field(4, rules);
+            assert_eq!(field.is_valid(&joker), expected);
+            assert_eq!(field.is_valid(&pair), expected);
+            assert!(field.is_valid(&Comb::Single(c(Suit::Heart, Rank::Five))));
+            // 最後の手札なら親として出せる
+            field.set_players(vec!["A".to_owned(); 4], vec![1, 5, 5, 5]);
+            assert!(field.is_valid(&joker));
+            assert_eq!(field.is_valid(&pair), expected);
+        }
+        for (joker_finish, expected_place) in [(false, 3), (true, 0)] {
+            let rules = RuleSet {
+                joker_finish,
+                ..RuleSet::default()
+            };
+            let mut field = create_field(4, rules);
+            let finish = field.put(Some(&joker), 0).unwrap().finish.unwrap();
+            assert_eq!(finish.foul, !joker_finish);
+            assert_eq!(finish.place, expected_place);
+        }
+        for (no_joker_bind, expected) in [(false, true), (true, false)] {
+            let rules = RuleSet {
+                no_joker_bind,
+                ..RuleSet::default()
+            };
+            let mut field = create_field(4, rules);
+            field
+                .put(Some(&Comb::Single(c(Suit::Heart, Rank::Four))), 4)
+                .unwrap();
+            // 縛りがなければジョーカーを出せる
+            assert!(field.is_valid(&joker));
+            field
+                .put(Some(&Comb::Single(c(Suit::Heart, Rank::Five))), 4)
+                .unwrap();
+            assert_eq!(field.is_valid(&joker), expected);
+            assert!(field.is_valid(&Comb::Single(c(Suit::Heart, Rank::Six))));
+        }
+    }
+
+    #[test]
+    fn test_min_seq() {
+        let seq = |ranks: &[Rank]| {
+            Comb::Seq(
+                ranks
+                    .iter()
+                    .map(|&r| Card::Normal(Suit::Heart, r))
+                    .collect(),
+            )
+        };
+        let three = seq(&[Rank::Three, Rank::Four, Rank::Five]);
+        let four = seq(&[Rank::Three, Rank::Four, Rank::Five, Rank::Six]);
+        for (min_seq, expected_three, expected_four) in
+            [(3, true, true), (4, false, true), (0, false, false)]
+        {
+            let rules = RuleSet {
+                min_seq,
+                ..RuleSet::default()
+            };
+            let field = create_field(4, rules);
+            assert_eq!(field.is_valid(&three), expected_three);
+            assert_eq!(field.is_valid(&four), expected_four);
+        }
+    }
+
+    #[test]
+    fn test_heads_up() {
+        let c = |suit, rank| Comb::Single(Card::Normal(suit, rank));
+        for (heads_up, expected_effects, expected_valid) in
+            [(false, vec![Effect::Bind], false), (true, vec![], true)]
+        {
+            let rules = RuleSet {
+                heads_up,
+                ..RuleSet::default()
+            };
+            let mut field = create_field(3, rules);
+            field.put(Some(&c(Suit::Heart, Rank::Four)), 4).unwrap();
+            // 上がって残りが2人になると縛りがなくなる
+            let outcome = field.put(Some(&c(Suit::Heart, Rank::Five)), 0).unwrap();
+            assert_eq!(outcome.effects, expected_effects);
+            assert_eq!(field.is_valid(&c(Suit::Spade, Rank::Six)), expected_valid);
+            assert_eq!(field.get_view().bind_suits.is_none(), expected_valid);
+            // 8切りはそのまま
+            let outcome = field.put(Some(&c(Suit::Heart, Rank::Eight)), 3).unwrap();
+            assert_eq!(outcome.effects, [Effect::EightCut]);
+        }
+    }
+
+    #[test]
+    fn test_super_revolution() {
+        let quad: Vec<Card> = [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade]
+            .map(|s| Card::Normal(s, Rank::Six))
+            .to_vec();
+        let five = Comb::Multi([quad.clone(), vec![Card::Joker]].concat());
+        for (super_revolution, comb, expected_effect, expected_idx) in [
+            (true, five.clone(), Effect::SuperRevolution, 0),
+            (false, five, Effect::Revolution, 1),
+            // 4枚の革命では場が流れない
+            (true, Comb::Multi(quad), Effect::Revolution, 1),
+        ] {
+            let rules = RuleSet {
+                super_revolution,
+                ..RuleSet::default()
+            };
+            let mut field = create_field(4, rules);
+            let outcome = field.put(Some(&comb), 4).unwrap();
+            assert_eq!(outcome.effects, [expected_effect]);
+            assert_eq!(field.get_order(), Order::Reversed);
+            // 大革命では場が流れて出したプレイヤーから出し直す
+            assert_eq!(field.get_idx(), expected_idx);
+            assert_eq!(field.get_prev_comb().is_none(), expected_idx == 0);
+            assert_eq!(field.get_lead_player().is_none(), expected_idx == 0);
+        }
+    }
+
+    #[test]
+    fn test_foul() {
+        let three = Comb::Single(Card::Normal(Suit::Club, Rank::Three));
+        let twos = Comb::Multi(
+            [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade]
+                .map(|s| Card::Normal(s, Rank::Two))
+                .to_vec(),
+        );
+        for (foul, comb, expected_effects, expected_prev_comb, expected_order) in [
+            (
+                FoulRule::Demote,
+                Comb::Single(Card::Joker),
+                vec![],
+                Some(Comb::Single(Card::Joker)),
+                Order::Normal,
+            ),
+            (
+                FoulRule::Demote,
+                twos.clone(),
+                vec![Effect::Revolution],
+                Some(twos.clone()),
+                Order::Reversed,
+            ),
+            // 出したカードは無効になり、革命も起きない
+            (
+                FoulRule::Void,
+                Comb::Single(Card::Joker),
+                vec![Effect::FoulVoid],
+                None,
+                Order::Normal,
+            ),
+            (
+                FoulRule::Void,
+                twos,
+                vec![Effect::FoulVoid],
+                None,
+                Order::Normal,
+            ),
+        ] {
+            let rules = RuleSet {
+                foul,
+                ..RuleSet::default()
+            };
+            let mut field = create_field(4, rules);
+            field.put(Some(&three), 4).unwrap();
+            let outcome = field.put(Some(&comb), 0).unwrap();
+            assert_eq!(
+                outcome.finish,
+                Some(Finish {
+                    idx: 1,
+                    place: 3,
+                    foul: true
+                })
+            );
+            assert_eq!(outcome.effects, expected_effects);
+            assert_eq!(outcome.cleared, foul == FoulRule::Void);
+            assert_eq!(field.get_prev_comb(), expected_prev_comb.as_ref());
+            assert_eq!(field.get_lead_player().is_some(), foul == FoulRule::Demote);
+            assert_eq!(field.get_order(), expected_order);
+            // 反則上がりしたプレイヤーの次の席から続ける
+            assert_eq!(field.get_idx(), 2);
+            assert!(field.check_invariants().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_lead_pass() {
+        for (lead_pass, expected, expected_idx) in [
+            (LeadPassRule::Reject, Err(PlayError::LeadPass), 0),
+            // 場の状態を変えずに次のプレイヤーに手番を移す
+            (LeadPassRule::Skip, Ok(TurnOutcome::default()), 1),
+        ] {
+            let rules = RuleSet {
+                lead_pass,
+                ..RuleSet::default()
+            };
+            let mut field = create_field(3, rules);
+            assert_eq!(field.put(None, 5), expected);
+            assert_eq!(field.get_idx(), expected_idx);
+            assert_eq!(field.get_prev_comb(), None);
+            assert_eq!(field.pass_counter, 0);
+        }
+        // ラウンドが終わった後の手
+        let mut field = create_field(2, RuleSet::default());
+        let comb = Comb::Single(Card::Normal(Suit::Club, Rank::Three));
+        field.put(Some(&comb), 0).unwrap();
+        assert_eq!(field.count_active_players(), 0);
+        assert_eq!(field.get_view().places, [Some(0), Some(1)]);
+        assert_eq!(field.put(None, 5), Err(PlayError::RoundOver));
+    }
+
+    #[test]
+    fn test_passed() {
+        let c = |rank| Comb::Single(Card::Normal(Suit::Club, rank));
+        let mut field = create_field(4, RuleSet::default());
+        field.put(Some(&c(Rank::Three)), 4).unwrap();
+        field.put(None, 5).unwrap();
+        field.put(None, 5).unwrap();
+        assert_eq!(field.get_passed(), [1, 2]);
+        assert_eq!(field.get_view().passed, [1, 2]);
+        assert_eq!(field.get_view().get_responders(), [3]);
+        // カードが出されたら全員が出し直せる
+        field.put(Some(&c(Rank::Four)), 4).unwrap();
+        assert!(field.get_passed().is_empty());
+        assert_eq!(field.get_view().get_responders(), [0, 1, 2]);
+        for _ in 0..3 {
+            field.put(None, 5).unwrap();
+        }
+        // 場が流れたら空にする
+        assert!(field.get_passed().is_empty());
+        assert_eq!(field.get_idx(), 3);
+    }
+
     #[test]
     fn test_is_rev_comb() {
         for (comb, expected) in [
@@ -0,0 +1,1211 @@
+use crate::card::{self, cmp_order, cmp_rank, Card, Order, Rank, Suit};
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::field::Field;
+use crate::i18n::trf;
+use crate::moves::get_legal_combs;
+use crate::observer::Observer;
+use crate::player::{Action, Player};
+use crate::rules::{LeadRule, RuleSet};
+use crate::save;
+use crate::state::{GameState, PlayerState};
+use crate::validator::Validator;
+use crate::{MAX_PLAYERS, MIN_PLAYERS};
+use rand::seq::SliceRandom;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::cmp::Ordering;
+use std::path::PathBuf;
+
+// 受け付けない手がこの回数続けば、パスか最も弱い手に置き換えて手番を進める
+const MAX_REJECTS: usize = 3;
+
+// 名前からNPCを作る
+type CreateNpc = Box<dyn FnMut(String) -> Box<dyn Player>>;
+
+pub struct Game {
+    players: Vec<Box<dyn Player>>,
+    field: Field,
+    rng: ChaCha8Rng,
+    // 現在のラウンドで最初に手番になったプレイヤーの席
+    dealer: usize,
+    quit: bool,
+    // NPCが手を選んだ理由をイベントで知らせる
+    explain: bool,
+    // 手番毎に全員の手札と場の内部の状態を標準エラー出力に書き出す
+    debug: bool,
+    // 操作のない人間のプレイヤーの席を引き継ぐNPCを作る
+    create_npc: Option<CreateNpc>,
+    // NPCに操作を任せている人間のプレイヤーとその席
+    away: Vec<(usize, Box<dyn Player>)>,
+    // このラウンドで場に出すかパスした手の数
+    turns: usize,
+    // 現在の手番で続けて受け付けなかった手の数
+    rejects: usize,
+    // 人間のプレイヤーが1人の場合、その手番の始めの状態とそれまでの手の数
+    undo_points: Vec<(GameState, usize)>,
+}
+
+impl Game {
+    pub fn new(mut players: Vec<Box<dyn Player>>, start_idx: usize, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let deck = get_split_deck(&mut rng, players.len());
+        players
+            .iter_mut()
+            .zip(deck)
+            .for_each(|(player, hands)| player.init(hands));
+        let field = create_field(&players, start_idx);
+        Self {
+            players,
+            field,
+            rng,
+            dealer: start_idx,
+            quit: false,
+            explain: false,
+            debug: false,
+            create_npc: None,
+            away: vec![],
+            turns: 0,
+            rejects: 0,
+            undo_points: vec![],
+        }
+    }
+
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.field.set_rules(rules);
+        self
+    }
+
+    pub fn get_rules(&self) -> RuleSet {
+        self.field.get_rules()
+    }
+
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    // 全員の手札と場の内部の状態
+    pub fn get_debug_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, player)| {
+                let hands = card::cards_to_ascii(player.get_hands());
+                format!("hands[{i}] {}: {hands}", player.get_name())
+            })
+            .collect();
+        lines.extend(self.field.get_debug_lines());
+        lines
+    }
+
+    // 保存したゲームを復元する(人間のプレイヤーはcreate_userで作る)
+    pub fn from_state<F>(state: GameState, mut create_user: F) -> Self
+    where
+        F: FnMut(String) -> Box<dyn Player>,
+    {
+        let players = state
+            .players
+            .into_iter()
+            .map(|saved| {
+                let mut player = match saved.strategy {
+                    Some(strategy) => strategy.create_npc(saved.name, rand::random()),
+                    None => create_user(saved.name),
+                };
+                player.init(saved.hands);
+                player
+            })
+            .collect();
+        Self {
+            players,
+            field: state.field,
+            rng: state.rng,
+            dealer: state.dealer,
+            quit: false,
+            explain: false,
+            debug: false,
+            create_npc: None,
+            away: vec![],
+            turns: 0,
+            rejects: 0,
+            undo_points: vec![],
+        }
+    }
+
+    // 手札、場(縛り、革命、手番、順位を含む)、乱数の状態を写し取る
+    pub fn snapshot(&self) -> GameState {
+        GameState {
+            players: self
+                .players
+                .iter()
+                .map(|player| PlayerState {
+                    name: player.get_name().to_owned(),
+                    strategy: player.get_strategy(),
+                    hands: player.get_hands().to_vec(),
+                })
+                .collect(),
+            field: self.field.clone(),
+            rng: self.rng.clone(),
+            dealer: self.dealer,
+        }
+    }
+
+    // snapshotで写し取った状態に戻す(プレイヤーはそのままで、手札を入れ替える)
+    pub fn restore(&mut self, state: GameState) -> Result<(), String> {
+        if state.players.len() != self.players.len() {
+            return Err(trf(
+                "restore_count",
+                &[
+                    ("saved", &state.players.len()),
+                    ("current", &self.players.len()),
+                ],
+            ));
+        }
+        self.load(state);
+        Ok(())
+    }
+
+    fn load(&mut self, state: GameState) {
+        let order = state.field.get_order();
+        for (player, saved) in self.players.iter_mut().zip(state.players) {
+            player.init(saved.hands);
+            player.sort_hands(order);
+        }
+        self.field = state.field;
+        self.rng = state.rng;
+        self.dealer = state.dealer;
+        self.quit = false;
+        self.rejects = 0;
+    }
+
+    pub fn get_names(&self) -> Vec<String> {
+        self.players
+            .iter()
+            .map(|p| p.get_name().to_owned())
+            .collect()
+    }
+
+    // 人間のプレイヤーの席
+    pub fn get_human_seats(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|i| self.players[*i].get_strategy().is_none())
+            .collect()
+    }
+
+    pub fn with_substitutes(
+        mut self,
+        create_npc: impl FnMut(String) -> Box<dyn Player> + 'static,
+    ) -> Self {
+        self.create_npc = Some(Box::new(create_npc));
+        self
+    }
+
+    pub fn get_field(&self) -> &Field {
+        &self.field
+    }
+
+    pub fn get_hands(&self, idx: usize) -> &[Card] {
+        self.players[idx].get_hands()
+    }
+
+    // 対戦を終えたプレイヤーを席順に取り出す
+    pub fn into_players(self) -> Vec<Box<dyn Player>> {
+        self.players
+    }
+
+    // 席のプレイヤーを入れ替え、抜けたプレイヤーを返す(手札は引き継ぐ)
+    pub fn replace_player(&mut self, seat: usize, mut player: Box<dyn Player>) -> Box<dyn Player> {
+        player.init(self.players[seat].get_hands().to_vec());
+        player.sort_hands(self.field.get_order());
+        std::mem::replace(&mut self.players[seat], player)
+    }
+
+    // 中断したプレイヤーの席をplayerに引き継がせ、ラウンドを続けられるようにする
+    pub fn substitute(&mut self, player: Box<dyn Player>, observer: &mut dyn Observer) -> usize {
+        let idx = self.field.get_idx();
+        tracing::info!(idx, "席を引き継ぐ");
+        self.replace_player(idx, player);
+        self.quit = false;
+        observer.notify(&GameEvent::Substitute { idx });
+        idx
+    }
+
+    // ラウンドの間にプレイヤーを最後の席に加え、次のラウンドに渡す順位を返す
+    // (加わったプレイヤーは真ん中の順位になる)
+    pub fn add_player(
+        &mut self,
+        player: Box<dyn Player>,
+        player_rank: &[usize],
+    ) -> Result<Vec<usize>, String> {
+        if self.players.len() >= MAX_PLAYERS {
+            return Err(trf("too_many_players", &[("max", &MAX_PLAYERS)]));
+        }
+        let mut player_rank = player_rank.to_vec();
+        player_rank.insert(player_rank.len() / 2, self.players.len());
+        self.players.push(player);
+        Ok(player_rank)
+    }
+
+    // ラウンドの間に席のプレイヤーを抜けさせ、抜けたプレイヤーと次のラウンドに渡す順位を返す
+    // (後ろの席は1つずつ詰める)
+    pub fn remove_player(
+        &mut self,
+        seat: usize,
+        player_rank: &[usize],
+    ) -> Result<(Box<dyn Player>, Vec<usize>), String> {
+        if self.players.len() <= MIN_PLAYERS {
+            return Err(trf("too_few_players", &[("min", &MIN_PLAYERS)]));
+        }
+        let player = self.players.remove(seat);
+        self.away.retain(|(idx, _)| *idx != seat);
+        self.away
+            .iter_mut()
+            .filter(|(idx, _)| *idx > seat)
+            .for_each(|(idx, _)| *idx -= 1);
+        let player_rank = player_rank
+            .iter()
+            .filter(|idx| **idx != seat)
+            .map(|idx| if *idx > seat { idx - 1 } else { *idx })
+            .collect();
+        if self.dealer > seat {
+            self.dealer -= 1;
+        }
+        self.dealer %= self.players.len();
+        Ok((player, player_rank))
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.field.count_active_players() == 0
+    }
+
+    // 人間のプレイヤーがゲームを中断したか
+    pub fn is_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn step(&mut self, observer: &mut dyn Observer) {
+        let idx = self.field.get_idx();
+        if self.debug {
+            for line in self.get_debug_lines() {
+                eprintln!("[debug] {line}");
+            }
+        }
+        // 1人で遊んでいれば、待ったで戻れるように手番の始めの状態を残す
+        let single =
+            self.players[idx].get_strategy().is_none() && self.get_human_seats().len() == 1;
+        if single && self.undo_points.last().map(|(_, turns)| *turns) != Some(self.turns) {
+            self.undo_points.push((self.snapshot(), self.turns));
+        }
+        let hands = self.explain.then(|| self.players[idx].get_hands().to_vec());
+        // 場に出すカードを取得
+        let (played_comb, away) = match self.players[idx].play(&self.field) {
+            Action::Move(comb) => (comb, false),
+            Action::Away(comb) => (comb, true),
+            Action::Save(path) => {
+                // 手番を進めずに保存し、同じプレイヤーの手番をやり直す
+                self.save(path, observer);
+                return;
+            }
+            Action::Quit(path) => {
+                if let Some(path) = path {
+                    self.save(path, observer);
+                }
+                tracing::info!(idx, "中断");
+                self.quit = true;
+                let view = self.field.get_view();
+                observer.notify(&GameEvent::Quit {
+                    places: self.field.get_places(),
+                    hands_counts: view.players.iter().map(|(_, count)| *count).collect(),
+                });
+                return;
+            }
+            Action::Undo => {
+                self.undo(observer);
+                return;
+            }
+            // チャットはネットワーク対戦でしか送れない
+            Action::Chat(_) => return,
+        };
+        let mut reason = hands
+            .and_then(|hands| self.players[idx].explain(&hands, played_comb.as_ref(), &self.field));
+        let mut played_comb = played_comb;
+        let mut hands_count = self.players[idx].count_hands();
+        // カードを場に出すかパス
+        let prev_order = self.field.get_order();
+        let outcome = match self.field.put(played_comb.as_ref(), hands_count) {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                tracing::warn!(idx, error = error.get_key(), "受け付けない手");
+                observer.notify(&GameEvent::Reject { idx, error });
+                // 受け付けない手なら手番を進めずに同じプレイヤーにやり直させる
+                self.rejects += 1;
+                if self.rejects < MAX_REJECTS {
+                    return;
+                }
+                // 繰り返し受け付けなければ、パスか最も弱い手に置き換える
+                played_comb = self.take_fallback(idx);
+                hands_count = self.players[idx].count_hands();
+                reason = None;
+                match self.field.put(played_comb.as_ref(), hands_count) {
+                    Ok(outcome) => outcome,
+                    Err(_) => return,
+                }
+            }
+        };
+        self.rejects = 0;
+        let order = self.field.get_order();
+        if order != prev_order {
+            // 革命や11バックで強さが変われば全プレイヤーの手札をソート
+            self.players
+                .iter_mut()
+                .for_each(|player| player.sort_hands(order));
+        }
+        let cleared = outcome.cleared;
+        self.turns += 1;
+        let event = GameEvent::Turn {
+            idx,
+            comb: played_comb,
+            hands_count,
+            outcome,
+        };
+        self.show_players(&event);
+        observer.notify(&event);
+        if let Some(reason) = reason {
+            observer.notify(&GameEvent::Reason { idx, reason });
+        }
+        if cleared && !self.is_over() {
+            let event = GameEvent::Lead {
+                idx: self.field.get_idx(),
+            };
+            self.show_players(&event);
+            observer.notify(&event);
+        }
+        if away {
+            self.hand_over(idx, observer);
+        }
+    }
+
+    // 途中の状態を保存して結果を知らせる
+    fn save(&self, path: PathBuf, observer: &mut dyn Observer) {
+        let result = save::write_save(&path, &self.snapshot());
+        observer.notify(&GameEvent::Save { path, result });
+    }
+
+    // 公開された進行をプレイヤーにも知らせる
+    fn show_players(&mut self, event: &GameEvent) {
+        self.players
+            .iter_mut()
+            .for_each(|player| player.observe(event));
+    }
+
+    // NPCの乱数をゲームの乱数から決め直す(ゲームの乱数は進めないので、同じ状態からは同じ手が選ばれる)
+    pub fn reseed_players(&mut self) {
+        let mut rng = self.rng.clone();
+        self.players
+            .iter_mut()
+            .for_each(|player| player.reseed(rng.next_u64()));
+    }
+
+    // パスできればパスし、できなければ手札から場に出せる最も弱い手を出させる
+    fn take_fallback(&mut self, idx: usize) -> Option<Comb> {
+        if self.field.can_pass() {
+            return None;
+        }
+        let hands = self.players[idx].get_hands();
+        let (comb, _) = get_legal_combs(hands, &self.field).into_iter().next()?;
+        self.players[idx].take_cards(comb.get_cards());
+        Some(comb)
+    }
+
+    // 人間のプレイヤーの前の手番の始めに戻す(今の手番の始めの状態は捨てる)
+    fn undo(&mut self, observer: &mut dyn Observer) {
+        if self.undo_points.len() < 2 {
+            observer.notify(&GameEvent::Undo { turns: 0 });
+            return;
+        }
+        self.undo_points.pop();
+        let Some((state, turns)) = self.undo_points.pop() else {
+            return;
+        };
+        tracing::info!(turns = self.turns - turns, "待った");
+        self.load(state);
+        self.reseed_players();
+        observer.notify(&GameEvent::Undo {
+            turns: self.turns - turns,
+        });
+        self.turns = turns;
+    }
+
+    // 操作のない席をラウンドの残りはNPCに任せる
+    fn hand_over(&mut self, idx: usize, observer: &mut dyn Observer) {
+        let Some(create_npc) = self.create_npc.as_mut() else {
+            return;
+        };
+        tracing::info!(idx, "操作のない席を引き継ぐ");
+        let npc = create_npc(self.players[idx].get_name().to_owned());
+        let player = self.replace_player(idx, npc);
+        self.away.push((idx, player));
+        observer.notify(&GameEvent::Substitute { idx });
+    }
+
+    // NPCに任せた席の人間のプレイヤーが操作に戻るかを確認し、戻る場合は席に戻す
+    fn offer_return(&mut self) {
+        for (idx, mut player) in std::mem::take(&mut self.away) {
+            if player.confirm_return() {
+                tracing::info!(idx, "操作に戻る");
+                self.players[idx] = player;
+            } else {
+                self.away.push((idx, player));
+            }
+        }
+    }
+
+    pub fn play_round(&mut self, observer: &mut dyn Observer) -> Vec<usize> {
+        self.start_round(observer);
+        self.continue_round(observer)
+    }
+
+    // ラウンドを終わりまで進める(中断した場合は空)
+    pub fn continue_round(&mut self, observer: &mut dyn Observer) -> Vec<usize> {
+        while !self.is_over() {
+            self.step(observer);
+            // 中断した場合はラウンドの結果を出さない
+            if self.quit {
+                return vec![];
+            }
+        }
+        self.end_round(observer)
+    }
+
+    // 1手ずつ進める場合はstart_roundとend_roundの間でstepを呼ぶ
+    pub fn start_round(&mut self, observer: &mut dyn Observer) {
+        tracing::info!(
+            names = ?self.get_names(),
+            dealer = self.dealer,
+            rules = ?self.field.get_rules(),
+            "ラウンド開始"
+        );
+        let event = GameEvent::Start {
+            names: self.get_names(),
+            hands: self
+                .players
+                .iter()
+                .map(|p| p.get_hands().to_vec())
+                .collect(),
+        };
+        // 他のプレイヤーの手札は見せない
+        for (i, player) in self.players.iter_mut().enumerate() {
+            player.observe(&event.censor(Some(i)));
+        }
+        observer.notify(&event);
+    }
+
+    pub fn end_round(&mut self, observer: &mut dyn Observer) -> Vec<usize> {
+        let player_rank = self.field.get_player_rank();
+        tracing::info!(?player_rank, "ラウンド終了");
+        let event = GameEvent::End {
+            player_rank: player_rank.clone(),
+        };
+        self.show_players(&event);
+        observer.notify(&event);
+        player_rank
+    }
+
+    pub fn next_round(&mut self, player_rank: &[usize], observer: &mut dyn Observer) {
+        self.offer_return();
+        self.turns = 0;
+        self.rejects = 0;
+        self.undo_points.clear();
+        // 新しいカードを配る
+        get_split_deck(&mut self.rng, self.players.len())
+            .into_iter()
+            .zip(self.players.iter_mut())
+            .for_each(|(hands, player)| player.init(hands));
+        // カードを交換
+        for (winner, loser, count) in get_exchange_pairs(player_rank) {
+            exchange_cards(&mut self.players, winner, loser, count, observer);
+        }
+        observer.notify(&GameEvent::Exchange);
+        // フィールドをリセット、ルールで決まるプレイヤーから開始
+        let rules = self.field.get_rules();
+        self.dealer = match rules.lead {
+            LeadRule::Loser => player_rank[player_rank.len() - 1],
+            LeadRule::Rotate => (self.dealer + 1) % self.players.len(),
+            LeadRule::Winner => player_rank[0],
+            LeadRule::Diamond => get_diamond_holder(self.players.iter().map(|p| p.get_hands()))
+                .unwrap_or(player_rank[player_rank.len() - 1]),
+            LeadRule::Cut => get_cut_winner(&self.rng, self.players.len()),
+        };
+        self.field = create_field(&self.players, self.dealer);
+        self.field.set_rules(rules);
+        self.field.set_capital(Some(player_rank[0]));
+    }
+}
+
+// ♦3を持っているプレイヤーの席
+pub fn get_diamond_holder<'a>(mut hands: impl Iterator<Item = &'a [Card]>) -> Option<usize> {
+    hands.position(|hands| hands.contains(&Card::Normal(Suit::Diamond, Rank::Three)))
+}
+
+// 山札を混ぜて引き、親になるプレイヤーの席を決める
+// 配る乱数の列を変えないように別のストリームで山札を混ぜる
+pub fn get_cut_winner(rng: &ChaCha8Rng, players_count: usize) -> usize {
+    let mut rng = rng.clone();
+    rng.set_stream(1);
+    let mut deck = card::create_deck();
+    deck.shuffle(&mut rng);
+    find_cut_winner(&deck, players_count)
+}
+
+// 山札から席の順に1枚ずつ引き、一番強い数字(ジョーカーが最も強い)を引いたプレイヤーの席
+// スートでは比べず、同じ数字で並んだプレイヤーだけで続けて引き直す
+fn find_cut_winner(deck: &[Card], players_count: usize) -> usize {
+    let mut seats: Vec<usize> = (0..players_count).collect();
+    let mut cards = deck.iter();
+    while seats.len() > 1 {
+        let drawn: Vec<(usize, &Card)> = seats
+            .iter()
+            .map_while(|seat| cards.next().map(|card| (*seat, card)))
+            .collect();
+        // 山札が尽きたら残った最初の席にする
+        let Some(best) = drawn
+            .iter()
+            .map(|(_, card)| *card)
+            .max_by(|a, b| cmp_rank(a, b))
+            .filter(|_| drawn.len() == seats.len())
+        else {
+            break;
+        };
+        seats = drawn
+            .iter()
+            .filter(|(_, card)| cmp_rank(card, best) == Ordering::Equal)
+            .map(|(seat, _)| *seat)
+            .collect();
+    }
+    seats.first().copied().unwrap_or(0)
+}
+
+// シードから各ラウンドの配った後に山札を引いて決まる親の席を求める
+pub fn get_cut_winners(seed: u64, players_counts: &[usize]) -> Vec<usize> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    players_counts
+        .iter()
+        .map(|count| {
+            get_split_deck(&mut rng, *count);
+            get_cut_winner(&rng, *count)
+        })
+        .collect()
+}
+
+// 順位から献上する組(勝ったプレイヤー、負けたプレイヤー、枚数)を求める
+// 大富豪と大貧民は2枚、4人以上なら富豪と貧民が1枚交換する
+pub fn get_exchange_pairs(player_rank: &[usize]) -> Vec<(usize, usize, usize)> {
+    let len = player_rank.len();
+    let mut pairs = vec![];
+    if len >= 2 {
+        pairs.push((player_rank[0], player_rank[len - 1], 2));
+    }
+    if len >= 4 {
+        pairs.push((player_rank[1], player_rank[len - 2], 1));
+    }
+    pairs
+}
+
+// シードから各ラウンドで配られるカードを求める(交換する前の手札、ラウンド毎の人数を指定する)
+pub fn get_deals(seed: u64, players_counts: &[usize]) -> Vec<Vec<Vec<Card>>> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    players_counts
+        .iter()
+        .map(|count| get_split_deck(&mut rng, *count))
+        .collect()
+}
+
+// 割り切れない分は後ろの席に1枚ずつ多く配る
+fn get_split_deck(rng: &mut ChaCha8Rng, players_count: usize) -> Vec<Vec<Card>> {
+    let mut deck = card::create_deck();
+    deck.shuffle(rng);
+    let size = deck.len() / players_count;
+    let extra = deck.len() % players_count;
+    let mut hands: Vec<Vec<Card>> = (0..players_count - 1)
+        .map(|i| {
+            let count = size + usize::from(i + extra >= players_count);
+            deck.split_off(deck.len() - count)
+        })
+        .collect();
+    hands.push(deck);
+    hands.iter_mut().for_each(|d| d.sort_by(cmp_order));
+    hands
+}
+
+fn create_field(players: &[Box<dyn Player>], start_idx: usize) -> Field {
+    let mut field = Field::new(players.len(), start_idx);
+    field.set_players(
+        players.iter().map(|p| p.get_name().to_owned()).collect(),
+        players.iter().map(|p| p.count_hands()).collect(),
+    );
+    field
+}
+
+fn exchange_cards(
+    players: &mut [Box<dyn Player>],
+    winner_idx: usize,
+    loser_idx: usize,
+    cards_count: usize,
+    observer: &mut dyn Observer,
+) {
+    // 献上: 負けたプレイヤーの最も強いカードを確認させてから渡す
+    let tribute = get_strongest_cards(players[loser_idx].get_hands(), cards_count);
+    players[loser_idx].confirm_tribute(&tribute);
+    let tribute = players[loser_idx].take_cards(&tribute);
+    observer.notify(&GameEvent::Tribute {
+        from: loser_idx,
+        to: winner_idx,
+        cards: tribute.clone(),
+    });
+    tracing::debug!(from = loser_idx, to = winner_idx, cards = %card::cards_to_ascii(&tribute), "献上");
+    players[winner_idx].receive_cards(tribute);
+    players[winner_idx].sort_hands(Order::Normal);
+    // 返礼: 勝ったプレイヤーが受け取ったカードも含めて選ぶ
+    let returns = players[winner_idx].get_needless_cards(cards_count);
+    observer.notify(&GameEvent::Return {
+        from: winner_idx,
+        to: loser_idx,
+        cards: returns.clone(),
+    });
+    tracing::debug!(from = winner_idx, to = loser_idx, cards = %card::cards_to_ascii(&returns), "返礼");
+    players[loser_idx].receive_cards(returns);
+    players[loser_idx].sort_hands(Order::Normal);
+}
+
+// 手札の並び順に関係なく最も強いカードを選ぶ
+pub fn get_strongest_cards(hands: &[Card], cards_count: usize) -> Vec<Card> {
+    let mut cards = hands.to_vec();
+    cards.sort_by(cmp_order);
+    cards.split_off(cards.len().saturating_sub(cards_count))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{parse_cards, Rank, Suit};
+    use crate::field::PlayError;
+    use crate::input::ScriptedInput;
+    use crate::npc::MinNpc;
+    use crate::observer::Observers;
+    use crate::pc::{Pc, TimeoutAction, TurnTimer};
+    use crate::player::take_from_hands;
+    use crate::render::Style;
+    use crate::rules::FoulRule;
+    use crate::strategy::Strategy;
+    use itertools::Itertools;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_exchange_cards() {
+        let c = |suit, rank| Card::Normal(suit, rank);
+        let mut players: Vec<Box<dyn Player>> = vec![
+            Box::new(MinNpc::new("A".to_owned())),
+            Box::new(MinNpc::new("B".to_owned())),
+        ];
+        players[0].init(vec![c(Suit::Club, Rank::Four), c(Suit::Heart, Rank::King)]);
+        // 並んでいない手札からも最も強いカードを献上する
+        players[1].init(vec![
+            Card::Joker,
+            c(Suit::Club, Rank::Three),
+            c(Suit::Spade, Rank::Two),
+            c(Suit::Heart, Rank::Five),
+        ]);
+        exchange_cards(&mut players, 0, 1, 2, &mut Observers(vec![]));
+        assert_eq!(
+            players[0].get_hands(),
+            [c(Suit::Spade, Rank::Two), Card::Joker]
+        );
+        assert_eq!(
+            players[1].get_hands(),
+            [
+                c(Suit::Club, Rank::Three),
+                c(Suit::Club, Rank::Four),
+                c(Suit::Heart, Rank::Five),
+                c(Suit::Heart, Rank::King),
+            ]
+        );
+    }
+
+    // ラウンドの進行を記録する
+    struct Recorder(Vec<GameEvent>);
+
+    impl Observer for Recorder {
+        fn notify(&mut self, event: &GameEvent) {
+            self.0.push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        for explain in [false, true] {
+            let players: Vec<Box<dyn Player>> = ["A", "B", "C", "D"]
+                .iter()
+                .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>)
+                .collect();
+            let mut game = Game::new(players, 0, 1).with_explain(explain);
+            let mut recorder = Recorder(vec![]);
+            game.play_round(&mut recorder);
+            // 理由は手の直後に同じ席から知らせる
+            let mut turns = 0;
+            for (i, event) in recorder.0.iter().enumerate() {
+                if let GameEvent::Turn { idx, .. } = event {
+                    turns += 1;
+                    let next = recorder.0.get(i + 1);
+                    match next {
+                        Some(GameEvent::Reason { idx: seat, reason }) => {
+                            assert!(explain);
+                            assert_eq!(seat, idx);
+                            assert!(!reason.is_empty());
+                        }
+                        _ => assert!(!explain),
+                    }
+                }
+            }
+            assert!(turns > 0);
+        }
+    }
+
+    // 受け取った公開の進行を記録するNPC
+    struct Watcher(MinNpc, Rc<RefCell<Vec<GameEvent>>>);
+
+    impl Player for Watcher {
+        fn init(&mut self, hands: Vec<Card>) {
+            self.0.init(hands);
+        }
+        fn count_hands(&self) -> usize {
+            self.0.count_hands()
+        }
+        fn get_name(&self) -> &str {
+            self.0.get_name()
+        }
+        fn get_hands(&self) -> &[Card] {
+            self.0.get_hands()
+        }
+        fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+            self.0.take_cards(cards)
+        }
+        fn receive_cards(&mut self, cards: Vec<Card>) {
+            self.0.receive_cards(cards);
+        }
+        fn sort_hands(&mut self, order: Order) {
+            self.0.sort_hands(order);
+        }
+        fn play(&mut self, validator: &dyn Validator) -> Action {
+            self.0.play(validator)
+        }
+        fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
+            self.0.get_needless_cards(cards_count)
+        }
+        fn observe(&mut self, event: &GameEvent) {
+            self.1.borrow_mut().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_observe() {
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut players: Vec<Box<dyn Player>> = ["A", "B", "C"]
+            .iter()
+            .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>)
+            .collect();
+        players.insert(
+            1,
+            Box::new(Watcher(MinNpc::new("W".to_owned()), events.clone())),
+        );
+        let mut game = Game::new(players, 0, 3);
+        let mut recorder = Recorder(vec![]);
+        game.play_round(&mut recorder);
+        // 自分以外の手札を隠した進行を、観戦者と同じ順に受け取る
+        let events = events.borrow();
+        let GameEvent::Start { hands, .. } = &events[0] else {
+            panic!("ラウンドの開始を受け取っていません");
+        };
+        let empty: Vec<bool> = hands.iter().map(|h| h.is_empty()).collect();
+        assert_eq!(empty, [true, false, true, true]);
+        let public: Vec<&GameEvent> = recorder.0.iter().skip(1).collect();
+        assert_eq!(events.iter().skip(1).collect::<Vec<_>>(), public);
+    }
+
+    #[test]
+    fn test_invariants() {
+        // ルールの組み合わせを変えながら、1手毎に場の状態が矛盾しないかを確かめる
+        for seed in 0..32_u64 {
+            let players: Vec<Box<dyn Player>> = (0..4)
+                .map(|i| {
+                    let strategy = [Strategy::Min, Strategy::Heuristic][i % 2];
+                    strategy.create_npc(format!("P{i}"), seed)
+                })
+                .collect();
+            let mut rules = RuleSet::default();
+            rules.set_toggles(std::array::from_fn(|i| ((seed * 37) >> i) & 1 == 1));
+            rules.foul = FoulRule::ALL[(seed % 2) as usize];
+            let mut game = Game::new(players, (seed % 4) as usize, seed).with_rules(rules);
+            let mut observers = Observers(vec![]);
+            for _ in 0..3 {
+                game.start_round(&mut observers);
+                assert_eq!(game.get_field().check_invariants(), Ok(()));
+                while !game.is_over() {
+                    game.step(&mut observers);
+                    assert_eq!(game.get_field().check_invariants(), Ok(()));
+                }
+                let player_rank = game.end_round(&mut observers);
+                assert_eq!(player_rank.len(), 4);
+                game.next_round(&player_rank, &mut observers);
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_deck() {
+        for (players_count, expected) in [
+            (4, vec![13, 13, 13, 14]),
+            (3, vec![17, 18, 18]),
+            (5, vec![10, 10, 11, 11, 11]),
+            (2, vec![26, 27]),
+        ] {
+            let mut rng = ChaCha8Rng::seed_from_u64(1);
+            let hands = get_split_deck(&mut rng, players_count);
+            let counts: Vec<usize> = hands.iter().map(Vec::len).collect();
+            assert_eq!(counts, expected);
+        }
+        assert_eq!(get_exchange_pairs(&[2, 0, 1]), [(2, 1, 2)]);
+        assert_eq!(get_exchange_pairs(&[4, 0, 3, 1, 2]), [(4, 2, 2), (0, 1, 1)]);
+    }
+
+    #[test]
+    fn test_change_players() {
+        let players: Vec<Box<dyn Player>> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>)
+            .collect();
+        let mut game = Game::new(players, 0, 1);
+        let mut observers = Observers(vec![]);
+        let player_rank = game.play_round(&mut observers);
+        // 抜けた席より後ろの席を詰める
+        let (removed, player_rank) = game.remove_player(1, &player_rank).unwrap();
+        assert_eq!(removed.get_name(), "B");
+        assert_eq!(game.get_names(), ["A", "C", "D"]);
+        assert_eq!(player_rank.iter().sorted().collect_vec(), [&0, &1, &2]);
+        game.next_round(&player_rank, &mut observers);
+        let player_rank = game.play_round(&mut observers);
+        assert_eq!(player_rank.len(), 3);
+        // 加わったプレイヤーは真ん中の順位になる
+        let player_rank = game.add_player(removed, &player_rank).unwrap();
+        let new_player = Box::new(MinNpc::new("E".to_owned()));
+        let player_rank = game.add_player(new_player, &player_rank).unwrap();
+        assert_eq!(game.get_names(), ["A", "C", "D", "B", "E"]);
+        assert_eq!(player_rank[2], 4);
+        game.next_round(&player_rank, &mut observers);
+        let cards: usize = (0..5).map(|idx| game.get_hands(idx).len()).sum();
+        assert_eq!(cards, card::DECK_LEN);
+        assert_eq!(game.play_round(&mut observers).len(), 5);
+        // 人数の範囲を超えられない
+        for _ in 0..3 {
+            game.remove_player(0, &[]).unwrap();
+        }
+        assert!(game.remove_player(0, &[]).is_err());
+        for _ in MIN_PLAYERS..MAX_PLAYERS {
+            let player = Box::new(MinNpc::new("F".to_owned()));
+            game.add_player(player, &[]).unwrap();
+        }
+        let player = Box::new(MinNpc::new("G".to_owned()));
+        assert!(game.add_player(player, &[]).is_err());
+    }
+
+    #[test]
+    fn test_substitute() {
+        let mut players: Vec<Box<dyn Player>> = vec![Box::new(
+            Pc::new("User".to_owned(), Style::new(false, true), false, None)
+                .with_input(Box::new(ScriptedInput::new(["q", "n"]))),
+        )];
+        players.extend(
+            ["A", "B", "C"]
+                .iter()
+                .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>),
+        );
+        let mut game = Game::new(players, 0, 1);
+        let mut recorder = Recorder(vec![]);
+        assert!(game.play_round(&mut recorder).is_empty());
+        assert!(game.is_quit());
+        let hands = game.get_hands(0).to_vec();
+        // 手札を引き継いだNPCがラウンドを続ける
+        let npc = Box::new(MinNpc::new("User".to_owned()));
+        assert_eq!(game.substitute(npc, &mut recorder), 0);
+        assert!(!game.is_quit());
+        assert!(game.get_human_seats().is_empty());
+        assert_eq!(game.get_hands(0), hands);
+        assert_eq!(recorder.0.last(), Some(&GameEvent::Substitute { idx: 0 }));
+        assert_eq!(game.continue_round(&mut recorder).len(), 4);
+    }
+
+    #[test]
+    fn test_hand_over() {
+        let timer = TurnTimer {
+            timeout: Duration::ZERO,
+            action: TimeoutAction::Pass,
+            afk_turns: Some(2),
+        };
+        // backを立てた後に1度だけyを入力し、それ以外は入力しない
+        let back = Rc::new(Cell::new(false));
+        let input = {
+            let back = back.clone();
+            move |_: &str, _| back.replace(false).then(|| "y".to_owned())
+        };
+        let mut players: Vec<Box<dyn Player>> = vec![Box::new(
+            Pc::new(
+                "User".to_owned(),
+                Style::new(false, true),
+                false,
+                Some(timer),
+            )
+            .with_input(Box::new(input)),
+        )];
+        players.extend(
+            ["A", "B", "C"]
+                .iter()
+                .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>),
+        );
+        let mut game = Game::new(players, 0, 1)
+            .with_substitutes(|name| Box::new(MinNpc::new(name)) as Box<dyn Player>);
+        let mut recorder = Recorder(vec![]);
+        // 2回続けて時間切れになるとNPCがラウンドの残りを引き継ぐ
+        let player_rank = game.play_round(&mut recorder);
+        assert_eq!(player_rank.len(), 4);
+        assert!(game.get_human_seats().is_empty());
+        let turns = recorder
+            .0
+            .iter()
+            .take_while(|event| **event != GameEvent::Substitute { idx: 0 })
+            .filter(|event| matches!(event, GameEvent::Turn { idx: 0, .. }))
+            .count();
+        assert_eq!(turns, 2);
+        // 確認に答えなければNPCに任せたまま
+        game.next_round(&player_rank, &mut recorder);
+        assert!(game.get_human_seats().is_empty());
+        let player_rank = game.play_round(&mut recorder);
+        back.set(true);
+        game.next_round(&player_rank, &mut recorder);
+        assert_eq!(game.get_human_seats(), vec![0]);
+        assert!(!game.get_hands(0).is_empty());
+    }
+
+    // 場にカードがなくても必ずパスするプレイヤー
+    struct Passer(Vec<Card>);
+
+    impl Player for Passer {
+        fn init(&mut self, hands: Vec<Card>) {
+            self.0 = hands;
+        }
+
+        fn count_hands(&self) -> usize {
+            self.0.len()
+        }
+
+        fn get_name(&self) -> &str {
+            "Passer"
+        }
+
+        fn get_hands(&self) -> &[Card] {
+            &self.0
+        }
+
+        fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+            take_from_hands(&mut self.0, cards)
+        }
+
+        fn receive_cards(&mut self, cards: Vec<Card>) {
+            self.0.extend(cards);
+        }
+
+        fn sort_hands(&mut self, order: Order) {
+            self.0.sort_by(order.get_comparator());
+        }
+
+        fn play(&mut self, _validator: &dyn Validator) -> Action {
+            Action::Move(None)
+        }
+
+        fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
+            self.0.drain(..cards_count).collect()
+        }
+    }
+
+    #[test]
+    fn test_reject() {
+        let mut players: Vec<Box<dyn Player>> = vec![Box::new(Passer(vec![]))];
+        players.extend(
+            ["A", "B", "C"]
+                .iter()
+                .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>),
+        );
+        let mut game = Game::new(players, 0, 1);
+        let mut recorder = Recorder(vec![]);
+        // 受け付けない手を繰り返しても最も弱い手に置き換えてラウンドが終わる
+        assert_eq!(game.play_round(&mut recorder).len(), 4);
+        let rejects: Vec<&GameEvent> = recorder
+            .0
+            .iter()
+            .skip(1)
+            .take_while(|event| !matches!(event, GameEvent::Turn { .. }))
+            .collect();
+        let reject = GameEvent::Reject {
+            idx: 0,
+            error: PlayError::LeadPass,
+        };
+        assert_eq!(rejects, vec![&reject; MAX_REJECTS]);
+        assert!(matches!(
+            recorder.0.get(MAX_REJECTS + 1),
+            Some(GameEvent::Turn {
+                idx: 0,
+                comb: Some(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let create_players = |names: &[&str]| -> Vec<Box<dyn Player>> {
+            names
+                .iter()
+                .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>)
+                .collect()
+        };
+        let mut game = Game::new(create_players(&["A", "B", "C", "D"]), 0, 3);
+        let mut observer = Observers(vec![]);
+        for _ in 0..6 {
+            game.step(&mut observer);
+        }
+        let state = game.snapshot();
+        let view = state.get_view();
+        assert_eq!(view.idx, game.get_field().get_idx());
+        assert_eq!(state.get_hands(2), game.get_hands(2));
+        let player_rank = game.continue_round(&mut observer);
+        assert_eq!(game.snapshot().get_standings(), player_rank);
+        // 戻した状態から続けると同じ結果になる
+        game.restore(state).unwrap();
+        assert_eq!(game.get_field().get_view(), view);
+        assert_eq!(game.continue_round(&mut observer), player_rank);
+        let mut other = Game::new(create_players(&["A", "B", "C"]), 0, 3);
+        assert!(other.restore(game.snapshot()).is_err());
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut players: Vec<Box<dyn Player>> = vec![Box::new(
+            Pc::new("User".to_owned(), Style::new(false, true), false, None).with_input(Box::new(
+                ScriptedInput::new(["undo", "0", "undo", "q", "n"]),
+            )),
+        )];
+        players.extend(
+            ["A", "B", "C"]
+                .iter()
+                .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>),
+        );
+        let mut game = Game::new(players, 0, 1);
+        let hands = game.get_hands(0).to_vec();
+        let mut recorder = Recorder(vec![]);
+        assert!(game.play_round(&mut recorder).is_empty());
+        // 最初の手番では戻せず、次の手番では最初の手番の始めに戻る
+        let undone: Vec<&GameEvent> = recorder
+            .0
+            .iter()
+            .filter(|event| matches!(event, GameEvent::Undo { .. }))
+            .collect();
+        let turns = recorder
+            .0
+            .iter()
+            .filter(|event| matches!(event, GameEvent::Turn { .. }))
+            .count();
+        assert_eq!(
+            undone,
+            vec![&GameEvent::Undo { turns: 0 }, &GameEvent::Undo { turns }]
+        );
+        assert_eq!(game.get_hands(0), hands);
+        assert_eq!(game.get_field().get_idx(), 0);
+    }
+
+    #[test]
+    fn test_next_round() {
+        for (lead, start_idx) in [
+            (LeadRule::Loser, 0),
+            (LeadRule::Rotate, 3),
+            (LeadRule::Winner, 1),
+            (LeadRule::Diamond, 2),
+            (LeadRule::Cut, 0),
+        ] {
+            let players: Vec<Box<dyn Player>> = ["A", "B", "C", "D"]
+                .iter()
+                .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>)
+                .collect();
+            let rules = RuleSet {
+                lead,
+                ..RuleSet::default()
+            };
+            let mut game = Game::new(players, start_idx, 1).with_rules(rules);
+            let mut observers = Observers(vec![]);
+            for round in 1..=3 {
+                let player_rank = game.play_round(&mut observers);
+                game.next_round(&player_rank, &mut observers);
+                let expected = match lead {
+                    LeadRule::Loser => player_rank[3],
+                    LeadRule::Rotate => (start_idx + round) % 4,
+                    LeadRule::Winner => player_rank[0],
+                    LeadRule::Diamond => (0..4)
+                        .find(|idx| {
+                            game.get_hands(*idx)
+                                .contains(&Card::Normal(Suit::Diamond, Rank::Three))
+                        })
+                        .unwrap(),
+                    LeadRule::Cut => get_cut_winners(1, &[4; 4])[round],
+                };
+                assert_eq!(game.get_field().get_idx(), expected);
+                assert_eq!(game.snapshot().dealer, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_cut_winner() {
+        for (deck, players_count, expected) in [
+            ("♠3 ♥K ♦5 ♣4", 4, 1),
+            ("♠2 JK ♦5", 3, 1),
+            // スートでは比べず、並んだ席だけで引き直す
+            ("♠K ♦5 ♥K ♣3 ♠4", 3, 2),
+            ("♠K ♥K ♦K ♠7 ♦7 ♣7 ♣4 ♥9 ♠8", 3, 1),
+            // 山札が尽きたら並んだ最初の席
+            ("♠K ♥K", 2, 0),
+        ] {
+            let deck = parse_cards(deck).unwrap();
+            assert_eq!(find_cut_winner(&deck, players_count), expected);
+        }
+    }
+
+    #[test]
+    fn test_get_cut_winner() {
+        for (seed, expected) in [
+            // ♦9 ♦4 ♠7 ♦3
+            (1, 0),
+            // 最初にジョーカーを引く
+            (17, 0),
+            // ♥Jと♦Jで並び、引き直して♠5と♣Jを引く
+            (19, 3),
+        ] {
+            let rng = ChaCha8Rng::seed_from_u64(seed);
+            assert_eq!(get_cut_winner(&rng, 4), expected);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::bitcards::HandMask;
+use crate::card::{Card, RANKS, SUITS};
+use crate::comb::MIN_SEQ;
+use crate::tables::RUNS;
+
+// 手札に含まれる組み合わせと強さの目安
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HandAnalysis {
+    // 同じ数字の2枚、3枚、4枚の組(ジョーカーは含めない)
+    pub pairs: Vec<Vec<Card>>,
+    pub triples: Vec<Vec<Card>>,
+    pub quads: Vec<Vec<Card>>,
+    // 同じスートで連続する3枚以上の並び(それ以上伸ばせない長さで、ジョーカーは含めない)
+    pub straights: Vec<Vec<Card>>,
+    // どの組にも入らないカード
+    pub singles: Vec<Card>,
+    pub jokers: usize,
+    // 同じ数字をまとめて出したときに出し切るまでにかかる手数
+    pub moves: usize,
+    // 0から1の強さの目安(カードの強さと手数の少なさの平均)
+    pub strength: f64,
+}
+
+// 手札を組み合わせに分けて強さを見積もる
+pub fn analyze(cards: &[Card]) -> HandAnalysis {
+    let mask = HandMask::from(cards);
+    let mut analysis = HandAnalysis {
+        jokers: mask.count_jokers(),
+        ..Default::default()
+    };
+    let mut grouped = HandMask::default();
+    for rank in RANKS {
+        let group: Vec<Card> = SUITS
+            .iter()
+            .filter(|suit| mask.contains(&Card::Normal(**suit, rank)))
+            .map(|suit| Card::Normal(*suit, rank))
+            .collect();
+        if !group.is_empty() {
+            analysis.moves += 1;
+        }
+        let groups = match group.len() {
+            2 => &mut analysis.pairs,
+            3 => &mut analysis.triples,
+            4 => &mut analysis.quads,
+            _ => continue,
+        };
+        group.iter().for_each(|card| grouped.insert(card));
+        groups.push(group);
+    }
+    for suit in SUITS {
+        let ranks = mask.get_suit_ranks(&suit);
+        // 1つ下の数字がない位置から始まる並びだけを数える
+        for low in (0..RANKS.len()).filter(|low| *low == 0 || ranks & (1 << (low - 1)) == 0) {
+            let run = RUNS[ranks as usize][low] as usize;
+            if run >= MIN_SEQ {
+                let straight: Vec<Card> = RANKS[low..low + run]
+                    .iter()
+                    .map(|rank| Card::Normal(suit, *rank))
+                    .collect();
+                straight.iter().for_each(|card| grouped.insert(card));
+                analysis.straights.push(straight);
+            }
+        }
+    }
+    analysis.singles = mask
+        .get_cards()
+        .into_iter()
+        .filter(|card| matches!(card, Card::Normal(..)) && !grouped.contains(card))
+        .collect();
+    // ジョーカーだけが残っていても1手はかかる
+    if analysis.moves == 0 && analysis.jokers > 0 {
+        analysis.moves = 1;
+    }
+    analysis.strength = match cards.len() {
+        0 => 1.0,
+        len => {
+            let average = cards
+                .iter()
+                .map(|card| get_strength(card, false))
+                .sum::<f64>()
+                / len as f64;
+            let tempo = 1.0 - (analysis.moves - 1) as f64 / len as f64;
+            (average + tempo) / 2.0
+        }
+    };
+    analysis
+}
+
+// カードの強さを0から1の値で取得する
+pub fn get_strength(card: &Card, is_rev: bool) -> f64 {
+    match card {
+        Card::Normal(_, r) => {
+            let value = f64::from(i32::from(r)) / 12.0;
+            match is_rev {
+                true => 1.0 - value,
+                false => value,
+            }
+        }
+        Card::Joker => 1.0,
+    }
+}
+
+// 革命を起こすと残りの手札が他のプレイヤーに比べて強くなるか
+// 見えていないカードの強さの平均は革命の前後で変わらないので、残りの手札の強さの合計だけを比べる
+pub fn improves_by_revolution(rest: &[Card], is_rev: bool) -> bool {
+    let total = |is_rev| {
+        rest.iter()
+            .map(|card| get_strength(card, is_rev))
+            .sum::<f64>()
+    };
+    rest.is_empty() || total(!is_rev) > total(is_rev)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{parse_cards, Rank, Suit};
+
+    #[test]
+    fn test_analyze() {
+        let c = |suit, rank| Card::Normal(suit, rank);
+        let analysis = analyze(&parse_cards("♣3 ♦3 ♥3 ♠5 ♥5 ♠6 ♠7 ♠8 ♥K Joker").unwrap());
+        assert_eq!(
+            analysis.pairs,
+            vec![vec![c(Suit::Heart, Rank::Five), c(Suit::Spade, Rank::Five)]]
+        );
+        assert_eq!(
+            analysis.triples,
+            vec![vec![
+                c(Suit::Club, Rank::Three),
+                c(Suit::Diamond, Rank::Three),
+                c(Suit::Heart, Rank::Three),
+            ]]
+        );
+        assert!(analysis.quads.is_empty());
+        assert_eq!(
+            analysis.straights,
+            vec![vec![
+                c(Suit::Spade, Rank::Five),
+                c(Suit::Spade, Rank::Six),
+                c(Suit::Spade, Rank::Seven),
+                c(Suit::Spade, Rank::Eight),
+            ]]
+        );
+        assert_eq!(analysis.singles, vec![c(Suit::Heart, Rank::King)]);
+        assert_eq!(analysis.jokers, 1);
+        assert_eq!(analysis.moves, 6);
+        for (text, moves, strength) in [
+            ("", 0, 1.0),
+            ("Joker", 1, 1.0),
+            ("♠3", 1, 0.5),
+            ("♠3 ♦3 ♥3 ♣3", 1, 0.5),
+            ("♠3 ♠2", 2, 0.5),
+        ] {
+            let analysis = analyze(&parse_cards(text).unwrap());
+            assert_eq!(analysis.moves, moves);
+            assert_eq!(analysis.strength, strength);
+        }
+        // 強いカードが多く、手数が少ないほど強い
+        let strong = analyze(&parse_cards("♠A ♥A ♠2 Joker").unwrap());
+        let weak = analyze(&parse_cards("♠3 ♥5 ♠7 ♦9").unwrap());
+        assert!(strong.strength > weak.strength);
+    }
+
+    #[test]
+    fn test_get_strength() {
+        for (card, is_rev, expected) in [
+            (Card::Normal(Suit::Spade, Rank::Three), false, 0.0),
+            (Card::Normal(Suit::Spade, Rank::Two), false, 1.0),
+            (Card::Normal(Suit::Spade, Rank::Two), true, 0.0),
+            (Card::Joker, true, 1.0),
+        ] {
+            assert_eq!(get_strength(&card, is_rev), expected);
+        }
+    }
+
+    #[test]
+    fn test_improves_by_revolution() {
+        for (rest, is_rev, expected) in [
+            // 弱いカードが残るなら革命を起こす
+            ("♠3 ♥5 ♦9", false, true),
+            ("♠3 ♥5 ♦9", true, false),
+            // 強いカードが残るなら革命を起こさない
+            ("♠K ♥2 ♦9", false, false),
+            ("♠K ♥2 ♦9", true, true),
+            // ジョーカーの強さは変わらない
+            ("♠9 Joker", false, false),
+            // 出し切るなら革命になっても構わない
+            ("", false, true),
+        ] {
+            let rest = parse_cards(rest).unwrap();
+            assert_eq!(improves_by_revolution(&rest, is_rev), expected, "{rest:?}");
+        }
+    }
+}
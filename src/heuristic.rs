@@ -0,0 +1,480 @@
+use crate::card::{Card, Order, Rank};
+use crate::comb::Comb;
+use crate::endgame::Planner;
+use crate::field::contains_eight;
+use crate::hand::{get_strength, improves_by_revolution};
+use crate::moves::{get_legal_combs, remove_cards};
+use crate::player::{take_from_hands, Action, Player};
+use crate::strategy::Strategy;
+use crate::validator::Validator;
+use crate::view::GameView;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+// 同じスートをこの枚数以上持っていれば縛りを狙う
+const LONG_SUIT: usize = 4;
+// 残りの手札がこの枚数以下なら8を残さない(8で上がると反則上がりになる)
+const LATE_EIGHT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct Weights {
+    // 出すカードの枚数
+    pub count: f64,
+    // 出すカードの強さ
+    pub strength: f64,
+    // ジョーカーを使うか
+    pub joker: f64,
+    // 同じ数字の組を崩すか
+    pub split: f64,
+    // この評価値を下回るならパスする
+    pub pass: f64,
+    // 多く持っているスートで縛りを狙うか
+    pub bind: f64,
+    // 8切りで親を取り返せるように8を残すか
+    pub eight: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            count: 1.0,
+            strength: 2.0,
+            joker: 1.5,
+            split: 0.8,
+            pass: -1.5,
+            bind: 0.6,
+            eight: 0.5,
+        }
+    }
+}
+
+pub struct HeuristicNpc {
+    name: String,
+    hands: Vec<Card>,
+    weights: Weights,
+    // 終盤に見つけた上がり方
+    planner: Planner,
+}
+
+impl HeuristicNpc {
+    pub fn new(name: String, weights: Weights) -> Self {
+        Self {
+            name,
+            hands: vec![],
+            weights,
+            planner: Planner::default(),
+        }
+    }
+
+    fn evaluate(&self, indices: &[usize], is_rev: bool) -> f64 {
+        // 全てのカードを出し切れるなら最優先
+        if indices.len() == self.hands.len() {
+            return f64::INFINITY;
+        }
+        let cards: Vec<Card> = indices.iter().map(|i| self.hands[*i]).collect();
+        let strength = cards
+            .iter()
+            .map(|card| get_strength(card, is_rev))
+            .sum::<f64>()
+            / cards.len() as f64;
+        let uses_joker = cards.contains(&Card::Joker);
+        // 出したあとに同じ数字のカードが手札に残るか
+        let splits = cards
+            .iter()
+            .filter_map(|card| match card {
+                Card::Normal(_, r) => Some(r),
+                Card::Joker => None,
+            })
+            .filter(|rank| {
+                self.hands.iter().enumerate().any(|(i, c)| {
+                    !indices.contains(&i) && matches!(c, Card::Normal(_, r) if r == *rank)
+                })
+            })
+            .count();
+        self.weights.count * cards.len() as f64
+            - self.weights.strength * strength
+            - self.weights.joker * f64::from(u8::from(uses_joker))
+            - self.weights.split * splits as f64
+    }
+
+    // 革命になる組み合わせは、革命で残りの手札が強くなるときだけ出す
+    fn is_timely(&self, comb: &Comb, indices: &[usize], view: &GameView) -> bool {
+        if !matches!(comb, Comb::Multi(cards) if cards.len() >= 4) || !view.rules.revolution {
+            return true;
+        }
+        let rest: Vec<Card> = (0..self.hands.len())
+            .filter(|i| !indices.contains(i))
+            .map(|i| self.hands[i])
+            .collect();
+        improves_by_revolution(&rest, view.is_rev)
+    }
+
+    // 親のときに最後の8を出すなら減点する
+    // 場にカードがあれば8切りで親を取れるので減点せず、終盤は反則上がりにならないように出してしまう
+    fn get_eight_penalty(&self, comb: &Comb, indices: &[usize], view: &GameView) -> f64 {
+        if !view.rules.eight_cut || view.prev_comb.is_some() || !contains_eight(comb) {
+            return 0.0;
+        }
+        let rest: Vec<Card> = (0..self.hands.len())
+            .filter(|i| !indices.contains(i))
+            .map(|i| self.hands[i])
+            .collect();
+        let kept = rest
+            .iter()
+            .any(|card| matches!(card, Card::Normal(_, Rank::Eight)));
+        match kept || rest.len() <= LATE_EIGHT {
+            true => 0.0,
+            false => self.weights.eight,
+        }
+    }
+
+    // 多く持っているスートの1枚で縛りにするか、縛りの続く場を取り続けるなら加点する
+    fn get_bind_bonus(&self, comb: &Comb, view: &GameView) -> f64 {
+        let Comb::Single(Card::Normal(suit, _)) = comb else {
+            return 0.0;
+        };
+        let active = view.players.iter().filter(|(_, count)| *count > 0).count();
+        if !view.rules.bind || (view.rules.heads_up && active == 2) {
+            return 0.0;
+        }
+        let long = self
+            .hands
+            .iter()
+            .filter(|card| matches!(card, Card::Normal(s, _) if s == suit))
+            .count();
+        if long < LONG_SUIT {
+            return 0.0;
+        }
+        match (&view.bind_suits, &view.prev_comb) {
+            // 縛られたスートを持たない他のプレイヤーは返せない
+            (Some(suits), _) if suits[..] == [*suit] => self.weights.bind,
+            // 場の1枚と同じスートを出して縛りにする
+            (None, Some(Comb::Single(Card::Normal(s, _)))) if s == suit => self.weights.bind,
+            // 親なら同じスートで返されて縛りになるのを待つ
+            (None, None) => self.weights.bind / 2.0,
+            _ => 0.0,
+        }
+    }
+
+    // 場に出す手を選ぶ(Noneはパス)
+    fn select(&mut self, validator: &dyn Validator) -> Option<Comb> {
+        // 誰にも返されずに上がれる順番が見つかればそれに従う
+        if let Some(comb) = self.planner.next(&self.hands, validator) {
+            if let Some(hands) = remove_cards(&self.hands, &comb) {
+                self.hands = hands;
+                return Some(comb);
+            }
+        }
+        let view = validator.get_view();
+        let (score, comb, indices) = get_legal_combs(&self.hands, validator)
+            .into_iter()
+            .filter(|(comb, indices)| self.is_timely(comb, indices, &view))
+            .map(|(comb, indices)| {
+                let score = self.evaluate(&indices, view.is_rev)
+                    + self.get_bind_bonus(&comb, &view)
+                    - self.get_eight_penalty(&comb, &indices, &view);
+                (score, comb, indices)
+            })
+            .max_by(|(s1, _, _), (s2, _, _)| s1.partial_cmp(s2).unwrap_or(Ordering::Equal))?;
+        // 場にカードがあり評価値が低いならパスする
+        if validator.get_prev_comb().is_some() && score < self.weights.pass {
+            return None;
+        }
+        for i in indices.iter().rev() {
+            self.hands.remove(*i);
+        }
+        Some(comb)
+    }
+}
+
+impl Player for HeuristicNpc {
+    fn init(&mut self, hands: Vec<Card>) {
+        self.hands = hands;
+        self.planner.clear();
+    }
+
+    fn count_hands(&self) -> usize {
+        self.hands.len()
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_hands(&self) -> &[Card] {
+        &self.hands
+    }
+
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+        take_from_hands(&mut self.hands, cards)
+    }
+
+    fn receive_cards(&mut self, cards: Vec<Card>) {
+        self.hands.extend(cards);
+    }
+
+    fn sort_hands(&mut self, order: Order) {
+        self.hands.sort_by(order.get_comparator());
+    }
+
+    fn get_strategy(&self) -> Option<Strategy> {
+        Some(Strategy::Heuristic)
+    }
+
+    fn play(&mut self, validator: &dyn Validator) -> Action {
+        Action::Move(self.select(validator))
+    }
+
+    fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
+        // 同じ数字の組にならない弱いカードから渡す
+        let mut indices: Vec<usize> = (0..self.hands.len()).collect();
+        indices.sort_by_key(|i| {
+            let paired = match self.hands[*i] {
+                Card::Normal(_, rank) => {
+                    self.hands
+                        .iter()
+                        .filter(|c| matches!(c, Card::Normal(_, r) if *r == rank))
+                        .count()
+                        > 1
+                }
+                Card::Joker => true,
+            };
+            (paired, *i)
+        });
+        let mut indices: Vec<usize> = indices.into_iter().take(cards_count).collect();
+        indices.sort();
+        indices
+            .iter()
+            .rev()
+            .map(|i| self.hands.remove(*i))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::Suit;
+    use crate::field::Field;
+    use crate::rules::RuleSet;
+
+    #[test]
+    fn test_heuristic_npc_play() {
+        for (hands, expected) in [
+            // ペアを崩さずに出す
+            (
+                vec![
+                    Card::Normal(Suit::Club, Rank::Three),
+                    Card::Normal(Suit::Heart, Rank::Three),
+                    Card::Normal(Suit::Club, Rank::Five),
+                    Card::Normal(Suit::Spade, Rank::King),
+                ],
+                Comb::Multi(vec![
+                    Card::Normal(Suit::Club, Rank::Three),
+                    Card::Normal(Suit::Heart, Rank::Three),
+                ]),
+            ),
+            // 出し切れるなら出し切る
+            (
+                vec![Card::Normal(Suit::Club, Rank::Two), Card::Joker],
+                Comb::Multi(vec![Card::Normal(Suit::Club, Rank::Two), Card::Joker]),
+            ),
+        ] {
+            let mut npc = HeuristicNpc::new(String::new(), Weights::default());
+            npc.init(hands);
+            let field = Field::new(4, 0);
+            assert_eq!(npc.select(&field), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_heuristic_npc_bind() {
+        let c = Card::Normal;
+        let hands = vec![
+            c(Suit::Heart, Rank::Seven),
+            c(Suit::Spade, Rank::Nine),
+            c(Suit::Spade, Rank::Jack),
+            c(Suit::Spade, Rank::Queen),
+            c(Suit::Spade, Rank::King),
+            c(Suit::Diamond, Rank::Ace),
+            c(Suit::Club, Rank::Ace),
+        ];
+        let five = Comb::Single(c(Suit::Spade, Rank::Five));
+        let six = Comb::Single(c(Suit::Diamond, Rank::Six));
+        let rules = |bind| RuleSet {
+            bind,
+            ..RuleSet::default()
+        };
+        for (rules, bind, prev_comb, expected) in [
+            // 多く持っているスートで縛りにする
+            (rules(true), 0.6, Some(&five), c(Suit::Spade, Rank::Nine)),
+            // 重みが0なら一番弱いカードを出す
+            (rules(false), 0.6, Some(&five), c(Suit::Heart, Rank::Seven)),
+            (rules(true), 0.0, Some(&five), c(Suit::Heart, Rank::Seven)),
+            // 場のカードとスートが違えば縛りにならない
+            (rules(true), 0.6, Some(&six), c(Suit::Heart, Rank::Seven)),
+        ] {
+            let mut field = Field::new(4, 1);
+            field.set_rules(rules);
+            field.set_players(vec![String::new(); 4], vec![7, 10, 10, 10]);
+            for comb in [prev_comb, None, None] {
+                field.put(comb, 10).unwrap();
+            }
+            assert_eq!(field.get_idx(), 0);
+            let mut npc = HeuristicNpc::new(
+                String::new(),
+                Weights {
+                    bind,
+                    ..Weights::default()
+                },
+            );
+            npc.init(hands.clone());
+            assert_eq!(npc.select(&field), Some(Comb::Single(expected)));
+        }
+        // 縛られている間は場を取り続ける
+        let mut npc = HeuristicNpc::new(String::new(), Weights::default());
+        npc.init(hands.clone());
+        let view = GameView {
+            bind_suits: Some(vec![Suit::Spade]),
+            prev_comb: Some(Comb::Single(c(Suit::Spade, Rank::Ten))),
+            players: vec![(String::new(), 7), (String::new(), 10)],
+            ..Default::default()
+        };
+        let jack = Comb::Single(c(Suit::Spade, Rank::Jack));
+        assert_eq!(npc.get_bind_bonus(&jack, &view), 0.6);
+        let ace = Comb::Single(c(Suit::Diamond, Rank::Ace));
+        assert_eq!(npc.get_bind_bonus(&ace, &view), 0.0);
+    }
+
+    #[test]
+    fn test_heuristic_npc_revolution() {
+        let c = Card::Normal;
+        let kings = vec![
+            c(Suit::Club, Rank::King),
+            c(Suit::Diamond, Rank::King),
+            c(Suit::Heart, Rank::King),
+            c(Suit::Spade, Rank::King),
+        ];
+        for (rest, expected) in [
+            // 弱いカードが残るなら革命を起こす
+            (
+                vec![
+                    c(Suit::Heart, Rank::Four),
+                    c(Suit::Diamond, Rank::Six),
+                    c(Suit::Club, Rank::Nine),
+                ],
+                Comb::Multi(kings.clone()),
+            ),
+            // 強いカードが残るなら革命を起こさない
+            (
+                vec![
+                    c(Suit::Heart, Rank::Queen),
+                    c(Suit::Diamond, Rank::Ace),
+                    c(Suit::Club, Rank::Two),
+                ],
+                Comb::Single(c(Suit::Heart, Rank::Queen)),
+            ),
+        ] {
+            let mut npc = HeuristicNpc::new(String::new(), Weights::default());
+            npc.init([kings.clone(), rest].concat());
+            let mut field = Field::new(4, 0);
+            field.set_players(vec![String::new(); 4], vec![13, 10, 10, 10]);
+            assert_eq!(npc.select(&field), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_heuristic_npc_eight() {
+        let c = Card::Normal;
+        let eight = c(Suit::Diamond, Rank::Eight);
+        let ten = c(Suit::Club, Rank::Ten);
+        let many = vec![
+            eight,
+            ten,
+            c(Suit::Spade, Rank::Jack),
+            c(Suit::Heart, Rank::Queen),
+            c(Suit::Spade, Rank::King),
+            c(Suit::Heart, Rank::Ace),
+            c(Suit::Club, Rank::Two),
+        ];
+        let five = Comb::Single(c(Suit::Club, Rank::Five));
+        let rules = |eight_cut| RuleSet {
+            eight_cut,
+            ..RuleSet::default()
+        };
+        for (hands, rules, weight, prev_comb, expected) in [
+            // 親なら8を残して親を取り返せるようにする
+            (many.clone(), rules(true), 0.5, None, ten),
+            // 重みが0か8切りがなければ一番弱い8を出す
+            (many.clone(), rules(true), 0.0, None, eight),
+            (many.clone(), rules(false), 0.5, None, eight),
+            // 場にカードがあれば8切りで親を取る
+            (many.clone(), rules(true), 0.5, Some(&five), eight),
+            // 残りが少なければ反則上がりにならないように出してしまう
+            (many[..3].to_vec(), rules(true), 0.5, None, eight),
+        ] {
+            // 場にカードがあれば前の席のプレイヤーが出した後に他の2人がパスした場面にする
+            let start = usize::from(prev_comb.is_some());
+            let mut field = Field::new(4, start);
+            field.set_rules(rules);
+            field.set_players(vec![String::new(); 4], vec![hands.len(), 10, 10, 10]);
+            if prev_comb.is_some() {
+                for comb in [prev_comb, None, None] {
+                    field.put(comb, 10).unwrap();
+                }
+            }
+            let mut npc = HeuristicNpc::new(
+                String::new(),
+                Weights {
+                    eight: weight,
+                    ..Weights::default()
+                },
+            );
+            npc.init(hands);
+            assert_eq!(npc.select(&field), Some(Comb::Single(expected)));
+        }
+        // 他にも8があれば1枚出しても減点しない
+        let mut npc = HeuristicNpc::new(String::new(), Weights::default());
+        npc.init([many.clone(), vec![c(Suit::Heart, Rank::Eight)]].concat());
+        let view = GameView::default();
+        assert_eq!(
+            npc.get_eight_penalty(&Comb::Single(eight), &[0], &view),
+            0.0
+        );
+        let pair = Comb::Multi(vec![eight, c(Suit::Heart, Rank::Eight)]);
+        assert_eq!(npc.get_eight_penalty(&pair, &[0, 7], &view), 0.5);
+    }
+
+    #[test]
+    fn test_heuristic_npc_endgame() {
+        // 評価値では弱いカードから出すが、返されないジョーカーを先に出して上がる
+        let three = Card::Normal(Suit::Club, Rank::Three);
+        let mut npc = HeuristicNpc::new(String::new(), Weights::default());
+        npc.init(vec![three, Card::Joker]);
+        let mut field = Field::new(4, 0);
+        field.set_players(vec![String::new(); 4], vec![2, 10, 10, 10]);
+        assert_eq!(npc.select(&field), Some(Comb::Single(Card::Joker)));
+        assert_eq!(npc.get_hands(), [three]);
+    }
+
+    #[test]
+    fn test_get_needless_cards() {
+        let mut npc = HeuristicNpc::new(String::new(), Weights::default());
+        npc.init(vec![
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Heart, Rank::Three),
+            Card::Normal(Suit::Club, Rank::Five),
+            Card::Normal(Suit::Spade, Rank::King),
+        ]);
+        assert_eq!(
+            npc.get_needless_cards(2),
+            vec![
+                Card::Normal(Suit::Spade, Rank::King),
+                Card::Normal(Suit::Club, Rank::Five),
+            ]
+        );
+        assert_eq!(npc.count_hands(), 2);
+    }
+}
@@ -0,0 +1,888 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// 画面に表示する文字列の言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Lang {
+    #[default]
+    Ja,
+    En,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::Ja, Lang::En];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Lang::Ja => "ja",
+            Lang::En => "en",
+        }
+    }
+
+    // キーと文字列の表(言語を増やすときは表を足す)
+    fn get_pack(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::Ja => JA,
+            Lang::En => EN,
+        }
+    }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lang::ALL
+            .into_iter()
+            .find(|lang| lang.get_name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Lang::ALL.iter().map(|l| l.get_name()).collect();
+                trf(
+                    "unknown_lang",
+                    &[("value", &s), ("choices", &names.join(", "))],
+                )
+            })
+    }
+}
+
+static LANG: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn get_lang() -> Lang {
+    Lang::ALL[LANG.load(Ordering::Relaxed) as usize]
+}
+
+// 選んだ言語の文字列
+pub fn tr(key: &'static str) -> &'static str {
+    get_text(get_lang(), key)
+}
+
+// {name}のような引数を埋めた選んだ言語の文字列
+pub fn trf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    format_text(tr(key), args)
+}
+
+// 表になければ日本語の文字列、日本語の表にもなければキーをそのまま返す
+pub fn get_text(lang: Lang, key: &'static str) -> &'static str {
+    [lang, Lang::Ja]
+        .iter()
+        .find_map(|lang| {
+            lang.get_pack()
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, text)| *text)
+        })
+        .unwrap_or(key)
+}
+
+pub fn format_text(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(text.to_owned(), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
+
+const JA: &[(&str, &str)] = &[
+    // 場の出来事
+    ("pass", "パス"),
+    ("eight_cut", "8切り"),
+    ("bind", "縛り"),
+    ("reversed", "カードの強さが逆転"),
+    ("super_reversed", "大革命 カードの強さが逆転して場が流れる"),
+    ("jack_back", "11バック"),
+    ("five_skip", "5飛ばし"),
+    ("capital_fall", "都落ち"),
+    ("fallen", "{name} 最下位に転落"),
+    ("foul_void", "反則上がりのカードは無効"),
+    ("rejected", "{name}の手を受け付けません: {error}"),
+    ("round_over_move", "ラウンドが終わった後の手です"),
+    ("out", "{name} 上がり"),
+    ("foul", "{name} 反則上がり"),
+    ("provisional", " (暫定: {title})"),
+    ("title_daifugo", "大富豪"),
+    ("title_fugo", "富豪"),
+    ("title_heimin", "平民"),
+    ("title_hinmin", "貧民"),
+    ("title_daihinmin", "大貧民"),
+    ("lead", "場が流れました ({name}から)"),
+    ("paused", "一時停止中 (Enterキーで再開)"),
+    ("speed", "速度: x{speed}"),
+    ("results", "結果発表"),
+    ("place", "{place}位: {name}"),
+    ("tribute", "{from}から{to}へ{count}枚献上"),
+    ("return", "{from}から{to}へ{count}枚返礼"),
+    ("exchange", "強いカードと不要なカードを交換"),
+    ("saved", "{path}に保存しました"),
+    ("save_failed", "保存できませんでした: {error}"),
+    ("standings", "途中経過"),
+    ("substitute", "{name}の席をNPCが引き継ぎます"),
+    ("finished", "{name}: {place}位で上がり"),
+    ("cards_left", "{name}: 残り{count}枚"),
+    ("record", "これまでの成績 ({rounds}ラウンド)"),
+    ("record_line", "{name}: 大富豪{wins}回 平均{average}位"),
+    // 場の状態
+    ("status_field", "場  "),
+    ("status_bind", "縛り"),
+    ("status_rev", "革命"),
+    ("status_hands", "手札"),
+    ("status_pass", "パス"),
+    ("status_responders", "出せる"),
+    ("status_standings", "順位"),
+    ("none", "なし"),
+    ("yes", "あり"),
+    ("jack_back_active", " (11バック中)"),
+    ("counter_ranks", "数字"),
+    ("counter_left", "残り"),
+    ("no_plays", "まだカードは出ていません"),
+    ("played_cards", "出たカード ({count}枚)"),
+    // ルールの説明
+    ("rules_title", "現在のルール"),
+    ("rules_on", "有効: {names}"),
+    ("rules_off", "無効: {names}"),
+    ("rules_strength", "強さ: {ranks}"),
+    ("rules_reversed", " (逆転中)"),
+    ("rules_lead", "2ラウンド目以降の親: {lead}"),
+    ("rules_lead_loser", "大貧民"),
+    ("rules_lead_rotate", "前のラウンドの次の席"),
+    ("rules_lead_winner", "大富豪"),
+    ("rules_lead_diamond", "♦3を持っているプレイヤー"),
+    ("rules_lead_cut", "山札から一番強いカードを引いたプレイヤー"),
+    // 入力
+    ("time_left", "[残り{secs}秒] {message}"),
+    ("timeout", "時間切れ"),
+    ("undone", "待った: {turns}手戻しました"),
+    ("undo_failed", "これ以上戻せません"),
+    ("afk", "操作がないため、このラウンドの残りはNPCに任せます"),
+    ("confirm_return", "{name}: 次のラウンドから操作に戻りますか? (y/n): "),
+    (
+        "handover",
+        "{name}さんにキーボードを渡してEnterキーを押してください",
+    ),
+    ("joker_rank", "ジョーカーの数字({choices}): "),
+    ("choose_from", "{choices}から選んでください"),
+    ("confirm_tribute", "{cards}を献上します(Enterキーで確認): "),
+    ("card_numbers", "カードの番号: "),
+    ("idle_reminder", "{name}の手番です (入力を待っています)"),
+    (
+        "save_before_quit",
+        "ゲームを保存してから終了しますか? (y/n): ",
+    ),
+    ("cannot_pass", "場にカードがないためパスできません"),
+    ("invalid_comb", "無効な組み合わせ"),
+    (
+        "select_invalid",
+        "「{input}」を読み取れません (カードの番号か表記を入力してください)",
+    ),
+    ("select_duplicate", "{idx}番が重複しています"),
+    ("select_out_of_range", "{idx}番のカードはありません (0から{max})"),
+    ("select_not_in_hand", "{card}は手札にありません"),
+    ("select_count", "{count}枚選んでください"),
+    ("needless_cards", "不要なカードを{count}枚選択: "),
+    ("checklist", "切り替える番号 (1-{max}、Enterキーで決定): "),
+    ("enter_number", "1から{max}の番号を入力してください"),
+    ("enter_candidate", "0から{max}の番号を入力してください"),
+    ("play_again", "もう一度遊びますか? (y/n): "),
+    // ローカルルール
+    ("local_rules", "ローカルルール"),
+    ("revolution", "革命"),
+    ("spade_three", "スペ3"),
+    (
+        "seq_extreme",
+        "階段をジョーカーを補った最も弱いカードで比べる",
+    ),
+    ("eight_return", "8返し"),
+    ("no_joker_lead", "ジョーカーで親になれない"),
+    ("joker_finish", "ジョーカー上がり"),
+    ("no_joker_bind", "縛りにジョーカーを出せない"),
+    ("super_revolution", "大革命"),
+    ("heads_up", "最後の2人は縛りなし"),
+    // チュートリアル
+    ("tutorial_tip", "[説明]"),
+    ("tutorial_continue", "(Enterキーで続ける)"),
+    (
+        "tutorial_intro",
+        "大富豪のチュートリアルへようこそ。手札を早く出し切ったプレイヤーの勝ちです。\n\
+         場のカードより強いカードを同じ枚数だけ出します。3が最も弱く、K、A、2、ジョーカーの順に強くなります。\n\
+         手番では手札の下の番号(例: 1 2)か、カード(例: ♦4 ♥4)を入力してください。",
+    ),
+    (
+        "tutorial_hands",
+        "あなたの手札には4のペア、4枚の6、♥9 ♥10 ♥Jの階段、8があります。説明に沿って使ってみましょう。",
+    ),
+    (
+        "tutorial_single",
+        "1枚出し: 場のカードより強いカードを1枚出します。",
+    ),
+    (
+        "tutorial_multi",
+        "複数枚出し: 同じ数字のカードはまとめて出せます。次のプレイヤーも同じ枚数で出す必要があります。",
+    ),
+    (
+        "tutorial_seq",
+        "階段: 同じスートで連続する3枚以上のカードもまとめて出せます。",
+    ),
+    (
+        "tutorial_pass",
+        "パス: 出せるカードがないときや出したくないときは、何も入力せずにEnterキーを押すとパスできます。",
+    ),
+    (
+        "tutorial_lead",
+        "場が流れる: 他の全員がパスすると場が流れ、最後にカードを出したプレイヤーが好きなカードから出せます。",
+    ),
+    (
+        "tutorial_eight",
+        "8切り: 8を含む組み合わせを出すと、その場で場が流れて自分から出し直せます。",
+    ),
+    (
+        "tutorial_bind",
+        "縛り: 同じスートが続けて出されると、場が流れるまでそのスートしか出せません。",
+    ),
+    (
+        "tutorial_rev",
+        "革命: 同じ数字を4枚以上出すとカードの強さが逆転し、3が最も強くなります。",
+    ),
+    (
+        "tutorial_out",
+        "上がり: 手札を全て出し切ると上がりです。早く上がった順に大富豪、富豪、貧民、大貧民になります。",
+    ),
+    (
+        "tutorial_foul",
+        "反則上がり: 最後に8や2(革命中は3)、ジョーカーを出して上がると、最下位になります。",
+    ),
+    (
+        "tutorial_end",
+        "ラウンドが終わりました。次のラウンドでは大貧民が大富豪に最も強いカードを2枚献上し、不要なカードを2枚受け取ります。",
+    ),
+    ("tutorial_next", "チュートリアルは終わりです。{command}で遊んでみましょう。"),
+    // NPCが手を選んだ理由
+    ("reason_finish", "この手で上がれる"),
+    ("reason_revolution", "革命でカードの強さを逆転させる"),
+    ("reason_eight", "8切りで場を流して親を取る"),
+    ("reason_joker", "ジョーカーで場を押さえる"),
+    ("reason_group", "{count}枚の組を出して手数を減らす"),
+    ("reason_hold_joker", "ジョーカーを終盤まで残して{cards}を出す"),
+    ("reason_lowest", "弱いカードから出して強いカードを残す"),
+    ("reason_play", "{cards}を出す"),
+    ("reason_no_move", "出せる手がない"),
+    ("reason_keep_group", "{cards}の組を崩さないためにパス"),
+    ("reason_keep_joker", "ジョーカーを終盤まで残すためにパス"),
+    ("reason_keep_strong", "強いカードを温存するためにパス"),
+    // エラー
+    (
+        "feature_required",
+        "{option}を使うには{feature}フィーチャーを有効にしてビルドしてください",
+    ),
+    ("tui_humans", "--tuiでは人間のプレイヤーを1人にしてください"),
+    (
+        "tui_outputs",
+        "--tuiと--kifu、--log-file、--analyze、--debugは同時に指定できません",
+    ),
+    ("tui_resume", "保存したゲームはTUIで再開できません"),
+    ("kifu_failed", "棋譜のファイルを作成できません: {error}"),
+    ("log_failed", "ログのファイルを開けません: {error}"),
+    ("log_write_failed", "ログの書き込みに失敗しました: {error}"),
+    ("dataset_failed", "学習データを書き出せません: {error}"),
+    ("data_too_large", "データが大きすぎます"),
+    ("not_utf8", "UTF-8の文字列ではありません"),
+    ("results_failed", "結果を書き出せません: {error}"),
+    ("no_config_dir", "設定ディレクトリが見つかりません"),
+    // ルールの組み合わせ
+    (
+        "invalid_min_seq",
+        "階段に必要な枚数は3から13で指定してください(0なら階段を出せません)",
+    ),
+    (
+        "eight_return_ignored",
+        "8切りがオフのため8返しは効果がありません(8切りをオンにするか8返しをオフにしてください)",
+    ),
+    (
+        "super_revolution_ignored",
+        "革命がオフのため大革命は効果がありません(革命をオンにするか大革命をオフにしてください)",
+    ),
+    (
+        "no_joker_bind_ignored",
+        "縛りがオフのため「縛りにジョーカーを出せない」は効果がありません(縛りをオンにしてください)",
+    ),
+    (
+        "heads_up_ignored",
+        "縛りがオフのため「最後の2人は縛りなし」は効果がありません(縛りをオンにしてください)",
+    ),
+    (
+        "seq_extreme_ignored",
+        "階段を出せないため階段の比べ方は効果がありません(--min-seqに3以上を指定してください)",
+    ),
+    // TUI
+    ("tui_help", "←→: 移動  Space: 選択/一時停止  Enter: 決定  p: パス  +/-: 速度  q: 終了"),
+    ("tui_paused", "一時停止中 (Spaceで再開)"),
+    ("tui_out", "上がり"),
+    ("tui_foul", "反則上がり"),
+    ("tui_place", "{place}位"),
+    ("your_turn", "あなたの番です"),
+    ("tui_confirm_tribute", "{cards}を献上します (何かキーを押してください)"),
+    ("tui_needless_cards", "不要なカードを{count}枚選択"),
+    ("tui_field", " 場  縛り: {bind}  革命: {rev} "),
+    ("tui_players", " プレイヤー "),
+    ("tui_hands", " 手札 "),
+    // ネットワーク対戦
+    ("client_help", "Enter: 開始  l: 一覧  r: ルール"),
+    ("connect_failed", "{addr}に接続できません: {error}"),
+    ("connected", "{addr}に接続しました"),
+    ("disconnected", "接続が切れました: {error}"),
+    ("seated", "席{seat}に座りました (再接続用のトークン: {token})"),
+    ("network_save", "ネットワーク対戦は保存できません"),
+    ("network_undo", "ネットワーク対戦は待ったできません"),
+    ("no_tables", "開始を待っているテーブルはありません"),
+    ("client_rules", "ルール: {rules}"),
+    ("table_info", "{name} ({count}/{max}人: {players}) ルール: {preset} NPC: {npc}"),
+    ("message_too_long", "メッセージが長すぎます: {len}バイト"),
+    ("unplayable", "場に出せない組み合わせです"),
+    ("cards_not_in_hand", "手札にないカードです"),
+    ("already_started", "既にゲームが始まっています"),
+    ("bind_failed", "ポート{port}で待機できません: {error}"),
+    ("lobby_opened", "ポート{port}でロビーを開きました"),
+    ("waiting_clients", "ポート{port}で{count}人の参加を待っています"),
+    ("no_seats", "空いている席がありません"),
+    ("joins_next_round", "{name}が次のラウンドから参加します"),
+    ("seat_notify_failed", "{name}に席を知らせられませんでした: {error}"),
+    ("table_names_required", "テーブルとプレイヤーの名前を指定してください"),
+    ("table_exists", "テーブル{name}は既にあります"),
+    ("no_table", "テーブル{name}はありません"),
+    ("table_full", "テーブル{name}は満席です"),
+    ("name_taken", "{name}という名前は既に使われています"),
+    ("table_closed", "ホストが退出したためテーブル{name}は閉じられました"),
+    ("invalid_token", "このトークンでは再接続できません"),
+    ("host_left", "{addr}が退出したためテーブル{name}を閉じました"),
+    ("table_created", "{addr}がテーブル{name}を作りました"),
+    ("no_open_table", "空いているテーブルがありません"),
+    ("not_at_table", "テーブルに着いていません"),
+    ("spectate_table_required", "観戦するテーブルを指定してください"),
+    ("table_started", "テーブル{name}の対戦を始めます"),
+    ("table_finished", "テーブル{name}の対戦が終わりました"),
+    ("host_only", "ゲームを開始できるのはテーブルのホストだけです"),
+    ("already_seated", "既にテーブルに着いています"),
+    ("not_started", "まだゲームが始まっていません"),
+    ("not_your_turn", "あなたの手番ではありません"),
+    ("turn_timed_out", "持ち時間を過ぎたため、パスか最も弱い手を出しました"),
+    ("game_finished", "ゲームは終了しています"),
+    ("move_parse_failed", "手を読み込めません: {error}"),
+    ("no_game", "ゲーム{id}はありません"),
+    ("no_url", "{url}はありません"),
+    ("settings_parse_failed", "ゲームの設定を読み込めません: {error}"),
+    ("game_settings_required", "名前と1以上のラウンド数を指定してください"),
+    ("too_many_rounds", "ラウンド数は{max}以下にしてください"),
+    ("too_many_games", "同時に進められるゲームは{max}個までです"),
+    ("http_listening", "ポート{port}でHTTPのリクエストを待っています"),
+    ("client_joined", "{name}が参加しました ({addr})"),
+    ("spectating", "{addr}が観戦を始めました"),
+    ("bad_join_from", "{addr}からの参加の要求が正しくありません"),
+    ("bad_join", "参加の要求が正しくありません"),
+    ("chat_flood", "チャットの送信が多すぎます。しばらく待ってください"),
+    ("connection_lost", "{name}の接続が切れました"),
+    ("waiting_next_round", "{name}が次のラウンドからの参加を待っています ({addr})"),
+    ("refused", "{addr}からの接続を断りました"),
+    ("cannot_join", "このテーブルには参加できません"),
+    ("rejoined", "{name}が再接続しました ({addr})"),
+    ("not_human_turn", "人間の手番ではありません"),
+    // 棋譜と再生
+    ("move_error", "{turn}手目: {error}"),
+    ("not_turn_of", "P{seat}の手番ではありません"),
+    ("no_turn", "{turns}手目はありません (このラウンドは{count}手)"),
+    ("no_seat", "P{seat}の席はありません"),
+    ("round_over_at", "{turns}手目でラウンドが終わっています"),
+    ("kifu_read_failed", "棋譜を読み込めません: {error}"),
+    ("round_error", "ラウンド{round}の{error}"),
+    ("replay_position", "ラウンド {round}/{rounds}  {turn}手目"),
+    ("replay_help", "Enter: 次へ  b: 戻る  q: 終了 "),
+    ("verify_round_error", "ラウンド{round}: {error}"),
+    ("wrong_start", "P{seat}から始まっています"),
+    ("rank_mismatch", "順位が一致しません (棋譜: {kifu}、再生: {replay})"),
+    ("round_verified", "ラウンド{round}: {count}手を再現しました"),
+    ("verified_with_seed", "シード{seed}で配られたカードと全ての手を再現しました"),
+    ("verified_without_seed", "全ての手を再現しました(シードがないため配られたカードは確かめていません)"),
+    ("exchange_mismatch", "交換したカードが一致しません"),
+    ("deal_mismatch", "P{seat}に配られたカードが一致しません (棋譜: {kifu}、再生: {replay})"),
+    ("player_parse_failed", "プレイヤーを読み取れません: {text}"),
+    ("unknown_min_seq", "不明な階段の枚数: {value}"),
+    ("unknown_rule", "不明なルール: {word}"),
+    ("kifu_line_error", "棋譜の{line}行目: {error}"),
+    ("move_before_round", "roundの前に手が書かれています"),
+    ("line_parse_failed", "解析できません: {line}"),
+    ("undo_parse_failed", "取り消す手の数を読み取れません: {text}"),
+    ("kifu_write_failed", "棋譜の書き込みに失敗しました: {error}"),
+    ("read_failed", "読み込めません: {error}"),
+    ("bad_format", "形式が正しくありません: {error}"),
+    ("no_round", "ラウンド{round}はありません (棋譜は{count}ラウンド)"),
+    ("whatif_start", "ラウンド{round}の{turn}手目から遊び直します"),
+    ("whatif_place", "P{seat}の順位: 棋譜では{before}位 → 今回は{after}位"),
+    ("whatif_again", "同じ場面からもう一度遊びますか? (y/n): "),
+    // サブコマンド
+    ("blunder", "{turn}手目: {played} → {best}のほうが有力でした (評価 {played_value}% → {best_value}%)"),
+    ("analysis", "分析"),
+    ("no_blunders", "悪手は見つかりませんでした"),
+    ("cannot_analyze", "このラウンドは分析できません"),
+    ("stats_header", "プレイヤー  ラウンド  1位  平均順位  反則上がり  最長連続大富豪  レーティング"),
+    ("rating", "レーティング"),
+    ("stats_save_failed", "統計を保存できませんでした: {error}"),
+    ("simulate_title", "シミュレーション: {games}ゲーム (seed: {seed})"),
+    ("dataset_written", "{count}手分の学習データを{path}に書き出しました"),
+    ("arena_duplicate_title", "アリーナ: 1組あたり{games}通りの配り方を席を入れ替えて2回ずつ (seed: {seed})"),
+    ("arena_title", "アリーナ: 1組あたり{games}ゲーム (seed: {seed})"),
+    ("arena_header", "対戦                   1位の割合  95%信頼区間"),
+    ("tournament_title", "トーナメント: {size}人 × {tables}テーブル × {rounds}ラウンド ({pairing}, seed: {seed})"),
+    ("tournament_table", "ラウンド{round} テーブル{table}: {ranking}"),
+    ("tournament_header", "順位  名前         得点  1位  平均順位  レーティング"),
+    ("bench_title", "ベンチマーク: {games}ゲーム [{seats}] (seed: {seed})"),
+    ("tune_title", "チューニング: {generations}世代 × {population}個体 × {games}ゲーム (seed: {seed})"),
+    ("tune_header", "世代  平均得点  重み"),
+    ("tune_best", "最良: {weights}"),
+    ("weights_written", "重みを{path}に書き込みました"),
+    ("results_written", "結果を{path}に書き出しました"),
+    ("no_stats", "まだ記録がありません"),
+    ("simulate_header", "席  戦略       {places}  平均順位"),
+    ("average_turns", "平均ターン数: {turns}"),
+    ("per_game", "{name}: {count}回/ゲーム"),
+    ("bench_result", "{games}ゲーム / {secs}秒 = {rate}ゲーム/秒"),
+    // エラー
+    ("unknown_seq_rule", "不明な階段のルール: {value} ({choices}から選んでください)"),
+    ("unknown_lead_rule", "不明な親のルール: {value} ({choices}から選んでください)"),
+    ("unknown_lead_pass_rule", "不明なパスのルール: {value} ({choices}から選んでください)"),
+    ("unknown_foul_rule", "不明な反則上がりのルール: {value} ({choices}から選んでください)"),
+    ("unknown_preset", "不明なプリセット: {value} ({choices}から選んでください)"),
+    ("unknown_strategy", "不明な戦略: {value} ({choices}から選んでください)"),
+    ("restore_count", "人数が違う状態には戻せません ({saved}人/{current}人)"),
+    ("too_many_players", "これ以上プレイヤーを加えられません (最大{max}人)"),
+    ("too_few_players", "これ以上プレイヤーを減らせません (最低{min}人)"),
+    ("unknown_lang", "不明な言語: {value} ({choices}から選んでください)"),
+    ("unknown_pairing", "不明な組み合わせ方: {value} ({choices}から選んでください)"),
+    ("too_many_humans", "人間のプレイヤーは参加者の人数({players})以下にしてください"),
+    ("uneven_tables", "参加者({players}人)を{tables}つのテーブルに均等に分けられません"),
+    ("invalid_table_size", "1つのテーブルは{min}〜{max}人にしてください ({size}人)"),
+    ("suit_parse_failed", "スートを読み取れません: {text}"),
+    ("rank_parse_failed", "数字を読み取れません: {text}"),
+    ("card_parse_failed", "カードを読み取れません: {text}"),
+    ("comb_parse_failed", "組み合わせになっていません: {text}"),
+];
+
+const EN: &[(&str, &str)] = &[
+    ("pass", "Pass"),
+    ("eight_cut", "Eight cut"),
+    ("bind", "Suit lock"),
+    ("reversed", "Card strength reversed"),
+    ("super_reversed", "Super revolution: card strength reversed and the field cleared"),
+    ("jack_back", "Jack back"),
+    ("five_skip", "Five skip"),
+    ("capital_fall", "Capital fall"),
+    ("fallen", "{name} drops to last place"),
+    ("foul_void", "Fouled cards are void"),
+    ("rejected", "{name}'s move was rejected: {error}"),
+    ("round_over_move", "The round is already over"),
+    ("out", "{name} is out"),
+    ("foul", "{name} fouled out"),
+    ("provisional", " (provisional: {title})"),
+    ("title_daifugo", "Daifugo"),
+    ("title_fugo", "Fugo"),
+    ("title_heimin", "Heimin"),
+    ("title_hinmin", "Hinmin"),
+    ("title_daihinmin", "Daihinmin"),
+    ("lead", "The field was cleared ({name} leads)"),
+    ("paused", "Paused (press Enter to resume)"),
+    ("speed", "Speed: x{speed}"),
+    ("results", "Results"),
+    ("place", "#{place}: {name}"),
+    ("tribute", "{from} gave {count} card(s) to {to}"),
+    ("return", "{from} returned {count} card(s) to {to}"),
+    ("exchange", "Strong cards were exchanged for unneeded ones"),
+    ("saved", "Saved to {path}"),
+    ("save_failed", "Could not save: {error}"),
+    ("standings", "Standings"),
+    ("substitute", "An NPC takes over {name}'s seat"),
+    ("finished", "{name}: finished #{place}"),
+    ("cards_left", "{name}: {count} card(s) left"),
+    ("record", "Record so far ({rounds} rounds)"),
+    (
+        "record_line",
+        "{name}: {wins} win(s) as Daifugo, average place {average}",
+    ),
+    ("status_field", "Field"),
+    ("status_bind", "Lock "),
+    ("status_rev", "Rev. "),
+    ("status_hands", "Hands"),
+    ("status_pass", "Pass "),
+    ("status_responders", "can play"),
+    ("status_standings", "Place"),
+    ("none", "none"),
+    ("yes", "yes"),
+    ("jack_back_active", " (jack back)"),
+    ("counter_ranks", "Rank"),
+    ("counter_left", "Left"),
+    ("no_plays", "No cards have been played yet"),
+    ("played_cards", "Played cards ({count})"),
+    ("rules_title", "Current rules"),
+    ("rules_on", "On: {names}"),
+    ("rules_off", "Off: {names}"),
+    ("rules_strength", "Strength: {ranks}"),
+    ("rules_reversed", " (reversed)"),
+    ("rules_lead", "Leader from round 2: {lead}"),
+    ("rules_lead_loser", "Daihinmin"),
+    ("rules_lead_rotate", "next seat after the last leader"),
+    ("rules_lead_winner", "Daifugo"),
+    ("rules_lead_diamond", "holder of the ♦3"),
+    ("rules_lead_cut", "winner of a cut of the deck"),
+    ("time_left", "[{secs}s left] {message}"),
+    ("timeout", "Time is up"),
+    ("undone", "Undo: took back {turns} moves"),
+    ("undo_failed", "Nothing to undo"),
+    ("afk", "No response, so an NPC plays the rest of this round"),
+    ("confirm_return", "{name}: Take back control from the next round? (y/n): "),
+    ("handover", "Hand the keyboard to {name} and press Enter"),
+    ("joker_rank", "Rank for the joker ({choices}): "),
+    ("choose_from", "Choose from {choices}"),
+    (
+        "confirm_tribute",
+        "Giving {cards} as tribute (press Enter to confirm): ",
+    ),
+    ("card_numbers", "Card numbers: "),
+    ("idle_reminder", "It is still {name}'s turn (waiting for input)"),
+    ("save_before_quit", "Save the game before quitting? (y/n): "),
+    ("cannot_pass", "You cannot pass while the field is empty"),
+    ("invalid_comb", "Invalid combination"),
+    (
+        "select_invalid",
+        "Could not read \"{input}\" (enter card numbers or cards)",
+    ),
+    ("select_duplicate", "Card number {idx} was entered more than once"),
+    ("select_out_of_range", "There is no card number {idx} (0 to {max})"),
+    ("select_not_in_hand", "{card} is not in your hand"),
+    ("select_count", "Choose {count} card(s)"),
+    ("needless_cards", "Choose {count} card(s) to give away: "),
+    (
+        "checklist",
+        "Number to toggle (1-{max}, Enter to confirm): ",
+    ),
+    ("enter_number", "Enter a number from 1 to {max}"),
+    ("enter_candidate", "Enter a number from 0 to {max}"),
+    ("play_again", "Play again? (y/n): "),
+    ("local_rules", "Local rules"),
+    ("revolution", "Revolution"),
+    ("spade_three", "Spade three"),
+    (
+        "seq_extreme",
+        "Compare sequences by their lowest card, counting the joker",
+    ),
+    ("eight_return", "Eight return"),
+    ("no_joker_lead", "Joker can't lead"),
+    ("joker_finish", "Joker finish allowed"),
+    ("no_joker_bind", "No joker under binds"),
+    ("super_revolution", "Super revolution"),
+    ("heads_up", "No binds heads-up"),
+    ("tutorial_tip", "[Tip]"),
+    ("tutorial_continue", "(press Enter to continue)"),
+    (
+        "tutorial_intro",
+        "Welcome to the Daifugo tutorial. The first player to get rid of all their cards wins.\n\
+         Play the same number of cards as the field, but stronger. 3 is the weakest, followed by K, A, 2 and the joker.\n\
+         On your turn, enter the numbers under your hand (e.g. 1 2) or the cards themselves (e.g. D4 H4).",
+    ),
+    (
+        "tutorial_hands",
+        "Your hand has a pair of 4s, four 6s, a H9 H10 HJ sequence and an 8. Try them as the tips come up.",
+    ),
+    (
+        "tutorial_single",
+        "Single: play one card stronger than the one on the field.",
+    ),
+    (
+        "tutorial_multi",
+        "Multiple: cards of the same rank can be played together. The next player must match the number of cards.",
+    ),
+    (
+        "tutorial_seq",
+        "Sequence: three or more consecutive cards of the same suit can also be played together.",
+    ),
+    (
+        "tutorial_pass",
+        "Pass: when you cannot or do not want to play, press Enter without typing anything.",
+    ),
+    (
+        "tutorial_lead",
+        "Clearing the field: when everyone else passes, the field is cleared and the last player to play leads with any cards.",
+    ),
+    (
+        "tutorial_eight",
+        "Eight cut: playing a combination with an 8 clears the field at once, and you lead again.",
+    ),
+    (
+        "tutorial_bind",
+        "Suit lock: when the same suits are played twice in a row, only those suits can be played until the field clears.",
+    ),
+    (
+        "tutorial_rev",
+        "Revolution: playing four or more cards of the same rank reverses the card strength, making 3 the strongest.",
+    ),
+    (
+        "tutorial_out",
+        "Going out: play all your cards to go out. Players are ranked Daifugo, Fugo, Hinmin and Daihinmin in the order they go out.",
+    ),
+    (
+        "tutorial_foul",
+        "Foul finish: going out with an 8, a 2 (a 3 during a revolution) or the joker puts you in last place.",
+    ),
+    (
+        "tutorial_end",
+        "The round is over. Next round the Daihinmin gives their two strongest cards to the Daifugo and receives two unneeded cards.",
+    ),
+    ("tutorial_next", "That's the end of the tutorial. Run {command} to play a game."),
+    ("reason_finish", "This play empties the hand"),
+    ("reason_revolution", "Revolution to reverse card strength"),
+    ("reason_eight", "Eight cut to clear the field and take the lead"),
+    ("reason_joker", "Using the Joker to take control"),
+    ("reason_group", "Playing {count} cards at once to save moves"),
+    ("reason_hold_joker", "Holding the Joker for the endgame and playing {cards}"),
+    ("reason_lowest", "Playing a weak card to keep the strong ones"),
+    ("reason_play", "Playing {cards}"),
+    ("reason_no_move", "No legal play"),
+    ("reason_keep_group", "Passing to keep {cards} together"),
+    ("reason_keep_joker", "Passing to hold the Joker for the endgame"),
+    ("reason_keep_strong", "Passing to save strong cards"),
+    (
+        "feature_required",
+        "Build with the {feature} feature enabled to use {option}",
+    ),
+    ("tui_humans", "--tui needs exactly one human player"),
+    (
+        "tui_outputs",
+        "--tui cannot be combined with --kifu, --log-file, --analyze or --debug",
+    ),
+    ("tui_resume", "Saved games cannot be resumed in the TUI"),
+    ("kifu_failed", "Could not create the kifu file: {error}"),
+    ("log_failed", "Could not open the log file: {error}"),
+    ("log_write_failed", "Could not write to the log: {error}"),
+    (
+        "dataset_failed",
+        "Could not write the training data: {error}",
+    ),
+    ("data_too_large", "The data is too large"),
+    ("not_utf8", "The string is not valid UTF-8"),
+    ("results_failed", "Could not write the results: {error}"),
+    ("no_config_dir", "The config directory was not found"),
+    (
+        "invalid_min_seq",
+        "The minimum sequence length must be between 3 and 13 (0 disables sequences)",
+    ),
+    (
+        "eight_return_ignored",
+        "Eight return has no effect without eight cut (turn eight cut on or eight return off)",
+    ),
+    (
+        "super_revolution_ignored",
+        "Super revolution has no effect without revolution (turn revolution on or super revolution off)",
+    ),
+    (
+        "no_joker_bind_ignored",
+        "No joker under binds has no effect without suit lock (turn suit lock on)",
+    ),
+    (
+        "heads_up_ignored",
+        "No binds heads-up has no effect without suit lock (turn suit lock on)",
+    ),
+    (
+        "seq_extreme_ignored",
+        "The sequence comparison has no effect when sequences are disabled (set --min-seq to 3 or more)",
+    ),
+    ("tui_help", "←→: move  Space: select/pause  Enter: confirm  p: pass  +/-: speed  q: quit"),
+    ("tui_paused", "Paused (press Space to resume)"),
+    ("tui_out", "Out"),
+    ("tui_foul", "Fouled out"),
+    ("tui_place", "#{place}"),
+    ("your_turn", "Your turn"),
+    ("tui_confirm_tribute", "Giving {cards} as tribute (press any key)"),
+    ("tui_needless_cards", "Choose {count} card(s) to give away"),
+    ("tui_field", " Field  Lock: {bind}  Rev.: {rev} "),
+    ("tui_players", " Players "),
+    ("tui_hands", " Hand "),
+    ("client_help", "Enter: start  l: list  r: rules"),
+    ("connect_failed", "Could not connect to {addr}: {error}"),
+    ("connected", "Connected to {addr}"),
+    ("disconnected", "Disconnected: {error}"),
+    ("seated", "Took seat {seat} (token for rejoining: {token})"),
+    ("network_save", "Network games cannot be saved"),
+    ("network_undo", "Network games cannot be undone"),
+    ("no_tables", "No tables are waiting to start"),
+    ("client_rules", "Rules: {rules}"),
+    ("table_info", "{name} ({count}/{max} players: {players}) rules: {preset} NPC: {npc}"),
+    ("message_too_long", "Message too long: {len} bytes"),
+    ("unplayable", "That combination cannot be played on the field"),
+    ("cards_not_in_hand", "Those cards are not in the hand"),
+    ("already_started", "The game has already started"),
+    ("bind_failed", "Could not listen on port {port}: {error}"),
+    ("lobby_opened", "Opened the lobby on port {port}"),
+    ("waiting_clients", "Waiting for {count} player(s) on port {port}"),
+    ("no_seats", "No seats are free"),
+    ("joins_next_round", "{name} joins from the next round"),
+    ("seat_notify_failed", "Could not tell {name} their seat: {error}"),
+    ("table_names_required", "Give names for the table and the player"),
+    ("table_exists", "Table {name} already exists"),
+    ("no_table", "There is no table {name}"),
+    ("table_full", "Table {name} is full"),
+    ("name_taken", "The name {name} is already taken"),
+    ("table_closed", "Table {name} was closed because the host left"),
+    ("invalid_token", "Cannot rejoin with this token"),
+    ("host_left", "Closed table {name} because {addr} left"),
+    ("table_created", "{addr} created table {name}"),
+    ("no_open_table", "No tables have a free seat"),
+    ("not_at_table", "You are not at a table"),
+    ("spectate_table_required", "Give the table to spectate"),
+    ("table_started", "Starting the game at table {name}"),
+    ("table_finished", "The game at table {name} has finished"),
+    ("host_only", "Only the table's host can start the game"),
+    ("already_seated", "You are already at a table"),
+    ("not_started", "The game has not started yet"),
+    ("not_your_turn", "It is not your turn"),
+    ("turn_timed_out", "Time is up: passed or played your weakest move"),
+    ("game_finished", "The game has finished"),
+    ("move_parse_failed", "Could not read the move: {error}"),
+    ("no_game", "There is no game {id}"),
+    ("no_url", "{url} was not found"),
+    ("settings_parse_failed", "Could not read the game settings: {error}"),
+    ("game_settings_required", "Give a name and at least one round"),
+    ("too_many_rounds", "Give at most {max} rounds"),
+    ("too_many_games", "At most {max} games can run at once"),
+    ("http_listening", "Waiting for HTTP requests on port {port}"),
+    ("client_joined", "{name} joined ({addr})"),
+    ("spectating", "{addr} started spectating"),
+    ("bad_join_from", "Invalid join request from {addr}"),
+    ("bad_join", "Invalid join request"),
+    ("chat_flood", "Too many chat messages. Please wait a moment"),
+    ("connection_lost", "{name} disconnected"),
+    ("waiting_next_round", "{name} is waiting to join from the next round ({addr})"),
+    ("refused", "Refused the connection from {addr}"),
+    ("cannot_join", "You cannot join this table"),
+    ("rejoined", "{name} rejoined ({addr})"),
+    ("not_human_turn", "It is not a human player's turn"),
+    ("move_error", "Move {turn}: {error}"),
+    ("not_turn_of", "It is not P{seat}'s turn"),
+    ("no_turn", "There is no move {turns} (this round has {count} moves)"),
+    ("no_seat", "There is no seat P{seat}"),
+    ("round_over_at", "The round is already over at move {turns}"),
+    ("kifu_read_failed", "Could not read the kifu: {error}"),
+    ("round_error", "Round {round}, {error}"),
+    ("replay_position", "Round {round}/{rounds}  move {turn}"),
+    ("replay_help", "Enter: next  b: back  q: quit "),
+    ("verify_round_error", "Round {round}: {error}"),
+    ("wrong_start", "The round starts with P{seat}"),
+    ("rank_mismatch", "The places do not match (kifu: {kifu}, replay: {replay})"),
+    ("round_verified", "Round {round}: reproduced {count} moves"),
+    ("verified_with_seed", "Reproduced the cards dealt with seed {seed} and every move"),
+    ("verified_without_seed", "Reproduced every move (the dealt cards were not checked because there is no seed)"),
+    ("exchange_mismatch", "The exchanged cards do not match"),
+    ("deal_mismatch", "The cards dealt to P{seat} do not match (kifu: {kifu}, replay: {replay})"),
+    ("player_parse_failed", "Could not read the player: {text}"),
+    ("unknown_min_seq", "Unknown minimum sequence length: {value}"),
+    ("unknown_rule", "Unknown rule: {word}"),
+    ("kifu_line_error", "Kifu line {line}: {error}"),
+    ("move_before_round", "A move is written before round"),
+    ("line_parse_failed", "Could not parse: {line}"),
+    ("undo_parse_failed", "Could not read the number of moves to undo: {text}"),
+    ("kifu_write_failed", "Could not write the kifu: {error}"),
+    ("read_failed", "Could not read: {error}"),
+    ("bad_format", "Invalid format: {error}"),
+    ("no_round", "There is no round {round} (the kifu has {count} rounds)"),
+    ("whatif_start", "Replaying round {round} from move {turn}"),
+    ("whatif_place", "P{seat}'s place: #{before} in the kifu → #{after} this time"),
+    ("whatif_again", "Play the same position again? (y/n): "),
+    ("blunder", "Move {turn}: {best} was stronger than {played} (value {played_value}% → {best_value}%)"),
+    ("analysis", "Analysis"),
+    ("no_blunders", "No blunders were found"),
+    ("cannot_analyze", "This round cannot be analyzed"),
+    ("stats_header", "Player        Rounds  Wins  Avg. place  Fouls  Longest streak  Rating"),
+    ("rating", "Rating"),
+    ("stats_save_failed", "Could not save the stats: {error}"),
+    ("simulate_title", "Simulation: {games} games (seed: {seed})"),
+    ("dataset_written", "Wrote training data for {count} moves to {path}"),
+    ("arena_duplicate_title", "Arena: {games} deals per matchup, each played twice with seats swapped (seed: {seed})"),
+    ("arena_title", "Arena: {games} games per matchup (seed: {seed})"),
+    ("arena_header", "Matchup                Win rate  95% CI"),
+    ("tournament_title", "Tournament: {size} players × {tables} tables × {rounds} rounds ({pairing}, seed: {seed})"),
+    ("tournament_table", "Round {round} table {table}: {ranking}"),
+    ("tournament_header", "Rank  Name       Points  1st Avg.place        Rating"),
+    ("bench_title", "Benchmark: {games} games [{seats}] (seed: {seed})"),
+    ("tune_title", "Tuning: {generations} generations × {population} individuals × {games} games (seed: {seed})"),
+    ("tune_header", "Gen.  Avg. score  Weights"),
+    ("tune_best", "Best: {weights}"),
+    ("weights_written", "Wrote the weights to {path}"),
+    ("results_written", "Wrote the results to {path}"),
+    ("no_stats", "No records yet"),
+    ("simulate_header", "Seat Strategy   {places}  Avg. place"),
+    ("average_turns", "Average turns: {turns}"),
+    ("per_game", "{name}: {count} per game"),
+    ("bench_result", "{games} games / {secs}s = {rate} games/s"),
+    ("unknown_seq_rule", "Unknown sequence rule: {value} (choose from {choices})"),
+    ("unknown_lead_rule", "Unknown lead rule: {value} (choose from {choices})"),
+    ("unknown_lead_pass_rule", "Unknown pass rule: {value} (choose from {choices})"),
+    ("unknown_foul_rule", "Unknown foul rule: {value} (choose from {choices})"),
+    ("unknown_preset", "Unknown preset: {value} (choose from {choices})"),
+    ("unknown_strategy", "Unknown strategy: {value} (choose from {choices})"),
+    ("restore_count", "Cannot restore a state with a different number of players ({saved}/{current})"),
+    ("too_many_players", "No more players can be added (at most {max})"),
+    ("too_few_players", "No more players can be removed (at least {min})"),
+    ("unknown_lang", "Unknown language: {value} (choose from {choices})"),
+    ("unknown_pairing", "Unknown pairing: {value} (choose from {choices})"),
+    ("too_many_humans", "The number of human players must not exceed the number of entrants ({players})"),
+    ("uneven_tables", "{players} entrants cannot be split evenly into {tables} tables"),
+    ("invalid_table_size", "Each table must seat {min} to {max} players ({size} players)"),
+    ("suit_parse_failed", "Could not read the suit: {text}"),
+    ("rank_parse_failed", "Could not read the rank: {text}"),
+    ("card_parse_failed", "Could not read the card: {text}"),
+    ("comb_parse_failed", "Not a valid combination: {text}"),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_text() {
+        for (lang, key, expected) in [
+            (Lang::Ja, "pass", "パス"),
+            (Lang::En, "pass", "Pass"),
+            (Lang::En, "unknown", "unknown"),
+        ] {
+            assert_eq!(get_text(lang, key), expected);
+        }
+        // 全ての言語の表に同じキーがある
+        for lang in Lang::ALL {
+            for (key, _) in JA {
+                assert!(
+                    lang.get_pack().iter().any(|(k, _)| k == key),
+                    "{}に{key}がありません",
+                    lang.get_name()
+                );
+            }
+            assert_eq!(lang.get_pack().len(), JA.len());
+            assert_eq!(lang.get_name().parse::<Lang>(), Ok(lang));
+        }
+        assert!("fr".parse::<Lang>().is_err());
+    }
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(
+            format_text(
+                get_text(Lang::En, "tribute"),
+                &[("from", &"NpcA"), ("to", &"User"), ("count", &2)]
+            ),
+            "NpcA gave 2 card(s) to User"
+        );
+        assert_eq!(
+            format_text(get_text(Lang::Ja, "out"), &[("name", &"User")]),
+            "User 上がり"
+        );
+    }
+}
@@ -1,3 +1,8 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Indexer {
     idx: usize,
     active_players: Vec<usize>,
@@ -13,10 +18,34 @@ impl Indexer {
         }
     }
 
+    pub fn from_hands_counts(hands_counts: &[usize], player: usize) -> Self {
+        let active_players: Vec<usize> = (0..hands_counts.len())
+            .filter(|i| hands_counts[*i] > 0)
+            .collect();
+        let mut indexer = Self {
+            idx: active_players
+                .iter()
+                .position(|p| *p == player)
+                .unwrap_or(0),
+            active_players,
+            player_rank: hands_counts.iter().map(|_| None).collect(),
+        };
+        // 上がったプレイヤーを上位に追加
+        (0..hands_counts.len())
+            .filter(|i| hands_counts[*i] == 0)
+            .for_each(|i| indexer.set_player_rank(i));
+        indexer
+    }
+
     pub fn get_idx(&self) -> usize {
         self.active_players[self.idx]
     }
 
+    // まだ上がっていないプレイヤーの席(手番の順)
+    pub fn get_active_players(&self) -> &[usize] {
+        &self.active_players
+    }
+
     pub fn count_active_players(&self) -> usize {
         self.active_players.len()
     }
@@ -25,10 +54,62 @@ impl Indexer {
         self.player_rank.iter().filter_map(|p| *p).collect()
     }
 
+    // 席毎の順位(0が1位、まだ決まっていなければNone)
+    pub fn get_places(&self) -> Vec<Option<usize>> {
+        (0..self.player_rank.len())
+            .map(|player| self.player_rank.iter().position(|p| *p == Some(player)))
+            .collect()
+    }
+
+    // 内部の状態が矛盾していないかを確かめる
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let players_count = self.player_rank.len();
+        let mut seen = vec![false; players_count];
+        for player in self.active_players.iter().chain(&self.get_player_rank()) {
+            if *player >= players_count || std::mem::replace(&mut seen[*player], true) {
+                return Err(format!("席{player}が重複しているか範囲外です"));
+            }
+        }
+        if let Some(player) = seen.iter().position(|seen| !seen) {
+            return Err(format!(
+                "席{player}がアクティブリストにも順位にもありません"
+            ));
+        }
+        // 空いている順位は上位と下位の間に続いて並ぶ
+        let empty = self.player_rank.iter().filter(|p| p.is_none()).count();
+        if let Some(first) = self.player_rank.iter().position(Option::is_none) {
+            if self.player_rank[first..first + empty]
+                .iter()
+                .any(Option::is_some)
+            {
+                return Err(format!(
+                    "空いている順位が続いていません: {:?}",
+                    self.player_rank
+                ));
+            }
+        }
+        if self.active_players.len() == 1 {
+            return Err("残りのプレイヤーが1人なのに順位が決まっていません".to_owned());
+        }
+        if !self.active_players.is_empty() && self.idx >= self.active_players.len() {
+            return Err(format!("手番のインデックスが範囲外です: {}", self.idx));
+        }
+        Ok(())
+    }
+
     pub fn next(&mut self) {
         self.idx = (self.idx + 1) % self.active_players.len();
     }
 
+    // playerの手番にする(上がっていればplayerの次の席のプレイヤー)
+    pub fn set_lead(&mut self, player: usize) {
+        self.idx = self
+            .active_players
+            .iter()
+            .position(|p| *p >= player)
+            .unwrap_or(0);
+    }
+
     pub fn set_player_rank(&mut self, player: usize) {
         if let Some((i, _)) = self
             .player_rank
@@ -44,6 +125,7 @@ impl Indexer {
         // 現在のプレイヤーをアクティブリストから除く
         let player = self.active_players.remove(self.idx);
         self.set_player_rank(player);
+        tracing::debug!(player, active = ?self.active_players, "上がり");
         // インデックスがアクティブリストの範囲内になるように調整
         self.idx = if self.idx > self.active_players.len() - 1 {
             0
@@ -58,8 +140,20 @@ impl Indexer {
     }
 
     pub fn set_rank_back(&mut self) {
-        // 現在のプレイヤーをアクティブリストから除く
-        let player = self.active_players.remove(self.idx);
+        self.demote(self.active_players[self.idx]);
+    }
+
+    // playerをアクティブリストから除いて空いている最も下の順位にする
+    pub fn demote(&mut self, player: usize) {
+        let Some(pos) = self.active_players.iter().position(|p| *p == player) else {
+            return;
+        };
+        self.active_players.remove(pos);
+        tracing::debug!(player, active = ?self.active_players, "最下位に移動");
+        // 手番のプレイヤーが変わらないように調整
+        if pos < self.idx {
+            self.idx -= 1;
+        }
         if let Some((i, _)) = self
             .player_rank
             .iter()
@@ -96,6 +190,14 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_from_hands_counts() {
+        let indexer = Indexer::from_hands_counts(&[3, 0, 5, 2], 2);
+        assert_eq!(indexer.get_idx(), 2);
+        assert_eq!(indexer.active_players, vec![0, 2, 3]);
+        assert_eq!(indexer.get_player_rank(), vec![1]);
+    }
+
     #[test]
     fn test_set_rank_front() {
         let mut indexer = Indexer::new(4, 0);
@@ -119,4 +221,46 @@ mod test {
             vec![Some(3), Some(2), Some(1), Some(0)]
         );
     }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut indexer = Indexer::new(4, 2);
+        assert_eq!(indexer.check_invariants(), Ok(()));
+        indexer.set_rank_front();
+        indexer.demote(0);
+        assert_eq!(indexer.check_invariants(), Ok(()));
+        // 上がったプレイヤーがアクティブリストに残っている
+        let mut broken = indexer.clone();
+        broken.active_players.push(2);
+        assert!(broken.check_invariants().is_err());
+        // 順位が決まっていないプレイヤー
+        let mut broken = indexer.clone();
+        broken.active_players.pop();
+        assert!(broken.check_invariants().is_err());
+        // 範囲外の手番
+        let mut broken = indexer.clone();
+        broken.idx = 2;
+        assert!(broken.check_invariants().is_err());
+        indexer.set_rank_front();
+        assert_eq!(indexer.get_player_rank().len(), 4);
+        assert_eq!(indexer.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_demote() {
+        let mut indexer = Indexer::new(4, 2);
+        indexer.demote(0);
+        assert_eq!(indexer.get_idx(), 2);
+        assert_eq!(indexer.get_places(), vec![Some(3), None, None, None]);
+        // 上がったプレイヤーは変わらない
+        indexer.set_rank_front();
+        indexer.demote(2);
+        assert_eq!(indexer.get_places(), vec![Some(3), None, Some(0), None]);
+        // 残りの1人は空いている最も上の順位になる
+        indexer.demote(3);
+        assert_eq!(
+            indexer.get_places(),
+            vec![Some(3), Some(1), Some(0), Some(2)]
+        );
+    }
 }
@@ -1,10 +1,216 @@
+use crate::i18n::trf;
+use std::collections::VecDeque;
 use std::io;
 use std::io::Write;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 標準入力から読み取った行のバッファ
+struct Lines {
+    buf: Mutex<(VecDeque<String>, bool)>,
+    cond: Condvar,
+}
+
+fn get_lines() -> &'static Lines {
+    // 標準入力は専用のスレッドで読み取り、バッファに溜める
+    static LINES: OnceLock<Lines> = OnceLock::new();
+    LINES.get_or_init(|| {
+        thread::spawn(|| loop {
+            let mut buf = String::new();
+            let result = io::stdin().read_line(&mut buf);
+            let lines = get_lines();
+            let mut guard = lines.buf.lock().unwrap();
+            match result {
+                Ok(0) | Err(_) => {
+                    // 入力の終端
+                    guard.1 = true;
+                    lines.cond.notify_all();
+                    break;
+                }
+                Ok(_) => {
+                    guard.0.push_back(buf.trim().to_string());
+                    lines.cond.notify_all();
+                }
+            }
+        });
+        Lines {
+            buf: Mutex::new((VecDeque::new(), false)),
+            cond: Condvar::new(),
+        }
+    })
+}
+
+// Ctrl+Cが押されたことを表す入力
+pub const INTERRUPT: &str = "\u{3}";
+
+// Ctrl+Cが押されたことを次の入力として伝える(まだ読み取られていなければfalseを返す)
+pub fn interrupt() -> bool {
+    let lines = get_lines();
+    let mut guard = lines.buf.lock().unwrap();
+    if guard.0.iter().any(|line| line == INTERRUPT) {
+        return false;
+    }
+    guard.0.push_back(INTERRUPT.to_owned());
+    lines.cond.notify_all();
+    true
+}
 
 pub fn get_input(mes: String) -> String {
+    get_input_with_timeout(mes, None).unwrap_or_default()
+}
+
+// 時間内に入力がなければNoneを返す
+pub fn get_input_with_timeout(mes: String, timeout: Option<Duration>) -> Option<String> {
     print!("{mes}");
     io::stdout().flush().unwrap();
-    let mut buf = String::new();
-    std::io::stdin().read_line(&mut buf).ok();
-    buf.trim().to_string()
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let lines = get_lines();
+    let mut guard = lines.buf.lock().unwrap();
+    loop {
+        if let Some(line) = guard.0.pop_front() {
+            return Some(line);
+        }
+        if guard.1 {
+            // 入力が終端に達した場合は空文字列を返す
+            return Some(String::new());
+        }
+        guard = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.checked_duration_since(Instant::now())?;
+                lines.cond.wait_timeout(guard, remaining).unwrap().0
+            }
+            None => lines.cond.wait(guard).unwrap(),
+        };
+    }
+}
+
+// プレイヤーの入力を受け取る先(標準入力の代わりにテストやデモ、フロントエンドから入力を与えられる)
+pub trait InputSource {
+    // メッセージを表示して1行を受け取る(時間内に入力がなければNoneを返す)
+    fn get_input(&mut self, mes: String, timeout: Option<Duration>) -> Option<String>;
+}
+
+// 標準入力から読み取る
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdinInput;
+
+impl InputSource for StdinInput {
+    fn get_input(&mut self, mes: String, timeout: Option<Duration>) -> Option<String> {
+        get_input_with_timeout(mes, timeout)
+    }
+}
+
+// 決めておいた行を順に返す(尽きた後は入力の終端と同じく空文字列を返す)
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedInput {
+    lines: VecDeque<String>,
+}
+
+impl ScriptedInput {
+    pub fn new<I, S>(lines: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            lines: lines.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn get_input(&mut self, mes: String, _timeout: Option<Duration>) -> Option<String> {
+        let line = self.lines.pop_front().unwrap_or_default();
+        println!("{mes}{line}");
+        Some(line)
+    }
+}
+
+// メッセージから入力を返す関数でも入力を与えられる
+impl<F> InputSource for F
+where
+    F: FnMut(&str, Option<Duration>) -> Option<String>,
+{
+    fn get_input(&mut self, mes: String, timeout: Option<Duration>) -> Option<String> {
+        self(&mes, timeout)
+    }
+}
+
+// 番号を入力して項目のオンとオフを切り替え、何も入力しなければ決定する
+pub fn get_checklist(title: &str, labels: &[&str], checked: &mut [bool]) {
+    loop {
+        println!("{title}");
+        for line in get_checklist_lines(labels, checked) {
+            println!("{line}");
+        }
+        let input = get_input(trf("checklist", &[("max", &labels.len())]));
+        match input.parse::<usize>() {
+            Ok(n) if (1..=labels.len()).contains(&n) => checked[n - 1] = !checked[n - 1],
+            _ if input.is_empty() || input == INTERRUPT => return,
+            _ => println!("{}", trf("enter_number", &[("max", &labels.len())])),
+        }
+    }
+}
+
+pub fn get_checklist_lines(labels: &[&str], checked: &[bool]) -> Vec<String> {
+    labels
+        .iter()
+        .zip(checked)
+        .enumerate()
+        .map(|(i, (label, checked))| {
+            let mark = match checked {
+                true => "x",
+                false => " ",
+            };
+            format!("{:>2}. [{mark}] {label}", i + 1)
+        })
+        .collect()
+}
+
+// 既に入力済みの行の先頭が条件を満たす場合のみ取り出す
+pub fn take_input<F>(predicate: F) -> Option<String>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut guard = get_lines().buf.lock().unwrap();
+    match guard.0.front() {
+        Some(line) if predicate(line) => guard.0.pop_front(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scripted_input() {
+        let mut input = ScriptedInput::new(["1 2", "p"]);
+        assert_eq!(
+            input.get_input("> ".to_owned(), None),
+            Some("1 2".to_owned())
+        );
+        assert_eq!(input.get_input("> ".to_owned(), None), Some("p".to_owned()));
+        assert!(input.is_empty());
+        // 尽きた後は入力の終端と同じになる
+        assert_eq!(input.get_input("> ".to_owned(), None), Some(String::new()));
+        let mut answer = |mes: &str, _| Some(mes.trim().to_uppercase());
+        assert_eq!(
+            answer.get_input("y ".to_owned(), None),
+            Some("Y".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_get_checklist_lines() {
+        assert_eq!(
+            get_checklist_lines(&["8切り", "縛り"], &[true, false]),
+            vec![" 1. [x] 8切り", " 2. [ ] 縛り"]
+        );
+    }
 }
@@ -0,0 +1,475 @@
+use crate::card::{parse_cards, Card, Suit};
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::i18n::{tr, trf};
+use crate::observer::Observer;
+use crate::rules::{FoulRule, LeadPassRule, LeadRule, RuleSet, TOGGLE_KEYS};
+use std::io::Write;
+
+pub const HEADER: &str = "# daifugo kifu";
+
+fn suit_to_kifu(suit: &Suit) -> &'static str {
+    match suit {
+        Suit::Spade => "♠",
+        Suit::Club => "♣",
+        Suit::Diamond => "♦",
+        Suit::Heart => "♥",
+    }
+}
+
+// 棋譜用のカードの表記(例: ♠7 ♥10 JK)
+pub fn card_to_kifu(card: &Card) -> String {
+    match card {
+        Card::Normal(suit, rank) => format!("{}{rank}", suit_to_kifu(suit)),
+        Card::Joker => "JK".to_owned(),
+    }
+}
+
+pub fn cards_to_kifu(cards: &[Card]) -> String {
+    cards.iter().map(card_to_kifu).collect()
+}
+
+// 棋譜用の1手の表記(例: P2: ♠7♥7、P3: pass)
+pub fn move_to_kifu(idx: usize, comb: &Option<Comb>) -> String {
+    match comb {
+        Some(Comb::Single(card)) => format!("P{idx}: {}", card_to_kifu(card)),
+        Some(Comb::Multi(cards)) | Some(Comb::Seq(cards)) => {
+            format!("P{idx}: {}", cards_to_kifu(cards))
+        }
+        None => format!("P{idx}: pass"),
+    }
+}
+
+// 棋譜の表記から1手を読み取る
+pub fn parse_move(text: &str) -> Result<Option<Comb>, String> {
+    let text = text.trim();
+    if text == "pass" {
+        return Ok(None);
+    }
+    text.parse::<Comb>().map(Some)
+}
+
+fn parse_player(text: &str) -> Result<usize, String> {
+    text.trim()
+        .strip_prefix('P')
+        .and_then(|n| n.parse::<usize>().ok())
+        .ok_or_else(|| trf("player_parse_failed", &[("text", &text)]))
+}
+
+// 棋譜用のルールの表記(例: eight_cut bind revolution lead=loser lead_pass=reject foul=demote min_seq=3)
+pub fn rules_to_kifu(rules: &RuleSet) -> String {
+    TOGGLE_KEYS
+        .iter()
+        .zip(rules.get_toggles())
+        .filter(|(_, on)| *on)
+        .map(|(key, _)| key.to_string())
+        .chain([
+            format!("lead={}", rules.lead.get_name()),
+            format!("lead_pass={}", rules.lead_pass.get_name()),
+            format!("foul={}", rules.foul.get_name()),
+            format!("min_seq={}", rules.min_seq),
+        ])
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// 棋譜の表記からルールを読み取る(書かれていないルールはオフになる)
+pub fn parse_rules(text: &str) -> Result<RuleSet, String> {
+    let mut toggles = [false; TOGGLE_KEYS.len()];
+    let mut rules = RuleSet::default();
+    for word in text.split_whitespace() {
+        if let Some(lead) = word.strip_prefix("lead=") {
+            rules.lead = lead.parse::<LeadRule>()?;
+            continue;
+        }
+        if let Some(lead_pass) = word.strip_prefix("lead_pass=") {
+            rules.lead_pass = lead_pass.parse::<LeadPassRule>()?;
+            continue;
+        }
+        if let Some(foul) = word.strip_prefix("foul=") {
+            rules.foul = foul.parse::<FoulRule>()?;
+            continue;
+        }
+        if let Some(min_seq) = word.strip_prefix("min_seq=") {
+            rules.min_seq = min_seq
+                .parse()
+                .map_err(|_| trf("unknown_min_seq", &[("value", &min_seq)]))?;
+            continue;
+        }
+        let pos = TOGGLE_KEYS
+            .iter()
+            .position(|key| *key == word)
+            .ok_or_else(|| trf("unknown_rule", &[("word", &word)]))?;
+        toggles[pos] = true;
+    }
+    rules.set_toggles(toggles);
+    Ok(rules)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct KifuRound {
+    pub names: Vec<String>,
+    pub deals: Vec<Vec<Card>>,
+    pub moves: Vec<(usize, Option<Comb>)>,
+    pub player_rank: Vec<usize>,
+    // 棋譜の先頭に書かれたルール(なければ従来のルール)
+    pub rules: RuleSet,
+    // 前のラウンドの大富豪(都落ちの判定に使う)
+    pub capital: Option<usize>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Kifu {
+    // カードを配るのに使ったシード(再開したゲームなどでは書かれない)
+    pub seed: Option<u64>,
+    pub rounds: Vec<KifuRound>,
+}
+
+// 棋譜をラウンド毎に読み取る
+pub fn parse_kifu(text: &str) -> Result<Kifu, String> {
+    let mut rounds: Vec<KifuRound> = vec![];
+    let mut seed = None;
+    let mut rules = RuleSet::default();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |mes: String| trf("kifu_line_error", &[("line", &(i + 1)), ("error", &mes)]);
+        if line.starts_with("round ") {
+            let capital = rounds.last().and_then(|r| r.player_rank.first().copied());
+            rounds.push(KifuRound {
+                rules,
+                capital,
+                ..Default::default()
+            });
+            continue;
+        }
+        if rounds.is_empty() {
+            if let Some(rest) = line.strip_prefix("seed ") {
+                seed = Some(
+                    rest.trim()
+                        .parse::<u64>()
+                        .map_err(|e| error(e.to_string()))?,
+                );
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("rules:") {
+                rules = parse_rules(rest).map_err(error)?;
+                continue;
+            }
+        }
+        let round = rounds
+            .last_mut()
+            .ok_or_else(|| error(tr("move_before_round").to_owned()))?;
+        if let Some(rest) = line.strip_prefix("player ") {
+            let (_, name) = rest.split_once(' ').unwrap_or((rest, ""));
+            round.names.push(name.to_owned());
+        } else if let Some(rest) = line.strip_prefix("deal ") {
+            let (_, cards) = rest
+                .split_once(':')
+                .ok_or_else(|| error(trf("line_parse_failed", &[("line", &line)])))?;
+            round.deals.push(parse_cards(cards).map_err(error)?);
+        } else if let Some(rest) = line.strip_prefix("undo ") {
+            let turns = rest
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|turns| *turns <= round.moves.len())
+                .ok_or_else(|| error(trf("undo_parse_failed", &[("text", &rest)])))?;
+            round.moves.truncate(round.moves.len() - turns);
+        } else if let Some(rest) = line.strip_prefix("rank:") {
+            round.player_rank = rest
+                .split_whitespace()
+                .map(parse_player)
+                .collect::<Result<_, _>>()
+                .map_err(error)?;
+        } else {
+            let (player, comb) = line
+                .split_once(':')
+                .ok_or_else(|| error(trf("line_parse_failed", &[("line", &line)])))?;
+            let idx = parse_player(player).map_err(error)?;
+            round.moves.push((idx, parse_move(comb).map_err(error)?));
+        }
+    }
+    Ok(Kifu { seed, rounds })
+}
+
+// ラウンド毎に配られたカード、全ての手、順位を書き出す
+pub struct KifuWriter<W: Write> {
+    writer: W,
+    round: usize,
+    seed: Option<u64>,
+    rules: RuleSet,
+}
+
+impl<W: Write> KifuWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            round: 0,
+            seed: None,
+            rules: RuleSet::default(),
+        }
+    }
+
+    // シードを書いておくとverifyで配られたカードも確かめられる
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    fn get_lines(&mut self, event: &GameEvent) -> Vec<String> {
+        match event {
+            GameEvent::Start { names, hands } => {
+                self.round += 1;
+                let mut lines = vec![];
+                if self.round == 1 {
+                    lines.push(HEADER.to_owned());
+                    lines.extend(self.seed.map(|seed| format!("seed {seed}")));
+                    lines.push(format!("rules: {}", rules_to_kifu(&self.rules)));
+                } else {
+                    lines.push(String::new());
+                }
+                lines.push(format!("round {}", self.round));
+                lines.extend(
+                    names
+                        .iter()
+                        .enumerate()
+                        .map(|(i, name)| format!("player P{i} {name}")),
+                );
+                lines.extend(
+                    hands
+                        .iter()
+                        .enumerate()
+                        .map(|(i, cards)| format!("deal P{i}: {}", cards_to_kifu(cards))),
+                );
+                lines
+            }
+            GameEvent::Turn { idx, comb, .. } => vec![move_to_kifu(*idx, comb)],
+            GameEvent::End { player_rank } => vec![format!(
+                "rank: {}",
+                player_rank
+                    .iter()
+                    .map(|idx| format!("P{idx}"))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            )],
+            // 取り消した手は読み取るときに除く
+            GameEvent::Undo { turns: 0 } => vec![],
+            GameEvent::Undo { turns } => vec![format!("undo {turns}")],
+            GameEvent::Lead { .. }
+            | GameEvent::Reject { .. }
+            | GameEvent::Reason { .. }
+            | GameEvent::Tribute { .. }
+            | GameEvent::Return { .. }
+            | GameEvent::Exchange
+            | GameEvent::Quit { .. }
+            | GameEvent::Substitute { .. }
+            | GameEvent::Save { .. } => vec![],
+        }
+    }
+}
+
+impl<W: Write> Observer for KifuWriter<W> {
+    fn notify(&mut self, event: &GameEvent) {
+        let result = self
+            .get_lines(event)
+            .iter()
+            .try_for_each(|line| writeln!(self.writer, "{line}"))
+            .and_then(|_| self.writer.flush());
+        if let Err(e) = result {
+            eprintln!("{}", trf("kifu_write_failed", &[("error", &e)]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::Rank;
+    use crate::field::{Finish, TurnOutcome};
+    use crate::rules::Preset;
+
+    #[test]
+    fn test_move_to_kifu() {
+        for (comb, expected) in [
+            (
+                Some(Comb::Multi(vec![
+                    Card::Normal(Suit::Spade, Rank::Seven),
+                    Card::Normal(Suit::Heart, Rank::Seven),
+                ])),
+                "P2: ♠7♥7",
+            ),
+            (
+                Some(Comb::Seq(vec![
+                    Card::Normal(Suit::Club, Rank::Nine),
+                    Card::Normal(Suit::Club, Rank::Ten),
+                    Card::Joker,
+                ])),
+                "P2: ♣9♣10JK",
+            ),
+            (None, "P2: pass"),
+        ] {
+            assert_eq!(move_to_kifu(2, &comb), expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_move() {
+        for (text, expected) in [
+            ("pass", Ok(None)),
+            (
+                "♠10",
+                Ok(Some(Comb::Single(Card::Normal(Suit::Spade, Rank::Ten)))),
+            ),
+            (
+                "♠7♥7",
+                Ok(Some(Comb::Multi(vec![
+                    Card::Normal(Suit::Heart, Rank::Seven),
+                    Card::Normal(Suit::Spade, Rank::Seven),
+                ]))),
+            ),
+            (
+                "♣J♣QJK",
+                Ok(Some(Comb::Seq(vec![
+                    Card::Normal(Suit::Club, Rank::Jack),
+                    Card::Normal(Suit::Club, Rank::Queen),
+                    Card::Joker,
+                ]))),
+            ),
+            ("♠7♥8", Err("組み合わせになっていません: ♠7♥8".to_owned())),
+            (
+                "S7",
+                Ok(Some(Comb::Single(Card::Normal(Suit::Spade, Rank::Seven)))),
+            ),
+            ("X7", Err("カードを読み取れません: X7".to_owned())),
+        ] {
+            assert_eq!(parse_move(text), expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_kifu() {
+        let text = "# daifugo kifu\nround 1\nplayer P0 User\nplayer P1 NpcA\n\
+                    deal P0: ♦3\ndeal P1: JK\nP0: ♦3\nrank: P0 P1\n";
+        let kifu = parse_kifu(text).unwrap();
+        assert_eq!(kifu.seed, None);
+        assert_eq!(
+            kifu.rounds,
+            vec![KifuRound {
+                names: vec!["User".to_owned(), "NpcA".to_owned()],
+                deals: vec![
+                    vec![Card::Normal(Suit::Diamond, Rank::Three)],
+                    vec![Card::Joker]
+                ],
+                moves: vec![(
+                    0,
+                    Some(Comb::Single(Card::Normal(Suit::Diamond, Rank::Three)))
+                )],
+                player_rank: vec![0, 1],
+                ..Default::default()
+            }]
+        );
+        assert!(parse_kifu("P0: ♦3").is_err());
+        assert!(parse_kifu("round 1\nQ0: ♦3").is_err());
+        // 先頭のシードとルールは全てのラウンドに適用し、前のラウンドの1位を大富豪とする
+        let text = "# daifugo kifu\nseed 42\nrules: bind jack_back lead=rotate\n\
+                    round 1\nrank: P1 P0\nround 2\n";
+        let kifu = parse_kifu(text).unwrap();
+        assert_eq!(kifu.seed, Some(42));
+        let rules = RuleSet {
+            eight_cut: false,
+            revolution: false,
+            jack_back: true,
+            lead: LeadRule::Rotate,
+            ..RuleSet::default()
+        };
+        assert_eq!(
+            kifu.rounds
+                .iter()
+                .map(|r| (r.rules, r.capital))
+                .collect::<Vec<_>>(),
+            vec![(rules, None), (rules, Some(1))]
+        );
+        // 待ったで取り消した手は除く
+        let text = "round 1\nP0: ♦3\nP1: pass\nundo 2\nP0: JK\n";
+        let kifu = parse_kifu(text).unwrap();
+        assert_eq!(
+            kifu.rounds[0].moves,
+            vec![(0, Some(Comb::Single(Card::Joker)))]
+        );
+        assert!(parse_kifu("round 1\nP0: ♦3\nundo 2").is_err());
+        assert!(parse_kifu("seed x\nround 1").is_err());
+        assert!(parse_kifu("rules: eight_cut joker\nround 1").is_err());
+    }
+
+    #[test]
+    fn test_rules_to_kifu() {
+        for (rules, expected) in [
+            (
+                RuleSet::default(),
+                "eight_cut bind revolution lead=loser lead_pass=reject foul=demote min_seq=3",
+            ),
+            (
+                Preset::Kanto.get_rules(),
+                "eight_cut bind revolution jack_back spade_three capital_fall lead=loser lead_pass=reject foul=demote min_seq=3",
+            ),
+            (
+                RuleSet {
+                    lead_pass: LeadPassRule::Skip,
+                    foul: FoulRule::Void,
+                    min_seq: 0,
+                    ..RuleSet::default()
+                },
+                "eight_cut bind revolution lead=loser lead_pass=skip foul=void min_seq=0",
+            ),
+        ] {
+            assert_eq!(rules_to_kifu(&rules), expected);
+            assert_eq!(parse_rules(expected), Ok(rules));
+        }
+    }
+
+    #[test]
+    fn test_kifu_writer() {
+        let mut writer = KifuWriter::new(vec![]).with_seed(Some(7));
+        for event in [
+            GameEvent::Start {
+                names: vec!["User".to_owned(), "NpcA".to_owned()],
+                hands: vec![
+                    vec![Card::Normal(Suit::Diamond, Rank::Three)],
+                    vec![Card::Joker],
+                ],
+            },
+            GameEvent::Turn {
+                idx: 0,
+                comb: Some(Comb::Single(Card::Normal(Suit::Diamond, Rank::Three))),
+                hands_count: 0,
+                outcome: TurnOutcome {
+                    finish: Some(Finish {
+                        idx: 0,
+                        place: 0,
+                        foul: false,
+                    }),
+                    ..TurnOutcome::default()
+                },
+            },
+            GameEvent::End {
+                player_rank: vec![0, 1],
+            },
+        ] {
+            writer.notify(&event);
+        }
+        assert_eq!(
+            String::from_utf8(writer.writer).unwrap(),
+            "# daifugo kifu\nseed 7\nrules: eight_cut bind revolution lead=loser lead_pass=reject foul=demote min_seq=3\n\
+             round 1\nplayer P0 User\nplayer P1 NpcA\n\
+             deal P0: ♦3\ndeal P1: JK\nP0: ♦3\nrank: P0 P1\n"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+pub mod analysis;
+pub mod arena;
+pub mod bench;
+pub mod bitcards;
+pub mod card;
+#[cfg(feature = "serde")]
+pub mod client;
+pub mod comb;
+#[cfg(feature = "serde")]
+pub mod config;
+pub mod dataset;
+pub mod encoding;
+pub mod endgame;
+pub mod event;
+#[cfg(feature = "serde")]
+pub mod event_log;
+#[cfg(feature = "serde")]
+pub mod external;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod field;
+pub mod game;
+pub mod hand;
+pub mod heuristic;
+pub mod i18n;
+pub mod indexer;
+pub mod input;
+pub mod kifu;
+pub mod mcts;
+pub mod moves;
+pub mod names;
+pub mod npc;
+pub mod observer;
+pub mod opponent;
+pub mod options;
+pub mod pc;
+pub mod player;
+#[cfg(feature = "onnx")]
+pub mod policy;
+#[cfg(feature = "serde")]
+pub mod protocol;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rating;
+pub mod reason;
+pub mod record;
+pub mod render;
+pub mod replay;
+pub mod rules;
+pub mod save;
+#[cfg(feature = "serde")]
+pub mod server;
+pub mod simulate;
+pub mod state;
+#[cfg(feature = "serde")]
+pub mod stats;
+pub mod strategy;
+pub mod suit_binder;
+pub mod tables;
+pub mod tournament;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod tune;
+pub mod tutorial;
+pub mod validator;
+pub mod verify;
+pub mod view;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod whatif;
+pub mod zobrist;
+
+pub const PLAYERS_COUNT: usize = 4;
+// ラウンドの間にプレイヤーが増減できる範囲
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+pub const NPC_NAMES: [&str; 3] = ["NpcA", "NpcB", "NpcC"];
@@ -1,134 +1,830 @@
-use crate::field::Flags;
-use card::cmp_order;
-use card::Card;
-use comb::Comb;
-use core::time;
-use field::Field;
+use analysis::Analyzer;
+use arena::ArenaOptions;
+use bench::BenchOptions;
+use daifugo::*;
+use game::Game;
+use heuristic::{HeuristicNpc, Weights};
+use i18n::{tr, trf, Lang};
 use input::get_input;
-use itertools::Itertools;
+use kifu::KifuWriter;
 use npc::MinNpc;
+use observer::{Observer, Observers};
+use options::{Command, HttpOptions, JoinOptions, Options, RulesOptions, ServeOptions};
 use pc::Pc;
 use player::Player;
 use rand::seq::SliceRandom;
-use std::thread;
-mod card;
-mod comb;
-mod field;
-mod indexer;
-mod input;
-mod npc;
-mod pc;
-mod player;
-mod suit_binder;
-mod validator;
-
-const PLAYERS_COUNT: usize = 4;
-
-fn get_split_deck() -> Vec<Vec<Card>> {
-    let mut deck = card::create_deck();
-    deck.shuffle(&mut rand::thread_rng());
-    let d1 = deck.split_off(deck.len() - 13);
-    let d2 = deck.split_off(deck.len() - 13);
-    let d3 = deck.split_off(deck.len() - 13);
-    let mut hands = vec![d1, d2, d3, deck];
-    hands.iter_mut().for_each(|d| d.sort_by(cmp_order));
-    hands
-}
-
-fn create_players() -> Vec<Box<dyn Player>> {
-    let mut players: Vec<Box<dyn Player>> = vec![
-        Box::new(Pc::new("User".to_owned())),
-        Box::new(MinNpc::new("NpcA".to_owned())),
-        Box::new(MinNpc::new("NpcB".to_owned())),
-        Box::new(MinNpc::new("NpcC".to_owned())),
-    ];
-    players
-        .iter_mut()
-        .zip(get_split_deck())
-        .for_each(|(player, hands)| player.init(hands));
-    players.shuffle(&mut rand::thread_rng());
+use render::{Renderer, Style};
+use rules::{RuleSet, TOGGLE_KEYS};
+use simulate::SimulateOptions;
+use std::fs::{self, File};
+use std::path::Path;
+use std::process;
+use strategy::Strategy;
+use tournament::TournamentOptions;
+use tune::TuneOptions;
+
+// オプションで指定した表示と入力方法の人間のプレイヤー
+fn create_pc(name: String, style: Style, hotseat: bool, options: &Options) -> Box<dyn Player> {
+    Box::new(
+        Pc::new(name, style, hotseat, options.timer)
+            .with_counter(options.counter)
+            .with_notice(options.notice)
+            .with_candidates(options.candidates),
+    )
+}
+
+fn create_users(names: Vec<String>, style: Style, options: &Options) -> Vec<Box<dyn Player>> {
+    // 複数人で遊ぶ場合は交代時に画面を消す
+    let hotseat = names.len() > 1;
+    names
+        .into_iter()
+        .map(|name| create_pc(name, style, hotseat, options))
+        .collect()
+}
+
+#[cfg(feature = "onnx")]
+fn create_policy_npcs(path: &Path, names: Vec<String>) -> Vec<Box<dyn Player>> {
+    let model = match policy::load_policy(path) {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+    names
+        .into_iter()
+        .map(|name| Box::new(policy::PolicyNpc::new(name, model.clone())) as Box<dyn Player>)
+        .collect()
+}
+
+#[cfg(not(feature = "onnx"))]
+fn create_policy_npcs(_path: &Path, _names: Vec<String>) -> Vec<Box<dyn Player>> {
+    eprintln!(
+        "{}",
+        trf(
+            "feature_required",
+            &[("option", &"--policy"), ("feature", &"onnx")]
+        )
+    );
+    process::exit(1);
+}
+
+// DAIFUGO_LOGで指定したレベルのログを標準エラー出力に書く(例: DAIFUGO_LOG=daifugo=debug)
+#[cfg(feature = "trace")]
+fn init_tracing() {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_env("DAIFUGO_LOG"))
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+#[cfg(not(feature = "trace"))]
+fn init_tracing() {}
+
+#[cfg(feature = "serde")]
+fn load_tuned_weights() -> Option<Weights> {
+    let path = config::get_config_path()?;
+    match config::load_config(&path) {
+        Ok(config) => config.heuristic,
+        Err(mes) => {
+            eprintln!("{}: {mes}", path.display());
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn load_tuned_weights() -> Option<Weights> {
+    None
+}
+
+// policyを指定すればONNXモデルで手を選ぶNPC、tuneで調整した重みがあればその重みのHeuristicNpc
+fn create_npcs(names: Vec<String>, policy: Option<&Path>) -> Vec<Box<dyn Player>> {
+    match (policy, load_tuned_weights()) {
+        (Some(path), _) => create_policy_npcs(path, names),
+        (None, Some(weights)) => names
+            .into_iter()
+            .map(|name| Box::new(HeuristicNpc::new(name, weights)) as Box<dyn Player>)
+            .collect(),
+        (None, None) => names
+            .into_iter()
+            .map(|name| Box::new(MinNpc::new(name)) as Box<dyn Player>)
+            .collect(),
+    }
+}
+
+// 空いた席をNPCで埋める
+// shuffleがfalseなら人間のプレイヤーを先の席に並べる
+fn create_players(
+    users: Vec<Box<dyn Player>>,
+    names: &PlayerNames,
+    policy: Option<&Path>,
+    shuffle: bool,
+) -> Vec<Box<dyn Player>> {
+    let taken: Vec<String> = users.iter().map(|u| u.get_name().to_owned()).collect();
+    let npc_names = names::get_npc_names(
+        PLAYERS_COUNT - users.len(),
+        &names.npc_names,
+        &taken,
+        names.random,
+        &mut rand::thread_rng(),
+    );
+    let npcs = create_npcs(npc_names, policy);
+    let mut players: Vec<Box<dyn Player>> = users.into_iter().chain(npcs).collect();
+    if shuffle {
+        players.shuffle(&mut rand::thread_rng());
+    }
     players
 }
 
-fn print_comb(comb: &Comb) -> String {
-    match comb {
-        Comb::Single(card) => String::from(card),
-        Comb::Multi(cards) | Comb::Seq(cards) => cards.iter().map(String::from).join(" "),
+#[cfg(feature = "tui")]
+fn run_tui(style: Style, options: &Options) {
+    let tui = tui::Tui::new(style, options.speed);
+    let names = PlayerNames::new(options);
+    let user = tui.create_player(names::get_user_names(&names.users, 1).remove(0));
+    tui.run(
+        Game::new(
+            create_players(vec![Box::new(user)], &names, None, options.shuffle_seats),
+            0,
+            options.seed.unwrap_or_else(rand::random),
+        ),
+        options.rounds,
+    );
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(_style: Style, _options: &Options) {
+    eprintln!(
+        "{}",
+        trf(
+            "feature_required",
+            &[("option", &"--tui"), ("feature", &"tui")]
+        )
+    );
+    process::exit(1);
+}
+
+fn run_simulate(options: SimulateOptions) {
+    println!(
+        "{}",
+        trf(
+            "simulate_title",
+            &[("games", &options.games), ("seed", &options.seed)]
+        )
+    );
+    let summary = simulate::simulate(&options);
+    for line in summary.get_lines(&options.seats) {
+        println!("{line}");
+    }
+    if let Some(path) = &options.output {
+        write_records(path, &summary.records);
+    }
+    if let Some(path) = &options.dataset {
+        match dataset::write_npz(path, &summary.samples) {
+            Ok(()) => println!(
+                "{}",
+                trf(
+                    "dataset_written",
+                    &[("count", &summary.samples.len()), ("path", &path.display())]
+                )
+            ),
+            Err(e) => {
+                eprintln!("{}", trf("dataset_failed", &[("error", &e)]));
+                process::exit(1);
+            }
+        }
     }
 }
 
-fn exchange_cards(
-    players: &mut [Box<dyn Player>],
-    winner_idx: usize,
-    loser_idx: usize,
-    cards_count: usize,
-) {
-    let needless_cards = players[winner_idx].get_needless_cards(cards_count);
-    let max_cards: Vec<Card> = (0..cards_count)
-        .filter_map(|_| players[loser_idx].get_hands().pop())
-        .collect();
-    players[winner_idx].get_hands().extend(max_cards);
-    players[winner_idx].get_hands().sort_by(cmp_order);
-    players[loser_idx].get_hands().extend(needless_cards);
-    players[loser_idx].get_hands().sort_by(cmp_order);
+fn run_arena(options: ArenaOptions) {
+    let key = match options.duplicate {
+        true => "arena_duplicate_title",
+        false => "arena_title",
+    };
+    println!(
+        "{}",
+        trf(key, &[("games", &options.games), ("seed", &options.seed)])
+    );
+    println!("{}", tr("arena_header"));
+    let matchups = arena::run_arena(&options);
+    for matchup in &matchups {
+        println!("{}", matchup.get_line());
+    }
+    if let Some(path) = &options.output {
+        write_records(path, matchups.iter().flat_map(|m| &m.records));
+    }
 }
 
-fn main() {
-    let mut players = create_players();
-    let mut field = Field::new(PLAYERS_COUNT, 0);
-    let duration = time::Duration::from_millis(300);
-    loop {
-        while field.count_active_players() > 0 {
-            let idx = field.get_idx();
-            // 場に出すカードを取得
-            let played_comb = players[idx].play(&field);
-            let hands_count = players[idx].count_hands();
-            let c = match &played_comb {
-                Some(comb) => print_comb(comb),
-                None => "パス".to_owned(),
-            };
-            println!("{} [{:2}]: {}", players[idx].get_name(), hands_count, c);
-            // カードを場に出すかパス
-            let flags = field.put(played_comb, hands_count);
-            if flags.contains(Flags::EIGHT) {
-                println!("8切り");
+fn run_tournament(options: TournamentOptions) {
+    let size = match options.get_table_size() {
+        Ok(size) => size,
+        Err(mes) => {
+            eprintln!("{mes}");
+            process::exit(1);
+        }
+    };
+    println!(
+        "{}",
+        trf(
+            "tournament_title",
+            &[
+                ("size", &size),
+                ("tables", &options.tables),
+                ("rounds", &options.rounds),
+                ("pairing", &options.pairing.get_name()),
+                ("seed", &options.seed),
+            ]
+        )
+    );
+    let tournament = tournament::run_tournament(&options, &mut |result| {
+        println!(
+            "{}",
+            trf(
+                "tournament_table",
+                &[
+                    ("round", &(result.round + 1)),
+                    ("table", &(result.table + 1)),
+                    ("ranking", &result.ranking.join(" > ")),
+                ]
+            )
+        );
+    });
+    for line in tournament.get_lines() {
+        println!("{line}");
+    }
+}
+
+fn run_bench(options: BenchOptions) {
+    let seats: Vec<&str> = options.seats.iter().map(Strategy::get_name).collect();
+    println!(
+        "{}",
+        trf(
+            "bench_title",
+            &[
+                ("games", &options.games),
+                ("seats", &seats.join(",")),
+                ("seed", &options.seed),
+            ]
+        )
+    );
+    println!("{}", String::from(&bench::run_bench(&options)));
+}
+
+fn run_tune(options: TuneOptions) {
+    println!(
+        "{}",
+        trf(
+            "tune_title",
+            &[
+                ("generations", &options.generations),
+                ("population", &options.population),
+                ("games", &options.games),
+                ("seed", &options.seed),
+            ]
+        )
+    );
+    println!("{}", tr("tune_header"));
+    let mut generation = 0;
+    let best = tune::run_tune(&options, &mut |current| {
+        generation += 1;
+        println!("{generation:>4}  {}", String::from(current));
+    });
+    println!("{}", trf("tune_best", &[("weights", &String::from(&best))]));
+    if options.write {
+        write_tuned_weights(best.best);
+    }
+}
+
+#[cfg(feature = "serde")]
+fn write_tuned_weights(weights: Weights) {
+    let Some(path) = config::get_config_path() else {
+        eprintln!("{}", tr("no_config_dir"));
+        process::exit(1);
+    };
+    let result = config::load_config(&path).and_then(|mut config| {
+        config.heuristic = Some(weights);
+        config::save_config(&path, &config)
+    });
+    match result {
+        Ok(()) => println!("{}", trf("weights_written", &[("path", &path.display())])),
+        Err(mes) => {
+            eprintln!("{}: {mes}", path.display());
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_tuned_weights(_weights: Weights) {
+    eprintln!(
+        "{}",
+        trf(
+            "feature_required",
+            &[("option", &"--write"), ("feature", &"serde")]
+        )
+    );
+    process::exit(1);
+}
+
+fn write_records<'a, I>(path: &Path, records: I)
+where
+    I: IntoIterator<Item = &'a record::GameRecord>,
+{
+    match record::write_csv(path, records) {
+        Ok(()) => println!("{}", trf("results_written", &[("path", &path.display())])),
+        Err(e) => {
+            eprintln!("{}", trf("results_failed", &[("error", &e)]));
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn create_event_log(path: &Path) -> Box<dyn Observer> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path);
+    match file {
+        Ok(file) => Box::new(event_log::EventLog::new(file)),
+        Err(e) => {
+            eprintln!("{}", trf("log_failed", &[("error", &e)]));
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn create_event_log(_path: &Path) -> Box<dyn Observer> {
+    eprintln!(
+        "{}",
+        trf(
+            "feature_required",
+            &[("option", &"--log-file"), ("feature", &"serde")]
+        )
+    );
+    process::exit(1);
+}
+
+#[cfg(feature = "serde")]
+fn create_stats_recorder() -> Option<Box<dyn Observer>> {
+    let path = stats::get_stats_path()?;
+    Some(Box::new(stats::StatsRecorder::new(path)))
+}
+
+#[cfg(not(feature = "serde"))]
+fn create_stats_recorder() -> Option<Box<dyn Observer>> {
+    None
+}
+
+// 棋譜を再生し直して、エンジンが同じ結果を出すかを確かめる
+fn run_verify(path: &Path) {
+    let result = fs::read_to_string(path)
+        .map_err(|e| trf("kifu_read_failed", &[("error", &e)]))
+        .and_then(|text| kifu::parse_kifu(&text))
+        .and_then(|kifu| verify::verify_kifu(&kifu));
+    match result {
+        Ok(lines) => {
+            for line in lines {
+                println!("{line}");
+            }
+        }
+        Err(mes) => {
+            eprintln!("{mes}");
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn run_stats() {
+    let Some(path) = stats::get_stats_path() else {
+        eprintln!("{}", tr("no_config_dir"));
+        process::exit(1);
+    };
+    match stats::load_stats(&path) {
+        Ok(stats) if stats.players.is_empty() => println!("{}", tr("no_stats")),
+        Ok(stats) => {
+            println!("{}", path.display());
+            for line in stats.get_lines() {
+                println!("{line}");
             }
-            if flags.contains(Flags::BIND) {
-                println!("縛り");
+        }
+        Err(mes) => {
+            eprintln!("{}: {mes}", path.display());
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn run_stats() {
+    eprintln!(
+        "{}",
+        trf(
+            "feature_required",
+            &[("option", &"stats"), ("feature", &"serde")]
+        )
+    );
+    process::exit(1);
+}
+
+// 選んだルールを棋譜の表記かJSONで表示する
+fn run_rules(options: RulesOptions) {
+    check_rules(&options.rules);
+    match options.json {
+        true => print_rules_json(&options.rules),
+        false => println!("{}", kifu::rules_to_kifu(&options.rules)),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn print_rules_json(rules: &RuleSet) {
+    println!("{}", serde_json::to_string_pretty(rules).unwrap());
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_rules_json(_rules: &RuleSet) {
+    eprintln!(
+        "{}",
+        trf(
+            "feature_required",
+            &[("option", &"--json"), ("feature", &"serde")]
+        )
+    );
+    process::exit(1);
+}
+
+#[cfg(feature = "serde")]
+fn run_serve(options: ServeOptions) {
+    let style = Style::new(options.color, options.ascii);
+    if let Err(mes) = server::run_server(&options, style) {
+        eprintln!("{mes}");
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn run_serve(_options: ServeOptions) {
+    eprintln!(
+        "{}",
+        trf(
+            "feature_required",
+            &[("option", &"serve"), ("feature", &"serde")]
+        )
+    );
+    process::exit(1);
+}
+
+#[cfg(feature = "http")]
+fn run_http(options: HttpOptions) {
+    if let Err(mes) = server::http::run_http_server(&options) {
+        eprintln!("{mes}");
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "http"))]
+fn run_http(_options: HttpOptions) {
+    eprintln!(
+        "{}",
+        trf(
+            "feature_required",
+            &[("option", &"http"), ("feature", &"http")]
+        )
+    );
+    process::exit(1);
+}
+
+#[cfg(feature = "serde")]
+fn run_join(options: JoinOptions) {
+    let style = Style::new(options.color, options.ascii);
+    if let Err(mes) = client::run_client(&options, style) {
+        eprintln!("{mes}");
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn run_join(_options: JoinOptions) {
+    eprintln!(
+        "{}",
+        trf(
+            "feature_required",
+            &[("option", &"join"), ("feature", &"serde")]
+        )
+    );
+    process::exit(1);
+}
+
+// 人間の手番でCtrl+Cが押されたら中断の確認をする
+#[cfg(unix)]
+fn handle_interrupt() {
+    use signal_hook::consts::SIGINT;
+    use signal_hook::iterator::Signals;
+    let Ok(mut signals) = Signals::new([SIGINT]) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            // 確認を待っている間にもう一度押されたらすぐに終了する
+            if !input::interrupt() {
+                process::exit(130);
             }
-            if flags.contains(Flags::REV) {
-                println!("カードの強さが逆転");
-                // 全プレイヤーの手札をソート
-                players
-                    .iter_mut()
-                    .for_each(|player| player.get_hands().sort_by(field.get_order_comparator()));
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn handle_interrupt() {}
+
+// ローカルルールのオンとオフをメニューで選ぶ
+fn select_rules(mut rules: RuleSet) -> RuleSet {
+    let mut toggles = rules.get_toggles();
+    input::get_checklist(tr("local_rules"), &TOGGLE_KEYS.map(tr), &mut toggles);
+    rules.set_toggles(toggles);
+    rules
+}
+
+// 効果のないルールを警告し、遊べない組み合わせなら終了する
+fn check_rules(rules: &RuleSet) {
+    match rules.validate() {
+        Ok(warnings) => {
+            for key in warnings {
+                eprintln!("{}", tr(key));
             }
-            if flags.contains(Flags::OUT) {
-                println!("{} 上がり", players[idx].get_name());
+        }
+        Err(key) => {
+            eprintln!("{}", tr(key));
+            process::exit(1);
+        }
+    }
+}
+
+fn load_game(path: &Path, style: Style, options: &Options) -> Game {
+    let state = match save::read_save(path) {
+        Ok(state) => state,
+        Err(mes) => {
+            eprintln!("{}: {mes}", path.display());
+            process::exit(1);
+        }
+    };
+    // 保存したときと同じ人数の人間のプレイヤーで再開する
+    let hotseat = state
+        .players
+        .iter()
+        .filter(|p| p.strategy.is_none())
+        .count()
+        > 1;
+    Game::from_state(state, |name| create_pc(name, style, hotseat, options))
+}
+
+// 新しいゲームで使うプレイヤーの名前
+struct PlayerNames {
+    users: Vec<String>,
+    npc_names: Vec<String>,
+    random: bool,
+}
+
+impl PlayerNames {
+    // 設定ファイルの名前よりオプションで指定した名前を優先する
+    fn new(options: &Options) -> Self {
+        let (user, npc_names, random) = load_config_names();
+        Self {
+            users: match (options.names.is_empty(), user) {
+                (false, _) => options.names.clone(),
+                (true, Some(user)) => vec![user],
+                (true, None) => vec!["User".to_owned()],
+            },
+            npc_names: match options.npc_names.is_empty() {
+                true => npc_names,
+                false => options.npc_names.clone(),
+            },
+            random: options.random_names || random,
+        }
+    }
+}
+
+// 設定ファイルに保存した名前(人間のプレイヤー、NPC、ランダムに選ぶか)
+#[cfg(feature = "serde")]
+fn load_config_names() -> (Option<String>, Vec<String>, bool) {
+    let config = config::get_config_path()
+        .and_then(|path| config::load_config(&path).ok())
+        .unwrap_or_default();
+    (
+        config.name,
+        config.npc_names.unwrap_or_default(),
+        config.random_names.unwrap_or(false),
+    )
+}
+
+#[cfg(not(feature = "serde"))]
+fn load_config_names() -> (Option<String>, Vec<String>, bool) {
+    (None, vec![], false)
+}
+
+#[cfg(feature = "serde")]
+fn load_config_lang() -> Option<Lang> {
+    let path = config::get_config_path()?;
+    config::load_config(&path).ok()?.lang
+}
+
+#[cfg(not(feature = "serde"))]
+fn load_config_lang() -> Option<Lang> {
+    None
+}
+
+fn main() {
+    init_tracing();
+    if let Some(lang) = load_config_lang() {
+        i18n::set_lang(lang);
+    }
+    let (options, save_path) = match Command::parse(std::env::args().skip(1)) {
+        Ok(Command::Play(options)) => (options, None),
+        Ok(Command::Resume(path, options)) => (options, Some(path)),
+        Ok(Command::Simulate(options)) => {
+            run_simulate(options);
+            return;
+        }
+        Ok(Command::Arena(options)) => {
+            run_arena(options);
+            return;
+        }
+        Ok(Command::Tournament(options)) => {
+            run_tournament(options);
+            return;
+        }
+        Ok(Command::Tune(options)) => {
+            run_tune(options);
+            return;
+        }
+        Ok(Command::Bench(options)) => {
+            run_bench(options);
+            return;
+        }
+        Ok(Command::Serve(options)) => {
+            run_serve(options);
+            return;
+        }
+        Ok(Command::Join(options)) => {
+            run_join(options);
+            return;
+        }
+        Ok(Command::Http(options)) => {
+            run_http(options);
+            return;
+        }
+        Ok(Command::Rules(options)) => {
+            run_rules(options);
+            return;
+        }
+        Ok(Command::Stats) => {
+            run_stats();
+            return;
+        }
+        Ok(Command::Tutorial(options)) => {
+            tutorial::run_tutorial(Style::new(options.color, options.ascii));
+            return;
+        }
+        Ok(Command::Verify(path)) => {
+            run_verify(&path);
+            return;
+        }
+        Ok(Command::WhatIf(options)) => {
+            if let Err(mes) = whatif::run_whatif(&options) {
+                eprintln!("{mes}");
+                process::exit(1);
             }
-            if flags.contains(Flags::LOSE) {
-                println!("{} 反則上がり", players[idx].get_name());
+            return;
+        }
+        Ok(Command::Replay(options)) => {
+            let style = Style::new(options.color, options.ascii);
+            if let Err(mes) = replay::run_replay(&options.path, style) {
+                eprintln!("{mes}");
+                process::exit(1);
             }
-            thread::sleep(duration);
+            return;
+        }
+        // --helpなら使い方を表示して終わる
+        Err(e) => e.exit(),
+    };
+    if let Some(lang) = options.lang {
+        i18n::set_lang(lang);
+    }
+    let style = Style::new(options.color, options.ascii);
+    if options.tui {
+        if options.humans != 1 {
+            eprintln!("{}", tr("tui_humans"));
+            process::exit(1);
+        }
+        if options.kifu.is_some() || options.log_file.is_some() || options.analyze || options.debug
+        {
+            eprintln!("{}", tr("tui_outputs"));
+            process::exit(1);
+        }
+        if save_path.is_some() {
+            eprintln!("{}", tr("tui_resume"));
+            process::exit(1);
+        }
+        run_tui(style, &options);
+        return;
+    }
+    // 再開したゲームはシードから配られたカードを求められない
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let kifu_seed = save_path.is_none().then_some(seed);
+    let mut game = match save_path {
+        Some(path) => load_game(&path, style, &options),
+        None => {
+            let names = PlayerNames::new(&options);
+            let users = create_users(
+                names::get_user_names(&names.users, options.humans),
+                style,
+                &options,
+            );
+            let players = create_players(
+                users,
+                &names,
+                options.policy.as_deref(),
+                options.shuffle_seats,
+            );
+            let rules = match options.select_rules {
+                true => select_rules(options.rules),
+                false => options.rules,
+            };
+            check_rules(&rules);
+            Game::new(players, 0, seed).with_rules(rules)
         }
-        println!("結果発表");
-        let player_rank = field.get_player_rank();
-        for (i, idx) in player_rank.iter().enumerate() {
-            println!("{}位: {}", i + 1, players[*idx].get_name());
+    }
+    .with_explain(options.explain_ai)
+    .with_debug(options.debug)
+    .with_substitutes({
+        let policy = options.policy.clone();
+        move |name| create_npcs(vec![name], policy.as_deref()).remove(0)
+    });
+    let thinking = render::get_thinking_times(
+        game.get_names().len(),
+        &game.get_human_seats(),
+        &options.thinking,
+    );
+    let mut renderer = Renderer::new(style, options.speed, options.quiet)
+        .with_open_hands(options.open_hands)
+        .with_thinking(thinking);
+    let mut kifu = match options.kifu.as_ref().map(File::create).transpose() {
+        Ok(file) => file.map(|file| {
+            KifuWriter::new(file)
+                .with_seed(kifu_seed)
+                .with_rules(game.get_rules())
+        }),
+        Err(e) => {
+            eprintln!("{}", trf("kifu_failed", &[("error", &e)]));
+            process::exit(1);
+        }
+    };
+    let mut observers: Vec<&mut dyn Observer> = vec![&mut renderer];
+    if let Some(kifu) = kifu.as_mut() {
+        observers.push(kifu);
+    }
+    let mut event_log = options.log_file.as_deref().map(create_event_log);
+    if let Some(event_log) = event_log.as_mut() {
+        observers.push(event_log.as_mut());
+    }
+    let mut stats_recorder = create_stats_recorder();
+    if let Some(stats_recorder) = stats_recorder.as_mut() {
+        observers.push(stats_recorder.as_mut());
+    }
+    let mut analyzer = options
+        .analyze
+        .then(|| Analyzer::new(game.get_human_seats(), style).with_rules(game.get_rules()));
+    if let Some(analyzer) = analyzer.as_mut() {
+        observers.push(analyzer);
+    }
+    let mut observers = Observers(observers);
+    handle_interrupt();
+    for round in 1.. {
+        let mut player_rank = game.play_round(&mut observers);
+        // 他に人間のプレイヤーが残っていれば、中断したプレイヤーの席をNPCが引き継いで続ける
+        while game.is_quit() && game.get_human_seats().len() > 1 {
+            let name = game.get_names()[game.get_field().get_idx()].clone();
+            let npc = create_npcs(vec![name], options.policy.as_deref()).remove(0);
+            game.substitute(npc, &mut observers);
+            player_rank = game.continue_round(&mut observers);
+        }
+        if game.is_quit() {
+            return;
         }
-        if get_input("もう一度遊びますか? (y/n): ".to_string()) != "y" {
+        // ラウンド数を指定すれば確認せずに続ける
+        let again = match options.rounds {
+            Some(rounds) => round < rounds,
+            None => get_input(tr("play_again").to_owned()) == "y",
+        };
+        if !again {
             break;
         }
-        // 新しいカードを配る
-        get_split_deck()
-            .into_iter()
-            .zip(players.iter_mut())
-            .for_each(|(hands, player)| player.init(hands));
-        // カードを交換
-        exchange_cards(&mut players, player_rank[0], player_rank[3], 2);
-        exchange_cards(&mut players, player_rank[1], player_rank[2], 1);
-        println!("強いカードと不要なカードを交換");
-        // フィールドをリセット、大貧民のプレイヤーから開始
-        field = Field::new(PLAYERS_COUNT, player_rank[3]);
+        game.next_round(&player_rank, &mut observers);
+    }
+    if options.rounds.is_some() {
+        for line in renderer.get_record_lines() {
+            println!("{line}");
+        }
     }
 }
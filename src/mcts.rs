@@ -0,0 +1,365 @@
+use crate::card::{self, Card, Order};
+use crate::comb::Comb;
+use crate::endgame::Planner;
+use crate::event::GameEvent;
+use crate::field::Field;
+use crate::moves::{get_legal_combs, remove_cards};
+use crate::npc::MinNpc;
+use crate::opponent::{deal_unseen, OpponentModels};
+use crate::player::{take_from_hands, Action, Player};
+use crate::strategy::Strategy;
+use crate::validator::Validator;
+use crate::view::GameView;
+use crate::zobrist::{hash_move, hash_state, Entry, TranspositionTable};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// 1手あたりのプレイアウトの回数
+const DEFAULT_PLAYOUTS: usize = 200;
+// UCB1の探索の強さ
+const EXPLORATION: f64 = 1.4;
+// 置換表のエントリ数
+pub const DEFAULT_TABLE_SIZE: usize = 1 << 12;
+
+pub struct MctsNpc {
+    name: String,
+    hands: Vec<Card>,
+    playouts: usize,
+    rng: StdRng,
+    // 候補の手を打った後の状態毎のプレイアウトの結果
+    table: TranspositionTable,
+    // ラウンドをまたいで覚える他のプレイヤーの傾向
+    models: OpponentModels,
+    // 終盤に見つけた上がり方
+    planner: Planner,
+}
+
+impl MctsNpc {
+    pub fn new(name: String, seed: u64) -> Self {
+        Self {
+            name,
+            hands: vec![],
+            playouts: DEFAULT_PLAYOUTS,
+            rng: StdRng::seed_from_u64(seed),
+            table: TranspositionTable::new(DEFAULT_TABLE_SIZE),
+            models: OpponentModels::default(),
+            planner: Planner::default(),
+        }
+    }
+
+    // 置換表のエントリ数を変える(0なら置換表を使わない)
+    pub fn with_table_size(mut self, size: usize) -> Self {
+        self.table = TranspositionTable::new(size);
+        self
+    }
+
+    // 見えていないカードを他のプレイヤーの傾向に合わせて配り直し、最後まで対戦する
+    fn playout(&mut self, view: &GameView, candidate: &Option<(Comb, Vec<usize>)>) -> f64 {
+        let me = view.idx;
+        let mut field = Field::from_view(view);
+        let mut prev_order = field.get_order();
+        let comparator = prev_order.get_comparator();
+        let unseen: Vec<Card> = card::create_deck()
+            .into_iter()
+            .filter(|card| {
+                !self.hands.contains(card)
+                    && !view.prev_comb.as_ref().is_some_and(|comb| match comb {
+                        Comb::Single(c) => c == card,
+                        Comb::Multi(cards) | Comb::Seq(cards) => cards.contains(card),
+                    })
+            })
+            .collect();
+        let counts: Vec<usize> = view
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, (_, count))| if i == me { 0 } else { *count })
+            .collect();
+        let weights = self.models.get_weights(view);
+        let mut players: Vec<MinNpc> = deal_unseen(unseen, &counts, &weights, &mut self.rng)
+            .into_iter()
+            .enumerate()
+            .map(|(i, hands)| {
+                let mut hands = match i == me {
+                    true => self.hands.clone(),
+                    false => hands,
+                };
+                hands.sort_by(comparator);
+                let mut player = MinNpc::new(String::new());
+                player.init(hands);
+                player
+            })
+            .collect();
+        // 候補の手を打つ
+        let comb = candidate.as_ref().map(|(comb, indices)| {
+            let mut hands_left = players[me].get_hands().to_vec();
+            for i in indices.iter().rev() {
+                hands_left.remove(*i);
+            }
+            hands_left.sort_by(comparator);
+            players[me].init(hands_left);
+            comb.clone()
+        });
+        let hands_count = players[me].count_hands();
+        if field.put(comb.as_ref(), hands_count).is_err() {
+            return 0.0;
+        }
+        while field.count_active_players() > 0 {
+            let order = field.get_order();
+            if order != prev_order {
+                players
+                    .iter_mut()
+                    .for_each(|player| player.sort_hands(order));
+                prev_order = order;
+            }
+            let idx = field.get_idx();
+            let comb = players[idx].select(&field);
+            if field
+                .put(comb.as_ref(), players[idx].count_hands())
+                .is_err()
+            {
+                break;
+            }
+        }
+        // 順位が高いほど報酬が大きい
+        let rank = field
+            .get_player_rank()
+            .iter()
+            .position(|p| *p == me)
+            .unwrap_or(view.players.len() - 1);
+        1.0 - rank as f64 / (view.players.len() - 1) as f64
+    }
+
+    // 候補の手をそれぞれ同じ回数プレイアウトして平均の報酬を求める
+    pub fn evaluate(
+        &mut self,
+        view: &GameView,
+        candidates: &[Option<(Comb, Vec<usize>)>],
+        playouts: usize,
+    ) -> Vec<f64> {
+        candidates
+            .iter()
+            .map(|candidate| {
+                let total: f64 = (0..playouts).map(|_| self.playout(view, candidate)).sum();
+                total / playouts.max(1) as f64
+            })
+            .collect()
+    }
+
+    // 場に出す手を選ぶ(Noneはパス)
+    fn select(&mut self, validator: &dyn Validator) -> Option<Comb> {
+        // 誰にも返されずに上がれる順番が見つかればプレイアウトせずに従う
+        if let Some(comb) = self.planner.next(&self.hands, validator) {
+            if let Some(hands) = remove_cards(&self.hands, &comb) {
+                self.hands = hands;
+                return Some(comb);
+            }
+        }
+        let mut view = validator.get_view();
+        // 手札の枚数をプレイヤー自身の手札に合わせる
+        view.players[view.idx].1 = self.hands.len();
+        let mut candidates = get_candidates(&self.hands, validator);
+        // 全てのカードを出し切れるならそれを選ぶ
+        let finishing = candidates.iter().position(|candidate| {
+            candidate
+                .as_ref()
+                .is_some_and(|(_, indices)| indices.len() == self.hands.len())
+        });
+        let best = match (finishing, candidates.len()) {
+            (Some(i), _) => i,
+            (None, 0) => return None,
+            (None, 1) => 0,
+            (None, _) => {
+                // 同じ状態になる手を以前に評価していればその結果から始める
+                let hash = hash_state(&view, &self.hands);
+                let hashes: Vec<u64> = candidates
+                    .iter()
+                    .map(|candidate| {
+                        let comb = candidate.as_ref().map(|(comb, _)| comb);
+                        hash_move(hash, &view, &self.hands, comb)
+                    })
+                    .collect();
+                let entries: Vec<Entry> = hashes
+                    .iter()
+                    .map(|hash| self.table.get(*hash).unwrap_or_default())
+                    .collect();
+                let mut visits: Vec<usize> = entries.iter().map(|e| e.visits).collect();
+                let mut rewards: Vec<f64> = entries.iter().map(|e| e.rewards).collect();
+                let offset = visits.iter().sum::<usize>();
+                // UCB1で候補の手を選んでプレイアウトを繰り返す
+                for n in offset + 1..=offset + self.playouts {
+                    let i = (0..candidates.len())
+                        .max_by(|a, b| {
+                            let ucb = |i: usize| match visits[i] {
+                                0 => f64::INFINITY,
+                                v => {
+                                    rewards[i] / v as f64
+                                        + EXPLORATION * ((n as f64).ln() / v as f64).sqrt()
+                                }
+                            };
+                            ucb(*a).total_cmp(&ucb(*b))
+                        })
+                        .unwrap_or(0);
+                    let reward = self.playout(&view, &candidates[i]);
+                    visits[i] += 1;
+                    rewards[i] += reward;
+                }
+                for (i, hash) in hashes.iter().enumerate() {
+                    let entry = Entry {
+                        visits: visits[i],
+                        rewards: rewards[i],
+                    };
+                    self.table.insert(*hash, entry);
+                }
+                (0..candidates.len())
+                    .max_by_key(|i| visits[*i])
+                    .unwrap_or(0)
+            }
+        };
+        let (comb, indices) = candidates.swap_remove(best)?;
+        for i in indices.iter().rev() {
+            self.hands.remove(*i);
+        }
+        Some(comb)
+    }
+}
+
+// 場に出せる手と、場にカードがあればパスを候補にする
+pub fn get_candidates(
+    hands: &[Card],
+    validator: &dyn Validator,
+) -> Vec<Option<(Comb, Vec<usize>)>> {
+    let mut candidates: Vec<Option<(Comb, Vec<usize>)>> = get_legal_combs(hands, validator)
+        .into_iter()
+        .map(Some)
+        .collect();
+    if validator.get_prev_comb().is_some() {
+        candidates.push(None);
+    }
+    candidates
+}
+
+impl Player for MctsNpc {
+    fn init(&mut self, hands: Vec<Card>) {
+        self.hands = hands;
+        self.planner.clear();
+    }
+
+    fn count_hands(&self) -> usize {
+        self.hands.len()
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_hands(&self) -> &[Card] {
+        &self.hands
+    }
+
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+        take_from_hands(&mut self.hands, cards)
+    }
+
+    fn receive_cards(&mut self, cards: Vec<Card>) {
+        self.hands.extend(cards);
+    }
+
+    fn sort_hands(&mut self, order: Order) {
+        self.hands.sort_by(order.get_comparator());
+    }
+
+    fn get_strategy(&self) -> Option<Strategy> {
+        Some(Strategy::Mcts)
+    }
+
+    fn observe(&mut self, event: &GameEvent) {
+        self.models.observe(event);
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.table = TranspositionTable::new(self.table.get_size());
+    }
+
+    fn play(&mut self, validator: &dyn Validator) -> Action {
+        Action::Move(self.select(validator))
+    }
+
+    fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
+        (0..cards_count).map(|_| self.hands.remove(0)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+
+    #[test]
+    fn test_mcts_npc_play() {
+        let mut field = Field::new(4, 0);
+        field.set_players(vec![String::new(); 4], vec![3, 5, 5, 5]);
+        // 出し切れるなら出し切る
+        let mut npc = MctsNpc::new(String::new(), 0);
+        npc.init(vec![
+            Card::Normal(Suit::Club, Rank::Four),
+            Card::Normal(Suit::Club, Rank::Five),
+            Card::Normal(Suit::Club, Rank::Six),
+        ]);
+        assert!(matches!(npc.select(&field), Some(Comb::Seq(_))));
+        assert_eq!(npc.count_hands(), 0);
+        // 同じシードなら同じ手を選ぶ
+        let hands = vec![
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Heart, Rank::Seven),
+            Card::Normal(Suit::Spade, Rank::Seven),
+            Card::Normal(Suit::Diamond, Rank::Ace),
+        ];
+        let combs: Vec<Option<Comb>> = (0..2)
+            .map(|_| {
+                let mut npc = MctsNpc::new(String::new(), 42);
+                npc.init(hands.clone());
+                npc.select(&field)
+            })
+            .collect();
+        assert!(combs[0].is_some());
+        assert_eq!(combs[0], combs[1]);
+    }
+
+    #[test]
+    fn test_mcts_npc_observe() {
+        let mut npc = MctsNpc::new("M".to_owned(), 0);
+        let names = vec!["M".to_owned(), "A".to_owned()];
+        npc.observe(&GameEvent::Start {
+            names: names.clone(),
+            hands: vec![vec![], vec![]],
+        });
+        npc.observe(&GameEvent::Turn {
+            idx: 1,
+            comb: Some(Comb::Single(Card::Joker)),
+            hands_count: 2,
+            outcome: Default::default(),
+        });
+        let model = npc.models.get_model("A").cloned();
+        assert!(model.is_some());
+        // 手札を配り直しても傾向は覚えている
+        npc.init(vec![Card::Normal(Suit::Club, Rank::Three)]);
+        npc.observe(&GameEvent::Start {
+            names,
+            hands: vec![vec![], vec![]],
+        });
+        assert_eq!(npc.models.get_model("A").cloned(), model);
+        let view = GameView {
+            players: vec![
+                ("M".to_owned(), 1),
+                ("A".to_owned(), 2),
+                ("B".to_owned(), 3),
+            ],
+            ..Default::default()
+        };
+        let weights = npc.models.get_weights(&view);
+        assert!(weights[1] > 1.0);
+        assert_eq!(weights[2], 1.0);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::bitcards::HandMask;
+use crate::card::{cards_to_ascii, Card, RANKS, SUITS};
+use crate::comb::{Comb, MIN_MULTI, MIN_SEQ};
+use crate::field::PlayError;
+use crate::i18n::tr;
+use crate::validator::Validator;
+use itertools::Itertools;
+
+// 場に出せる全ての組み合わせと手札のインデックスを取得する
+pub fn get_legal_combs(cards: &[Card], validator: &dyn Validator) -> Vec<(Comb, Vec<usize>)> {
+    get_all_combs(cards)
+        .into_iter()
+        .filter(|(comb, _)| validator.is_valid(comb))
+        .collect()
+}
+
+// 手札から作れる全ての組み合わせと手札のインデックスを取得する
+pub fn get_all_combs(cards: &[Card]) -> Vec<(Comb, Vec<usize>)> {
+    let mask = HandMask::from(cards);
+    // スートと数字から手札のインデックスを引く表
+    let mut positions = [[0; 13]; 4];
+    let mut jokers = vec![];
+    for (i, card) in cards.iter().enumerate() {
+        match card {
+            Card::Normal(suit, rank) => positions[*suit as usize][i32::from(rank) as usize] = i,
+            Card::Joker => jokers.push(i),
+        }
+    }
+    // 1枚
+    let mut combs: Vec<(Comb, Vec<usize>)> = (0..cards.len())
+        .map(|i| (Comb::Single(cards[i]), vec![i]))
+        .collect();
+    // 同じ数字の複数枚(ジョーカーを含む)
+    for rank in RANKS {
+        let suits = mask.get_rank_suits(&rank);
+        if suits == 0 || suits.count_ones() as usize + jokers.len() < MIN_MULTI {
+            continue;
+        }
+        let mut pool: Vec<usize> = (0..SUITS.len())
+            .filter(|s| suits & (1 << s) != 0)
+            .map(|s| positions[s][i32::from(&rank) as usize])
+            .collect();
+        pool.sort();
+        pool.extend(jokers.iter().copied());
+        for len in MIN_MULTI..=pool.len() {
+            for mut indices in pool.iter().copied().combinations(len) {
+                indices.sort();
+                let comb = Comb::Multi(indices.iter().map(|i| cards[*i]).collect());
+                combs.push((comb, indices));
+            }
+        }
+    }
+    // 同じスートの階段(ジョーカーは足りない数字を1枚補う)
+    for suit in SUITS {
+        let ranks = mask.get_suit_ranks(&suit);
+        if ranks == 0 || ranks.count_ones() as usize + jokers.len() < MIN_SEQ {
+            continue;
+        }
+        for low in 0..RANKS.len() {
+            for len in MIN_SEQ..=RANKS.len() - low {
+                let window = ((1u16 << len) - 1) << low;
+                let missing = len - (ranks & window).count_ones() as usize;
+                if missing > jokers.len() {
+                    break;
+                }
+                if missing == len {
+                    continue;
+                }
+                let mut joker_iter = jokers.iter();
+                let seq: Vec<usize> = (low..low + len)
+                    .filter_map(|r| match ranks & (1 << r) != 0 {
+                        true => Some(positions[suit as usize][r]),
+                        false => joker_iter.next().copied(),
+                    })
+                    .collect();
+                let Ok(comb) = Comb::try_from(seq.iter().map(|i| cards[*i]).collect_vec()) else {
+                    continue;
+                };
+                combs.push((comb, seq.into_iter().sorted().collect()));
+            }
+        }
+    }
+    combs
+}
+
+// 受け付けられる手なら出した後の手札を、受け付けられなければ理由を返す
+pub fn check_move(
+    validator: &dyn Validator,
+    hands: &[Card],
+    comb: Option<&Comb>,
+) -> Result<Vec<Card>, &'static str> {
+    let result = match comb {
+        Some(comb) if !validator.is_valid(comb) => Err(tr("unplayable")),
+        Some(comb) => remove_cards(hands, comb).ok_or(tr("cards_not_in_hand")),
+        None if !validator.can_pass() => Err(tr(PlayError::LeadPass.get_key())),
+        None => Ok(hands.to_vec()),
+    };
+    if let Err(reason) = result {
+        tracing::debug!(
+            comb = %comb.map_or("-".to_owned(), |comb| cards_to_ascii(comb.get_cards())),
+            reason,
+            "手を受け付けない"
+        );
+    }
+    result
+}
+
+// 組み合わせのカードを全て取り除いた手札を取得する
+pub fn remove_cards(hands: &[Card], comb: &Comb) -> Option<Vec<Card>> {
+    let cards = match comb {
+        Comb::Single(card) => vec![*card],
+        Comb::Multi(cards) | Comb::Seq(cards) => cards.clone(),
+    };
+    let mut hands_left = hands.to_vec();
+    for card in cards {
+        let position = hands_left.iter().position(|c| *c == card)?;
+        hands_left.remove(position);
+    }
+    Some(hands_left)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+
+    #[test]
+    fn test_get_all_combs() {
+        let cards = vec![
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Club, Rank::Four),
+            Card::Normal(Suit::Heart, Rank::Four),
+            Card::Normal(Suit::Club, Rank::Six),
+            Card::Joker,
+        ];
+        for (comb, expected) in [
+            (Comb::Single(Card::Joker), Some(vec![4])),
+            (
+                Comb::Multi(vec![
+                    Card::Normal(Suit::Club, Rank::Four),
+                    Card::Normal(Suit::Heart, Rank::Four),
+                    Card::Joker,
+                ]),
+                Some(vec![1, 2, 4]),
+            ),
+            (
+                Comb::Multi(vec![Card::Normal(Suit::Club, Rank::Three), Card::Joker]),
+                Some(vec![0, 4]),
+            ),
+            (
+                Comb::Seq(vec![
+                    Card::Normal(Suit::Club, Rank::Three),
+                    Card::Normal(Suit::Club, Rank::Four),
+                    Card::Joker,
+                    Card::Normal(Suit::Club, Rank::Six),
+                ]),
+                Some(vec![0, 1, 3, 4]),
+            ),
+            (
+                Comb::Seq(vec![
+                    Card::Normal(Suit::Club, Rank::Three),
+                    Card::Normal(Suit::Club, Rank::Four),
+                    Card::Joker,
+                ]),
+                Some(vec![0, 1, 4]),
+            ),
+            (
+                Comb::Seq(vec![
+                    Card::Normal(Suit::Heart, Rank::Four),
+                    Card::Joker,
+                    Card::Normal(Suit::Heart, Rank::Six),
+                ]),
+                None,
+            ),
+        ] {
+            let indices = get_all_combs(&cards)
+                .into_iter()
+                .find(|(c, _)| c == &comb)
+                .map(|(_, indices)| indices);
+            assert_eq!(indices, expected);
+        }
+    }
+
+    #[test]
+    fn test_remove_cards() {
+        let hands = vec![
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Heart, Rank::Three),
+            Card::Joker,
+        ];
+        for (comb, expected) in [
+            (Comb::Single(Card::Joker), Some(hands[..2].to_vec())),
+            (
+                Comb::Multi(vec![hands[0], hands[1]]),
+                Some(vec![Card::Joker]),
+            ),
+            (Comb::Single(Card::Normal(Suit::Spade, Rank::Three)), None),
+            (Comb::Multi(vec![Card::Joker, Card::Joker]), None),
+        ] {
+            assert_eq!(remove_cards(&hands, &comb), expected);
+        }
+    }
+}
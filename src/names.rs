@@ -0,0 +1,139 @@
+use crate::NPC_NAMES;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+// ランダムにNPCの名前を選ぶときの候補
+pub const NAME_POOL: [&str; 16] = [
+    "Taro", "Hanako", "Jiro", "Sakura", "Kenta", "Yuki", "Haruto", "Mei", "Sota", "Aoi", "Ren",
+    "Hina", "Daichi", "Rin", "Kaito", "Nana",
+];
+
+// 空いた席のNPCの名前をcount人分選ぶ
+// 指定した名前を先に使い、足りない分はrandomならNAME_POOLからランダムに、そうでなければNPC_NAMESから補う
+// 人間のプレイヤーの名前や、既に選んだ名前とは重複しない
+pub fn get_npc_names<R: Rng>(
+    count: usize,
+    preferred: &[String],
+    taken: &[String],
+    random: bool,
+    rng: &mut R,
+) -> Vec<String> {
+    let mut pool: Vec<&str> = match random {
+        true => NAME_POOL.to_vec(),
+        false => NPC_NAMES.to_vec(),
+    };
+    if random {
+        pool.shuffle(rng);
+    }
+    // 候補が全て使われていてもNpcD、NpcE、…、Npc1、Npc2、…の名前で埋める
+    let candidates = preferred
+        .iter()
+        .cloned()
+        .chain(pool.into_iter().map(String::from))
+        .chain((b'D'..=b'Z').map(|c| format!("Npc{}", c as char)))
+        .chain((1..).map(|i| format!("Npc{i}")));
+    let mut names: Vec<String> = vec![];
+    for name in candidates {
+        if names.len() == count {
+            break;
+        }
+        if !taken.contains(&name) && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+// humans人の人間のプレイヤーの名前
+// 1つの名前で複数人なら番号を付け、複数の名前を指定して足りなければUser1、User2、…で補う
+pub fn get_user_names(names: &[String], humans: usize) -> Vec<String> {
+    if let [name] = names {
+        if humans > 1 {
+            return (1..=humans).map(|i| format!("{name}{i}")).collect();
+        }
+    }
+    let candidates = names
+        .iter()
+        .cloned()
+        .chain((1..).map(|i| format!("User{i}")));
+    let mut users: Vec<String> = vec![];
+    for name in candidates {
+        if users.len() == humans {
+            break;
+        }
+        if !users.contains(&name) {
+            users.push(name);
+        }
+    }
+    users
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_get_npc_names() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let s = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        for (count, preferred, taken, expected) in [
+            (3, vec![], vec!["User"], vec!["NpcA", "NpcB", "NpcC"]),
+            (2, vec![], vec!["User"], vec!["NpcA", "NpcB"]),
+            (
+                3,
+                vec!["Alice"],
+                vec!["User"],
+                vec!["Alice", "NpcA", "NpcB"],
+            ),
+            (
+                3,
+                vec!["Alice", "Alice"],
+                vec!["User"],
+                vec!["Alice", "NpcA", "NpcB"],
+            ),
+            (
+                3,
+                vec!["User", "Bob"],
+                vec!["User"],
+                vec!["Bob", "NpcA", "NpcB"],
+            ),
+            (3, vec![], vec!["NpcA"], vec!["NpcB", "NpcC", "NpcD"]),
+            (4, vec![], vec![], vec!["NpcA", "NpcB", "NpcC", "NpcD"]),
+        ] {
+            assert_eq!(
+                get_npc_names(count, &s(&preferred), &s(&taken), false, &mut rng),
+                s(&expected)
+            );
+        }
+        // 候補から重複せずに選ぶ
+        let names = get_npc_names(3, &[], &s(&["Taro"]), true, &mut rng);
+        assert!((0..3).all(|i| !names[..i].contains(&names[i])));
+        assert!(names
+            .iter()
+            .all(|name| name != "Taro" && NAME_POOL.contains(&name.as_str())));
+        // 候補を使い切ると番号を付ける
+        let taken: Vec<String> = (b'A'..=b'Z').map(|c| format!("Npc{}", c as char)).collect();
+        assert_eq!(
+            get_npc_names(2, &[], &taken, false, &mut rng),
+            s(&["Npc1", "Npc2"])
+        );
+    }
+
+    #[test]
+    fn test_get_user_names() {
+        let s = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        for (names, humans, expected) in [
+            (vec!["Alice"], 1, vec!["Alice"]),
+            (vec!["User"], 2, vec!["User1", "User2"]),
+            (vec!["Alice", "Bob"], 2, vec!["Alice", "Bob"]),
+            (vec!["Alice", "Bob"], 1, vec!["Alice"]),
+            (vec!["Alice", "Bob"], 3, vec!["Alice", "Bob", "User1"]),
+            (vec!["Alice", "Alice"], 2, vec!["Alice", "User1"]),
+            (vec!["Alice"], 0, vec![]),
+        ] {
+            assert_eq!(get_user_names(&s(&names), humans), s(&expected));
+        }
+    }
+}
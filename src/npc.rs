@@ -1,8 +1,11 @@
-use crate::card::Card;
+use crate::bitcards::HandMask;
+use crate::card::{cmp_rank, Card, Order, Rank, Suit, SUITS};
 use crate::comb::{Comb, MIN_MULTI, MIN_SEQ};
-use crate::player::Player;
+use crate::hand::improves_by_revolution;
+use crate::player::{take_from_hands, Action, Player};
+use crate::strategy::Strategy;
+use crate::tables::{get_suit_sets, has_run};
 use crate::validator::Validator;
-use itertools::Itertools;
 
 pub struct MinNpc {
     name: String,
@@ -23,26 +26,23 @@ impl MinNpc {
             self.hands.remove(*i);
         }
     }
-}
-
-impl Player for MinNpc {
-    fn init(&mut self, hands: Vec<Card>) {
-        self.hands = hands;
-    }
-
-    fn count_hands(&self) -> usize {
-        self.hands.len()
-    }
-
-    fn get_name(&self) -> &str {
-        &self.name
-    }
 
-    fn get_hands(&mut self) -> &mut Vec<Card> {
-        &mut self.hands
+    // 革命になる組み合わせは、革命で残りの手札が強くなるときだけ出す
+    fn is_timely(&self, comb: &Comb, indices: &[usize], validator: &dyn Validator) -> bool {
+        if !matches!(comb, Comb::Multi(cards) if cards.len() >= 4)
+            || !validator.get_rules().revolution
+        {
+            return true;
+        }
+        let rest: Vec<Card> = (0..self.hands.len())
+            .filter(|i| !indices.contains(i))
+            .map(|i| self.hands[i])
+            .collect();
+        improves_by_revolution(&rest, validator.get_view().is_rev)
     }
 
-    fn play(&mut self, validator: &dyn Validator) -> Option<Comb> {
+    // 場に出す手を選ぶ(Noneはパス)
+    pub fn select(&mut self, validator: &dyn Validator) -> Option<Comb> {
         match validator.get_prev_comb() {
             Some(comb) => match comb {
                 Comb::Single(_) => {
@@ -57,118 +57,176 @@ impl Player for MinNpc {
                 }
                 Comb::Multi(cards) => {
                     let len = cards.len();
-                    get_indices_grouped_by_rank(&self.hands, len)
-                        .into_iter()
-                        .find_map(|indices| {
-                            // 場に出せる最小のカードの組み合わせを探す
-                            let cards = get_cards(&self.hands, &indices[0..len]);
-                            let new_comb = Comb::try_from(cards).ok()?;
-                            validator.is_valid(&new_comb).then(|| {
-                                self.remove_hands(&indices[0..len]);
-                                new_comb
-                            })
-                        })
+                    let mask = HandMask::from(&self.hands[..]);
+                    // 場に出せる最小のカードの組み合わせを探す
+                    let (new_comb, indices) = get_ranks(&self.hands).find_map(|rank| {
+                        let suits = get_suit_sets(mask.get_rank_suits(&rank), len).next()?;
+                        let (new_comb, indices) = get_multi(&self.hands, rank, suits);
+                        validator.is_valid(&new_comb).then_some((new_comb, indices))
+                    })?;
+                    self.remove_hands(&indices);
+                    Some(new_comb)
                 }
                 Comb::Seq(cards) => {
                     let len = cards.len();
-                    get_indices_grouped_by_suit(&self.hands, len)
-                        .into_iter()
-                        .find_map(|indices| {
-                            // 場に出せる最小のカードの組み合わせを探す
-                            let (new_comb, indices) = find_seq(&self.hands, &indices, len)?;
-                            validator.is_valid(&new_comb).then(|| {
-                                self.remove_hands(&indices[0..len]);
-                                new_comb
-                            })
+                    let mask = HandMask::from(&self.hands[..]);
+                    SUITS.iter().find_map(|suit| {
+                        // 場に出せる最小のカードの組み合わせを探す
+                        let (new_comb, indices) = find_seq(&self.hands, &mask, suit, len)?;
+                        validator.is_valid(&new_comb).then(|| {
+                            self.remove_hands(&indices);
+                            new_comb
                         })
+                    })
                 }
             },
             None => {
-                // 複数のカードを出す
-                let new_comb: Option<Comb> = get_indices_grouped_by_rank(&self.hands, MIN_MULTI)
-                    .into_iter()
-                    .find_map(|indices| {
-                        let cards = get_cards(&self.hands, &indices);
-                        let comb = Comb::try_from(cards).ok()?;
-                        self.remove_hands(&indices);
-                        Some(comb)
-                    });
-                if new_comb.is_some() {
-                    return new_comb;
+                let mask = HandMask::from(&self.hands[..]);
+                // 複数のカードを出す(革命で手札が弱くなる数字は残しておく)
+                let mut kept = vec![];
+                let new_comb = get_ranks(&self.hands).find_map(|rank| {
+                    let suits = mask.get_rank_suits(&rank);
+                    if (suits.count_ones() as usize) < MIN_MULTI {
+                        return None;
+                    }
+                    let (comb, indices) = get_multi(&self.hands, rank, suits);
+                    if self.is_timely(&comb, &indices, validator) {
+                        return Some((comb, indices));
+                    }
+                    kept.push(rank);
+                    None
+                });
+                if let Some((comb, indices)) = new_comb {
+                    self.remove_hands(&indices);
+                    return Some(comb);
                 }
-                // 階段を出す
-                let new_comb: Option<Comb> = get_indices_grouped_by_suit(&self.hands, MIN_SEQ)
-                    .into_iter()
-                    .find_map(|indices| {
-                        // 階段となる組み合わせを探す(枚数の多い順に探す)
-                        let (comb, indices) = (MIN_SEQ..indices.len() + 1)
-                            .rev()
-                            .find_map(|len| find_seq(&self.hands, &indices, len))?;
-                        self.remove_hands(&indices);
-                        Some(comb)
-                    });
-                if new_comb.is_some() {
-                    return new_comb;
+                // 階段を出す(ルールで出せる枚数の中から多い順に探す)
+                let rules = validator.get_rules();
+                let new_comb = SUITS.iter().find_map(|suit| {
+                    let count = mask.get_suit_ranks(suit).count_ones() as usize;
+                    (MIN_SEQ..=count)
+                        .rev()
+                        .filter(|len| rules.is_seq_allowed(*len))
+                        .find_map(|len| find_seq(&self.hands, &mask, suit, len))
+                });
+                if let Some((comb, indices)) = new_comb {
+                    self.remove_hands(&indices);
+                    return Some(comb);
                 }
-                //1枚のカードを出す
-                (!self.hands.is_empty()).then(|| Comb::Single(self.hands.remove(0)))
+                //1枚のカードを出す(残しておく数字はできるだけ崩さない)
+                let i = (0..self.hands.len())
+                    .find(|i| !matches!(self.hands[*i], Card::Normal(_, r) if kept.contains(&r)))
+                    .unwrap_or(0);
+                (!self.hands.is_empty()).then(|| Comb::Single(self.hands.remove(i)))
             }
         }
     }
+}
+
+impl Player for MinNpc {
+    fn init(&mut self, hands: Vec<Card>) {
+        self.hands = hands;
+    }
+
+    fn count_hands(&self) -> usize {
+        self.hands.len()
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_hands(&self) -> &[Card] {
+        &self.hands
+    }
+
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+        take_from_hands(&mut self.hands, cards)
+    }
+
+    fn receive_cards(&mut self, cards: Vec<Card>) {
+        self.hands.extend(cards);
+    }
+
+    fn sort_hands(&mut self, order: Order) {
+        self.hands.sort_by(order.get_comparator());
+    }
+
+    fn get_strategy(&self) -> Option<Strategy> {
+        Some(Strategy::Min)
+    }
+
+    fn play(&mut self, validator: &dyn Validator) -> Action {
+        Action::Move(self.select(validator))
+    }
 
     fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
         (0..cards_count).map(|_| self.hands.remove(0)).collect()
     }
 }
 
-fn get_cards(cards: &[Card], indices: &[usize]) -> Vec<Card> {
-    indices.iter().map(|i| cards[*i]).collect()
+// 手札に出てくる順に数字を取得する
+fn get_ranks(cards: &[Card]) -> impl Iterator<Item = Rank> + '_ {
+    let mut seen = 0u16;
+    cards.iter().filter_map(move |card| match card {
+        Card::Normal(_, rank) if seen & (1 << i32::from(rank)) == 0 => {
+            seen |= 1 << i32::from(rank);
+            Some(*rank)
+        }
+        _ => None,
+    })
 }
 
-fn get_indices_grouped_by_rank(cards: &[Card], len: usize) -> Vec<Vec<usize>> {
-    // 数字毎にグループ分けしたインデックスのベクタを取得する
-    (0..cards.len())
-        .group_by(|i| match cards[*i] {
-            Card::Normal(_, r) => Some(r),
-            _ => None,
-        })
-        .into_iter()
-        .map(|(_, grp)| grp.collect::<Vec<usize>>())
-        .filter(|indices| indices.len() >= len)
-        .collect()
+// 数字がrankでスートがsuits(ビットの位置はSUITSの順)に含まれるカードを手札の順に取得する
+fn get_multi(cards: &[Card], rank: Rank, suits: u8) -> (Comb, Vec<usize>) {
+    let indices: Vec<usize> = (0..cards.len())
+        .filter(
+            |i| matches!(cards[*i], Card::Normal(s, r) if r == rank && suits & (1 << s as u8) != 0),
+        )
+        .collect();
+    let comb = Comb::Multi(indices.iter().map(|i| cards[*i]).collect());
+    (comb, indices)
 }
 
-fn get_indices_grouped_by_suit(cards: &[Card], len: usize) -> Vec<Vec<usize>> {
-    // スート毎にグループ分けしたインデックスのベクタを取得する
-    (0..cards.len())
-        .into_group_map_by(|i| match cards[*i] {
-            Card::Normal(s, _) => Some(s),
-            _ => None,
-        })
+// suitのカードで手札の順に最初に見つかるlen枚の階段を探す
+fn find_seq(
+    cards: &[Card],
+    mask: &HandMask,
+    suit: &Suit,
+    len: usize,
+) -> Option<(Comb, Vec<usize>)> {
+    let ranks = mask.get_suit_ranks(suit);
+    if (ranks.count_ones() as usize) < len {
+        return None;
+    }
+    let indices: Vec<usize> = (0..cards.len())
+        .filter(|i| matches!(cards[*i], Card::Normal(s, _) if s == *suit))
+        .collect();
+    // 手札が数字の降順に並んでいれば強い数字から探す
+    let is_desc = matches!(
+        (cards[indices[0]], cards[indices[indices.len() - 1]]),
+        (Card::Normal(_, first), Card::Normal(_, last)) if first > last
+    );
+    let mut lows = 0..=13 - len;
+    let low = match is_desc {
+        true => lows.rfind(|low| has_run(ranks, *low, len)),
+        false => lows.find(|low| has_run(ranks, *low, len)),
+    }?;
+    let indices: Vec<usize> = indices
         .into_iter()
-        .filter(|(k, indices)| k.is_some() && (indices.len() >= len))
-        .sorted_by(|x, y| {
-            let s1 = x.0.unwrap();
-            let s2 = y.0.unwrap();
-            s1.cmp(&s2)
-        })
-        .map(|(_, indices)| indices)
-        .collect()
-}
-
-fn find_seq(cards: &[Card], indices: &[usize], len: usize) -> Option<(Comb, Vec<usize>)> {
-    // 階段となる組み合わせのカードを探す
-    (0..indices.len() + 1 - len).find_map(|i| {
-        let seq_cards = get_cards(cards, &indices[i..len + i]);
-        let comb = Comb::try_from(seq_cards).ok()?;
-        Some((comb, indices[i..len + i].to_vec()))
-    })
+        .filter(|i| matches!(cards[*i], Card::Normal(_, r) if (low..low + len).contains(&(i32::from(&r) as usize))))
+        .collect();
+    // 階段のカードは手札の順に関わらず数字の昇順に並べる
+    let mut seq: Vec<Card> = indices.iter().map(|i| cards[*i]).collect();
+    seq.sort_by(cmp_rank);
+    Some((Comb::Seq(seq), indices))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::card::{cmp_rank, cmp_rank_reversely, Rank, Suit};
+    use crate::view::GameView;
 
     struct TestValidator {
         is_revolution: bool,
@@ -201,10 +259,18 @@ mod test {
                 None => true,
             }
         }
+
+        fn get_view(&self) -> GameView {
+            GameView {
+                prev_comb: self.prev_comb.clone(),
+                is_rev: self.is_revolution,
+                ..Default::default()
+            }
+        }
     }
 
     #[test]
-    fn test_get_indices_grouped_by_rank() {
+    fn test_get_multi() {
         let cards = vec![
             Card::Normal(Suit::Spade, Rank::Three),
             Card::Normal(Suit::Heart, Rank::Four),
@@ -218,12 +284,26 @@ mod test {
             Card::Normal(Suit::Heart, Rank::Six),
             Card::Joker,
         ];
-        let expected = vec![vec![1, 2], vec![3, 4, 5, 6], vec![7, 8, 9]];
-        assert_eq!(get_indices_grouped_by_rank(&cards, 2), expected);
+        assert_eq!(
+            get_ranks(&cards).collect::<Vec<_>>(),
+            vec![Rank::Three, Rank::Four, Rank::Five, Rank::Six]
+        );
+        for (rank, suits, expected) in [
+            (Rank::Four, 0b1100, vec![1, 2]),
+            (Rank::Five, 0b1111, vec![3, 4, 5, 6]),
+            (Rank::Six, 0b0101, vec![7, 9]),
+        ] {
+            let (comb, indices) = get_multi(&cards, rank, suits);
+            assert_eq!(
+                comb,
+                Comb::Multi(indices.iter().map(|i| cards[*i]).collect())
+            );
+            assert_eq!(indices, expected);
+        }
     }
 
     #[test]
-    fn test_get_indices_grouped_by_suit() {
+    fn test_find_seq_by_suit() {
         let cards = vec![
             Card::Normal(Suit::Spade, Rank::Three),
             Card::Normal(Suit::Heart, Rank::Four),
@@ -238,8 +318,17 @@ mod test {
             Card::Normal(Suit::Spade, Rank::Six),
             Card::Joker,
         ];
-        let expected = vec![vec![3, 7], vec![4, 8], vec![1, 5, 9], vec![0, 2, 6, 10]];
-        assert_eq!(get_indices_grouped_by_suit(&cards, 2), expected);
+        let mask = HandMask::from(&cards[..]);
+        for (suit, len, expected) in [
+            (Suit::Club, 2, Some(vec![3, 7])),
+            (Suit::Club, 3, None),
+            (Suit::Heart, 3, Some(vec![1, 5, 9])),
+            (Suit::Spade, 3, Some(vec![0, 2, 6])),
+            (Suit::Spade, 4, Some(vec![0, 2, 6, 10])),
+        ] {
+            let indices = find_seq(&cards, &mask, &suit, len).map(|(_, indices)| indices);
+            assert_eq!(indices, expected);
+        }
     }
 
     #[test]
@@ -256,7 +345,7 @@ mod test {
             Card::Normal(Suit::Spade, Rank::Ace),
             Card::Normal(Suit::Spade, Rank::Two),
         ];
-        let actual = find_seq(&cards, &(0..cards.len()).collect::<Vec<usize>>(), 4);
+        let actual = find_seq(&cards, &HandMask::from(&cards[..]), &Suit::Spade, 4);
         let expected = Some((
             Comb::Seq(vec![
                 Card::Normal(Suit::Spade, Rank::Seven),
@@ -300,7 +389,7 @@ mod test {
             ),
         ] {
             validator.prev_comb = prev_comb.clone();
-            let actual = player.play(&validator);
+            let actual = player.select(&validator);
             assert_eq!(actual, expected);
         }
         assert_eq!(player.count_hands(), 2);
@@ -325,7 +414,7 @@ mod test {
             ),
         ] {
             validator.prev_comb = prev_comb.clone();
-            let actual = player.play(&validator);
+            let actual = player.select(&validator);
             assert_eq!(expected, actual);
         }
         assert_eq!(player.count_hands(), 3);
@@ -381,7 +470,7 @@ mod test {
             ),
         ] {
             validator.prev_comb = prev_comb.clone();
-            let actual = player.play(&validator);
+            let actual = player.select(&validator);
             assert_eq!(actual, expected);
         }
         assert_eq!(player.count_hands(), 4);
@@ -437,7 +526,7 @@ mod test {
             ),
         ] {
             validator.prev_comb = prev_comb.clone();
-            let actual = player.play(&validator);
+            let actual = player.select(&validator);
             assert_eq!(actual, expected);
         }
         assert_eq!(player.count_hands(), 2);
@@ -481,7 +570,7 @@ mod test {
             ),
         ] {
             validator.prev_comb = prev_comb.clone();
-            let actual = player.play(&validator);
+            let actual = player.select(&validator);
             assert_eq!(actual, expected);
         }
         assert_eq!(player.count_hands(), 4);
@@ -501,41 +590,41 @@ mod test {
         for (prev_comb, expected) in [
             (
                 Some(Comb::Seq(vec![
-                    Card::Normal(Suit::Spade, Rank::Two),
-                    Card::Normal(Suit::Spade, Rank::Ace),
-                    Card::Normal(Suit::Spade, Rank::King),
                     Card::Normal(Suit::Spade, Rank::Queen),
+                    Card::Normal(Suit::Spade, Rank::King),
+                    Card::Normal(Suit::Spade, Rank::Ace),
+                    Card::Normal(Suit::Spade, Rank::Two),
                 ])),
                 Some(Comb::Seq(vec![
-                    Card::Normal(Suit::Heart, Rank::King),
-                    Card::Normal(Suit::Heart, Rank::Queen),
-                    Card::Normal(Suit::Heart, Rank::Jack),
                     Card::Normal(Suit::Heart, Rank::Ten),
+                    Card::Normal(Suit::Heart, Rank::Jack),
+                    Card::Normal(Suit::Heart, Rank::Queen),
+                    Card::Normal(Suit::Heart, Rank::King),
                 ])),
             ),
             (
                 Some(Comb::Seq(vec![
-                    Card::Normal(Suit::Diamond, Rank::Queen),
-                    Card::Joker,
                     Card::Normal(Suit::Diamond, Rank::Ten),
+                    Card::Joker,
+                    Card::Normal(Suit::Diamond, Rank::Queen),
                 ])),
                 Some(Comb::Seq(vec![
-                    Card::Normal(Suit::Club, Rank::Ten),
-                    Card::Normal(Suit::Club, Rank::Nine),
                     Card::Normal(Suit::Club, Rank::Eight),
+                    Card::Normal(Suit::Club, Rank::Nine),
+                    Card::Normal(Suit::Club, Rank::Ten),
                 ])),
             ),
             (
                 Some(Comb::Seq(vec![
-                    Card::Normal(Suit::Diamond, Rank::Five),
-                    Card::Normal(Suit::Diamond, Rank::Four),
                     Card::Normal(Suit::Diamond, Rank::Three),
+                    Card::Normal(Suit::Diamond, Rank::Four),
+                    Card::Normal(Suit::Diamond, Rank::Five),
                 ])),
                 None,
             ),
         ] {
             validator.prev_comb = prev_comb.clone();
-            let actual = player.play(&validator);
+            let actual = player.select(&validator);
             assert_eq!(actual, expected);
         }
     }
@@ -587,10 +676,43 @@ mod test {
                 Some(Comb::Single(Card::Normal(Suit::Heart, Rank::Eight))),
                 2,
             ),
+            // 革命で弱いカードが残るなら4枚を残し、崩さずに1枚を出す
+            (
+                vec![
+                    Card::Normal(Suit::Club, Rank::Three),
+                    Card::Normal(Suit::Diamond, Rank::Three),
+                    Card::Normal(Suit::Heart, Rank::Three),
+                    Card::Normal(Suit::Spade, Rank::Three),
+                    Card::Normal(Suit::Spade, Rank::King),
+                    Card::Normal(Suit::Heart, Rank::Ace),
+                    Card::Normal(Suit::Diamond, Rank::Two),
+                ],
+                Some(Comb::Single(Card::Normal(Suit::Spade, Rank::King))),
+                6,
+            ),
+            // 革命で強いカードが残るなら革命を起こす
+            (
+                vec![
+                    Card::Normal(Suit::Heart, Rank::Four),
+                    Card::Normal(Suit::Diamond, Rank::Six),
+                    Card::Normal(Suit::Club, Rank::Nine),
+                    Card::Normal(Suit::Club, Rank::King),
+                    Card::Normal(Suit::Diamond, Rank::King),
+                    Card::Normal(Suit::Heart, Rank::King),
+                    Card::Normal(Suit::Spade, Rank::King),
+                ],
+                Some(Comb::Multi(vec![
+                    Card::Normal(Suit::Club, Rank::King),
+                    Card::Normal(Suit::Diamond, Rank::King),
+                    Card::Normal(Suit::Heart, Rank::King),
+                    Card::Normal(Suit::Spade, Rank::King),
+                ])),
+                3,
+            ),
         ] {
             let mut player = MinNpc::new("A".to_owned());
             player.init(cards);
-            let actual = player.play(&validator);
+            let actual = player.select(&validator);
             assert_eq!(actual, expected_comb);
             assert_eq!(player.count_hands(), expected_len);
         }
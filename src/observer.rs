@@ -0,0 +1,16 @@
+use crate::event::GameEvent;
+
+pub trait Observer {
+    fn notify(&mut self, event: &GameEvent);
+}
+
+// 複数のオブザーバーにイベントを通知する
+pub struct Observers<'a>(pub Vec<&'a mut dyn Observer>);
+
+impl Observer for Observers<'_> {
+    fn notify(&mut self, event: &GameEvent) {
+        self.0
+            .iter_mut()
+            .for_each(|observer| observer.notify(event));
+    }
+}
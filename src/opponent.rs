@@ -0,0 +1,258 @@
+use crate::card::{Card, Rank};
+use crate::event::GameEvent;
+use crate::field::Effect;
+use crate::view::GameView;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+
+// この枚数以下になってから出した強いカードは終盤まで残していたとみなす
+const LATE_HANDS: usize = 3;
+
+// 2とジョーカーを強いカードとして数える
+fn is_strong(card: &Card) -> bool {
+    matches!(card, Card::Normal(_, Rank::Two) | Card::Joker)
+}
+
+// 1人のプレイヤーの傾向(公開された進行だけから数える)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpponentModel {
+    // 場にカードがあるときの手番の数と、そのうちパスした数
+    turns: usize,
+    passes: usize,
+    // 出した強いカードの枚数と、そのうち終盤に出した枚数
+    strong: usize,
+    late_strong: usize,
+}
+
+impl OpponentModel {
+    // 場にカードがあるときにパスする割合(記録がなければ0.5)
+    pub fn get_pass_rate(&self) -> f64 {
+        (self.passes + 1) as f64 / (self.turns + 2) as f64
+    }
+
+    // 強いカードを終盤まで残す割合(記録がなければ0.5)
+    pub fn get_hoard_rate(&self) -> f64 {
+        (self.late_strong + 1) as f64 / (self.strong + 2) as f64
+    }
+
+    // 見えていないカードを配り直すときに強いカードを持たせる重み(記録がなければ1.0)
+    // 強いカードを残しがちなら多く、パスしがちなら少なく持たせる
+    pub fn get_strong_weight(&self) -> f64 {
+        (0.5 + self.get_hoard_rate()) * (1.5 - self.get_pass_rate())
+    }
+}
+
+// ラウンドをまたいで名前毎に傾向を覚える
+#[derive(Debug, Clone, Default)]
+pub struct OpponentModels {
+    models: HashMap<String, OpponentModel>,
+    // 今のラウンドの席毎の名前
+    names: Vec<String>,
+    // 場にカードがあるか
+    occupied: bool,
+}
+
+impl OpponentModels {
+    pub fn get_model(&self, name: &str) -> Option<&OpponentModel> {
+        self.models.get(name)
+    }
+
+    pub fn observe(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Start { names, .. } => {
+                self.names = names.clone();
+                self.occupied = false;
+            }
+            GameEvent::Turn {
+                idx,
+                comb,
+                hands_count,
+                outcome,
+            } => {
+                let Some(name) = self.names.get(*idx) else {
+                    return;
+                };
+                let model = self.models.entry(name.clone()).or_default();
+                if self.occupied {
+                    model.turns += 1;
+                    model.passes += usize::from(comb.is_none());
+                }
+                if let Some(comb) = comb {
+                    let strong = comb.get_cards().iter().filter(|c| is_strong(c)).count();
+                    model.strong += strong;
+                    // hands_countは出した後の枚数
+                    if *hands_count <= LATE_HANDS {
+                        model.late_strong += strong;
+                    }
+                }
+                // 8切りや大革命、反則上がりで無効になった場合も場は空く
+                let emptied = [Effect::EightCut, Effect::SuperRevolution, Effect::FoulVoid]
+                    .into_iter()
+                    .any(|effect| outcome.contains(effect));
+                self.occupied = match outcome.cleared || emptied {
+                    true => false,
+                    false => self.occupied || comb.is_some(),
+                };
+            }
+            GameEvent::Lead { .. } | GameEvent::End { .. } => self.occupied = false,
+            _ => {}
+        }
+    }
+
+    // viewの席毎の強いカードの重み(記録のないプレイヤーは1.0)
+    pub fn get_weights(&self, view: &GameView) -> Vec<f64> {
+        view.players
+            .iter()
+            .map(|(name, _)| {
+                self.get_model(name)
+                    .map_or(1.0, |model| model.get_strong_weight())
+            })
+            .collect()
+    }
+}
+
+// 見えていないカードを席毎にcounts枚ずつ配り直す(配られないカードは捨てる)
+// 強いカードは空いている枚数と重みに比例した確率で配るので、重みが全て同じなら一様に配るのと変わらない
+pub fn deal_unseen<R: Rng>(
+    mut unseen: Vec<Card>,
+    counts: &[usize],
+    weights: &[f64],
+    rng: &mut R,
+) -> Vec<Vec<Card>> {
+    unseen.shuffle(rng);
+    let (strong, mut weak): (Vec<Card>, Vec<Card>) = unseen.into_iter().partition(is_strong);
+    let total = strong.len() + weak.len();
+    let mut hands: Vec<Vec<Card>> = counts.iter().map(|_| vec![]).collect();
+    // 誰にも配られない残りの枚数
+    let mut rest = total.saturating_sub(counts.iter().sum());
+    for card in strong {
+        let slots: Vec<f64> = counts
+            .iter()
+            .zip(&hands)
+            .zip(weights)
+            .map(|((count, hands), weight)| (count - hands.len()) as f64 * weight)
+            .chain([rest as f64])
+            .collect();
+        let sum: f64 = slots.iter().sum();
+        if sum <= 0.0 {
+            // 重みのある席が埋まっていれば他の札と同じように配る
+            weak.push(card);
+            continue;
+        }
+        let mut x = rng.gen::<f64>() * sum;
+        let i = slots
+            .iter()
+            .position(|slot| {
+                x -= slot;
+                x < 0.0
+            })
+            .unwrap_or(counts.len());
+        match hands.get_mut(i) {
+            Some(hands) => hands.push(card),
+            None => rest -= 1,
+        }
+    }
+    for (hands, count) in hands.iter_mut().zip(counts) {
+        let lack = count - hands.len();
+        hands.extend(weak.split_off(weak.len().saturating_sub(lack)));
+    }
+    hands
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{self, Suit};
+    use crate::comb::Comb;
+    use crate::field::TurnOutcome;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn turn(idx: usize, comb: Option<Comb>, hands_count: usize, cleared: bool) -> GameEvent {
+        GameEvent::Turn {
+            idx,
+            comb,
+            hands_count,
+            outcome: TurnOutcome {
+                cleared,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_observe() {
+        let two = Card::Normal(Suit::Spade, Rank::Two);
+        let three = Card::Normal(Suit::Club, Rank::Three);
+        let mut models = OpponentModels::default();
+        let events = [
+            GameEvent::Start {
+                names: vec!["A".to_owned(), "B".to_owned()],
+                hands: vec![vec![], vec![]],
+            },
+            // 場にカードがないときの手はパスの割合に数えない
+            turn(0, Some(Comb::Single(three)), 10, false),
+            turn(1, None, 10, false),
+            turn(0, Some(Comb::Single(two)), 9, false),
+            turn(1, None, 10, true),
+            GameEvent::Lead { idx: 0 },
+            turn(0, Some(Comb::Multi(vec![two, Card::Joker])), 3, false),
+            GameEvent::End {
+                player_rank: vec![0, 1],
+            },
+        ];
+        for event in &events {
+            models.observe(event);
+        }
+        let a = models.get_model("A").unwrap().clone();
+        let b = models.get_model("B").unwrap().clone();
+        let cases = [
+            (a.get_pass_rate(), 1.0 / 3.0),
+            (a.get_hoard_rate(), 3.0 / 5.0),
+            (b.get_pass_rate(), 3.0 / 4.0),
+            (b.get_hoard_rate(), 0.5),
+            (OpponentModel::default().get_strong_weight(), 1.0),
+        ];
+        for (actual, expected) in cases {
+            assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+        }
+        // 強いカードを残しがちでパスの少ないプレイヤーに強いカードを多く持たせる
+        assert!(a.get_strong_weight() > b.get_strong_weight());
+        // 次のラウンドも覚えている
+        models.observe(&events[0]);
+        assert_eq!(models.get_model("A"), Some(&a));
+    }
+
+    #[test]
+    fn test_deal_unseen() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let unseen = card::create_deck();
+        for (counts, weights) in [
+            (vec![13, 0, 14, 14], vec![1.0, 1.0, 1.0, 1.0]),
+            (vec![5, 5, 5, 5], vec![0.0, 0.0, 0.0, 0.0]),
+            (vec![26, 27, 0, 0], vec![1.0, 0.0, 1.0, 1.0]),
+        ] {
+            let hands = deal_unseen(unseen.clone(), &counts, &weights, &mut rng);
+            let lens: Vec<usize> = hands.iter().map(|h| h.len()).collect();
+            assert_eq!(lens, counts);
+            let mut cards: Vec<Card> = hands.concat();
+            cards.sort();
+            cards.dedup();
+            assert_eq!(cards.len(), counts.iter().sum::<usize>());
+        }
+        // 重みが0の席には強いカードが配られない
+        let hands = deal_unseen(unseen.clone(), &[26, 27], &[0.0, 1.0], &mut rng);
+        assert!(!hands[0].iter().any(is_strong));
+        assert_eq!(hands[1].iter().filter(|c| is_strong(c)).count(), 5);
+        // 重みが大きい席ほど強いカードを多く持つ
+        let mut strong = [0, 0];
+        for _ in 0..200 {
+            let hands = deal_unseen(unseen.clone(), &[10, 10], &[2.0, 0.5], &mut rng);
+            for (count, hands) in strong.iter_mut().zip(&hands) {
+                *count += hands.iter().filter(|c| is_strong(c)).count();
+            }
+        }
+        assert!(strong[0] > strong[1] * 2, "{strong:?}");
+    }
+}
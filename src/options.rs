@@ -0,0 +1,1403 @@
+use crate::arena::ArenaOptions;
+use crate::bench::BenchOptions;
+use crate::card::RANKS;
+use crate::comb::MIN_SEQ;
+use crate::i18n::Lang;
+use crate::pc::{TimeoutAction, TurnNotice, TurnTimer};
+use crate::render::ThinkingTime;
+use crate::rules::{find_preset, FoulRule, LeadPassRule, LeadRule, RuleSet, SeqRule};
+use crate::simulate::SimulateOptions;
+use crate::strategy::Strategy;
+use crate::tournament::{Pairing, TournamentOptions};
+use crate::tune::TuneOptions;
+use crate::whatif::WhatIfOptions;
+use clap::{Args, Parser, Subcommand};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::iter;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub enum Command {
+    Play(Options),
+    Simulate(SimulateOptions),
+    Arena(ArenaOptions),
+    Tournament(TournamentOptions),
+    Tune(TuneOptions),
+    Bench(BenchOptions),
+    Replay(ReplayOptions),
+    Verify(PathBuf),
+    WhatIf(WhatIfOptions),
+    Tutorial(TutorialOptions),
+    Resume(PathBuf, Options),
+    Stats,
+    Serve(ServeOptions),
+    Join(JoinOptions),
+    Http(HttpOptions),
+    Rules(RulesOptions),
+}
+
+pub struct ServeOptions {
+    pub port: u16,
+    // リモートから参加するプレイヤーの人数
+    pub clients: usize,
+    // 開始前に待つ観戦者の人数
+    pub spectators: usize,
+    // 空いた席を埋めるNPCの戦略
+    pub npc: Strategy,
+    pub rounds: usize,
+    // TCPの代わりにWebSocketで接続を受け付ける
+    pub ws: bool,
+    // 接続が切れた席の再接続を待つ手番の数
+    pub reconnect_turns: usize,
+    // 手番の持ち時間(過ぎたらパスか最も弱い手を出す、Noneなら制限しない)
+    pub turn_timeout: Option<Duration>,
+    // クライアントがテーブルを作って対戦相手を集める
+    pub lobby: bool,
+    pub color: bool,
+    pub ascii: bool,
+}
+
+pub struct JoinOptions {
+    // 接続先(host:port)
+    pub addr: String,
+    pub name: String,
+    // 接続が切れた席に戻るためのトークン
+    pub token: Option<String>,
+    // ロビーで参加するテーブル
+    pub table: Option<String>,
+    // ロビーに作るテーブルとその設定
+    pub create: Option<String>,
+    pub preset: String,
+    pub npc: Strategy,
+    // ロビーのテーブルの一覧を表示して終わる
+    pub list: bool,
+    // 席に着かずに観戦する
+    pub spectate: bool,
+    pub notice: TurnNotice,
+    pub color: bool,
+    pub ascii: bool,
+    pub speed: f64,
+}
+
+pub struct HttpOptions {
+    pub port: u16,
+}
+
+pub struct RulesOptions {
+    pub rules: RuleSet,
+    // 棋譜の表記の代わりにJSONで表示する
+    pub json: bool,
+}
+
+pub struct ReplayOptions {
+    pub path: PathBuf,
+    pub color: bool,
+    pub ascii: bool,
+}
+
+pub struct TutorialOptions {
+    pub color: bool,
+    pub ascii: bool,
+}
+
+pub struct Options {
+    pub color: bool,
+    pub ascii: bool,
+    pub tui: bool,
+    pub humans: usize,
+    pub timer: Option<TurnTimer>,
+    pub notice: TurnNotice,
+    pub speed: f64,
+    // NPCの席に順に割り当てる手を表示するまでの待ち時間
+    pub thinking: Vec<ThinkingTime>,
+    pub quiet: bool,
+    pub kifu: Option<PathBuf>,
+    pub log_file: Option<PathBuf>,
+    pub analyze: bool,
+    // 手番毎にまだ見えていないカードの枚数を表示する
+    pub counter: bool,
+    // NPCが手を選ぶのに使うONNXモデル
+    pub policy: Option<PathBuf>,
+    pub rules: RuleSet,
+    // 開始前にローカルルールを選ぶメニューを表示する
+    pub select_rules: bool,
+    // 画面に表示する言語(省略すると設定ファイルの言語)
+    pub lang: Option<Lang>,
+    // 人間のプレイヤーの名前(省略すると設定ファイルの名前、なければUser)
+    pub names: Vec<String>,
+    // NPCに付ける名前(足りない分は候補から選ぶ)
+    pub npc_names: Vec<String>,
+    // NPCの名前を候補からランダムに選ぶ
+    pub random_names: bool,
+    // 人間とNPCの席をランダムに並べる
+    pub shuffle_seats: bool,
+    // 確認せずに遊ぶラウンド数
+    pub rounds: Option<usize>,
+    // 全てのプレイヤーの手札を表示する
+    pub open_hands: bool,
+    // 場に出せる組み合わせを番号付きで並べて選ばせる
+    pub candidates: bool,
+    // NPCが手を選んだ理由を表示する
+    pub explain_ai: bool,
+    // 手番毎に全員の手札と場の内部の状態を表示する
+    pub debug: bool,
+    // カードを配るのに使うシード(省略するとランダム)
+    pub seed: Option<u64>,
+}
+
+impl Command {
+    pub fn parse<I>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let cli = Cli::try_parse_from(iter::once("daifugo".to_owned()).chain(args))?;
+        Ok(match cli.command {
+            None => Command::Play(cli.play.into()),
+            Some(Sub::Play(args)) => Command::Play(args.into()),
+            Some(Sub::Resume { path, play }) => Command::Resume(path, play.into()),
+            Some(Sub::Simulate(args)) => Command::Simulate(args.into()),
+            Some(Sub::Arena(args)) => Command::Arena(args.into()),
+            Some(Sub::Tournament(args)) => Command::Tournament(args.into()),
+            Some(Sub::Tune(args)) => Command::Tune(args.into()),
+            Some(Sub::Bench(args)) => Command::Bench(args.into()),
+            Some(Sub::Replay(args)) => Command::Replay(args.into()),
+            Some(Sub::Verify { path }) => Command::Verify(path),
+            Some(Sub::Whatif(args)) => Command::WhatIf(args.into()),
+            Some(Sub::Tutorial(args)) => Command::Tutorial(args.into()),
+            Some(Sub::Stats) => Command::Stats,
+            Some(Sub::Serve(args)) => Command::Serve(args.into()),
+            Some(Sub::Join(args)) => Command::Join(args.into()),
+            Some(Sub::Http(args)) => Command::Http(args.into()),
+            Some(Sub::Rules(args)) => Command::Rules(args.into()),
+        })
+    }
+}
+
+// サブコマンドを省略するとplayとして遊ぶ
+#[derive(Parser)]
+#[command(
+    name = "daifugo",
+    version,
+    about = "ターミナル上で動作する大富豪",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Sub>,
+    #[command(flatten)]
+    play: PlayArgs,
+}
+
+#[derive(Subcommand)]
+enum Sub {
+    /// NPCと大富豪を遊ぶ(サブコマンドを省略した場合と同じ)
+    Play(PlayArgs),
+    /// 保存したゲームを続きから遊ぶ
+    Resume {
+        /// saveで保存したファイル
+        path: PathBuf,
+        #[command(flatten)]
+        play: PlayArgs,
+    },
+    /// NPC同士の対戦を繰り返して結果を集計する
+    Simulate(SimulateArgs),
+    /// 全ての戦略を総当たりで対戦させる
+    Arena(ArenaArgs),
+    /// 参加者をテーブルに振り分け直しながら対戦し、通算の成績を表示する
+    Tournament(TournamentArgs),
+    /// heuristicのNPCの重みを遺伝的アルゴリズムで調整する
+    Tune(TuneArgs),
+    /// 1秒あたりに処理できるゲーム数を計る
+    Bench(BenchArgs),
+    /// 棋譜を再生する
+    Replay(ReplayArgs),
+    /// 棋譜をエンジンで再生し直して同じ結果になるかを確かめる
+    Verify {
+        /// 確かめる棋譜のファイル
+        path: PathBuf,
+    },
+    /// 棋譜の途中の場面から違う手を選んで遊び直す
+    Whatif(WhatifArgs),
+    /// 決まった配り方で1ラウンドを遊びながらルールを学ぶ
+    Tutorial(TutorialArgs),
+    /// 記録したプレイヤー毎の成績を表示する
+    Stats,
+    /// テーブルを立ててリモートのプレイヤーと対戦する
+    Serve(ServeArgs),
+    /// serveで立てたテーブルに接続する
+    Join(JoinArgs),
+    /// NPCとの対戦を操作するHTTPサーバーを立てる
+    Http(HttpArgs),
+    /// オプションで選んだルールの組み合わせを表示する
+    Rules(RulesArgs),
+}
+
+#[derive(Args)]
+struct PlayArgs {
+    /// 色付きの表示を無効にする
+    #[arg(long)]
+    no_color: bool,
+    /// カードをASCII文字のみで表示する(例: S3 H10 JK)
+    #[arg(long)]
+    ascii: bool,
+    /// TUIで遊ぶ(tuiフィーチャーが必要)
+    #[arg(long)]
+    tui: bool,
+    /// N人(2〜4)で1台の端末を交代で使って遊ぶ
+    #[arg(long, value_name = "N", value_parser = in_range(2, 4))]
+    hotseat: Option<usize>,
+    /// 人間のプレイヤーの人数(0〜4、0ならNPC同士の対戦を観戦する)
+    #[arg(long, value_name = "N", value_parser = in_range(0, 4), conflicts_with = "hotseat")]
+    humans: Option<usize>,
+    /// 全てのプレイヤーの手札を表示する
+    #[arg(long)]
+    open_hands: bool,
+    /// ゲームの進行速度の倍率
+    #[arg(long, value_name = "X", default_value_t = 1.0, value_parser = parse_speed)]
+    speed: f64,
+    /// NPCが手を表示するまでのミリ秒(200-800のような範囲も可、カンマ区切りでNPCの席毎に指定)
+    #[arg(long, value_name = "MS", value_delimiter = ',', default_value = "300")]
+    thinking_time: Vec<ThinkingTime>,
+    /// 毎ターンの表示と待ち時間を省き、結果のみ表示する
+    #[arg(long)]
+    quiet: bool,
+    /// 1ターンの持ち時間の秒数
+    #[arg(long, value_name = "SECS", value_parser = at_least(1_u64))]
+    turn_timeout: Option<u64>,
+    /// 持ち時間を過ぎた場合にパスするか、最も弱いカードを出すか
+    #[arg(long, value_name = "pass|lowest", default_value = "pass", value_parser = parse_timeout_action)]
+    timeout_action: TimeoutAction,
+    /// 続けてこの回数だけ時間切れになると、ラウンドの残りをNPCに任せる
+    #[arg(long, value_name = "N", requires = "turn_timeout", value_parser = at_least(1_usize))]
+    afk_turns: Option<usize>,
+    #[command(flatten)]
+    notice: NoticeArgs,
+    /// 対局の棋譜をファイルに書き出す
+    #[arg(long, value_name = "PATH")]
+    kifu: Option<PathBuf>,
+    /// 全てのイベントをJSON Lines形式でファイルに追記する
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+    /// ラウンドの終了後に人間のプレイヤーの手を分析して悪手を表示する
+    #[arg(long)]
+    analyze: bool,
+    /// 手番毎にまだ見えていないカードの枚数を数字毎に表示する
+    #[arg(long)]
+    counter: bool,
+    /// 場に出せる組み合わせを番号付きで並べ、番号で選ぶ(0でパス)
+    #[arg(long)]
+    candidates: bool,
+    /// NPCが手を選んだ理由を表示する
+    #[arg(long)]
+    explain_ai: bool,
+    /// 手番毎に全員の手札と場の内部の状態を標準エラー出力に表示する
+    #[arg(long)]
+    debug: bool,
+    /// NPCがONNXモデルで手を選ぶ(onnxフィーチャーが必要)
+    #[arg(long, value_name = "PATH")]
+    policy: Option<PathBuf>,
+    #[command(flatten)]
+    rules: RuleArgs,
+    /// 開始前にローカルルールのオンとオフを選ぶメニューを表示する
+    #[arg(long)]
+    select_rules: bool,
+    /// 画面に表示する言語(省略すると設定ファイルの言語、なければja)
+    #[arg(long, value_name = "ja|en")]
+    lang: Option<Lang>,
+    /// 自分の名前(複数人ではカンマ区切りで並べるか、1つの名前に番号を付ける。省略すると設定ファイルの名前、なければUser)
+    #[arg(long, value_name = "NAME", value_delimiter = ',', value_parser = parse_name)]
+    name: Vec<String>,
+    /// NPCに付ける名前をカンマ区切りで並べる(足りない分は自動で付ける)
+    #[arg(long, value_name = "A,B,C", value_delimiter = ',', value_parser = parse_name)]
+    npc_names: Vec<String>,
+    /// NPCの名前を組み込みの候補からランダムに選ぶ
+    #[arg(long)]
+    random_names: bool,
+    /// 席をランダムに並べず、自分を最初の席にする
+    #[arg(long)]
+    no_shuffle_seats: bool,
+    /// もう一度遊ぶかを確認せずにNラウンド遊び、最後に通算の成績を表示する
+    #[arg(long, value_name = "N", value_parser = at_least(1_usize))]
+    rounds: Option<usize>,
+    /// カードを配るのに使うシード(棋譜に書き出され、verifyで配られたカードを確かめられる)
+    #[arg(long, value_name = "N")]
+    seed: Option<u64>,
+}
+
+impl From<PlayArgs> for Options {
+    fn from(args: PlayArgs) -> Self {
+        Self {
+            color: !args.no_color,
+            ascii: args.ascii,
+            tui: args.tui,
+            humans: args.humans.or(args.hotseat).unwrap_or(1),
+            timer: args.turn_timeout.map(|secs| TurnTimer {
+                timeout: Duration::from_secs(secs),
+                action: args.timeout_action,
+                afk_turns: args.afk_turns,
+            }),
+            notice: args.notice.into(),
+            speed: args.speed,
+            thinking: args.thinking_time,
+            quiet: args.quiet,
+            kifu: args.kifu,
+            log_file: args.log_file,
+            analyze: args.analyze,
+            counter: args.counter,
+            policy: args.policy,
+            rules: args.rules.into(),
+            select_rules: args.select_rules,
+            lang: args.lang,
+            names: args.name,
+            npc_names: args.npc_names,
+            random_names: args.random_names,
+            shuffle_seats: !args.no_shuffle_seats,
+            rounds: args.rounds,
+            open_hands: args.open_hands,
+            candidates: args.candidates,
+            explain_ai: args.explain_ai,
+            debug: args.debug,
+            seed: args.seed,
+        }
+    }
+}
+
+impl Options {
+    // サブコマンドを付けずにゲームのオプションだけを解析する
+    pub fn parse<I>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        match Command::parse(iter::once("play".to_owned()).chain(args))? {
+            Command::Play(options) => Ok(options),
+            _ => unreachable!("playのオプションとして解析しています"),
+        }
+    }
+}
+
+// playとrulesで共通のルールのオプション
+#[derive(Args)]
+struct RuleArgs {
+    /// 階段の強さの比べ方(デフォルトはpairwise)
+    #[arg(long, value_name = "pairwise|extreme")]
+    seq_rule: Option<SeqRule>,
+    /// 階段に必要な最小の枚数(0なら階段を出せない。デフォルトは3)
+    #[arg(long, value_name = "N", value_parser = parse_min_seq)]
+    min_seq: Option<usize>,
+    /// 2ラウンド目以降に最初に手番になるプレイヤー(loserは大貧民、rotateは前のラウンドの次の席、winnerは大富豪、diamondは♦3を持っているプレイヤー。デフォルトはloser)
+    #[arg(long, value_name = "loser|rotate|winner|diamond")]
+    lead_rule: Option<LeadRule>,
+    /// 場にカードがないときのパス(rejectはパスできない、skipは次のプレイヤーに手番を移す。デフォルトはreject)
+    #[arg(long, value_name = "reject|skip")]
+    lead_pass: Option<LeadPassRule>,
+    /// 反則上がり(demoteは最下位にするだけ、voidは出したカードを無効にして場を流す。デフォルトはdemote)
+    #[arg(long, value_name = "demote|void")]
+    foul: Option<FoulRule>,
+    /// ローカルルールの組み合わせ(設定ファイルに定義したプリセットも選べる。デフォルトはstandard)
+    #[arg(long, value_name = "standard|kanto|kansai|tournament", value_parser = parse_preset)]
+    preset: Option<RuleSet>,
+}
+
+impl From<RuleArgs> for RuleSet {
+    fn from(args: RuleArgs) -> Self {
+        let mut rules = args.preset.unwrap_or_default();
+        // プリセットより個別に指定したルールを優先する
+        if let Some(min_seq) = args.min_seq {
+            rules.min_seq = min_seq;
+        }
+        if let Some(seq) = args.seq_rule {
+            rules.seq = seq;
+        }
+        if let Some(lead) = args.lead_rule {
+            rules.lead = lead;
+        }
+        if let Some(lead_pass) = args.lead_pass {
+            rules.lead_pass = lead_pass;
+        }
+        if let Some(foul) = args.foul {
+            rules.foul = foul;
+        }
+        rules
+    }
+}
+
+#[derive(Args)]
+struct SimulateArgs {
+    /// 対戦するゲーム数
+    #[arg(long, default_value_t = 1000, value_parser = at_least(1_usize))]
+    games: usize,
+    /// 4人分の戦略をカンマ区切りで指定する
+    #[arg(long, value_name = "A,B,C,D", default_value = "min,min,min,min")]
+    seats: Seats,
+    /// カードを配る乱数のシード(省略するとランダム)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// ゲーム毎の結果をCSVファイルに書き出す
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+    /// 1手ごとの学習データをnpzファイルに書き出す
+    #[arg(long, value_name = "PATH")]
+    dataset: Option<PathBuf>,
+}
+
+impl From<SimulateArgs> for SimulateOptions {
+    fn from(args: SimulateArgs) -> Self {
+        Self {
+            games: args.games,
+            seats: args.seats.0,
+            seed: args.seed.unwrap_or_else(rand::random),
+            output: args.output,
+            dataset: args.dataset,
+        }
+    }
+}
+
+#[derive(Args)]
+struct ArenaArgs {
+    /// 戦略の組み合わせ毎に対戦するゲーム数
+    #[arg(long, default_value_t = 200, value_parser = at_least(1_usize))]
+    games: usize,
+    /// カードを配る乱数のシード(省略するとランダム)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// 同じ配り方で戦略の席を入れ替えて対戦する
+    #[arg(long)]
+    duplicate: bool,
+    /// 結果をCSVファイルに書き出す
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct TournamentArgs {
+    /// 参加者の人数(人間のプレイヤーを含む)
+    #[arg(long, default_value_t = 8, value_parser = at_least(2_usize))]
+    players: usize,
+    /// テーブルの数(参加者はテーブルに均等に分ける)
+    #[arg(long, default_value_t = 2, value_parser = at_least(1_usize))]
+    tables: usize,
+    /// 対戦するラウンド数
+    #[arg(long, default_value_t = 10, value_parser = at_least(1_usize))]
+    rounds: usize,
+    /// ラウンド毎に参加者をテーブルに振り分ける方法(swissは成績が近い参加者を同じテーブルにする)
+    #[arg(long, value_name = "rotate|swiss", default_value = "rotate")]
+    pairing: Pairing,
+    /// 参加する人間のプレイヤーの人数
+    #[arg(long, default_value_t = 0)]
+    humans: usize,
+    /// NPCの戦略をカンマ区切りで指定する(NPCの参加者を等分して割り当てる)
+    #[arg(
+        long,
+        value_name = "A,B,C",
+        value_delimiter = ',',
+        default_value = "min"
+    )]
+    npcs: Vec<Strategy>,
+    /// カードを配る乱数のシード(省略するとランダム)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// 色付きの表示を無効にする
+    #[arg(long)]
+    no_color: bool,
+    /// カードをASCII文字のみで表示する
+    #[arg(long)]
+    ascii: bool,
+}
+
+impl From<TournamentArgs> for TournamentOptions {
+    fn from(args: TournamentArgs) -> Self {
+        Self {
+            players: args.players,
+            tables: args.tables,
+            rounds: args.rounds,
+            pairing: args.pairing,
+            humans: args.humans,
+            npcs: args.npcs,
+            seed: args.seed.unwrap_or_else(rand::random),
+            color: !args.no_color,
+            ascii: args.ascii,
+        }
+    }
+}
+
+impl From<ArenaArgs> for ArenaOptions {
+    fn from(args: ArenaArgs) -> Self {
+        Self {
+            games: args.games,
+            seed: args.seed.unwrap_or_else(rand::random),
+            duplicate: args.duplicate,
+            output: args.output,
+        }
+    }
+}
+
+#[derive(Args)]
+struct TuneArgs {
+    /// 世代数
+    #[arg(long, default_value_t = 10, value_parser = at_least(1_usize))]
+    generations: usize,
+    /// 1世代の個体数
+    #[arg(long, default_value_t = 8, value_parser = at_least(2_usize))]
+    population: usize,
+    /// 1つの個体を評価するゲーム数
+    #[arg(long, default_value_t = 40, value_parser = at_least(1_usize))]
+    games: usize,
+    /// カードを配る乱数のシード(省略するとランダム)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// 最良の重みを設定ファイルに書き込む(serdeフィーチャーが必要)
+    #[arg(long)]
+    write: bool,
+}
+
+impl From<TuneArgs> for TuneOptions {
+    fn from(args: TuneArgs) -> Self {
+        Self {
+            generations: args.generations,
+            population: args.population,
+            games: args.games,
+            seed: args.seed.unwrap_or_else(rand::random),
+            write: args.write,
+        }
+    }
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    /// 繰り返すゲーム数
+    #[arg(long, default_value_t = 1000, value_parser = at_least(1_usize))]
+    games: usize,
+    /// 4人分の戦略をカンマ区切りで指定する
+    #[arg(long, value_name = "A,B,C,D", default_value = "min,min,min,min")]
+    seats: Seats,
+    /// カードを配る乱数のシード(計測を比べられるように既定は0)
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+impl From<BenchArgs> for BenchOptions {
+    fn from(args: BenchArgs) -> Self {
+        Self {
+            games: args.games,
+            seats: args.seats.0,
+            seed: args.seed,
+        }
+    }
+}
+
+#[derive(Args)]
+struct TutorialArgs {
+    /// 色付きの表示を無効にする
+    #[arg(long)]
+    no_color: bool,
+    /// カードをASCII文字のみで表示する
+    #[arg(long)]
+    ascii: bool,
+}
+
+impl From<TutorialArgs> for TutorialOptions {
+    fn from(args: TutorialArgs) -> Self {
+        Self {
+            color: !args.no_color,
+            ascii: args.ascii,
+        }
+    }
+}
+
+#[derive(Args)]
+struct ReplayArgs {
+    /// 再生する棋譜のファイル
+    path: PathBuf,
+    /// 色付きの表示を無効にする
+    #[arg(long)]
+    no_color: bool,
+    /// カードをASCII文字のみで表示する
+    #[arg(long)]
+    ascii: bool,
+}
+
+impl From<ReplayArgs> for ReplayOptions {
+    fn from(args: ReplayArgs) -> Self {
+        Self {
+            path: args.path,
+            color: !args.no_color,
+            ascii: args.ascii,
+        }
+    }
+}
+
+#[derive(Args)]
+struct WhatifArgs {
+    /// 遊び直す棋譜のファイル
+    path: PathBuf,
+    /// 遊び直すラウンド
+    #[arg(long, default_value_t = 1, value_parser = at_least(1_usize))]
+    round: usize,
+    /// 棋譜の通りに進める手の数(0ならラウンドの最初から)
+    #[arg(long, default_value_t = 0)]
+    turn: usize,
+    /// 操作する席の番号(棋譜のP0、P1…)
+    #[arg(long, default_value_t = 0)]
+    seat: usize,
+    /// それ以外の席のNPCの戦略
+    #[arg(long, default_value = "min")]
+    npc: Strategy,
+    /// 色付きの表示を無効にする
+    #[arg(long)]
+    no_color: bool,
+    /// カードをASCII文字のみで表示する
+    #[arg(long)]
+    ascii: bool,
+}
+
+impl From<WhatifArgs> for WhatIfOptions {
+    fn from(args: WhatifArgs) -> Self {
+        Self {
+            path: args.path,
+            round: args.round,
+            turn: args.turn,
+            seat: args.seat,
+            npc: args.npc,
+            color: !args.no_color,
+            ascii: args.ascii,
+        }
+    }
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// 待ち受けるポート番号
+    #[arg(long, default_value_t = 7777)]
+    port: u16,
+    /// リモートから参加するプレイヤーの人数(0〜4)
+    #[arg(long, default_value_t = 1, value_parser = in_range(0, 4))]
+    clients: usize,
+    /// 開始前に待つ観戦者の人数
+    #[arg(long, default_value_t = 0)]
+    spectators: usize,
+    /// 空いた席を埋めるNPCの戦略
+    #[arg(long, default_value = "min")]
+    npc: Strategy,
+    /// 対戦するラウンド数
+    #[arg(long, default_value_t = 1, value_parser = at_least(1_usize))]
+    rounds: usize,
+    /// TCPの代わりにWebSocketで接続を受け付ける(wsフィーチャーが必要)
+    #[arg(long)]
+    ws: bool,
+    /// 接続が切れた席の再接続を待つ手番の数
+    #[arg(long, default_value_t = 10)]
+    reconnect_turns: usize,
+    /// 手番の持ち時間の秒数(過ぎたらパスか最も弱い手を出す、0で制限しない)
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    turn_timeout: u64,
+    /// クライアントがテーブルを作って対戦相手を集めるロビーを開く
+    #[arg(long)]
+    lobby: bool,
+    /// 色付きの表示を無効にする
+    #[arg(long)]
+    no_color: bool,
+    /// カードをASCII文字のみで表示する
+    #[arg(long)]
+    ascii: bool,
+}
+
+impl From<ServeArgs> for ServeOptions {
+    fn from(args: ServeArgs) -> Self {
+        Self {
+            port: args.port,
+            clients: args.clients,
+            spectators: args.spectators,
+            npc: args.npc,
+            rounds: args.rounds,
+            ws: args.ws,
+            reconnect_turns: args.reconnect_turns,
+            turn_timeout: (args.turn_timeout > 0).then(|| Duration::from_secs(args.turn_timeout)),
+            lobby: args.lobby,
+            color: !args.no_color,
+            ascii: args.ascii,
+        }
+    }
+}
+
+#[derive(Args)]
+struct JoinArgs {
+    /// 接続先(host:port)
+    addr: String,
+    /// プレイヤーの名前
+    #[arg(long, default_value = "User", value_parser = parse_name)]
+    name: String,
+    /// 接続が切れた席に戻るためのトークン
+    #[arg(long)]
+    token: Option<String>,
+    /// ロビーで参加するテーブル
+    #[arg(long, value_parser = parse_name, conflicts_with_all = ["create", "list"])]
+    table: Option<String>,
+    /// ロビーにテーブルを作る
+    #[arg(long, value_parser = parse_name, conflicts_with_all = ["list", "spectate"])]
+    create: Option<String>,
+    /// 作るテーブルのルールのプリセット
+    #[arg(long, default_value = "standard")]
+    preset: String,
+    /// 作るテーブルの空いた席を埋めるNPCの戦略
+    #[arg(long, default_value = "min")]
+    npc: Strategy,
+    /// ロビーのテーブルの一覧を表示して終わる
+    #[arg(long, conflicts_with = "spectate")]
+    list: bool,
+    /// 席に着かずに観戦する
+    #[arg(long)]
+    spectate: bool,
+    #[command(flatten)]
+    notice: NoticeArgs,
+    /// 色付きの表示を無効にする
+    #[arg(long)]
+    no_color: bool,
+    /// カードをASCII文字のみで表示する
+    #[arg(long)]
+    ascii: bool,
+    /// ゲームの進行速度の倍率
+    #[arg(long, value_name = "X", default_value_t = 1.0, value_parser = parse_speed)]
+    speed: f64,
+}
+
+impl From<JoinArgs> for JoinOptions {
+    fn from(args: JoinArgs) -> Self {
+        Self {
+            addr: args.addr,
+            name: args.name,
+            token: args.token,
+            table: args.table,
+            create: args.create,
+            preset: args.preset,
+            npc: args.npc,
+            list: args.list,
+            spectate: args.spectate,
+            notice: args.notice.into(),
+            color: !args.no_color,
+            ascii: args.ascii,
+            speed: args.speed,
+        }
+    }
+}
+
+#[derive(Args)]
+struct NoticeArgs {
+    /// 手番が来てもベルを鳴らさない
+    #[arg(long)]
+    no_bell: bool,
+    /// 入力がないまま過ぎると場の状況を表示し直す秒数(0で表示しない)
+    #[arg(long, value_name = "SECS", default_value_t = TurnNotice::DEFAULT_REMINDER.as_secs())]
+    reminder: u64,
+}
+
+impl From<NoticeArgs> for TurnNotice {
+    fn from(args: NoticeArgs) -> Self {
+        Self {
+            bell: !args.no_bell,
+            reminder: (args.reminder > 0).then(|| Duration::from_secs(args.reminder)),
+        }
+    }
+}
+
+#[derive(Args)]
+struct HttpArgs {
+    /// 待ち受けるポート番号
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+}
+
+impl From<HttpArgs> for HttpOptions {
+    fn from(args: HttpArgs) -> Self {
+        Self { port: args.port }
+    }
+}
+
+#[derive(Args)]
+struct RulesArgs {
+    #[command(flatten)]
+    rules: RuleArgs,
+    /// 外部のクライアントが読み取れるようにJSONで表示する(serdeフィーチャーが必要)
+    #[arg(long)]
+    json: bool,
+}
+
+impl From<RulesArgs> for RulesOptions {
+    fn from(args: RulesArgs) -> Self {
+        Self {
+            rules: args.rules.into(),
+            json: args.json,
+        }
+    }
+}
+
+// 4人分の戦略をカンマ区切りで並べた席
+#[derive(Clone)]
+struct Seats(Vec<Strategy>);
+
+impl FromStr for Seats {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let seats = s
+            .split(',')
+            .map(|name| name.trim().parse::<Strategy>())
+            .collect::<Result<Vec<_>, _>>()?;
+        match seats.len() {
+            4 => Ok(Seats(seats)),
+            _ => Err("4人分の戦略をカンマ区切りで指定してください".to_owned()),
+        }
+    }
+}
+
+// min以上の整数を受け付ける
+fn at_least<T>(min: T) -> impl Fn(&str) -> Result<T, String> + Clone
+where
+    T: FromStr + PartialOrd + Display + Copy,
+{
+    move |s| match s.parse::<T>() {
+        Ok(n) if n >= min => Ok(n),
+        _ => Err(format!("{min}以上の整数を指定してください")),
+    }
+}
+
+// min以上max以下の整数を受け付ける
+fn in_range(min: usize, max: usize) -> impl Fn(&str) -> Result<usize, String> + Clone {
+    move |s| match s.parse::<usize>() {
+        Ok(n) if (min..=max).contains(&n) => Ok(n),
+        _ => Err(format!("{min}から{max}の整数を指定してください")),
+    }
+}
+
+fn parse_speed(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err("正の倍率を指定してください".to_owned()),
+    }
+}
+
+// 階段の最小の枚数は0(階段なし)か、3から13
+fn parse_min_seq(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if n == 0 || (MIN_SEQ..=RANKS.len()).contains(&n) => Ok(n),
+        _ => Err(format!(
+            "0(階段なし)か{MIN_SEQ}から{}の整数を指定してください",
+            RANKS.len()
+        )),
+    }
+}
+
+fn parse_timeout_action(s: &str) -> Result<TimeoutAction, String> {
+    match s {
+        "pass" => Ok(TimeoutAction::Pass),
+        "lowest" => Ok(TimeoutAction::Lowest),
+        _ => Err("passかlowestを指定してください".to_owned()),
+    }
+}
+
+// 空白だけではない名前を受け付ける
+fn parse_name(s: &str) -> Result<String, String> {
+    match s.trim().is_empty() {
+        true => Err("名前を指定してください".to_owned()),
+        false => Ok(s.to_owned()),
+    }
+}
+
+// 組み込みのプリセットか設定ファイルに定義したプリセットを受け付ける
+fn parse_preset(s: &str) -> Result<RuleSet, String> {
+    find_preset(s, &load_custom_presets())
+}
+
+#[cfg(feature = "serde")]
+fn load_custom_presets() -> BTreeMap<String, RuleSet> {
+    crate::config::get_config_path()
+        .and_then(|path| crate::config::load_config(&path).ok())
+        .and_then(|config| config.presets)
+        .unwrap_or_default()
+}
+
+#[cfg(not(feature = "serde"))]
+fn load_custom_presets() -> BTreeMap<String, RuleSet> {
+    BTreeMap::new()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let options = Options::parse(vec![]).unwrap();
+        assert!(options.color);
+        assert!(!options.ascii);
+        assert!(!options.quiet);
+        assert!(!options.analyze);
+        assert!(
+            Options::parse(["--analyze"].map(String::from))
+                .unwrap()
+                .analyze
+        );
+        assert!(Options::parse(vec!["--quiet".to_owned()]).unwrap().quiet);
+        assert!(!options.counter);
+        assert!(
+            Options::parse(vec!["--counter".to_owned()])
+                .unwrap()
+                .counter
+        );
+        let options = Options::parse(vec!["--no-color".to_owned()]).unwrap();
+        assert!(!options.color);
+        assert!(matches!(
+            Command::parse(["stats"].map(String::from)),
+            Ok(Command::Stats)
+        ));
+        let options = Options::parse(vec!["--ascii".to_owned(), "--no-color".to_owned()]).unwrap();
+        assert!(options.ascii);
+        let Ok(Command::Resume(path, options)) =
+            Command::parse(["resume", "save.json", "--no-color"].map(String::from))
+        else {
+            panic!("resumeとして解析されませんでした");
+        };
+        assert_eq!(path, PathBuf::from("save.json"));
+        assert!(!options.color);
+        assert!(!options.color);
+        assert!(Options::parse(vec!["--color".to_owned()]).is_err());
+        assert_eq!(options.humans, 1);
+        let options = Options::parse(vec!["--hotseat".to_owned(), "3".to_owned()]).unwrap();
+        assert_eq!(options.humans, 3);
+        let args = ["--humans", "2", "--name", "Alice,Bob"];
+        let options = Options::parse(args.map(String::from)).unwrap();
+        assert_eq!(options.humans, 2);
+        assert_eq!(options.names, vec!["Alice", "Bob"]);
+        for value in ["1", "5", "x"] {
+            assert!(Options::parse(vec!["--hotseat".to_owned(), value.to_owned()]).is_err());
+        }
+        assert!(Options::parse(vec!["--hotseat".to_owned()]).is_err());
+        assert_eq!(options.rules, RuleSet::default());
+        let options = Options::parse(["--seq-rule", "extreme"].map(String::from)).unwrap();
+        assert_eq!(options.rules.seq, SeqRule::Extreme);
+        assert!(options.shuffle_seats);
+        let args = ["--lead-rule", "rotate", "--no-shuffle-seats"];
+        let options = Options::parse(args.map(String::from)).unwrap();
+        assert_eq!(options.rules.lead, LeadRule::Rotate);
+        assert!(!options.shuffle_seats);
+        let options = Options::parse(["--lead-pass", "skip"].map(String::from)).unwrap();
+        assert_eq!(options.rules.lead_pass, LeadPassRule::Skip);
+        assert!(Options::parse(["--lead-pass", "pass"].map(String::from)).is_err());
+        let options = Options::parse(["--foul", "void"].map(String::from)).unwrap();
+        assert_eq!(options.rules.foul, FoulRule::Void);
+        for (value, expected) in [("4", Some(4)), ("0", Some(0)), ("2", None), ("14", None)] {
+            let options = Options::parse(["--min-seq", value].map(String::from));
+            assert_eq!(options.ok().map(|options| options.rules.min_seq), expected);
+        }
+        assert_eq!(options.rounds, None);
+        let options = Options::parse(["--rounds", "3"].map(String::from)).unwrap();
+        assert_eq!(options.rounds, Some(3));
+        assert!(!options.open_hands);
+        assert!(!options.candidates);
+        assert!(!options.explain_ai);
+        assert!(!options.debug);
+        assert!(Options::parse(["--debug"].map(String::from)).unwrap().debug);
+        assert!(
+            Options::parse(["--explain-ai"].map(String::from))
+                .unwrap()
+                .explain_ai
+        );
+        assert!(
+            Options::parse(["--candidates"].map(String::from))
+                .unwrap()
+                .candidates
+        );
+        let options = Options::parse(["--humans", "0", "--open-hands"].map(String::from)).unwrap();
+        assert_eq!(options.humans, 0);
+        assert!(options.open_hands);
+        for args in [
+            vec!["--lead-rule", "king"],
+            vec!["--rounds", "0"],
+            vec!["--humans", "5"],
+            vec!["--humans", "2", "--hotseat", "2"],
+            vec!["--seq-rule"],
+            vec!["--seq-rule", "official"],
+            vec!["--preset"],
+            vec!["--preset", "osaka"],
+        ] {
+            assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+        }
+        // プリセットの前後どちらに指定しても階段のルールが優先される
+        for args in [
+            ["--seq-rule", "extreme", "--preset", "kanto"],
+            ["--preset", "kanto", "--seq-rule", "extreme"],
+        ] {
+            let rules = Options::parse(args.map(String::from)).unwrap().rules;
+            assert!(rules.jack_back);
+            assert_eq!(rules.seq, SeqRule::Extreme);
+        }
+        let rules =
+            Options::parse(["--preset", "tournament", "--foul", "demote"].map(String::from))
+                .unwrap()
+                .rules;
+        assert!(rules.no_joker_lead);
+        assert_eq!(rules.foul, FoulRule::Demote);
+        assert_eq!(options.lang, None);
+        let options = Options::parse(["--lang", "en"].map(String::from)).unwrap();
+        assert_eq!(options.lang, Some(Lang::En));
+        assert!(Options::parse(["--lang", "fr"].map(String::from)).is_err());
+        assert!(options.names.is_empty());
+        assert!(options.npc_names.is_empty());
+        let args = [
+            "--name",
+            "Alice",
+            "--npc-names",
+            "Bob,Carol",
+            "--random-names",
+        ];
+        let options = Options::parse(args.map(String::from)).unwrap();
+        assert_eq!(options.names, vec!["Alice"]);
+        assert_eq!(options.npc_names, vec!["Bob", "Carol"]);
+        assert!(options.random_names);
+        assert!(Options::parse(["--name", " "].map(String::from)).is_err());
+        assert!(Options::parse(["--npc-names", "Bob,,Carol"].map(String::from)).is_err());
+        assert!(!options.select_rules);
+        assert!(
+            Options::parse(["--select-rules"].map(String::from))
+                .unwrap()
+                .select_rules
+        );
+        assert!(options.timer.is_none());
+        assert!(options.kifu.is_none());
+        let options = Options::parse(["--kifu", "game.kifu"].map(String::from)).unwrap();
+        assert_eq!(options.kifu, Some(PathBuf::from("game.kifu")));
+        assert!(Options::parse(["--kifu"].map(String::from)).is_err());
+        assert!(options.seed.is_none());
+        let options = Options::parse(["--seed", "42"].map(String::from)).unwrap();
+        assert_eq!(options.seed, Some(42));
+        let options = Options::parse(["--log-file", "game.jsonl"].map(String::from)).unwrap();
+        assert_eq!(options.log_file, Some(PathBuf::from("game.jsonl")));
+        assert!(Options::parse(["--log-file"].map(String::from)).is_err());
+        let options = Options::parse(["--policy", "policy.onnx"].map(String::from)).unwrap();
+        assert_eq!(options.policy, Some(PathBuf::from("policy.onnx")));
+        assert!(Options::parse(["--policy"].map(String::from)).is_err());
+        assert_eq!(options.speed, 1.0);
+        let options = Options::parse(["--speed", "2.5"].map(String::from)).unwrap();
+        assert_eq!(options.speed, 2.5);
+        assert_eq!(options.thinking, vec![ThinkingTime::DEFAULT]);
+        let args = ["--thinking-time", "0,500-1500"];
+        let options = Options::parse(args.map(String::from)).unwrap();
+        assert_eq!(
+            options.thinking,
+            vec![
+                ThinkingTime { min: 0, max: 0 },
+                ThinkingTime {
+                    min: 500,
+                    max: 1500
+                },
+            ]
+        );
+        let args = ["--timeout-action", "lowest", "--turn-timeout", "30"];
+        let options = Options::parse(args.map(String::from)).unwrap();
+        let timer = options.timer.unwrap();
+        assert_eq!(timer.timeout, Duration::from_secs(30));
+        assert_eq!(timer.action, TimeoutAction::Lowest);
+        assert_eq!(timer.afk_turns, None);
+        let args = ["--turn-timeout", "5", "--afk-turns", "3"];
+        let options = Options::parse(args.map(String::from)).unwrap();
+        assert_eq!(options.timer.unwrap().action, TimeoutAction::Pass);
+        assert_eq!(options.timer.unwrap().afk_turns, Some(3));
+        assert_eq!(
+            options.notice,
+            TurnNotice {
+                bell: true,
+                reminder: Some(Duration::from_secs(30)),
+            }
+        );
+        let args = ["--no-bell", "--reminder", "0"];
+        let options = Options::parse(args.map(String::from)).unwrap();
+        assert_eq!(options.notice, TurnNotice::default());
+        for args in [
+            vec!["--turn-timeout", "0"],
+            vec!["--turn-timeout", "-1"],
+            vec!["--timeout-action", "random"],
+            vec!["--afk-turns", "3"],
+            vec!["--turn-timeout", "5", "--afk-turns", "0"],
+            vec!["--speed", "0"],
+            vec!["--speed", "fast"],
+            vec!["--thinking-time", "900-100"],
+        ] {
+            assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_cli() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_simulate() {
+        let args = [
+            "simulate",
+            "--games",
+            "10",
+            "--seats",
+            "min,min,heuristic,mcts",
+            "--seed",
+            "7",
+            "--output",
+            "results.csv",
+            "--dataset",
+            "selfplay.npz",
+        ];
+        let Ok(Command::Simulate(options)) = Command::parse(args.map(String::from)) else {
+            panic!("simulateとして解析されませんでした");
+        };
+        assert_eq!(options.games, 10);
+        assert_eq!(
+            options.seats,
+            vec![
+                Strategy::Min,
+                Strategy::Min,
+                Strategy::Heuristic,
+                Strategy::Mcts
+            ]
+        );
+        assert_eq!(options.seed, 7);
+        assert_eq!(options.output, Some(PathBuf::from("results.csv")));
+        assert_eq!(options.dataset, Some(PathBuf::from("selfplay.npz")));
+        let args = ["arena", "--games", "50", "--seed", "3", "--duplicate"];
+        let Ok(Command::Arena(options)) = Command::parse(args.map(String::from)) else {
+            panic!("arenaとして解析されませんでした");
+        };
+        assert_eq!(options.games, 50);
+        assert_eq!(options.seed, 3);
+        assert!(options.duplicate);
+        assert_eq!(options.output, None);
+        let args = [
+            "tournament",
+            "--players",
+            "12",
+            "--tables",
+            "3",
+            "--humans",
+            "1",
+            "--npcs",
+            "min,heuristic",
+            "--pairing",
+            "swiss",
+        ];
+        let Ok(Command::Tournament(options)) = Command::parse(args.map(String::from)) else {
+            panic!("tournamentとして解析されませんでした");
+        };
+        assert_eq!(
+            (
+                options.players,
+                options.tables,
+                options.rounds,
+                options.humans
+            ),
+            (12, 3, 10, 1)
+        );
+        assert_eq!(options.npcs, vec![Strategy::Min, Strategy::Heuristic]);
+        assert_eq!(options.pairing, Pairing::Swiss);
+        assert!(options.color);
+        let args = [
+            "tune",
+            "--generations",
+            "5",
+            "--population",
+            "6",
+            "--games",
+            "20",
+            "--seed",
+            "9",
+            "--write",
+        ];
+        let Ok(Command::Tune(options)) = Command::parse(args.map(String::from)) else {
+            panic!("tuneとして解析されませんでした");
+        };
+        assert_eq!(
+            (options.generations, options.population, options.games),
+            (5, 6, 20)
+        );
+        assert_eq!(options.seed, 9);
+        assert!(options.write);
+        let args = [
+            "bench",
+            "--games",
+            "500",
+            "--seats",
+            "min,min,min,heuristic",
+        ];
+        let Ok(Command::Bench(options)) = Command::parse(args.map(String::from)) else {
+            panic!("benchとして解析されませんでした");
+        };
+        assert_eq!(options.games, 500);
+        assert_eq!(options.seats[3], Strategy::Heuristic);
+        assert_eq!(options.seed, 0);
+        let Ok(Command::Replay(options)) =
+            Command::parse(["replay", "game.kifu", "--ascii"].map(String::from))
+        else {
+            panic!("replayとして解析されませんでした");
+        };
+        assert_eq!(options.path, PathBuf::from("game.kifu"));
+        assert!(options.color);
+        assert!(options.ascii);
+        let Ok(Command::Verify(path)) = Command::parse(["verify", "game.kifu"].map(String::from))
+        else {
+            panic!("verifyとして解析されませんでした");
+        };
+        assert_eq!(path, PathBuf::from("game.kifu"));
+        let args = [
+            "whatif",
+            "game.kifu",
+            "--round",
+            "2",
+            "--turn",
+            "14",
+            "--npc",
+            "mcts",
+        ];
+        let Ok(Command::WhatIf(options)) = Command::parse(args.map(String::from)) else {
+            panic!("whatifとして解析されませんでした");
+        };
+        assert_eq!(options.path, PathBuf::from("game.kifu"));
+        assert_eq!((options.round, options.turn, options.seat), (2, 14, 0));
+        assert_eq!(options.npc, Strategy::Mcts);
+        let Ok(Command::Tutorial(options)) =
+            Command::parse(["tutorial", "--no-color"].map(String::from))
+        else {
+            panic!("tutorialとして解析されませんでした");
+        };
+        assert!(!options.color);
+        assert!(!options.ascii);
+        let args = [
+            "serve",
+            "--port",
+            "9000",
+            "--clients",
+            "3",
+            "--rounds",
+            "2",
+            "--ws",
+            "--reconnect-turns",
+            "3",
+            "--lobby",
+            "--spectators",
+            "1",
+            "--npc",
+            "heuristic",
+        ];
+        let Ok(Command::Serve(options)) = Command::parse(args.map(String::from)) else {
+            panic!("serveとして解析されませんでした");
+        };
+        assert_eq!(
+            (options.port, options.clients, options.rounds),
+            (9000, 3, 2)
+        );
+        assert!(options.ws);
+        assert_eq!(options.reconnect_turns, 3);
+        assert_eq!(options.turn_timeout, Some(Duration::from_secs(60)));
+        assert!(options.lobby);
+        assert_eq!(options.spectators, 1);
+        assert_eq!(options.npc, Strategy::Heuristic);
+        let args = ["join", "localhost:7777", "--name", "Alice", "--no-color"];
+        let Ok(Command::Join(options)) = Command::parse(args.map(String::from)) else {
+            panic!("joinとして解析されませんでした");
+        };
+        assert_eq!(options.addr, "localhost:7777");
+        assert_eq!(options.name, "Alice");
+        assert_eq!(options.token, None);
+        assert_eq!(options.table, None);
+        assert!(!options.spectate);
+        assert!(options.notice.bell);
+        assert!(!options.color);
+        let args = ["join", "a:1", "--create", "Room", "--npc", "mcts"];
+        let Ok(Command::Join(options)) = Command::parse(args.map(String::from)) else {
+            panic!("joinとして解析されませんでした");
+        };
+        assert_eq!(options.create.as_deref(), Some("Room"));
+        assert_eq!(options.preset, "standard");
+        assert_eq!(options.npc, Strategy::Mcts);
+        let Ok(Command::Http(options)) = Command::parse(["http"].map(String::from)) else {
+            panic!("httpとして解析されませんでした");
+        };
+        assert_eq!(options.port, 8080);
+        let args = [
+            "rules",
+            "--preset",
+            "kanto",
+            "--lead-rule",
+            "winner",
+            "--json",
+        ];
+        let Ok(Command::Rules(options)) = Command::parse(args.map(String::from)) else {
+            panic!("rulesとして解析されませんでした");
+        };
+        assert!(options.json);
+        assert_eq!(
+            options.rules,
+            RuleSet {
+                lead: LeadRule::Winner,
+                ..crate::rules::Preset::Kanto.get_rules()
+            }
+        );
+        assert!(matches!(
+            Command::parse(["--quiet"].map(String::from)),
+            Ok(Command::Play(_))
+        ));
+        let Ok(Command::Play(options)) = Command::parse(["play", "--quiet"].map(String::from))
+        else {
+            panic!("playとして解析されませんでした");
+        };
+        assert!(options.quiet);
+        // --helpは使い方を表示するためのエラーになる
+        for args in [vec!["--help"], vec!["simulate", "--help"]] {
+            let Err(e) = Command::parse(args.into_iter().map(String::from)) else {
+                panic!("使い方が表示されませんでした");
+            };
+            assert_eq!(e.kind(), clap::error::ErrorKind::DisplayHelp);
+        }
+        for args in [
+            vec!["simulate", "--games", "0"],
+            vec!["simulate", "--seats", "min,min,min"],
+            vec!["simulate", "--seats", "min,min,min,max"],
+            vec!["simulate", "--seed", "x"],
+            vec!["simulate", "--quiet"],
+            vec!["simulate", "--output"],
+            vec!["simulate", "--dataset"],
+            vec!["arena", "--output"],
+            vec!["arena", "--games", "-1"],
+            vec!["arena", "--seats", "min,min,min,min"],
+            vec!["tune", "--generations", "0"],
+            vec!["tune", "--population", "1"],
+            vec!["tune", "--output", "a.csv"],
+            vec!["bench", "--games", "0"],
+            vec!["bench", "--seats", "min,min"],
+            vec!["bench", "--write"],
+            vec!["replay"],
+            vec!["replay", "a.kifu", "b.kifu"],
+            vec!["resume"],
+            vec!["resume", "--quiet"],
+            vec!["stats", "--all"],
+            vec!["serve", "--port", "70000"],
+            vec!["serve", "--spectators", "x"],
+            vec!["serve", "--clients", "5"],
+            vec!["serve", "--rounds", "0"],
+            vec!["join"],
+            vec!["join", "--name", "Alice"],
+            vec!["join", "localhost:7777", "--name"],
+            vec!["join", "a:1", "b:2"],
+            vec!["join", "a:1", "--token"],
+            vec!["join", "a:1", "--table", " "],
+            vec!["join", "a:1", "--npc", "max"],
+            vec!["join", "a:1", "--create", "Room", "--list"],
+            vec!["join", "a:1", "--create", "Room", "--spectate"],
+            vec!["serve", "--reconnect-turns", "-1"],
+            vec!["http", "--port"],
+            vec!["http", "--clients", "1"],
+            vec!["--quiet", "simulate"],
+        ] {
+            assert!(Command::parse(args.into_iter().map(String::from)).is_err());
+        }
+    }
+}
@@ -1,17 +1,294 @@
-use crate::{card::Card, comb::Comb, input::get_input, player::Player, validator::Validator};
+use crate::{
+    card::{
+        cmp_order, cmp_order_reversely, cmp_rank, cmp_rank_reversely, cmp_suit_order,
+        cmp_suit_order_reversely, create_deck, parse_cards, Card, Order, Rank, RANKS, SUITS,
+    },
+    comb::Comb,
+    i18n::{tr, trf},
+    input::{InputSource, StdinInput, INTERRUPT},
+    moves::get_legal_combs,
+    player::{take_from_hands, Action, Player},
+    render::{clear_screen, get_title, Style},
+    rules::{LeadRule, TOGGLE_KEYS},
+    validator::Validator,
+    view::GameView,
+};
 use itertools::Itertools;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const CELL_WIDTH: usize = 7;
+const DEFAULT_SAVE_PATH: &str = "save.json";
+
+// 手札の表示方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    Rank,
+    Suit,
+}
+
+impl Grouping {
+    fn toggle(self) -> Self {
+        match self {
+            Grouping::Rank => Grouping::Suit,
+            Grouping::Suit => Grouping::Rank,
+        }
+    }
+}
+
+// 手札の並び順
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Rank,
+    Suit,
+}
+
+// 持ち時間を過ぎた場合の行動
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutAction {
+    Pass,
+    Lowest,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TurnTimer {
+    pub timeout: Duration,
+    pub action: TimeoutAction,
+    // 続けてこの回数だけ時間切れになると、ラウンドの残りをNPCに任せる
+    pub afk_turns: Option<usize>,
+}
+
+// 手番が来たことを知らせる方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TurnNotice {
+    // 手番の開始時に端末のベルを鳴らす
+    pub bell: bool,
+    // 入力がないまま過ぎると場の状況と入力を促すメッセージを表示し直す時間
+    pub reminder: Option<Duration>,
+}
+
+impl TurnNotice {
+    pub const DEFAULT_REMINDER: Duration = Duration::from_secs(30);
+}
 
 pub struct Pc {
     name: String,
     hands: Vec<Card>,
+    style: Style,
+    grouping: Grouping,
+    sort_order: SortOrder,
+    hotseat: bool,
+    timer: Option<TurnTimer>,
+    deadline: Option<Instant>,
+    notice: TurnNotice,
+    // 入力を待つ間に表示し直す場の状況
+    summary: Option<String>,
+    // 続けて時間切れになった回数
+    timeouts: usize,
+    // ネットワーク対戦ではsayでチャットを送れる
+    chat: bool,
+    counter: bool,
+    // 場に出せる組み合わせに含まれるカード(手札の表示で印を付ける)
+    playable: Vec<Card>,
+    // 場に出せる組み合わせを番号付きで並べて選ばせる
+    candidates: bool,
+    input: Box<dyn InputSource>,
 }
 
 impl Pc {
-    pub fn new(name: String) -> Self {
+    pub fn new(name: String, style: Style, hotseat: bool, timer: Option<TurnTimer>) -> Self {
         Self {
             name,
             hands: vec![],
+            style,
+            grouping: Grouping::Rank,
+            sort_order: SortOrder::Rank,
+            hotseat,
+            timer,
+            deadline: None,
+            notice: TurnNotice::default(),
+            summary: None,
+            timeouts: 0,
+            chat: false,
+            counter: false,
+            playable: vec![],
+            candidates: false,
+            input: Box::new(StdinInput),
+        }
+    }
+
+    pub fn with_counter(mut self, counter: bool) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    pub fn with_candidates(mut self, candidates: bool) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    pub fn with_notice(mut self, notice: TurnNotice) -> Self {
+        self.notice = notice;
+        self
+    }
+
+    // 標準入力の代わりに入力を受け取る先を指定する
+    pub fn with_input(mut self, input: Box<dyn InputSource>) -> Self {
+        self.input = input;
+        self
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn enable_chat(&mut self) {
+        self.chat = true;
+    }
+
+    fn start_timer(&mut self) {
+        self.deadline = self.timer.map(|timer| Instant::now() + timer.timeout);
+    }
+
+    fn read_input(&mut self, mes: String) -> Option<String> {
+        loop {
+            // 持ち時間が設定されていれば残り時間だけ入力を待つ
+            let remaining = self
+                .deadline
+                .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let prompt = match remaining {
+                Some(remaining) => {
+                    let secs = remaining.as_millis().div_ceil(1000);
+                    trf("time_left", &[("secs", &secs), ("message", &mes)])
+                }
+                None => mes.clone(),
+            };
+            let timeout = match (remaining, self.notice.reminder) {
+                (Some(remaining), Some(reminder)) => Some(remaining.min(reminder)),
+                (remaining, reminder) => remaining.or(reminder),
+            };
+            match self.input.get_input(prompt, timeout) {
+                Some(input) => return Some(input),
+                None if timeout.is_none() => return Some(String::new()),
+                None if self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline) =>
+                {
+                    return None;
+                }
+                // 持ち時間が残っていれば知らせ直して入力を待ち続ける
+                None => self.remind(),
+            }
+        }
+    }
+
+    fn ring_bell(&self) {
+        if self.notice.bell {
+            print!("\x07");
+        }
+    }
+
+    fn remind(&self) {
+        println!();
+        self.ring_bell();
+        println!("{}", trf("idle_reminder", &[("name", &self.name)]));
+        if let Some(summary) = &self.summary {
+            println!("{summary}");
         }
+        self.print_hands();
+    }
+
+    fn play_on_timeout(&mut self, validator: &dyn Validator, is_rev: bool) -> Option<Comb> {
+        println!();
+        println!("{}", tr("timeout"));
+        let action = self.timer.map(|timer| timer.action);
+        // パスできなければ最小のカードを出す
+        if action == Some(TimeoutAction::Pass) && validator.can_pass() {
+            return None;
+        }
+        let idx = find_lowest_single(&self.hands, validator, is_rev)?;
+        Some(Comb::Single(self.hands.remove(idx)))
+    }
+
+    // 続けて時間切れになった回数を数え、指定した回数に達したら操作をNPCに任せる
+    fn count_timeout(&mut self) -> bool {
+        self.timeouts += 1;
+        let limit = self.timer.and_then(|timer| timer.afk_turns);
+        if limit.is_some_and(|limit| self.timeouts >= limit) {
+            println!("{}", tr("afk"));
+            self.timeouts = 0;
+            return true;
+        }
+        false
+    }
+
+    fn begin_turn(&mut self) {
+        self.ring_bell();
+        // 他のプレイヤーに手札が見えないように画面を消してから交代する
+        if self.hotseat {
+            clear_screen();
+            let mes = trf("handover", &[("name", &self.name)]);
+            self.input.get_input(mes, None);
+        }
+    }
+
+    fn end_turn(&mut self) {
+        self.playable.clear();
+        self.summary = None;
+        if self.hotseat {
+            clear_screen();
+        }
+    }
+
+    fn apply_sort_order(&mut self, is_rev: bool) {
+        let comparator = match (self.sort_order, is_rev) {
+            (SortOrder::Rank, false) => cmp_order,
+            (SortOrder::Rank, true) => cmp_order_reversely,
+            (SortOrder::Suit, false) => cmp_suit_order,
+            (SortOrder::Suit, true) => cmp_suit_order_reversely,
+        };
+        self.hands.sort_by(comparator);
+    }
+
+    fn set_sort_order(&mut self, sort_order: SortOrder, is_rev: bool) {
+        self.sort_order = sort_order;
+        self.apply_sort_order(is_rev);
+        self.print_hands();
+    }
+
+    // 階段の端のジョーカーが表す数字を選ばせる(何も入力しなければそのまま)
+    fn declare_joker(&mut self, comb: Comb) -> Comb {
+        let choices = comb.get_joker_choices();
+        if choices.len() < 2 {
+            return comb;
+        }
+        let mes = trf("joker_rank", &[("choices", &choices.iter().join("/"))]);
+        loop {
+            let Some(input) = self
+                .read_input(mes.clone())
+                .filter(|input| !input.is_empty())
+            else {
+                return comb;
+            };
+            match input.parse::<Rank>() {
+                Ok(rank) if choices.contains(&rank) => {
+                    return comb.declare_joker(&rank).unwrap_or(comb);
+                }
+                _ => println!(
+                    "{}",
+                    trf("choose_from", &[("choices", &choices.iter().join(", "))])
+                ),
+            }
+        }
+    }
+
+    fn print_hands(&self) {
+        println!(
+            "{}",
+            get_cards_with_indices(&self.hands, &self.style, self.grouping, &self.playable)
+        );
+    }
+
+    fn toggle_grouping(&mut self) {
+        self.grouping = self.grouping.toggle();
+        self.print_hands();
     }
 }
 
@@ -28,119 +305,1073 @@ impl Player for Pc {
         &self.name
     }
 
-    fn get_hands(&mut self) -> &mut Vec<Card> {
-        &mut self.hands
+    fn get_hands(&self) -> &[Card] {
+        &self.hands
+    }
+
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+        take_from_hands(&mut self.hands, cards)
+    }
+
+    fn receive_cards(&mut self, cards: Vec<Card>) {
+        self.hands.extend(cards);
     }
 
-    fn play(&mut self, validator: &dyn Validator) -> Option<Comb> {
+    fn sort_hands(&mut self, order: Order) {
+        self.apply_sort_order(order == Order::Reversed);
+    }
+
+    fn confirm_tribute(&mut self, cards: &[Card]) {
+        self.begin_turn();
+        self.print_hands();
+        let cards = cards.iter().map(|card| self.style.card(card)).join(" ");
+        self.start_timer();
+        self.read_input(trf("confirm_tribute", &[("cards", &cards)]));
+        self.end_turn();
+    }
+
+    fn confirm_return(&mut self) -> bool {
+        self.begin_turn();
+        self.start_timer();
+        let answer = self.read_input(trf("confirm_return", &[("name", &self.name)]));
+        self.end_turn();
+        answer.as_deref() == Some("y")
+    }
+
+    fn play(&mut self, validator: &dyn Validator) -> Action {
+        self.begin_turn();
         let prev_comb = validator.get_prev_comb();
-        let comb_str = match prev_comb {
-            Some(Comb::Single(card)) => format!("({}) ", String::from(card)),
-            Some(Comb::Multi(cards) | Comb::Seq(cards)) => {
-                let str = cards.iter().map(String::from).join(" ");
-                format!("({}) ", str)
+        let view = validator.get_view();
+        let summary = get_status(&view, &self.style);
+        println!("{summary}");
+        self.summary = Some(summary);
+        if self.counter {
+            for line in get_counter_lines(&self.hands, validator.get_history()) {
+                println!("{line}");
             }
-            None => "".to_owned(),
+        }
+        // 設定された並び順で手札を並べ替える
+        self.apply_sort_order(view.is_rev);
+        // 場にカードがなければどのカードも出せるので印を付けない
+        if prev_comb.is_some() {
+            self.playable = get_playable_cards(&self.hands, validator);
+        }
+        self.print_hands();
+        // 候補の番号は手札を並べ替えても変わらない
+        let candidates: Vec<Comb> = match self.candidates {
+            true => get_legal_combs(&self.hands, validator)
+                .into_iter()
+                .map(|(comb, _)| comb)
+                .collect(),
+            false => vec![],
         };
-        println!("{}", get_cards_with_indices(&self.hands));
+        if self.candidates {
+            for line in get_candidate_lines(&candidates, validator.can_pass(), &self.style) {
+                println!("{line}");
+            }
+        }
+        self.start_timer();
         loop {
-            let input = get_input(format!("カードの番号{}: ", comb_str));
-            if input.is_empty() && prev_comb.is_some() {
-                return None;
+            let Some(input) = self.read_input(tr("card_numbers").to_owned()) else {
+                let comb = self.play_on_timeout(validator, view.is_rev);
+                let away = self.count_timeout();
+                self.end_turn();
+                return match away {
+                    true => Action::Away(comb),
+                    false => Action::Move(comb),
+                };
+            };
+            self.timeouts = 0;
+            if is_toggle(&input) {
+                self.toggle_grouping();
+                continue;
             }
-            let result = parse_idx(&input);
-            if result.is_err() {
+            if let Some(sort_order) = parse_sort(&input) {
+                self.set_sort_order(sort_order, view.is_rev);
                 continue;
             }
-            let indices = result.unwrap();
-            let result = get_cards(&indices, &self.hands);
-            if result.is_err() {
+            if is_help(&input) {
+                for line in get_rules_lines(&view, &self.style) {
+                    println!("{line}");
+                }
+                continue;
+            }
+            if is_log(&input) {
+                for line in get_log_lines(validator.get_history(), &view, &self.style) {
+                    println!("{line}");
+                }
                 continue;
             }
-            match conver_to_comb(result.unwrap()) {
+            if is_quit(&input) {
+                // 手番を進めずに中断する(保存するかを選べる)
+                println!();
+                let answer = self
+                    .input
+                    .get_input(tr("save_before_quit").to_owned(), None);
+                let path = match answer.as_deref() {
+                    Some("y") => Some(PathBuf::from(DEFAULT_SAVE_PATH)),
+                    _ => None,
+                };
+                self.end_turn();
+                return Action::Quit(path);
+            }
+            if is_undo(&input) {
+                // 手番を進めずに前の手番に戻す
+                self.end_turn();
+                return Action::Undo;
+            }
+            if let Some(path) = parse_save(&input) {
+                // 手番を進めずにゲームを保存する
+                self.end_turn();
+                return Action::Save(path);
+            }
+            if let Some(text) = parse_chat(&input).filter(|_| self.chat) {
+                // 手番を進めずにチャットを送る
+                self.end_turn();
+                return Action::Chat(text);
+            }
+            let candidate = match self.candidates {
+                true => parse_candidate(&input, candidates.len()),
+                false => None,
+            };
+            if let Some(Err(max)) = candidate {
+                println!("{}", trf("enter_candidate", &[("max", &max)]));
+                continue;
+            }
+            if let Some(Ok(n)) = candidate.filter(|n| *n != Ok(0)) {
+                let comb = self.declare_joker(candidates[n - 1].clone());
+                if validator.is_valid(&comb) {
+                    take_from_hands(&mut self.hands, comb.get_cards());
+                    self.end_turn();
+                    return Action::Move(Some(comb));
+                }
+                println!("{}", tr("invalid_comb"));
+                continue;
+            }
+            if is_pass(&input) || candidate.is_some() {
+                if validator.can_pass() {
+                    self.end_turn();
+                    return Action::Move(None);
+                }
+                // 場にカードがない場合はルールによってパスできない
+                println!("{}", tr("cannot_pass"));
+                continue;
+            }
+            let (indices, cards) = match select_cards(&input, &self.hands) {
+                Ok(selected) => selected,
+                Err(e) => {
+                    println!("{}", e.get_message(&self.style));
+                    continue;
+                }
+            };
+            let min_seq = validator.get_rules().min_seq;
+            let comb = conver_to_comb(cards, min_seq).map(|comb| self.declare_joker(comb));
+            match comb {
                 Ok(comb) if validator.is_valid(&comb) => {
                     // 手札からカードを除く
                     for i in indices.iter().rev() {
                         self.hands.remove(*i);
                     }
-                    return Some(comb);
+                    self.end_turn();
+                    return Action::Move(Some(comb));
                 }
                 _ => {
-                    println!("無効な組み合わせ");
+                    println!("{}", tr("invalid_comb"));
                 }
             }
         }
     }
 
     fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
-        println!("{}", get_cards_with_indices(&self.hands));
+        self.begin_turn();
+        self.apply_sort_order(false);
+        self.print_hands();
+        self.start_timer();
         loop {
-            let input = get_input(format!("不要なカードを{}枚選択: ", cards_count));
-            let result = parse_idx(&input);
-            if result.is_err() {
+            let mes = trf("needless_cards", &[("count", &cards_count)]);
+            let Some(input) = self.read_input(mes) else {
+                // 時間切れの場合は弱いカードから選ぶ
+                println!();
+                println!("{}", tr("timeout"));
+                self.hands.sort_by(cmp_order);
+                self.end_turn();
+                return self.hands.drain(0..cards_count).collect();
+            };
+            if is_toggle(&input) {
+                self.toggle_grouping();
                 continue;
             }
-            let indices = result.unwrap();
-            let result = get_cards(&indices, &self.hands);
-            if result.is_err() {
+            if let Some(sort_order) = parse_sort(&input) {
+                self.set_sort_order(sort_order, false);
                 continue;
             }
+            let (indices, cards) = match select_cards(&input, &self.hands) {
+                Ok((indices, _)) if indices.len() != cards_count => {
+                    println!("{}", trf("select_count", &[("count", &cards_count)]));
+                    continue;
+                }
+                Ok(selected) => selected,
+                Err(e) => {
+                    println!("{}", e.get_message(&self.style));
+                    continue;
+                }
+            };
             // 手札からカードを除く
             for i in indices.iter().rev() {
                 self.hands.remove(*i);
             }
-            return result.unwrap();
+            self.end_turn();
+            return cards;
         }
     }
 }
 
-fn get_cards_with_indices(cards: &[Card]) -> String {
+pub fn get_status(view: &GameView, style: &Style) -> String {
+    let comb = match &view.prev_comb {
+        Some(comb) => style.comb(comb),
+        None => tr("none").to_owned(),
+    };
+    let bind = match &view.bind_suits {
+        Some(suits) => suits.iter().map(|s| style.suit(s)).join(" "),
+        None => tr("none").to_owned(),
+    };
+    let mut rev = match view.is_rev {
+        true => tr("yes"),
+        false => tr("none"),
+    }
+    .to_owned();
+    if view.is_jack_back {
+        rev.push_str(tr("jack_back_active"));
+    }
+    let players = view
+        .players
+        .iter()
+        .map(|(name, count)| format!("{name}[{count}]"))
+        .join(" ");
+    // パスしたプレイヤーと、場のカードにまだ出せるプレイヤーを並べる
+    let mut pass = view.pass_count.to_string();
+    if !view.passed.is_empty() {
+        let names = |seats: &[usize]| {
+            seats
+                .iter()
+                .filter_map(|idx| view.players.get(*idx))
+                .map(|(name, _)| name)
+                .join(" ")
+        };
+        pass.push_str(&format!(
+            " {} ({}: {})",
+            names(&view.passed),
+            tr("status_responders"),
+            names(&view.get_responders())
+        ));
+    }
+    let mut lines = vec![
+        "-".repeat(32),
+        format!("{}: {comb}", tr("status_field")),
+        format!("{}: {bind}", tr("status_bind")),
+        format!("{}: {rev}", tr("status_rev")),
+        format!("{}: {players}", tr("status_hands")),
+        format!("{}: {pass}", tr("status_pass")),
+    ];
+    // 上がったプレイヤーがいれば途中の順位を表示する
+    let standings = view
+        .places
+        .iter()
+        .zip(&view.players)
+        .filter_map(|(place, (name, _))| place.map(|place| (place, name)))
+        .sorted()
+        .map(|(place, name)| format!("{} {name}", get_title(place, view.players.len())))
+        .join(" / ");
+    if !standings.is_empty() {
+        lines.push(format!("{}: {standings}", tr("status_standings")));
+    }
+    lines.push("-".repeat(32));
+    lines.join("\n")
+}
+
+// ?で表示する現在のルールと場の強さの説明
+pub fn get_rules_lines(view: &GameView, style: &Style) -> Vec<String> {
+    let toggles = view.rules.get_toggles();
+    let get_names = |on: bool| {
+        let names: Vec<&str> = TOGGLE_KEYS
+            .iter()
+            .zip(toggles)
+            .filter(|(_, toggle)| *toggle == on)
+            .map(|(key, _)| tr(key))
+            .collect();
+        match names.is_empty() {
+            true => tr("none").to_owned(),
+            false => names.join(", "),
+        }
+    };
+    // 革命と11バックが重なると元の強さに戻る
+    let is_reversed = view.is_rev != view.is_jack_back;
+    let mut ranks: Vec<String> = RANKS.iter().map(|r| r.to_string()).collect();
+    if is_reversed {
+        ranks.reverse();
+    }
+    ranks.push(Card::Joker.to_string());
+    let mut strength = ranks.join(" < ");
+    if is_reversed {
+        strength.push_str(tr("rules_reversed"));
+    }
+    let bind = match &view.bind_suits {
+        Some(suits) => suits.iter().map(|s| style.suit(s)).join(" "),
+        None => tr("none").to_owned(),
+    };
+    let lead = match view.rules.lead {
+        LeadRule::Loser => tr("rules_lead_loser"),
+        LeadRule::Rotate => tr("rules_lead_rotate"),
+        LeadRule::Winner => tr("rules_lead_winner"),
+        LeadRule::Diamond => tr("rules_lead_diamond"),
+        LeadRule::Cut => tr("rules_lead_cut"),
+    };
+    vec![
+        style.bold(tr("rules_title")),
+        trf("rules_on", &[("names", &get_names(true))]),
+        trf("rules_off", &[("names", &get_names(false))]),
+        trf("rules_strength", &[("ranks", &strength)]),
+        format!("{}: {bind}", tr("status_bind")),
+        trf("rules_lead", &[("lead", &lead)]),
+    ]
+}
+
+// 番号付きの候補の一覧(1行に6つ、場にカードがあれば0でパスできる)
+fn get_candidate_lines(candidates: &[Comb], can_pass: bool, style: &Style) -> Vec<String> {
+    let pass = can_pass.then(|| format!("0: {}", tr("pass")));
+    pass.into_iter()
+        .chain(
+            candidates
+                .iter()
+                .enumerate()
+                .map(|(i, comb)| format!("{}: {}", i + 1, style.comb(comb))),
+        )
+        .chunks(6)
+        .into_iter()
+        .map(|mut chunk| chunk.join("  "))
+        .collect()
+}
+
+// 候補の番号(0はパス)、範囲外なら候補の数をErrで返す。数でなければNone
+fn parse_candidate(input: &str, len: usize) -> Option<Result<usize, usize>> {
+    let n = input.parse::<usize>().ok()?;
+    Some(match n <= len {
+        true => Ok(n),
+        false => Err(len),
+    })
+}
+
+// 場に出せる組み合わせのいずれかに含まれるカード
+fn get_playable_cards(cards: &[Card], validator: &dyn Validator) -> Vec<Card> {
+    get_legal_combs(cards, validator)
+        .iter()
+        .flat_map(|(comb, _)| comb.get_cards())
+        .copied()
+        .unique()
+        .collect()
+}
+
+// playableに含まれるカードには*を付ける
+fn get_cards_with_indices(
+    cards: &[Card],
+    style: &Style,
+    grouping: Grouping,
+    playable: &[Card],
+) -> String {
+    let cell = |i: usize| get_cell(i, &cards[i], style, playable.contains(&cards[i]));
+    let mut rows = match grouping {
+        Grouping::Rank => get_rows_grouped_by_rank(cards, &cell),
+        Grouping::Suit => get_rows_grouped_by_suit(cards, style, &cell),
+    };
+    // ジョーカーは最後の行にまとめる
+    let jokers = (0..cards.len())
+        .filter(|i| cards[*i] == Card::Joker)
+        .map(cell)
+        .join(" ");
+    if !jokers.is_empty() {
+        rows.push(format!("JK| {jokers}"));
+    }
+    rows.iter().map(|row| row.trim_end()).join("\n")
+}
+
+fn get_rows_grouped_by_rank(cards: &[Card], cell: &dyn Fn(usize) -> String) -> Vec<String> {
+    // 手札に現れる順に数字毎の行を作り、スート毎に列を揃える
     cards
         .iter()
-        .enumerate()
-        .map(|(idx, card)| format!("{:2}:{}", idx, String::from(card)))
-        .join("\n")
+        .filter_map(|card| match card {
+            Card::Normal(_, r) => Some(*r),
+            Card::Joker => None,
+        })
+        .unique()
+        .map(|rank| {
+            let cells = SUITS
+                .iter()
+                .map(|suit| {
+                    let card = Card::Normal(*suit, rank);
+                    match cards.iter().position(|c| *c == card) {
+                        Some(i) => cell(i),
+                        None => " ".repeat(CELL_WIDTH),
+                    }
+                })
+                .join(" ");
+            format!("{rank:>2}| {cells}")
+        })
+        .collect()
+}
+
+fn get_rows_grouped_by_suit(
+    cards: &[Card],
+    style: &Style,
+    cell: &dyn Fn(usize) -> String,
+) -> Vec<String> {
+    // スート毎の行に手札の順でカードを並べる
+    SUITS
+        .iter()
+        .filter_map(|suit| {
+            let cells = (0..cards.len())
+                .filter(|i| matches!(cards[*i], Card::Normal(s, _) if s == *suit))
+                .map(cell)
+                .join(" ");
+            let label = style.suit_text(suit);
+            let pad = " ".repeat(2usize.saturating_sub(get_width(&label)));
+            (!cells.is_empty()).then(|| format!("{pad}{}| {cells}", style.suit(suit)))
+        })
+        .collect()
+}
+
+fn get_cell(idx: usize, card: &Card, style: &Style, playable: bool) -> String {
+    let mark = if playable { "*" } else { "" };
+    // 色を付ける前の文字列の幅で列を揃える
+    let text = format!("{:2}:{}{mark}", idx, style.card_text(card));
+    let pad = " ".repeat(CELL_WIDTH.saturating_sub(get_width(&text)));
+    format!("{:2}:{}{mark}{}", idx, style.card(card), pad)
+}
+
+fn get_width(text: &str) -> usize {
+    // 異体字セレクタは幅に含めない
+    text.chars()
+        .filter(|c| !matches!(c, '\u{FE0E}' | '\u{FE0F}'))
+        .count()
+}
+
+fn find_lowest_single(cards: &[Card], validator: &dyn Validator, is_rev: bool) -> Option<usize> {
+    // 場に出せる1枚のカードのうち最も弱いもののインデックスを探す
+    let comparator = match is_rev {
+        true => cmp_rank_reversely,
+        false => cmp_rank,
+    };
+    (0..cards.len())
+        .filter(|i| validator.is_valid(&Comb::Single(cards[*i])))
+        .min_by(|i, j| comparator(&cards[*i], &cards[*j]))
+}
+
+fn is_toggle(input: &str) -> bool {
+    matches!(input, "v" | "view")
 }
 
-fn conver_to_comb(cards: Vec<Card>) -> Result<Comb, ()> {
+// 山札から手札と場に出されたカードを除いた、まだ見えていないカードの枚数を数字毎に数える
+fn count_unseen(hands: &[Card], history: &[(usize, Comb)]) -> Vec<(Option<Rank>, usize)> {
+    let seen: Vec<&Card> = hands
+        .iter()
+        .chain(history.iter().flat_map(|(_, comb)| comb.get_cards()))
+        .collect();
+    let count = |cards: &[&Card], rank: Option<Rank>| {
+        cards
+            .iter()
+            .filter(|card| match card {
+                Card::Normal(_, r) => Some(*r) == rank,
+                Card::Joker => rank.is_none(),
+            })
+            .count()
+    };
+    let deck = create_deck();
+    let deck: Vec<&Card> = deck.iter().collect();
+    RANKS
+        .into_iter()
+        .map(Some)
+        .chain([None])
+        .map(|rank| (rank, count(&deck, rank).saturating_sub(count(&seen, rank))))
+        .collect()
+}
+
+fn get_counter_lines(hands: &[Card], history: &[(usize, Comb)]) -> Vec<String> {
+    let unseen = count_unseen(hands, history);
+    let ranks: String = unseen
+        .iter()
+        .map(|(rank, _)| match rank {
+            Some(rank) => format!("{rank:>3}"),
+            None => " JK".to_owned(),
+        })
+        .collect();
+    let counts: String = unseen
+        .iter()
+        .map(|(_, count)| format!("{count:>3}"))
+        .collect();
+    vec![
+        format!("{}{ranks}", tr("counter_ranks")),
+        format!("{}{counts}", tr("counter_left")),
+    ]
+}
+
+fn is_quit(input: &str) -> bool {
+    matches!(input, "q" | "quit" | INTERRUPT)
+}
+
+fn is_help(input: &str) -> bool {
+    matches!(input, "?" | "help")
+}
+
+fn is_log(input: &str) -> bool {
+    matches!(input, "log")
+}
+
+fn is_undo(input: &str) -> bool {
+    matches!(input, "undo")
+}
+
+// このラウンドで場に出されたカードを出した順に並べる
+fn get_log_lines(history: &[(usize, Comb)], view: &GameView, style: &Style) -> Vec<String> {
+    if history.is_empty() {
+        return vec![tr("no_plays").to_owned()];
+    }
+    let count: usize = history.iter().map(|(_, comb)| comb.get_cards().len()).sum();
+    let mut lines = vec![style.bold(&trf("played_cards", &[("count", &count)]))];
+    lines.extend(history.iter().map(|(idx, comb)| {
+        let name = view.players.get(*idx).map_or("", |(name, _)| name);
+        format!("{name}: {}", style.comb(comb))
+    }));
+    lines
+}
+
+fn parse_sort(input: &str) -> Option<SortOrder> {
+    match input.split_whitespace().collect::<Vec<_>>()[..] {
+        ["sort", "rank"] => Some(SortOrder::Rank),
+        ["sort", "suit"] => Some(SortOrder::Suit),
+        _ => None,
+    }
+}
+
+fn parse_save(input: &str) -> Option<PathBuf> {
+    match input.split_whitespace().collect::<Vec<_>>()[..] {
+        ["save"] => Some(PathBuf::from(DEFAULT_SAVE_PATH)),
+        ["save", path] => Some(PathBuf::from(path)),
+        _ => None,
+    }
+}
+
+fn parse_chat(input: &str) -> Option<String> {
+    let text = input.trim_start().strip_prefix("say ")?.trim();
+    (!text.is_empty()).then(|| text.to_owned())
+}
+
+fn is_pass(input: &str) -> bool {
+    matches!(input, "" | "p" | "pass")
+}
+
+pub(crate) fn conver_to_comb(cards: Vec<Card>, min_seq: usize) -> Result<Comb, ()> {
     if cards.len() == 1 {
         Ok(Comb::Single(cards[0]))
     } else {
-        Comb::try_from(cards)
+        Comb::try_from_cards(cards, min_seq).ok_or(())
     }
 }
 
-fn parse_idx(input: &str) -> Result<Vec<usize>, ()> {
-    let results: Vec<_> = input.split(' ').map(|s| s.parse::<usize>()).collect();
-    match results.iter().all(|r| r.is_ok()) {
-        true => Ok(results.into_iter().map(|r| r.unwrap()).sorted().collect()),
-        false => Err(()),
+// 手札のカードを選ぶ入力の誤り
+#[derive(Debug, PartialEq, Eq)]
+enum SelectError {
+    // 番号でもカードの表記でもない
+    Invalid(String),
+    // 同じ番号を2回以上入力した
+    Duplicate(usize),
+    // 手札の枚数を超える番号
+    OutOfRange { idx: usize, len: usize },
+    // 手札にない(または手札の枚数より多く入力した)カード
+    NotInHand(Card),
+}
+
+impl SelectError {
+    fn get_message(&self, style: &Style) -> String {
+        match self {
+            SelectError::Invalid(input) => trf("select_invalid", &[("input", input)]),
+            SelectError::Duplicate(idx) => trf("select_duplicate", &[("idx", idx)]),
+            SelectError::OutOfRange { idx, len } => trf(
+                "select_out_of_range",
+                &[("idx", idx), ("max", &len.saturating_sub(1))],
+            ),
+            SelectError::NotInHand(card) => {
+                trf("select_not_in_hand", &[("card", &style.card_text(card))])
+            }
+        }
     }
 }
 
-fn get_cards(indices: &[usize], cards: &[Card]) -> Result<Vec<Card>, ()> {
-    let cards: Vec<Option<&Card>> = indices.iter().map(|idx| cards.get(*idx)).collect();
-    match cards.iter().any(|card| card.is_none()) {
-        true => Err(()),
-        false => Ok(cards.iter().map(|card| *card.unwrap()).collect()),
+// 空白かカンマで区切った入力
+fn split_input(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+}
+
+// 番号かカードの表記で選んだカードの手札のインデックス(昇順)と、入力した順のカード
+fn select_cards(input: &str, cards: &[Card]) -> Result<(Vec<usize>, Vec<Card>), SelectError> {
+    let indices = match parse_idx(input) {
+        Err(SelectError::Invalid(_)) => find_indices(input, cards)?,
+        result => result?,
+    };
+    let selected = get_cards(&indices, cards)?;
+    Ok((indices.into_iter().sorted().collect(), selected))
+}
+
+// 入力した順の番号(同じ番号は受け付けない)
+fn parse_idx(input: &str) -> Result<Vec<usize>, SelectError> {
+    let mut indices = vec![];
+    for s in split_input(input) {
+        let idx = s
+            .parse::<usize>()
+            .map_err(|_| SelectError::Invalid(s.to_owned()))?;
+        if indices.contains(&idx) {
+            return Err(SelectError::Duplicate(idx));
+        }
+        indices.push(idx);
     }
+    match indices.is_empty() {
+        true => Err(SelectError::Invalid(input.to_owned())),
+        false => Ok(indices),
+    }
+}
+
+// カードの表記(例: ♠3 ♠4、S3,JK)で選んだカードの手札のインデックスを取得する
+fn find_indices(input: &str, cards: &[Card]) -> Result<Vec<usize>, SelectError> {
+    let mut indices = vec![];
+    for s in split_input(input) {
+        let parsed = parse_cards(s).map_err(|_| SelectError::Invalid(s.to_owned()))?;
+        for card in parsed {
+            let idx = (0..cards.len())
+                .find(|i| cards[*i] == card && !indices.contains(i))
+                .ok_or(SelectError::NotInHand(card))?;
+            indices.push(idx);
+        }
+    }
+    indices.sort();
+    Ok(indices)
+}
+
+fn get_cards(indices: &[usize], cards: &[Card]) -> Result<Vec<Card>, SelectError> {
+    indices
+        .iter()
+        .map(|idx| {
+            cards.get(*idx).copied().ok_or(SelectError::OutOfRange {
+                idx: *idx,
+                len: cards.len(),
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
-        card::{Card, Rank, Suit},
-        comb::Comb,
-        pc::{conver_to_comb, get_cards, get_cards_with_indices, parse_idx},
+        card::{parse_cards, Card, Rank, Suit},
+        comb::{Comb, MIN_SEQ},
+        field::Field,
+        input::{ScriptedInput, INTERRUPT},
+        pc::{
+            conver_to_comb, find_indices, find_lowest_single, get_candidate_lines, get_cards,
+            get_cards_with_indices, get_counter_lines, get_log_lines, get_playable_cards,
+            get_rules_lines, get_status, is_help, is_log, is_pass, is_quit, is_undo,
+            parse_candidate, parse_chat, parse_idx, parse_save, parse_sort, select_cards, Grouping,
+            Pc, SelectError, SortOrder, TimeoutAction, TurnNotice, TurnTimer,
+        },
+        player::{Action, Player},
+        render::Style,
+        rules::Preset,
+        view::GameView,
     };
+    use std::path::PathBuf;
+    use std::time::Duration;
 
     #[test]
     fn test_get_cards_with_indices() {
         let cards = vec![
             Card::Normal(Suit::Heart, Rank::Three),
+            Card::Normal(Suit::Club, Rank::Five),
             Card::Normal(Suit::Spade, Rank::Five),
+            Card::Normal(Suit::Heart, Rank::Ten),
+            Card::Joker,
+        ];
+        let style = Style::new(false, true);
+        assert_eq!(
+            get_cards_with_indices(&cards, &style, Grouping::Rank, &[]),
+            [
+                " 3|                  0:H3",
+                " 5|  1:C5                    2:S5",
+                "10|                  3:H10",
+                "JK|  4:JK",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            get_cards_with_indices(&cards, &style, Grouping::Suit, &[]),
+            [" C|  1:C5", " H|  0:H3    3:H10", " S|  2:S5", "JK|  4:JK",].join("\n")
+        );
+        assert_eq!(
+            get_cards_with_indices(&cards[0..1], &Style::default(), Grouping::Rank, &[]),
+            " 3|                  0:♥3"
+        );
+        // 場に出せるカードに印を付ける
+        let mut field = Field::new(2, 0);
+        field
+            .put(
+                Some(&Comb::Single(Card::Normal(Suit::Spade, Rank::Four))),
+                5,
+            )
+            .unwrap();
+        let playable = get_playable_cards(&cards, &field);
+        assert_eq!(playable, cards[1..].to_vec());
+        assert_eq!(
+            get_cards_with_indices(&cards, &style, Grouping::Rank, &playable),
+            [
+                " 3|                  0:H3",
+                " 5|  1:C5*                   2:S5*",
+                "10|                  3:H10*",
+                "JK|  4:JK*",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_get_candidate_lines() {
+        let style = Style::new(false, true);
+        let candidates: Vec<Comb> = ["S5", "H7 D7", "C9 C10 CJ"]
+            .iter()
+            .map(|text| conver_to_comb(parse_cards(text).unwrap(), MIN_SEQ).unwrap())
+            .collect();
+        assert_eq!(
+            get_candidate_lines(&candidates, true, &style),
+            vec!["0: パス  1: S5  2: D7 H7  3: C9 C10 CJ"]
+        );
+        assert_eq!(
+            get_candidate_lines(&candidates[..1], false, &style),
+            vec!["1: S5"]
+        );
+        let singles: Vec<Comb> = (0..7).map(|_| Comb::Single(Card::Joker)).collect();
+        assert_eq!(get_candidate_lines(&singles, true, &style).len(), 2);
+        for (input, expected) in [
+            ("0", Some(Ok(0))),
+            ("3", Some(Ok(3))),
+            ("4", Some(Err(3))),
+            ("S5", None),
+            ("1 2", None),
+        ] {
+            assert_eq!(parse_candidate(input, 3), expected);
+        }
+    }
+
+    #[test]
+    fn test_get_status() {
+        let view = GameView {
+            idx: 0,
+            prev_comb: Some(Comb::Multi(vec![
+                Card::Normal(Suit::Heart, Rank::Four),
+                Card::Normal(Suit::Spade, Rank::Four),
+            ])),
+            bind_suits: Some(vec![Suit::Heart, Suit::Spade]),
+            is_rev: true,
+            players: vec![("User".to_owned(), 10), ("NpcA".to_owned(), 0)],
+            pass_count: 1,
+            ..Default::default()
+        };
+        let expected = [
+            "-".repeat(32),
+            "場  : ♥4 ♠️4".to_owned(),
+            "縛り: ♥ ♠️".to_owned(),
+            "革命: あり".to_owned(),
+            "手札: User[10] NpcA[0]".to_owned(),
+            "パス: 1".to_owned(),
+            "-".repeat(32),
+        ]
+        .join("\n");
+        assert_eq!(get_status(&view, &Style::default()), expected);
+        let expected = [
+            "-".repeat(32),
+            "場  : なし".to_owned(),
+            "縛り: なし".to_owned(),
+            "革命: なし".to_owned(),
+            "手札: ".to_owned(),
+            "パス: 0".to_owned(),
+            "-".repeat(32),
+        ]
+        .join("\n");
+        assert_eq!(
+            get_status(&GameView::default(), &Style::default()),
+            expected
+        );
+        // パスしたプレイヤーと、まだ出せるプレイヤー
+        let view = GameView {
+            players: ["User", "NpcA", "NpcB", "NpcC"]
+                .map(|name| (name.to_owned(), 5))
+                .to_vec(),
+            pass_count: 2,
+            lead: Some(0),
+            passed: vec![2, 3],
+            ..Default::default()
+        };
+        let status = get_status(&view, &Style::default());
+        assert!(status.contains("パス: 2 NpcB NpcC (出せる: NpcA)"));
+        assert!(!status.contains("順位"));
+        // 上がったプレイヤーの途中の順位
+        let view = GameView {
+            places: vec![None, Some(3), None, Some(0)],
+            ..view
+        };
+        let status = get_status(&view, &Style::default());
+        assert!(status.contains("順位: 大富豪 NpcC / 大貧民 NpcA"));
+    }
+
+    #[test]
+    fn test_scripted_input() {
+        let input = ScriptedInput::new(["3 3", "0 9", "♠4", "0 1", "", "1", "0"]);
+        let mut pc = Pc::new("User".to_owned(), Style::new(false, true), false, None)
+            .with_input(Box::new(input));
+        pc.init(parse_cards("♠3 ♥3 ♠5").unwrap());
+        let mut field = Field::new(2, 0);
+        field.set_players(vec!["User".to_owned(), "NpcA".to_owned()], vec![3, 5]);
+        // 誤った入力は読み飛ばして次の行を使う
+        assert_eq!(
+            pc.play(&field),
+            Action::Move(Some(Comb::Multi(parse_cards("♥3 ♠3").unwrap())))
+        );
+        assert_eq!(pc.get_hands(), parse_cards("♠5").unwrap());
+        assert_eq!(pc.get_needless_cards(1), parse_cards("♠5").unwrap());
+    }
+
+    #[test]
+    fn test_play_action() {
+        let input = ScriptedInput::new(["save x.json", "undo", "q", "y", "q", "n", "♠5"]);
+        let mut pc = Pc::new("User".to_owned(), Style::new(false, true), false, None)
+            .with_input(Box::new(input));
+        pc.init(parse_cards("♠5").unwrap());
+        let mut field = Field::new(2, 0);
+        field.set_players(vec!["User".to_owned(), "NpcA".to_owned()], vec![1, 5]);
+        // カードを出す以外の操作は手札を変えずに返す
+        for expected in [
+            Action::Save(PathBuf::from("x.json")),
+            Action::Undo,
+            Action::Quit(Some(PathBuf::from("save.json"))),
+            Action::Quit(None),
+            Action::Move(Some(Comb::Single(parse_cards("♠5").unwrap()[0]))),
+        ] {
+            assert_eq!(pc.count_hands(), 1);
+            assert_eq!(pc.play(&field), expected);
+        }
+        assert_eq!(pc.count_hands(), 0);
+    }
+
+    #[test]
+    fn test_read_input() {
+        let mut timeouts = vec![];
+        let mut answers = vec![Some("1".to_owned()), None];
+        let input = move |_: &str, timeout| {
+            timeouts.push(timeout);
+            assert!(timeouts.iter().all(|t| *t == Some(Duration::from_secs(5))));
+            answers.pop().unwrap()
+        };
+        let notice = TurnNotice {
+            bell: false,
+            reminder: Some(Duration::from_secs(5)),
+        };
+        let mut pc = Pc::new("User".to_owned(), Style::new(false, true), false, None)
+            .with_notice(notice)
+            .with_input(Box::new(input));
+        // 時間内に入力がなければ知らせ直して待ち続ける
+        assert_eq!(pc.read_input(String::new()), Some("1".to_owned()));
+        // 持ち時間が過ぎていれば知らせ直さない
+        let timer = TurnTimer {
+            timeout: Duration::ZERO,
+            action: TimeoutAction::Pass,
+            afk_turns: None,
+        };
+        let input = |_: &str, timeout| {
+            assert_eq!(timeout, Some(Duration::ZERO));
+            None
+        };
+        let mut pc = Pc::new(
+            "User".to_owned(),
+            Style::new(false, true),
+            false,
+            Some(timer),
+        )
+        .with_notice(notice)
+        .with_input(Box::new(input));
+        pc.start_timer();
+        assert_eq!(pc.read_input(String::new()), None);
+    }
+
+    #[test]
+    fn test_find_lowest_single() {
+        let cards = vec![
+            Card::Normal(Suit::Spade, Rank::Ace),
+            Card::Normal(Suit::Heart, Rank::Four),
+            Card::Normal(Suit::Club, Rank::Nine),
+            Card::Joker,
         ];
-        assert_eq!(get_cards_with_indices(&cards), " 0:♥3\n 1:♠️5");
+        let mut field = Field::new(4, 0);
+        assert_eq!(find_lowest_single(&cards, &field, false), Some(1));
+        assert_eq!(find_lowest_single(&cards, &field, true), Some(0));
+        field
+            .put(
+                Some(&Comb::Single(Card::Normal(Suit::Diamond, Rank::Five))),
+                10,
+            )
+            .unwrap();
+        assert_eq!(find_lowest_single(&cards, &field, false), Some(2));
+        field.put(Some(&Comb::Single(Card::Joker)), 9).unwrap();
+        assert_eq!(find_lowest_single(&cards, &field, false), None);
+    }
+
+    #[test]
+    fn test_get_counter_lines() {
+        let hands = [
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Heart, Rank::Three),
+        ];
+        let history = [
+            (1, Comb::Single(Card::Normal(Suit::Spade, Rank::Three))),
+            (
+                2,
+                Comb::Seq(vec![
+                    Card::Normal(Suit::Heart, Rank::Queen),
+                    Card::Normal(Suit::Heart, Rank::King),
+                    Card::Joker,
+                ]),
+            ),
+        ];
+        assert_eq!(
+            get_counter_lines(&hands, &history),
+            vec![
+                "数字  3  4  5  6  7  8  9 10  J  Q  K  A  2 JK",
+                "残り  1  4  4  4  4  4  4  4  4  3  3  4  4  0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_log_lines() {
+        let view = GameView {
+            players: vec![("User".to_owned(), 5), ("NpcA".to_owned(), 4)],
+            ..Default::default()
+        };
+        let style = Style::new(false, true);
+        let history = [
+            (0, Comb::Single(Card::Normal(Suit::Club, Rank::Three))),
+            (
+                1,
+                Comb::Multi(vec![Card::Normal(Suit::Heart, Rank::Five), Card::Joker]),
+            ),
+        ];
+        assert_eq!(
+            get_log_lines(&history, &view, &style),
+            vec!["出たカード (3枚)", "User: C3", "NpcA: H5 JK"]
+        );
+        assert_eq!(
+            get_log_lines(&[], &view, &style),
+            vec!["まだカードは出ていません"]
+        );
+        assert!(is_log("log"));
+        for (input, expected) in [("q", true), ("quit", true), (INTERRUPT, true), ("Q", false)] {
+            assert_eq!(is_quit(input), expected);
+        }
+        assert!(!is_log("lo"));
+        assert!(is_undo("undo"));
+        assert!(!is_undo("u"));
+        assert!(is_help("?"));
+        assert!(!is_help("h"));
+    }
+
+    #[test]
+    fn test_get_rules_lines() {
+        let view = GameView {
+            bind_suits: Some(vec![Suit::Heart]),
+            is_rev: true,
+            rules: Preset::Kanto.get_rules(),
+            ..Default::default()
+        };
+        assert_eq!(
+            get_rules_lines(&view, &Style::new(false, true)),
+            vec![
+                "現在のルール",
+                "有効: 8切り, 縛り, 革命, 11バック, スペ3, 都落ち",
+                "無効: 5飛ばし, 階段をジョーカーを補った最も弱いカードで比べる, 8返し, ジョーカーで親になれない, ジョーカー上がり, 縛りにジョーカーを出せない, 大革命, 最後の2人は縛りなし",
+                "強さ: 2 < A < K < Q < J < 10 < 9 < 8 < 7 < 6 < 5 < 4 < 3 < Joker (逆転中)",
+                "縛り: H",
+                "2ラウンド目以降の親: 大貧民",
+            ]
+        );
+        // 革命中の11バックでは元の強さに戻る
+        let view = GameView {
+            is_rev: true,
+            is_jack_back: true,
+            ..Default::default()
+        };
+        let lines = get_rules_lines(&view, &Style::new(false, true));
+        assert_eq!(
+            lines[3],
+            "強さ: 3 < 4 < 5 < 6 < 7 < 8 < 9 < 10 < J < Q < K < A < 2 < Joker"
+        );
+        assert_eq!(lines[4], "縛り: なし");
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        for (input, expected) in [
+            ("sort rank", Some(SortOrder::Rank)),
+            ("sort  suit", Some(SortOrder::Suit)),
+            ("sort", None),
+            ("sort color", None),
+            ("0 1", None),
+        ] {
+            assert_eq!(parse_sort(input), expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_save() {
+        for (input, expected) in [
+            ("save", Some(PathBuf::from("save.json"))),
+            ("save  game.json", Some(PathBuf::from("game.json"))),
+            ("save a b", None),
+            ("p", None),
+        ] {
+            assert_eq!(parse_save(input), expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_chat() {
+        for (input, expected) in [
+            ("say gg", Some("gg".to_owned())),
+            ("say  よろしく ", Some("よろしく".to_owned())),
+            ("say ", None),
+            ("say", None),
+            ("sayonara", None),
+        ] {
+            assert_eq!(parse_chat(input), expected);
+        }
+    }
+
+    #[test]
+    fn test_is_pass() {
+        for (input, expected) in [
+            ("", true),
+            ("p", true),
+            ("pass", true),
+            ("P", false),
+            ("0", false),
+            ("pas", false),
+        ] {
+            assert_eq!(is_pass(input), expected);
+        }
     }
 
     #[test]
@@ -174,7 +1405,12 @@ mod test {
             ),
             (vec![], Err(())),
         ] {
-            assert_eq!(conver_to_comb(cards), expected);
+            assert_eq!(conver_to_comb(cards, MIN_SEQ), expected);
+        }
+        // ルールの枚数に足りない階段は組み合わせにならない
+        let cards = parse_cards("♣5 ♣6 ♣7").unwrap();
+        for (min_seq, expected) in [(3, true), (4, false), (0, false)] {
+            assert_eq!(conver_to_comb(cards.clone(), min_seq).is_ok(), expected);
         }
     }
 
@@ -182,14 +1418,46 @@ mod test {
     fn test_parse_idx() {
         for (input, expected) in [
             ("1 2 3 4", Ok(vec![1, 2, 3, 4])),
-            ("0 2 1", Ok(vec![0, 1, 2])),
-            ("1 2 a 4", Err(())),
-            ("xyz", Err(())),
+            ("0 2 1", Ok(vec![0, 2, 1])),
+            ("  1   2 ", Ok(vec![1, 2])),
+            ("1,2, 3", Ok(vec![1, 2, 3])),
+            ("3 3", Err(SelectError::Duplicate(3))),
+            ("1 2 a 4", Err(SelectError::Invalid("a".to_owned()))),
+            ("xyz", Err(SelectError::Invalid("xyz".to_owned()))),
+            (",", Err(SelectError::Invalid(",".to_owned()))),
         ] {
             assert_eq!(parse_idx(input), expected);
         }
     }
 
+    #[test]
+    fn test_find_indices() {
+        let cards = [
+            Card::Normal(Suit::Spade, Rank::Three),
+            Card::Normal(Suit::Spade, Rank::Four),
+            Card::Normal(Suit::Heart, Rank::Four),
+            Card::Joker,
+        ];
+        for (input, expected) in [
+            ("♠4 ♠3", Ok(vec![0, 1])),
+            ("S3 S4 JK", Ok(vec![0, 1, 3])),
+            ("S3,S4", Ok(vec![0, 1])),
+            ("♠3♠4", Ok(vec![0, 1])),
+            ("♥4", Ok(vec![2])),
+            (
+                "♦4",
+                Err(SelectError::NotInHand(Card::Normal(
+                    Suit::Diamond,
+                    Rank::Four,
+                ))),
+            ),
+            ("JK JK", Err(SelectError::NotInHand(Card::Joker))),
+            ("S", Err(SelectError::Invalid("S".to_owned()))),
+        ] {
+            assert_eq!(find_indices(input, &cards), expected);
+        }
+    }
+
     #[test]
     fn test_get_cards() {
         let cards = vec![
@@ -213,9 +1481,32 @@ mod test {
                     Card::Normal(Suit::Heart, Rank::Four),
                 ]),
             ),
-            (vec![1, 4], Err(())),
+            (vec![1, 4], Err(SelectError::OutOfRange { idx: 4, len: 3 })),
         ] {
             assert_eq!(get_cards(&indices, &cards), expected);
         }
+        // 番号の昇順のインデックスと入力した順のカード
+        assert_eq!(
+            select_cards("2 0", &cards),
+            Ok((
+                vec![0, 2],
+                vec![
+                    Card::Normal(Suit::Spade, Rank::Five),
+                    Card::Normal(Suit::Heart, Rank::Three),
+                ]
+            ))
+        );
+        assert_eq!(select_cards("3 3", &cards), Err(SelectError::Duplicate(3)));
+        let style = Style::new(false, true);
+        for (error, expected) in [
+            (SelectError::Duplicate(3), "3番が重複しています"),
+            (
+                SelectError::OutOfRange { idx: 4, len: 3 },
+                "4番のカードはありません (0から2)",
+            ),
+            (SelectError::NotInHand(Card::Joker), "JKは手札にありません"),
+        ] {
+            assert_eq!(error.get_message(&style), expected);
+        }
     }
 }
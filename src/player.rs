@@ -1,12 +1,102 @@
-use crate::card::Card;
+use crate::card::{Card, Order};
 use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::reason;
+use crate::strategy::Strategy;
 use crate::validator::Validator;
+use std::path::PathBuf;
+
+// playで選んだ操作(Move以外は手番を進めない)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    // カードを場に出す(Noneはパス)
+    Move(Option<Comb>),
+    // ゲームを保存して同じ手番をやり直す
+    Save(PathBuf),
+    // ゲームを中断する(保存先があれば保存してから)
+    Quit(Option<PathBuf>),
+    // 前の手番に戻す
+    Undo,
+    // チャットを送って同じ手番をやり直す(ネットワーク対戦のみ)
+    Chat(String),
+    // 時間切れが続いたので手を出し、ラウンドの残りの操作をNPCに任せる
+    Away(Option<Comb>),
+}
 
 pub trait Player {
     fn init(&mut self, hands: Vec<Card>);
     fn count_hands(&self) -> usize;
     fn get_name(&self) -> &str;
-    fn get_hands(&mut self) -> &mut Vec<Card>;
-    fn play(&mut self, validator: &dyn Validator) -> Option<Comb>;
+    fn get_hands(&self) -> &[Card];
+    // 指定したカードを手札から取り出す(持っていないカードは除く)
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card>;
+    // 交換で受け取ったカードを手札に加える(並べ替えはsort_handsで行う)
+    fn receive_cards(&mut self, cards: Vec<Card>);
+    fn sort_hands(&mut self, order: Order);
+    fn play(&mut self, validator: &dyn Validator) -> Action;
     fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card>;
+
+    // 献上するカードを確認させる(NPCは何もしない)
+    fn confirm_tribute(&mut self, _cards: &[Card]) {}
+
+    // NPCの戦略(人間のプレイヤーはNone)
+    fn get_strategy(&self) -> Option<Strategy> {
+        None
+    }
+
+    // 直前のplayで手を選んだ理由(handsはplayの前の手札、人間のプレイヤーはNone)
+    fn explain(
+        &self,
+        hands: &[Card],
+        comb: Option<&Comb>,
+        validator: &dyn Validator,
+    ) -> Option<String> {
+        self.get_strategy()
+            .map(|_| reason::get_reason(hands, comb, validator))
+    }
+
+    // 公開されたゲームの進行を受け取る(相手の傾向を覚えるNPC以外は何もしない)
+    fn observe(&mut self, _event: &GameEvent) {}
+
+    // 乱数で手を選ぶNPCの乱数を初期化し直す(それ以外は何もしない)
+    fn reseed(&mut self, _seed: u64) {}
+
+    // NPCに任せた席の操作に次のラウンドから戻るかを確認する
+    fn confirm_return(&mut self) -> bool {
+        false
+    }
+}
+
+// take_cardsの実装に使う、手札から1枚ずつ探して取り除く
+pub fn take_from_hands(hands: &mut Vec<Card>, cards: &[Card]) -> Vec<Card> {
+    cards
+        .iter()
+        .filter_map(|card| {
+            let i = hands.iter().position(|c| c == card)?;
+            Some(hands.remove(i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+
+    #[test]
+    fn test_take_from_hands() {
+        let (three, two) = (
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Spade, Rank::Two),
+        );
+        for (cards, expected, left) in [
+            (vec![two, Card::Joker], vec![two, Card::Joker], vec![three]),
+            (vec![three, three], vec![three], vec![two, Card::Joker]),
+            (vec![], vec![], vec![three, two, Card::Joker]),
+        ] {
+            let mut hands = vec![three, two, Card::Joker];
+            assert_eq!(take_from_hands(&mut hands, &cards), expected);
+            assert_eq!(hands, left);
+        }
+    }
 }
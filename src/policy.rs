@@ -0,0 +1,213 @@
+use crate::card::{Card, Order};
+use crate::comb::Comb;
+use crate::encoding::{encode_comb, encode_state, CARDS_LEN, STATE_LEN};
+use crate::mcts::get_candidates;
+use crate::player::{take_from_hands, Action, Player};
+use crate::strategy::Strategy;
+use crate::validator::Validator;
+use std::path::Path;
+use std::rc::Rc;
+use tract_onnx::prelude::*;
+
+// モデルの入力は状態と候補の手を並べた1行
+pub const INPUT_LEN: usize = STATE_LEN + CARDS_LEN;
+
+pub type PolicyModel = TypedRunnableModel<TypedModel>;
+
+// 状態と候補の手から1つの評価値を出力するONNXモデルを読み込む
+pub fn load_policy(path: &Path) -> Result<Rc<PolicyModel>, String> {
+    tract_onnx::onnx()
+        .model_for_path(path)
+        .and_then(optimize)
+        .map(Rc::new)
+        .map_err(|e| format!("{}を読み込めません: {e}", path.display()))
+}
+
+fn optimize(model: InferenceModel) -> TractResult<PolicyModel> {
+    let model = model
+        .with_input_fact(0, f32::fact([1, INPUT_LEN]).into())?
+        .into_optimized()?
+        .into_runnable()?;
+    // 出力が1つの値になるかを確かめる
+    evaluate(&model, vec![0.0; INPUT_LEN])?;
+    Ok(model)
+}
+
+fn evaluate(model: &PolicyModel, input: Vec<f32>) -> TractResult<f32> {
+    let input = tract_ndarray::Array2::from_shape_vec((1, INPUT_LEN), input)?;
+    let outputs = model.run(tvec!(Tensor::from(input).into()))?;
+    let output = outputs[0].to_array_view::<f32>()?;
+    match output.len() {
+        1 => Ok(output.iter().sum()),
+        len => Err(tract_onnx::tract_core::anyhow::anyhow!(
+            "出力の要素数が1ではありません: {len}"
+        )),
+    }
+}
+
+// 出せる手の中からモデルの評価値が最も高い手を選ぶNPC
+pub struct PolicyNpc {
+    name: String,
+    hands: Vec<Card>,
+    model: Rc<PolicyModel>,
+}
+
+impl PolicyNpc {
+    pub fn new(name: String, model: Rc<PolicyModel>) -> Self {
+        Self {
+            name,
+            hands: vec![],
+            model,
+        }
+    }
+
+    // 場に出す手を選ぶ(Noneはパス)
+    fn select(&mut self, validator: &dyn Validator) -> Option<Comb> {
+        let state = encode_state(&validator.get_view(), &self.hands);
+        // 場に出せない手は候補に含まれない
+        let mut candidates = get_candidates(&self.hands, validator);
+        let scores: Vec<f32> = candidates
+            .iter()
+            .map(|candidate| {
+                let mut input = state.clone();
+                input.extend(encode_comb(candidate.as_ref().map(|(comb, _)| comb)));
+                evaluate(&self.model, input).unwrap_or(f32::NEG_INFINITY)
+            })
+            .collect();
+        let best = (0..candidates.len()).max_by(|a, b| scores[*a].total_cmp(&scores[*b]))?;
+        let (comb, indices) = candidates.swap_remove(best)?;
+        for i in indices.iter().rev() {
+            self.hands.remove(*i);
+        }
+        Some(comb)
+    }
+}
+
+impl Player for PolicyNpc {
+    fn init(&mut self, hands: Vec<Card>) {
+        self.hands = hands;
+    }
+
+    fn count_hands(&self) -> usize {
+        self.hands.len()
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_hands(&self) -> &[Card] {
+        &self.hands
+    }
+
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+        take_from_hands(&mut self.hands, cards)
+    }
+
+    fn receive_cards(&mut self, cards: Vec<Card>) {
+        self.hands.extend(cards);
+    }
+
+    fn sort_hands(&mut self, order: Order) {
+        self.hands.sort_by(order.get_comparator());
+    }
+
+    // 保存したゲームを再開するとminのNPCになる
+    fn get_strategy(&self) -> Option<Strategy> {
+        Some(Strategy::Min)
+    }
+
+    fn play(&mut self, validator: &dyn Validator) -> Action {
+        Action::Move(self.select(validator))
+    }
+
+    fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
+        (0..cards_count).map(|_| self.hands.remove(0)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+    use crate::field::Field;
+    use tract_onnx::pb::{self, tensor_proto::DataType, type_proto, TensorShapeProto};
+
+    // 出すカードの枚数を評価値とする線形のモデル
+    fn create_model() -> PolicyModel {
+        let value_info = |name: &str, dims: [i64; 2]| pb::ValueInfoProto {
+            name: name.to_owned(),
+            r#type: Some(pb::TypeProto {
+                value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                    elem_type: DataType::Float as i32,
+                    shape: Some(TensorShapeProto {
+                        dim: dims
+                            .map(|d| pb::tensor_shape_proto::Dimension {
+                                value: Some(pb::tensor_shape_proto::dimension::Value::DimValue(d)),
+                                ..Default::default()
+                            })
+                            .to_vec(),
+                    }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut weights = vec![0.0; STATE_LEN];
+        weights.extend([1.0; CARDS_LEN]);
+        let proto = pb::ModelProto {
+            ir_version: 7,
+            opset_import: vec![pb::OperatorSetIdProto {
+                domain: String::new(),
+                version: 13,
+            }],
+            graph: Some(pb::GraphProto {
+                node: vec![pb::NodeProto {
+                    input: vec!["input".to_owned(), "weights".to_owned()],
+                    output: vec!["score".to_owned()],
+                    op_type: "MatMul".to_owned(),
+                    ..Default::default()
+                }],
+                initializer: vec![pb::TensorProto {
+                    dims: vec![INPUT_LEN as i64, 1],
+                    data_type: DataType::Float as i32,
+                    float_data: weights,
+                    name: "weights".to_owned(),
+                    ..Default::default()
+                }],
+                input: vec![value_info("input", [1, INPUT_LEN as i64])],
+                output: vec![value_info("score", [1, 1])],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let model = tract_onnx::onnx().model_for_proto_model(&proto).unwrap();
+        optimize(model).unwrap()
+    }
+
+    #[test]
+    fn test_policy_npc() {
+        let mut npc = PolicyNpc::new("Policy".to_owned(), Rc::new(create_model()));
+        npc.init(vec![
+            Card::Normal(Suit::Club, Rank::Three),
+            Card::Normal(Suit::Heart, Rank::Five),
+            Card::Normal(Suit::Spade, Rank::Five),
+            Card::Normal(Suit::Diamond, Rank::Five),
+        ]);
+        let mut field = Field::new(2, 0);
+        field.set_players(vec!["Policy".to_owned(), "NpcA".to_owned()], vec![4, 5]);
+        // 最も多くのカードを出す
+        assert_eq!(
+            npc.select(&field),
+            Some(Comb::Multi(vec![
+                Card::Normal(Suit::Heart, Rank::Five),
+                Card::Normal(Suit::Spade, Rank::Five),
+                Card::Normal(Suit::Diamond, Rank::Five),
+            ]))
+        );
+        assert_eq!(npc.count_hands(), 1);
+        // 場に出せる手がなければパスする
+        field.put(Some(&Comb::Single(Card::Joker)), 5).unwrap();
+        assert_eq!(npc.select(&field), None);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::card::Card;
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::i18n::trf;
+use crate::rules::RuleSet;
+use crate::strategy::Strategy;
+use crate::view::GameView;
+use crate::PLAYERS_COUNT;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+// 1つのメッセージの最大のバイト数
+const MAX_MESSAGE_LEN: usize = 1 << 20;
+// 長さが届いてから残りが届くまで待つ時間(過ぎたら切断する)
+const FRAME_TIMEOUT: Duration = Duration::from_secs(5);
+
+// サーバーからクライアントへのメッセージ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ServerMessage {
+    // 参加を受け付けて席と再接続用のトークンを知らせる
+    Welcome { seat: usize, token: String },
+    // 配られた手札
+    Deal { hands: Vec<Card> },
+    // 手番のプレイヤーに出すカードを求める
+    TurnRequest { view: GameView, hands: Vec<Card> },
+    // 受け付けられなかった手とその理由
+    Reject { message: String },
+    // ゲームの進行
+    Event { event: GameEvent },
+    // ロビーのテーブルの一覧
+    Tables { tables: Vec<TableInfo> },
+    // テーブルに着いて開始を待っている
+    TableJoined { table: TableInfo },
+    // プレイヤーのチャット
+    Chat { name: String, text: String },
+    // テーブルで適用するルール
+    Rules { rules: RuleSet },
+}
+
+// クライアントからサーバーへのメッセージ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ClientMessage {
+    Join {
+        name: String,
+    },
+    // 接続が切れた席に戻る
+    Rejoin {
+        token: String,
+    },
+    ListTables,
+    // テーブルを作ってホストになる
+    CreateTable {
+        table: String,
+        name: String,
+        settings: TableSettings,
+    },
+    JoinTable {
+        table: String,
+        name: String,
+    },
+    // ホストがゲームを開始する
+    StartGame,
+    // 手札を見ずに進行だけを受け取る(ロビーではテーブルを指定する)
+    Spectate {
+        table: Option<String>,
+    },
+    Chat {
+        text: String,
+    },
+    // テーブルのルールを求める(手番でなくても送れる)
+    GetRules,
+    Play {
+        comb: Comb,
+    },
+    Pass,
+}
+
+// テーブルのルールと空いた席を埋めるNPC
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableSettings {
+    pub preset: String,
+    pub npc: Strategy,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub name: String,
+    pub players: Vec<String>,
+    pub settings: TableSettings,
+    // プリセットから決まるルール
+    pub rules: RuleSet,
+}
+
+impl From<&TableInfo> for String {
+    fn from(table: &TableInfo) -> Self {
+        trf(
+            "table_info",
+            &[
+                ("name", &table.name),
+                ("count", &table.players.len()),
+                ("max", &PLAYERS_COUNT),
+                ("players", &table.players.join(", ")),
+                ("preset", &table.settings.preset),
+                ("npc", &table.settings.npc.get_name()),
+            ],
+        )
+    }
+}
+
+// 4バイトのビッグエンディアンの長さに続けてJSONを書き込む
+pub fn write_message<W, T>(writer: &mut W, message: &T) -> io::Result<()>
+where
+    W: Write,
+    T: Serialize,
+{
+    let json = serde_json::to_vec(message)?;
+    // 長さとJSONが別々のパケットにならないようにまとめて書き込む
+    let mut buffer = (json.len() as u32).to_be_bytes().to_vec();
+    buffer.extend(json);
+    writer.write_all(&buffer)?;
+    writer.flush()
+}
+
+pub fn read_message<R, T>(reader: &mut R) -> io::Result<T>
+where
+    R: Read,
+    T: DeserializeOwned,
+{
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            trf("message_too_long", &[("len", &len)]),
+        ));
+    }
+    let mut json = vec![0; len];
+    reader.read_exact(&mut json)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+// サーバーから見たクライアントとの接続
+pub trait Connection: Send {
+    fn send(&mut self, message: &ServerMessage) -> io::Result<()>;
+    fn receive(&mut self) -> io::Result<ClientMessage>;
+    // 届いているメッセージがなければ待たずにNoneを返す
+    fn try_receive(&mut self) -> io::Result<Option<ClientMessage>>;
+}
+
+// 長さを前に付けたJSONで送受信する
+impl Connection for TcpStream {
+    fn send(&mut self, message: &ServerMessage) -> io::Result<()> {
+        write_message(self, message)
+    }
+
+    fn receive(&mut self) -> io::Result<ClientMessage> {
+        read_message(self)
+    }
+
+    fn try_receive(&mut self) -> io::Result<Option<ClientMessage>> {
+        try_read_message(self, FRAME_TIMEOUT)
+    }
+}
+
+// 長さが届いていればtimeoutまで残りを待って読む
+// 長さだけを送って止まる相手でテーブルのスレッドを止めないように、間に合わなければエラーにする
+fn try_read_message(
+    stream: &mut TcpStream,
+    timeout: Duration,
+) -> io::Result<Option<ClientMessage>> {
+    stream.set_nonblocking(true)?;
+    let mut len = [0; 4];
+    let result = stream.peek(&mut len);
+    stream.set_nonblocking(false)?;
+    match result {
+        Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+        Ok(4) => {
+            stream.set_read_timeout(Some(timeout))?;
+            let result = read_message(stream);
+            stream.set_read_timeout(None)?;
+            result.map(Some)
+        }
+        Ok(_) => Ok(None),
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(feature = "ws")]
+fn from_ws_error(e: tungstenite::Error) -> io::Error {
+    match e {
+        tungstenite::Error::Io(e) => e,
+        e => io::Error::other(e),
+    }
+}
+
+// 1つのテキストメッセージに1つのJSONを入れて送受信する
+#[cfg(feature = "ws")]
+impl Connection for tungstenite::WebSocket<TcpStream> {
+    fn send(&mut self, message: &ServerMessage) -> io::Result<()> {
+        let json = serde_json::to_string(message)?;
+        tungstenite::WebSocket::send(self, tungstenite::Message::text(json)).map_err(from_ws_error)
+    }
+
+    fn receive(&mut self) -> io::Result<ClientMessage> {
+        loop {
+            match self.read().map_err(from_ws_error)? {
+                tungstenite::Message::Text(json) => return Ok(serde_json::from_str(&json)?),
+                tungstenite::Message::Binary(json) => return Ok(serde_json::from_slice(&json)?),
+                tungstenite::Message::Close(_) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                // PingとPongはtungsteniteが応答する
+                _ => {}
+            }
+        }
+    }
+
+    fn try_receive(&mut self) -> io::Result<Option<ClientMessage>> {
+        // 途中まで届いたフレームはtungsteniteが保持する
+        self.get_mut().set_nonblocking(true)?;
+        let result = Connection::receive(self);
+        self.get_mut().set_nonblocking(false)?;
+        match result {
+            Ok(message) => Ok(Some(message)),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+    use crate::field::TurnOutcome;
+
+    #[test]
+    fn test_read_message() {
+        let mut buffer = vec![];
+        let messages = [
+            ServerMessage::Welcome {
+                seat: 2,
+                token: "0123456789abcdef".to_owned(),
+            },
+            ServerMessage::TurnRequest {
+                view: GameView {
+                    idx: 2,
+                    prev_comb: Some(Comb::Single(Card::Normal(Suit::Heart, Rank::Five))),
+                    bind_suits: Some(vec![Suit::Heart]),
+                    is_rev: false,
+                    players: vec![("User".to_owned(), 3), ("NpcA".to_owned(), 5)],
+                    pass_count: 1,
+                    ..Default::default()
+                },
+                hands: vec![Card::Joker],
+            },
+            ServerMessage::Event {
+                event: GameEvent::Turn {
+                    idx: 0,
+                    comb: None,
+                    hands_count: 4,
+                    outcome: TurnOutcome::default(),
+                },
+            },
+        ];
+        for message in &messages {
+            write_message(&mut buffer, message).unwrap();
+        }
+        assert_eq!(&buffer[..4], &[0, 0, 0, 54]);
+        let mut reader = buffer.as_slice();
+        for message in messages {
+            assert_eq!(
+                read_message::<_, ServerMessage>(&mut reader).unwrap(),
+                message
+            );
+        }
+        assert!(read_message::<_, ServerMessage>(&mut reader).is_err());
+        // 長すぎるメッセージは読まない
+        let mut reader: &[u8] = &[0xff, 0xff, 0xff, 0xff];
+        assert!(read_message::<_, ClientMessage>(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_try_read_message() {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        let timeout = Duration::from_millis(50);
+        assert!(try_read_message(&mut server, timeout).unwrap().is_none());
+        write_message(&mut client, &ClientMessage::Pass).unwrap();
+        let message = loop {
+            if let Some(message) = try_read_message(&mut server, timeout).unwrap() {
+                break message;
+            }
+        };
+        assert_eq!(message, ClientMessage::Pass);
+        // 長さだけを送って止まった相手はエラーになる
+        client.write_all(&[0, 0, 0, 16]).unwrap();
+        let result = loop {
+            match try_read_message(&mut server, timeout) {
+                Ok(None) => {}
+                result => break result,
+            }
+        };
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,240 @@
+use crate::card::parse_cards;
+use crate::card::{self, Card};
+use crate::comb::Comb;
+use crate::external::ExternalGame;
+use crate::kifu::{card_to_kifu, cards_to_kifu};
+use crate::strategy::Strategy;
+use crate::PLAYERS_COUNT;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+
+// 棋譜の表記(例: ♠7、JK)で作るカード
+#[pyclass(name = "Card", module = "daifugo", frozen, eq, hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PyCard(Card);
+
+#[pymethods]
+impl PyCard {
+    #[new]
+    fn new(text: &str) -> PyResult<Self> {
+        match parse_cards(text).map_err(PyValueError::new_err)?[..] {
+            [card] => Ok(Self(card)),
+            _ => Err(PyValueError::new_err(format!(
+                "1枚のカードではありません: {text}"
+            ))),
+        }
+    }
+
+    // スートのASCII表記(ジョーカーはNone)
+    #[getter]
+    fn suit(&self) -> Option<String> {
+        match &self.0 {
+            Card::Normal(suit, _) => Some(card::suit_to_ascii(suit)),
+            Card::Joker => None,
+        }
+    }
+
+    // 3を0、2を12とする強さ(ジョーカーはNone)
+    #[getter]
+    fn rank(&self) -> Option<i32> {
+        match &self.0 {
+            Card::Normal(_, rank) => Some(rank.into()),
+            Card::Joker => None,
+        }
+    }
+
+    #[getter]
+    fn is_joker(&self) -> bool {
+        self.0 == Card::Joker
+    }
+
+    fn __str__(&self) -> String {
+        card_to_kifu(&self.0)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Card('{}')", card_to_kifu(&self.0))
+    }
+}
+
+// 1枚、または組み合わせになる複数枚のカード
+#[pyclass(name = "Comb", module = "daifugo", frozen, eq)]
+#[derive(Clone, PartialEq, Eq)]
+pub struct PyComb(Comb);
+
+#[pymethods]
+impl PyComb {
+    #[new]
+    fn new(cards: Vec<PyCard>) -> PyResult<Self> {
+        let cards: Vec<Card> = cards.into_iter().map(|card| card.0).collect();
+        match cards[..] {
+            [card] => Ok(Self(Comb::Single(card))),
+            _ => Comb::try_from(cards.clone()).map(Self).map_err(|_| {
+                PyValueError::new_err(format!(
+                    "組み合わせになっていません: {}",
+                    cards_to_kifu(&cards)
+                ))
+            }),
+        }
+    }
+
+    #[getter]
+    fn cards(&self) -> Vec<PyCard> {
+        match &self.0 {
+            Comb::Single(card) => vec![PyCard(*card)],
+            Comb::Multi(cards) | Comb::Seq(cards) => cards.iter().copied().map(PyCard).collect(),
+        }
+    }
+
+    // single、multi、seqのいずれか
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match &self.0 {
+            Comb::Single(_) => "single",
+            Comb::Multi(_) => "multi",
+            Comb::Seq(_) => "seq",
+        }
+    }
+
+    fn __str__(&self) -> String {
+        match &self.0 {
+            Comb::Single(card) => card_to_kifu(card),
+            Comb::Multi(cards) | Comb::Seq(cards) => cards_to_kifu(cards),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Comb('{}')", self.__str__())
+    }
+}
+
+// humans人の席をPythonから操作し、残りの席をNPCが埋めるゲーム
+#[pyclass(name = "Game", module = "daifugo", unsendable)]
+pub struct PyGame(ExternalGame);
+
+#[pymethods]
+impl PyGame {
+    #[new]
+    #[pyo3(signature = (seed = 0, humans = PLAYERS_COUNT, npc = "min"))]
+    fn new(seed: u64, humans: usize, npc: &str) -> PyResult<Self> {
+        if !(1..=PLAYERS_COUNT).contains(&humans) {
+            return Err(PyValueError::new_err(format!(
+                "humansは1から{PLAYERS_COUNT}で指定してください"
+            )));
+        }
+        let npc = npc.parse::<Strategy>().map_err(PyValueError::new_err)?;
+        Ok(Self(ExternalGame::new(seed, humans, npc)))
+    }
+
+    // Pythonから操作する席
+    #[getter]
+    fn seats(&self) -> Vec<usize> {
+        self.0.get_seats().to_vec()
+    }
+
+    // Pythonから操作する席の手番ならその席
+    #[getter]
+    fn turn(&self) -> Option<usize> {
+        self.0.get_turn()
+    }
+
+    fn hands(&mut self, seat: usize) -> PyResult<Vec<PyCard>> {
+        if seat >= PLAYERS_COUNT {
+            return Err(PyIndexError::new_err(format!("不明な席: {seat}")));
+        }
+        Ok(self.0.get_hands(seat).iter().copied().map(PyCard).collect())
+    }
+
+    #[getter]
+    fn hand_counts(&self) -> Vec<usize> {
+        self.0
+            .get_view()
+            .players
+            .into_iter()
+            .map(|(_, count)| count)
+            .collect()
+    }
+
+    #[getter]
+    fn prev_comb(&self) -> Option<PyComb> {
+        self.0.get_view().prev_comb.map(PyComb)
+    }
+
+    #[getter]
+    fn is_rev(&self) -> bool {
+        self.0.get_view().is_rev
+    }
+
+    // ラウンドが終わっていれば順位ごとの席
+    #[getter]
+    fn player_rank(&self) -> Option<Vec<usize>> {
+        self.0.get_player_rank().map(<[usize]>::to_vec)
+    }
+
+    // 手番の席から出せる手の一覧(Noneはパス)
+    fn legal_moves(&mut self) -> Vec<Option<PyComb>> {
+        self.0
+            .get_legal_moves()
+            .into_iter()
+            .map(|comb| comb.map(PyComb))
+            .collect()
+    }
+
+    // 手(Noneならパス)を出し、次にPythonから操作する席の手番までNPCの手番を進める
+    #[pyo3(signature = (comb))]
+    fn play(&mut self, comb: Option<PyComb>) -> PyResult<()> {
+        self.0
+            .play(comb.map(|comb| comb.0))
+            .map_err(PyValueError::new_err)
+    }
+
+    fn next_round(&mut self) -> bool {
+        self.0.next_round()
+    }
+
+    // 席から見える状態をJSONで取得する
+    fn state_json(&mut self, seat: usize) -> PyResult<String> {
+        if seat >= PLAYERS_COUNT {
+            return Err(PyIndexError::new_err(format!("不明な席: {seat}")));
+        }
+        Ok(self.0.get_state_json(seat))
+    }
+}
+
+#[pymodule]
+fn daifugo(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyCard>()?;
+    m.add_class::<PyComb>()?;
+    m.add_class::<PyGame>()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_py_game() {
+        for (text, expected) in [("♠7", Some(("S", 4))), ("JK", None)] {
+            let card = PyCard::new(text).unwrap();
+            assert_eq!(card.suit().as_deref().zip(card.rank()), expected);
+            assert_eq!(card.__str__(), text);
+        }
+        assert!(PyCard::new("♠7♥7").is_err());
+        let sevens = vec![PyCard::new("♠7").unwrap(), PyCard::new("♥7").unwrap()];
+        assert_eq!(PyComb::new(sevens).unwrap().kind(), "multi");
+        assert!(PyGame::new(0, 0, "min").is_err());
+        assert!(PyGame::new(0, 1, "unknown").is_err());
+        // 全ての席をPythonから操作して自己対戦する
+        let mut game = PyGame::new(0, PLAYERS_COUNT, "min").unwrap();
+        while game.player_rank().is_none() {
+            let seat = game.turn().unwrap();
+            let comb = game.legal_moves().pop().unwrap();
+            let count = game.hands(seat).unwrap().len();
+            let played = comb.as_ref().map_or(0, |comb| comb.cards().len());
+            game.play(comb).unwrap();
+            assert_eq!(game.hands(seat).unwrap().len(), count - played);
+        }
+        assert_eq!(game.hand_counts().iter().filter(|c| **c > 0).count(), 1);
+        assert!(game.next_round());
+    }
+}
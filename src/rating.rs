@@ -0,0 +1,54 @@
+// Eloレーティング
+pub const INITIAL_RATING: f64 = 1500.0;
+const K_FACTOR: f64 = 32.0;
+
+// 順位を全てのプレイヤー同士の対戦の結果とみなしてレーティングを更新する
+pub fn update_ratings(ratings: &[f64], player_rank: &[usize]) -> Vec<f64> {
+    let n = ratings.len();
+    if n < 2 {
+        return ratings.to_vec();
+    }
+    let places: Vec<usize> = (0..n)
+        .map(|idx| player_rank.iter().position(|p| *p == idx).unwrap_or(n - 1))
+        .collect();
+    (0..n)
+        .map(|i| {
+            let delta: f64 = (0..n)
+                .filter(|j| *j != i)
+                .map(|j| {
+                    let expected = 1.0 / (1.0 + 10f64.powf((ratings[j] - ratings[i]) / 400.0));
+                    let score = match places[i].cmp(&places[j]) {
+                        std::cmp::Ordering::Less => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+                    score - expected
+                })
+                .sum();
+            ratings[i] + K_FACTOR / (n - 1) as f64 * delta
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update_ratings() {
+        for (ratings, player_rank, expected) in [
+            (
+                vec![1500.0; 4],
+                vec![2, 0, 3, 1],
+                vec![1505.33, 1484.0, 1516.0, 1494.67],
+            ),
+            (vec![1500.0, 1500.0], vec![1, 0], vec![1484.0, 1516.0]),
+            (vec![1800.0, 1400.0], vec![0, 1], vec![1802.91, 1397.09]),
+        ] {
+            let ratings = update_ratings(&ratings, &player_rank);
+            for (rating, expected) in ratings.iter().zip(expected) {
+                assert!((rating - expected).abs() < 0.01);
+            }
+        }
+    }
+}
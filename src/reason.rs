@@ -0,0 +1,138 @@
+use crate::card::{Card, Rank};
+use crate::comb::Comb;
+use crate::hand::get_strength;
+use crate::i18n::{tr, trf};
+use crate::moves::get_legal_combs;
+use crate::validator::Validator;
+use itertools::Itertools;
+
+// NPCが手を選んだ理由の短い説明(handsは手を選ぶ前の手札)
+pub fn get_reason(hands: &[Card], comb: Option<&Comb>, validator: &dyn Validator) -> String {
+    match comb {
+        Some(comb) => get_play_reason(hands, comb, validator),
+        None => get_pass_reason(hands, validator),
+    }
+}
+
+fn get_play_reason(hands: &[Card], comb: &Comb, validator: &dyn Validator) -> String {
+    let view = validator.get_view();
+    let cards = comb.get_cards();
+    if cards.len() == hands.len() {
+        return tr("reason_finish").to_owned();
+    }
+    if view.rules.revolution && matches!(comb, Comb::Multi(_)) && cards.len() >= 4 {
+        return tr("reason_revolution").to_owned();
+    }
+    if view.rules.eight_cut
+        && cards
+            .iter()
+            .any(|c| matches!(c, Card::Normal(_, Rank::Eight)))
+    {
+        return tr("reason_eight").to_owned();
+    }
+    if cards.contains(&Card::Joker) {
+        return tr("reason_joker").to_owned();
+    }
+    if cards.len() > 1 {
+        return trf("reason_group", &[("count", &cards.len())]);
+    }
+    if hands.contains(&Card::Joker) {
+        return trf("reason_hold_joker", &[("cards", comb)]);
+    }
+    // 出せる1枚のカードの中で最も弱ければ強いカードを残したことになる
+    let is_rev = view.is_rev != view.is_jack_back;
+    let strength = get_strength(&cards[0], is_rev);
+    let is_lowest = get_legal_combs(hands, validator)
+        .iter()
+        .filter_map(|(legal, _)| match legal {
+            Comb::Single(card) => Some(get_strength(card, is_rev)),
+            _ => None,
+        })
+        .all(|s| s >= strength);
+    match is_lowest {
+        true => tr("reason_lowest").to_owned(),
+        false => trf("reason_play", &[("cards", comb)]),
+    }
+}
+
+fn get_pass_reason(hands: &[Card], validator: &dyn Validator) -> String {
+    let legal_combs = get_legal_combs(hands, validator);
+    if legal_combs.is_empty() {
+        return tr("reason_no_move").to_owned();
+    }
+    // 出せば同じ数字の組が崩れるなら、その組を残すためにパスしたことになる
+    let group = legal_combs.iter().find_map(|(comb, _)| {
+        let rank = get_rank(comb)?;
+        let group: Vec<&Card> = hands
+            .iter()
+            .filter(|c| matches!(c, Card::Normal(_, r) if *r == rank))
+            .collect();
+        (group.len() > comb.get_cards().len()).then_some(group)
+    });
+    if let Some(group) = group {
+        return trf("reason_keep_group", &[("cards", &group.iter().join(" "))]);
+    }
+    if legal_combs
+        .iter()
+        .all(|(comb, _)| comb.get_cards().contains(&Card::Joker))
+    {
+        return tr("reason_keep_joker").to_owned();
+    }
+    tr("reason_keep_strong").to_owned()
+}
+
+// 1枚のカードか同じ数字の組の数字(ジョーカーだけなら数字はない)
+fn get_rank(comb: &Comb) -> Option<Rank> {
+    match comb {
+        Comb::Single(_) | Comb::Multi(_) => comb.get_cards().iter().find_map(|card| match card {
+            Card::Normal(_, rank) => Some(*rank),
+            Card::Joker => None,
+        }),
+        Comb::Seq(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::field::Field;
+
+    // カードの表記の異体字セレクタを除いて比べる
+    fn get_plain_reason(hands: &str, comb: Option<&str>, validator: &dyn Validator) -> String {
+        let hands = parse_cards(hands).unwrap();
+        let comb = comb.map(|comb| comb.parse::<Comb>().unwrap());
+        get_reason(&hands, comb.as_ref(), validator).replace('\u{fe0f}', "")
+    }
+
+    #[test]
+    fn test_get_reason() {
+        let mut field = Field::new(2, 0);
+        field.set_players(vec!["NpcA".to_owned(), "NpcB".to_owned()], vec![5, 5]);
+        for (hands, comb, expected) in [
+            ("♠3", Some("♠3"), "この手で上がれる"),
+            (
+                "♠5 ♥5 ♦5 ♣5 ♠K",
+                Some("♠5 ♥5 ♦5 ♣5"),
+                "革命でカードの強さを逆転させる",
+            ),
+            ("♠8 ♠K", Some("♠8"), "8切りで場を流して親を取る"),
+            ("♠4 ♥4 ♠K", Some("♠4 ♥4"), "2枚の組を出して手数を減らす"),
+            ("♠4 JK", Some("♠4"), "ジョーカーを終盤まで残して♠4を出す"),
+            ("♠4 ♠K", Some("♠4"), "弱いカードから出して強いカードを残す"),
+            ("♠4 ♠K", Some("♠K"), "♠Kを出す"),
+        ] {
+            assert_eq!(get_plain_reason(hands, comb, &field), expected);
+        }
+        // 場に♠6がある場合のパス
+        field.put(Some(&"♠6".parse::<Comb>().unwrap()), 4).unwrap();
+        for (hands, expected) in [
+            ("♠3 ♥4", "出せる手がない"),
+            ("♠K ♥K ♠3", "♠K ♥Kの組を崩さないためにパス"),
+            ("JK ♠3", "ジョーカーを終盤まで残すためにパス"),
+            ("♠A ♠3", "強いカードを温存するためにパス"),
+        ] {
+            assert_eq!(get_plain_reason(hands, None, &field), expected);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::event::GameEvent;
+use crate::field::Effect;
+use crate::observer::Observer;
+use crate::strategy::Strategy;
+use itertools::Itertools;
+use std::path::Path;
+
+pub const CSV_HEADER: &str = "seed,seats,player_rank,turns,revs,binds,eights";
+
+// 1ゲーム分の対戦結果
+#[derive(Debug, Default, PartialEq)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub seats: Vec<Strategy>,
+    // 上がった順の席番号
+    pub player_rank: Vec<usize>,
+    pub turns: usize,
+    pub revs: usize,
+    pub binds: usize,
+    pub eights: usize,
+}
+
+impl GameRecord {
+    pub fn new(seed: u64, seats: Vec<Strategy>) -> Self {
+        Self {
+            seed,
+            seats,
+            ..Default::default()
+        }
+    }
+}
+
+impl Observer for GameRecord {
+    fn notify(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Turn { outcome, .. } => {
+                self.turns += 1;
+                self.revs += usize::from(
+                    outcome.contains(Effect::Revolution)
+                        || outcome.contains(Effect::SuperRevolution),
+                );
+                self.binds += usize::from(outcome.contains(Effect::Bind));
+                self.eights += usize::from(outcome.contains(Effect::EightCut));
+            }
+            GameEvent::End { player_rank } => self.player_rank = player_rank.clone(),
+            _ => {}
+        }
+    }
+}
+
+// CSVの1行に変換する(席順と順位は空白区切り)
+impl From<&GameRecord> for String {
+    fn from(record: &GameRecord) -> Self {
+        format!(
+            "{},{},{},{},{},{},{}",
+            record.seed,
+            record.seats.iter().map(|s| s.get_name()).join(" "),
+            record.player_rank.iter().join(" "),
+            record.turns,
+            record.revs,
+            record.binds,
+            record.eights
+        )
+    }
+}
+
+pub fn write_csv<'a, I>(path: &Path, records: I) -> Result<(), String>
+where
+    I: IntoIterator<Item = &'a GameRecord>,
+{
+    let mut text = format!("{CSV_HEADER}\n");
+    for record in records {
+        text.push_str(&String::from(record));
+        text.push('\n');
+    }
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_game_record() {
+        for (record, expected) in [
+            (GameRecord::new(0, vec![]), "0,,,0,0,0,0"),
+            (
+                GameRecord {
+                    seed: 42,
+                    seats: vec![
+                        Strategy::Min,
+                        Strategy::Heuristic,
+                        Strategy::Min,
+                        Strategy::Mcts,
+                    ],
+                    player_rank: vec![2, 0, 3, 1],
+                    turns: 37,
+                    revs: 1,
+                    binds: 2,
+                    eights: 3,
+                },
+                "42,min heuristic min mcts,2 0 3 1,37,1,2,3",
+            ),
+        ] {
+            assert_eq!(String::from(&record), expected);
+        }
+    }
+}
@@ -0,0 +1,636 @@
+use crate::card::{card_to_ascii, suit_to_ascii, Card, Suit};
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::field::{Effect, TurnOutcome};
+use crate::i18n::{tr, trf};
+use crate::input::{get_input, take_input};
+use crate::observer::Observer;
+use crate::player::take_from_hands;
+use itertools::Itertools;
+use rand::Rng;
+use std::str::FromStr;
+use std::{thread, time::Duration};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const MAGENTA: &str = "\x1b[35m";
+const BANNER: &str = "\x1b[1;30;43m";
+const BASE_DELAY: Duration = Duration::from_millis(300);
+const MIN_SPEED: f64 = 0.125;
+const MAX_SPEED: f64 = 64.0;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Style {
+    color: bool,
+    ascii: bool,
+}
+
+impl Style {
+    pub fn new(color: bool, ascii: bool) -> Self {
+        Self { color, ascii }
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("{code}{text}{RESET}")
+        } else {
+            text.to_owned()
+        }
+    }
+
+    pub fn suit_text(&self, suit: &Suit) -> String {
+        match self.ascii {
+            true => suit_to_ascii(suit),
+            false => suit.to_string(),
+        }
+    }
+
+    pub fn card_text(&self, card: &Card) -> String {
+        match self.ascii {
+            true => card_to_ascii(card),
+            false => card.to_string(),
+        }
+    }
+
+    pub fn suit(&self, suit: &Suit) -> String {
+        let text = self.suit_text(suit);
+        match suit {
+            Suit::Heart | Suit::Diamond => self.paint(RED, &text),
+            Suit::Club | Suit::Spade => text,
+        }
+    }
+
+    pub fn card(&self, card: &Card) -> String {
+        let text = self.card_text(card);
+        match card {
+            Card::Normal(Suit::Heart | Suit::Diamond, _) => self.paint(RED, &text),
+            Card::Normal(_, _) => text,
+            Card::Joker => self.paint(MAGENTA, &text),
+        }
+    }
+
+    pub fn comb(&self, comb: &Comb) -> String {
+        match comb {
+            Comb::Single(card) => self.card(card),
+            Comb::Multi(cards) | Comb::Seq(cards) => cards.iter().map(|c| self.card(c)).join(" "),
+        }
+    }
+
+    pub fn banner(&self, text: &str) -> String {
+        if self.color {
+            self.paint(BANNER, &format!(" {text} "))
+        } else {
+            text.to_owned()
+        }
+    }
+
+    pub fn bold(&self, text: &str) -> String {
+        self.paint(BOLD, text)
+    }
+
+    pub fn dim(&self, text: &str) -> String {
+        self.paint(DIM, text)
+    }
+}
+
+// 速度に応じた1ターン毎の待ち時間
+pub fn get_delay(speed: f64) -> Duration {
+    BASE_DELAY.div_f64(speed)
+}
+
+// NPCが手を考えているように見せる待ち時間の範囲(ミリ秒)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThinkingTime {
+    pub min: u64,
+    pub max: u64,
+}
+
+impl ThinkingTime {
+    pub const DEFAULT: ThinkingTime = ThinkingTime {
+        min: BASE_DELAY.as_millis() as u64,
+        max: BASE_DELAY.as_millis() as u64,
+    };
+
+    // 範囲からランダムに選んだ待ち時間を速度に応じて縮める
+    pub fn get_delay<R: Rng>(&self, speed: f64, rng: &mut R) -> Duration {
+        Duration::from_millis(rng.gen_range(self.min..=self.max)).div_f64(speed)
+    }
+}
+
+// 300のような1つの値か、200-800のような範囲
+impl FromStr for ThinkingTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (min, max) = s.split_once('-').unwrap_or((s, s));
+        match (min.trim().parse::<u64>(), max.trim().parse::<u64>()) {
+            (Ok(min), Ok(max)) if min <= max => Ok(Self { min, max }),
+            _ => Err(format!(
+                "不正な待ち時間: {s} (300や200-800のようにミリ秒で指定してください)"
+            )),
+        }
+    }
+}
+
+// 人間の席を除いた席に順に待ち時間を割り当てる(足りなければ最初から繰り返す)
+pub fn get_thinking_times(
+    players_count: usize,
+    human_seats: &[usize],
+    times: &[ThinkingTime],
+) -> Vec<Option<ThinkingTime>> {
+    let mut times = times.iter().cycle();
+    (0..players_count)
+        .map(|seat| match human_seats.contains(&seat) {
+            true => None,
+            false => times.next().copied(),
+        })
+        .collect()
+}
+
+pub fn change_speed(speed: f64, command: &str) -> f64 {
+    match command {
+        "+" => (speed * 2.0).min(MAX_SPEED),
+        "-" => (speed / 2.0).max(MIN_SPEED),
+        _ => speed,
+    }
+}
+
+// 順位の称号(人数に応じて大富豪、富豪、平民、貧民、大貧民を割り当てる)
+pub fn get_title(place: usize, players_count: usize) -> &'static str {
+    let key = match place {
+        0 => "title_daifugo",
+        p if p + 1 >= players_count => "title_daihinmin",
+        1 if players_count >= 4 => "title_fugo",
+        p if players_count >= 4 && p + 2 == players_count => "title_hinmin",
+        _ => "title_heimin",
+    };
+    tr(key)
+}
+
+pub fn get_effect_text(effect: &Effect) -> &'static str {
+    let key = match effect {
+        Effect::EightCut => "eight_cut",
+        Effect::Bind => "bind",
+        Effect::Revolution => "reversed",
+        Effect::SuperRevolution => "super_reversed",
+        Effect::JackBack => "jack_back",
+        Effect::FiveSkip => "five_skip",
+        Effect::CapitalFall(_) => "capital_fall",
+        Effect::FoulVoid => "foul_void",
+        Effect::EightReturn => "eight_return",
+    };
+    tr(key)
+}
+
+pub fn get_lead_line(name: &str) -> String {
+    trf("lead", &[("name", &name)])
+}
+
+pub fn clear_screen() {
+    print!("\x1b[2J\x1b[H");
+}
+
+pub struct Renderer {
+    style: Style,
+    names: Vec<String>,
+    speed: f64,
+    quiet: bool,
+    // 席毎の終わったラウンドの順位(0が大富豪)
+    places: Vec<Vec<usize>>,
+    // 全てのプレイヤーの手札を表示する
+    open_hands: bool,
+    hands: Vec<Vec<Card>>,
+    // 席毎の手を表示するまでの待ち時間(人間の席はNone、指定がなければ一定の待ち時間)
+    thinking: Vec<Option<ThinkingTime>>,
+}
+
+impl Renderer {
+    pub fn new(style: Style, speed: f64, quiet: bool) -> Self {
+        Self {
+            style,
+            names: vec![],
+            speed,
+            quiet,
+            places: vec![],
+            open_hands: false,
+            hands: vec![],
+            thinking: vec![],
+        }
+    }
+
+    pub fn with_thinking(mut self, thinking: Vec<Option<ThinkingTime>>) -> Self {
+        self.thinking = thinking;
+        self
+    }
+
+    fn get_thinking_delay(&self, idx: usize) -> Duration {
+        match self.thinking.get(idx) {
+            Some(Some(time)) => time.get_delay(self.speed, &mut rand::thread_rng()),
+            // 人間のプレイヤーは既に手を選んでいるので待たない
+            Some(None) => Duration::ZERO,
+            None => get_delay(self.speed),
+        }
+    }
+
+    pub fn with_open_hands(mut self, open_hands: bool) -> Self {
+        self.open_hands = open_hands;
+        self
+    }
+
+    fn get_reason_line(&self, idx: usize, reason: &str) -> String {
+        self.style.dim(&format!(
+            "  {}: {reason}",
+            self.names.get(idx).map_or("", |n| n)
+        ))
+    }
+
+    fn get_hands_line(&self, idx: usize) -> String {
+        let cards = self.hands[idx].iter().map(|c| self.style.card(c)).join(" ");
+        self.style.dim(&format!("  {}: {cards}", self.names[idx]))
+    }
+
+    fn wait(&mut self, idx: usize) {
+        // 入力済みの速度変更と一時停止のコマンドを処理する
+        while let Some(command) = take_input(|line| matches!(line, "+" | "-" | "")) {
+            if command.is_empty() {
+                get_input(self.style.dim(tr("paused")));
+            } else {
+                self.speed = change_speed(self.speed, &command);
+                let speed = trf("speed", &[("speed", &self.speed)]);
+                println!("{}", self.style.dim(&speed));
+            }
+        }
+        thread::sleep(self.get_thinking_delay(idx));
+    }
+
+    // 中断したときの途中経過とこれまでのラウンドの成績
+    pub fn get_quit_lines(&self, places: &[Option<usize>], hands_counts: &[usize]) -> Vec<String> {
+        let mut lines = vec![self.style.bold(tr("standings"))];
+        for (i, name) in self.names.iter().enumerate() {
+            lines.push(match places.get(i).copied().flatten() {
+                Some(place) => trf("finished", &[("name", name), ("place", &(place + 1))]),
+                None => trf(
+                    "cards_left",
+                    &[("name", name), ("count", hands_counts.get(i).unwrap_or(&0))],
+                ),
+            });
+        }
+        lines.extend(self.get_record_lines());
+        lines
+    }
+
+    // これまでに終わったラウンドの成績(終わったラウンドがなければ空)
+    pub fn get_record_lines(&self) -> Vec<String> {
+        let rounds = self.places.first().map_or(0, Vec::len);
+        if rounds == 0 {
+            return vec![];
+        }
+        let mut lines = vec![self.style.bold(&trf("record", &[("rounds", &rounds)]))];
+        for (name, places) in self.names.iter().zip(&self.places) {
+            let wins = places.iter().filter(|place| **place == 0).count();
+            let average = places.iter().sum::<usize>() as f64 / rounds as f64 + 1.0;
+            lines.push(trf(
+                "record_line",
+                &[
+                    ("name", name),
+                    ("wins", &wins),
+                    ("average", &format!("{average:.1}")),
+                ],
+            ));
+        }
+        lines
+    }
+
+    pub fn get_turn_lines(
+        &self,
+        idx: usize,
+        comb: &Option<Comb>,
+        hands_count: usize,
+        outcome: &TurnOutcome,
+    ) -> Vec<String> {
+        let name = &self.names[idx];
+        let mut lines = vec![match comb {
+            Some(comb) => format!("{} [{:2}]: {}", name, hands_count, self.style.comb(comb)),
+            None => self
+                .style
+                .dim(&format!("{} [{:2}]: {}", name, hands_count, tr("pass"))),
+        }];
+        if self.open_hands && hands_count > 0 {
+            lines.push(self.get_hands_line(idx));
+        }
+        for effect in &outcome.effects {
+            lines.push(self.style.banner(get_effect_text(effect)));
+        }
+        if let Some(finish) = outcome.finish {
+            let key = if finish.foul { "foul" } else { "out" };
+            // ラウンドの途中でも上がった時点の順位の称号を表示する
+            let title = get_title(finish.place, self.names.len());
+            let line = trf(key, &[("name", name)]) + &trf("provisional", &[("title", &title)]);
+            lines.push(self.style.bold(&line));
+        }
+        if let Some(fallen) = outcome.get_fallen() {
+            let fallen = &self.names[fallen];
+            lines.push(self.style.bold(&trf("fallen", &[("name", fallen)])));
+        }
+        lines
+    }
+
+    fn get_exchange_line(&self, key: &'static str, from: usize, to: usize, count: usize) -> String {
+        trf(
+            key,
+            &[
+                ("from", &self.names[from]),
+                ("to", &self.names[to]),
+                ("count", &count),
+            ],
+        )
+    }
+}
+
+impl Observer for Renderer {
+    fn notify(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Start { names, hands } => {
+                if self.places.len() != names.len() {
+                    self.places = vec![vec![]; names.len()];
+                }
+                self.names = names.clone();
+                self.hands = hands.clone();
+                if self.open_hands && !self.quiet {
+                    for idx in 0..self.hands.len() {
+                        println!("{}", self.get_hands_line(idx));
+                    }
+                }
+            }
+            GameEvent::Turn {
+                idx,
+                comb,
+                hands_count,
+                outcome,
+            } => {
+                if let (Some(comb), Some(hands)) = (comb, self.hands.get_mut(*idx)) {
+                    take_from_hands(hands, comb.get_cards());
+                }
+                // 静かなモードでは毎ターンの表示と待ち時間を省く
+                if self.quiet {
+                    return;
+                }
+                // NPCが考えている間を置いてから手を表示する
+                self.wait(*idx);
+                for line in self.get_turn_lines(*idx, comb, *hands_count, outcome) {
+                    println!("{line}");
+                }
+            }
+            GameEvent::Lead { idx } => {
+                if !self.quiet {
+                    println!("{}", self.style.dim(&get_lead_line(&self.names[*idx])));
+                }
+            }
+            GameEvent::End { player_rank } => {
+                println!("{}", self.style.bold(tr("results")));
+                for (i, idx) in player_rank.iter().enumerate() {
+                    let name = &self.names[*idx];
+                    println!("{}", trf("place", &[("place", &(i + 1)), ("name", name)]));
+                    if let Some(places) = self.places.get_mut(*idx) {
+                        places.push(i);
+                    }
+                }
+            }
+            GameEvent::Tribute { from, to, cards } => {
+                println!(
+                    "{}",
+                    self.get_exchange_line("tribute", *from, *to, cards.len())
+                );
+            }
+            GameEvent::Return { from, to, cards } => {
+                println!(
+                    "{}",
+                    self.get_exchange_line("return", *from, *to, cards.len())
+                );
+            }
+            GameEvent::Exchange => {
+                println!("{}", tr("exchange"));
+            }
+            GameEvent::Reject { idx, error } => {
+                let name = &self.names[*idx];
+                let line = trf(
+                    "rejected",
+                    &[("name", name), ("error", &tr(error.get_key()))],
+                );
+                println!("{line}");
+            }
+            GameEvent::Reason { idx, reason } => {
+                if !self.quiet {
+                    println!("{}", self.get_reason_line(*idx, reason));
+                }
+            }
+            GameEvent::Quit {
+                places,
+                hands_counts,
+            } => {
+                for line in self.get_quit_lines(places, hands_counts) {
+                    println!("{line}");
+                }
+            }
+            GameEvent::Substitute { idx } => {
+                println!("{}", trf("substitute", &[("name", &self.names[*idx])]));
+            }
+            GameEvent::Undo { turns: 0 } => println!("{}", tr("undo_failed")),
+            GameEvent::Undo { turns } => println!("{}", trf("undone", &[("turns", turns)])),
+            GameEvent::Save { path, result } => match result {
+                Ok(()) => println!("{}", trf("saved", &[("path", &path.display())])),
+                Err(e) => println!("{}", trf("save_failed", &[("error", e)])),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::Rank;
+    use crate::field::Finish;
+
+    #[test]
+    fn test_style() {
+        let card = Card::Normal(Suit::Heart, Rank::Ten);
+        let style = Style::new(false, false);
+        assert_eq!(style.card(&card), "♥10");
+        assert_eq!(style.banner("8切り"), "8切り");
+        let style = Style::new(false, true);
+        assert_eq!(style.card(&card), "H10");
+        assert_eq!(style.suit(&Suit::Club), "C");
+        assert_eq!(style.card(&Card::Joker), "JK");
+        let style = Style::new(true, true);
+        assert_eq!(style.card(&card), "\x1b[31mH10\x1b[0m");
+        let style = Style::new(true, false);
+        assert_eq!(style.card(&card), "\x1b[31m♥10\x1b[0m");
+        assert_eq!(
+            style.card(&Card::Normal(Suit::Spade, Rank::Ten)),
+            "♠️10".to_owned()
+        );
+        assert_eq!(style.dim("パス"), "\x1b[2mパス\x1b[0m");
+        assert_eq!(style.banner("8切り"), "\x1b[1;30;43m 8切り \x1b[0m");
+    }
+
+    #[test]
+    fn test_change_speed() {
+        assert_eq!(get_delay(1.0), Duration::from_millis(300));
+        assert_eq!(get_delay(2.0), Duration::from_millis(150));
+        assert_eq!(change_speed(1.0, "+"), 2.0);
+        assert_eq!(change_speed(1.0, "-"), 0.5);
+        assert_eq!(change_speed(MAX_SPEED, "+"), MAX_SPEED);
+        assert_eq!(change_speed(MIN_SPEED, "-"), MIN_SPEED);
+        assert_eq!(change_speed(1.0, "x"), 1.0);
+    }
+
+    #[test]
+    fn test_thinking_time() {
+        for (s, expected) in [
+            ("300", Ok(ThinkingTime { min: 300, max: 300 })),
+            ("200-800", Ok(ThinkingTime { min: 200, max: 800 })),
+            ("0", Ok(ThinkingTime { min: 0, max: 0 })),
+        ] {
+            assert_eq!(s.parse::<ThinkingTime>(), expected);
+        }
+        for s in ["800-200", "fast", "-1", "1-2-3", ""] {
+            assert!(s.parse::<ThinkingTime>().is_err());
+        }
+        let mut rng = rand::thread_rng();
+        let time = ThinkingTime { min: 200, max: 800 };
+        for _ in 0..10 {
+            let delay = time.get_delay(2.0, &mut rng);
+            assert!(Duration::from_millis(100) <= delay && delay <= Duration::from_millis(400));
+        }
+        assert_eq!(ThinkingTime::DEFAULT.get_delay(1.0, &mut rng), BASE_DELAY);
+        let fast = ThinkingTime { min: 0, max: 0 };
+        let slow = ThinkingTime {
+            min: 1000,
+            max: 3000,
+        };
+        assert_eq!(
+            get_thinking_times(4, &[1], &[fast, slow]),
+            vec![Some(fast), None, Some(slow), Some(fast)]
+        );
+        let renderer =
+            Renderer::new(Style::default(), 1.0, false).with_thinking(vec![None, Some(fast)]);
+        assert_eq!(renderer.get_thinking_delay(0), Duration::ZERO);
+        assert_eq!(renderer.get_thinking_delay(1), Duration::ZERO);
+        // 席毎の指定がなければ一定の待ち時間になる
+        assert_eq!(renderer.get_thinking_delay(2), BASE_DELAY);
+    }
+
+    #[test]
+    fn test_get_turn_lines() {
+        let mut renderer = Renderer::new(Style::new(false, false), 1.0, false);
+        renderer.notify(&GameEvent::Start {
+            names: vec!["User".to_owned(), "NpcA".to_owned()],
+            hands: vec![vec![], vec![]],
+        });
+        let comb = Some(Comb::Single(Card::Normal(Suit::Club, Rank::Eight)));
+        assert_eq!(
+            renderer.get_turn_lines(
+                1,
+                &comb,
+                0,
+                &TurnOutcome {
+                    effects: vec![Effect::EightCut],
+                    finish: Some(Finish {
+                        idx: 1,
+                        place: 1,
+                        foul: true
+                    }),
+                    cleared: false,
+                }
+            ),
+            vec!["NpcA [ 0]: ♣️8", "8切り", "NpcA 反則上がり (暫定: 大貧民)"]
+        );
+        assert_eq!(
+            renderer.get_turn_lines(0, &None, 5, &TurnOutcome::default()),
+            vec!["User [ 5]: パス"]
+        );
+        // 都落ちした大富豪の名前を表示する
+        let outcome = TurnOutcome {
+            effects: vec![Effect::CapitalFall(0)],
+            finish: Some(Finish {
+                idx: 1,
+                place: 0,
+                foul: false,
+            }),
+            cleared: false,
+        };
+        assert_eq!(
+            renderer.get_turn_lines(1, &comb, 0, &outcome),
+            vec![
+                "NpcA [ 0]: ♣️8",
+                "都落ち",
+                "NpcA 上がり (暫定: 大富豪)",
+                "User 最下位に転落"
+            ]
+        );
+        assert_eq!(get_lead_line("NpcA"), "場が流れました (NpcAから)");
+        // 手札を公開すると出した後の手札を表示する
+        let mut renderer = Renderer::new(Style::new(false, true), 1.0, true).with_open_hands(true);
+        renderer.notify(&GameEvent::Start {
+            names: vec!["User".to_owned(), "NpcA".to_owned()],
+            hands: vec![
+                vec![Card::Normal(Suit::Club, Rank::Three)],
+                vec![Card::Normal(Suit::Club, Rank::Eight), Card::Joker],
+            ],
+        });
+        renderer.notify(&GameEvent::Turn {
+            idx: 1,
+            comb: comb.clone(),
+            hands_count: 1,
+            outcome: TurnOutcome {
+                effects: vec![Effect::EightCut],
+                ..TurnOutcome::default()
+            },
+        });
+        assert_eq!(
+            renderer.get_turn_lines(1, &comb, 1, &TurnOutcome::default()),
+            vec!["NpcA [ 1]: C8", "  NpcA: JK"]
+        );
+    }
+
+    #[test]
+    fn test_get_title() {
+        for (players_count, expected) in [
+            (2, vec!["大富豪", "大貧民"]),
+            (3, vec!["大富豪", "平民", "大貧民"]),
+            (4, vec!["大富豪", "富豪", "貧民", "大貧民"]),
+            (5, vec!["大富豪", "富豪", "平民", "貧民", "大貧民"]),
+        ] {
+            let titles: Vec<&str> = (0..players_count)
+                .map(|place| get_title(place, players_count))
+                .collect();
+            assert_eq!(titles, expected);
+        }
+    }
+
+    #[test]
+    fn test_get_quit_lines() {
+        let mut renderer = Renderer::new(Style::new(false, false), 1.0, true);
+        renderer.notify(&GameEvent::Start {
+            names: vec!["User".to_owned(), "NpcA".to_owned()],
+            hands: vec![vec![], vec![]],
+        });
+        assert_eq!(
+            renderer.get_quit_lines(&[None, Some(0)], &[3, 0]),
+            vec!["途中経過", "User: 残り3枚", "NpcA: 1位で上がり"]
+        );
+        for player_rank in [vec![0, 1], vec![1, 0]] {
+            renderer.notify(&GameEvent::End { player_rank });
+        }
+        assert_eq!(
+            renderer.get_quit_lines(&[None, None], &[3, 4])[3..],
+            [
+                "これまでの成績 (2ラウンド)",
+                "User: 大富豪1回 平均1.5位",
+                "NpcA: 大富豪1回 平均1.5位"
+            ]
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use crate::card::Card;
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::field::{Field, PlayError, TurnOutcome};
+use crate::i18n::{tr, trf};
+use crate::input::get_input;
+use crate::kifu::{parse_kifu, KifuRound};
+use crate::observer::Observer;
+use crate::pc::get_status;
+use crate::render::{clear_screen, Renderer, Style};
+use crate::state::{GameState, PlayerState};
+use crate::strategy::Strategy;
+use crate::validator::Validator;
+use crate::view::GameView;
+use itertools::Itertools;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fs;
+use std::path::Path;
+
+// 1手ごとの場と手札の状態
+#[derive(Debug, PartialEq)]
+pub struct Snapshot {
+    pub view: GameView,
+    pub hands: Vec<Vec<Card>>,
+    pub turn: Option<(usize, Option<Comb>, TurnOutcome)>,
+    pub player_rank: Vec<usize>,
+}
+
+// ラウンドの最初の場
+fn create_field(round: &KifuRound) -> Field {
+    let start_idx = round.moves.first().map(|(idx, _)| *idx).unwrap_or(0);
+    let mut field = Field::new(round.deals.len(), start_idx);
+    field.set_players(
+        round.names.clone(),
+        round.deals.iter().map(Vec::len).collect(),
+    );
+    field.set_rules(round.rules);
+    field.set_capital(round.capital);
+    field
+}
+
+// 棋譜のi番目の手を場に出す
+fn apply_move(
+    field: &mut Field,
+    hands: &mut [Vec<Card>],
+    i: usize,
+    (idx, comb): &(usize, Option<Comb>),
+) -> Result<TurnOutcome, String> {
+    let error = |mes: &str| trf("move_error", &[("turn", &(i + 1)), ("error", &mes)]);
+    if field.count_active_players() == 0 {
+        return Err(error(tr(PlayError::RoundOver.get_key())));
+    }
+    if *idx != field.get_idx() {
+        return Err(error(&trf("not_turn_of", &[("seat", idx)])));
+    }
+    if let Some(comb) = comb {
+        if !field.is_valid(comb) {
+            return Err(error(tr("unplayable")));
+        }
+        let cards = match comb {
+            Comb::Single(card) => vec![*card],
+            Comb::Multi(cards) | Comb::Seq(cards) => cards.clone(),
+        };
+        for card in cards {
+            let position = hands[*idx]
+                .iter()
+                .position(|c| *c == card)
+                .ok_or_else(|| error(tr("cards_not_in_hand")))?;
+            hands[*idx].remove(position);
+        }
+    }
+    field
+        .put(comb.as_ref(), hands[*idx].len())
+        .map_err(|e| error(tr(e.get_key())))
+}
+
+// 棋譜の手を順にエンジンで再生して各時点の状態を取得する
+pub fn get_snapshots(round: &KifuRound) -> Result<Vec<Snapshot>, String> {
+    let mut field = create_field(round);
+    let mut hands = round.deals.clone();
+    let mut snapshots = vec![Snapshot {
+        view: field.get_view(),
+        hands: hands.clone(),
+        turn: None,
+        player_rank: field.get_player_rank(),
+    }];
+    for (i, turn) in round.moves.iter().enumerate() {
+        let outcome = apply_move(&mut field, &mut hands, i, turn)?;
+        let (idx, comb) = turn;
+        snapshots.push(Snapshot {
+            view: field.get_view(),
+            hands: hands.clone(),
+            turn: Some((*idx, comb.clone(), outcome)),
+            player_rank: field.get_player_rank(),
+        });
+    }
+    Ok(snapshots)
+}
+
+// 棋譜のラウンドを最初のturns手まで再生したゲームの状態
+// (seatの席は人間のプレイヤー、それ以外の席はnpcの戦略のNPCにする)
+pub fn get_state(
+    round: &KifuRound,
+    turns: usize,
+    seat: usize,
+    npc: Strategy,
+    seed: u64,
+) -> Result<GameState, String> {
+    if turns > round.moves.len() {
+        return Err(trf(
+            "no_turn",
+            &[("turns", &turns), ("count", &round.moves.len())],
+        ));
+    }
+    if seat >= round.names.len() {
+        return Err(trf("no_seat", &[("seat", &seat)]));
+    }
+    let mut field = create_field(round);
+    let mut hands = round.deals.clone();
+    for (i, turn) in round.moves[..turns].iter().enumerate() {
+        apply_move(&mut field, &mut hands, i, turn)?;
+    }
+    if field.count_active_players() == 0 {
+        return Err(trf("round_over_at", &[("turns", &turns)]));
+    }
+    let dealer = round.moves.first().map(|(idx, _)| *idx).unwrap_or(0);
+    Ok(GameState {
+        players: round
+            .names
+            .iter()
+            .zip(hands)
+            .enumerate()
+            .map(|(i, (name, hands))| PlayerState {
+                name: name.clone(),
+                strategy: (i != seat).then_some(npc),
+                hands,
+            })
+            .collect(),
+        field,
+        rng: ChaCha8Rng::seed_from_u64(seed),
+        dealer,
+    })
+}
+
+// 棋譜を1手ずつ表示する(Enterで進む、bで戻る、qで終了)
+pub fn run_replay(path: &Path, style: Style) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| trf("kifu_read_failed", &[("error", &e)]))?;
+    let rounds = parse_kifu(&text)?.rounds;
+    let snapshots: Vec<(usize, &KifuRound, Snapshot)> = rounds
+        .iter()
+        .enumerate()
+        .map(|(r, round)| {
+            let snapshots = get_snapshots(round)
+                .map_err(|e| trf("round_error", &[("round", &(r + 1)), ("error", &e)]))?;
+            Ok(snapshots.into_iter().map(move |s| (r, round, s)))
+        })
+        .collect::<Result<Vec<_>, String>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+    let mut renderer = Renderer::new(style, 1.0, true);
+    let mut pos = 0;
+    while let Some((r, round, snapshot)) = snapshots.get(pos) {
+        renderer.notify(&GameEvent::Start {
+            names: round.names.clone(),
+            hands: snapshot.hands.clone(),
+        });
+        clear_screen();
+        let position = trf(
+            "replay_position",
+            &[
+                ("round", &(r + 1)),
+                ("rounds", &rounds.len()),
+                ("turn", &round_turn(&snapshots, pos)),
+            ],
+        );
+        println!("{position}");
+        if let Some((idx, comb, outcome)) = &snapshot.turn {
+            let hands_count = snapshot.hands[*idx].len();
+            for line in renderer.get_turn_lines(*idx, comb, hands_count, outcome) {
+                println!("{line}");
+            }
+        }
+        println!("{}", get_status(&snapshot.view, &style));
+        for (i, cards) in snapshot.hands.iter().enumerate() {
+            let cards = cards.iter().map(|card| style.card(card)).join(" ");
+            println!("P{i} {}: {cards}", round.names[i]);
+        }
+        // ラウンドの最後の手なら順位を表示する
+        let is_last = snapshots.get(pos + 1).is_none_or(|(next, _, _)| next != r);
+        if is_last {
+            println!("{}", style.bold(tr("results")));
+            for (i, idx) in round.player_rank.iter().enumerate() {
+                let name = &round.names[*idx];
+                println!("{}", trf("place", &[("place", &(i + 1)), ("name", name)]));
+            }
+        }
+        match get_input(style.dim(tr("replay_help"))).as_str() {
+            "b" => pos = pos.saturating_sub(1),
+            "q" => break,
+            _ => pos += 1,
+        }
+    }
+    Ok(())
+}
+
+fn round_turn(snapshots: &[(usize, &KifuRound, Snapshot)], pos: usize) -> usize {
+    let r = snapshots[pos].0;
+    snapshots[..pos].iter().filter(|(s, _, _)| *s == r).count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+
+    #[test]
+    fn test_get_snapshots() {
+        let mut round = KifuRound {
+            names: vec!["A".to_owned(), "B".to_owned()],
+            deals: vec![
+                vec![
+                    Card::Normal(Suit::Club, Rank::Three),
+                    Card::Normal(Suit::Club, Rank::Five),
+                ],
+                vec![Card::Normal(Suit::Heart, Rank::Four)],
+            ],
+            moves: vec![
+                (0, Some(Comb::Single(Card::Normal(Suit::Club, Rank::Three)))),
+                (1, Some(Comb::Single(Card::Normal(Suit::Heart, Rank::Four)))),
+            ],
+            player_rank: vec![1, 0],
+            ..Default::default()
+        };
+        let snapshots = get_snapshots(&round).unwrap();
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(
+            snapshots[1].hands,
+            vec![
+                vec![Card::Normal(Suit::Club, Rank::Five)],
+                vec![Card::Normal(Suit::Heart, Rank::Four)],
+            ]
+        );
+        let outcome = &snapshots[2].turn.as_ref().unwrap().2;
+        assert_eq!(
+            outcome.finish.map(|finish| (finish.idx, finish.place)),
+            Some((1, 0))
+        );
+        assert_eq!(snapshots[2].view.players[1], ("B".to_owned(), 0));
+        assert_eq!(snapshots[2].player_rank, vec![1, 0]);
+        // ラウンドが終わった後の手
+        round.moves.push((0, None));
+        assert_eq!(
+            get_snapshots(&round),
+            Err("3手目: ラウンドが終わった後の手です".to_owned())
+        );
+        round.moves.pop();
+        // 手番でないプレイヤーの手
+        round.moves[1].0 = 0;
+        assert_eq!(
+            get_snapshots(&round),
+            Err("2手目: P0の手番ではありません".to_owned())
+        );
+        // 手札にないカード
+        round.moves[1] = (1, Some(Comb::Single(Card::Joker)));
+        assert_eq!(
+            get_snapshots(&round),
+            Err("2手目: 手札にないカードです".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_get_state() {
+        let round = KifuRound {
+            names: vec!["A".to_owned(), "B".to_owned()],
+            deals: vec![
+                vec![
+                    Card::Normal(Suit::Club, Rank::Three),
+                    Card::Normal(Suit::Club, Rank::Five),
+                ],
+                vec![
+                    Card::Normal(Suit::Heart, Rank::Four),
+                    Card::Normal(Suit::Heart, Rank::Six),
+                ],
+            ],
+            moves: vec![
+                (0, Some(Comb::Single(Card::Normal(Suit::Club, Rank::Three)))),
+                (1, Some(Comb::Single(Card::Normal(Suit::Heart, Rank::Four)))),
+            ],
+            ..Default::default()
+        };
+        let state = get_state(&round, 1, 1, Strategy::Mcts, 0).unwrap();
+        assert_eq!(state.get_view().idx, 1);
+        assert_eq!(state.get_hands(0), [Card::Normal(Suit::Club, Rank::Five)]);
+        assert_eq!(
+            state.players.iter().map(|p| p.strategy).collect::<Vec<_>>(),
+            vec![Some(Strategy::Mcts), None]
+        );
+        assert!(get_state(&round, 3, 0, Strategy::Min, 0).is_err());
+        assert!(get_state(&round, 0, 2, Strategy::Min, 0).is_err());
+    }
+}
@@ -0,0 +1,596 @@
+use crate::card::RANKS;
+use crate::comb::MIN_SEQ;
+use crate::i18n::trf;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// 階段の強さの比べ方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SeqRule {
+    // 同じ位置のカード同士を比べる(ジョーカーを含む位置は比べない)
+    #[default]
+    Pairwise,
+    // ジョーカーを補った数字で最も弱いカード同士を比べる
+    Extreme,
+}
+
+impl SeqRule {
+    pub const ALL: [SeqRule; 2] = [SeqRule::Pairwise, SeqRule::Extreme];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            SeqRule::Pairwise => "pairwise",
+            SeqRule::Extreme => "extreme",
+        }
+    }
+}
+
+impl FromStr for SeqRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SeqRule::ALL
+            .into_iter()
+            .find(|rule| rule.get_name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = SeqRule::ALL.iter().map(|r| r.get_name()).collect();
+                trf(
+                    "unknown_seq_rule",
+                    &[("value", &s), ("choices", &names.join(", "))],
+                )
+            })
+    }
+}
+
+// 2ラウンド目以降に最初に手番になるプレイヤー
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum LeadRule {
+    // 前のラウンドの大貧民から始める
+    #[default]
+    Loser,
+    // 前のラウンドの最初のプレイヤーの次の席から始める
+    Rotate,
+    // 前のラウンドの大富豪から始める
+    Winner,
+    // ♦3を持っているプレイヤーから始める(カードを交換した後の手札で決める)
+    Diamond,
+    // 山札から1枚ずつ引いて一番強いカードを引いたプレイヤーから始める
+    Cut,
+}
+
+impl LeadRule {
+    pub const ALL: [LeadRule; 5] = [
+        LeadRule::Loser,
+        LeadRule::Rotate,
+        LeadRule::Winner,
+        LeadRule::Diamond,
+        LeadRule::Cut,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            LeadRule::Loser => "loser",
+            LeadRule::Rotate => "rotate",
+            LeadRule::Winner => "winner",
+            LeadRule::Diamond => "diamond",
+            LeadRule::Cut => "cut",
+        }
+    }
+}
+
+impl FromStr for LeadRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LeadRule::ALL
+            .into_iter()
+            .find(|rule| rule.get_name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = LeadRule::ALL.iter().map(|r| r.get_name()).collect();
+                trf(
+                    "unknown_lead_rule",
+                    &[("value", &s), ("choices", &names.join(", "))],
+                )
+            })
+    }
+}
+
+// 場にカードがないときのパスの扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum LeadPassRule {
+    // パスできない(Field::putはPlayError::LeadPassを返す)
+    #[default]
+    Reject,
+    // 場の状態を変えずに次のプレイヤーに手番を移す
+    Skip,
+}
+
+impl LeadPassRule {
+    pub const ALL: [LeadPassRule; 2] = [LeadPassRule::Reject, LeadPassRule::Skip];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            LeadPassRule::Reject => "reject",
+            LeadPassRule::Skip => "skip",
+        }
+    }
+}
+
+impl FromStr for LeadPassRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LeadPassRule::ALL
+            .into_iter()
+            .find(|rule| rule.get_name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = LeadPassRule::ALL.iter().map(|r| r.get_name()).collect();
+                trf(
+                    "unknown_lead_pass_rule",
+                    &[("value", &s), ("choices", &names.join(", "))],
+                )
+            })
+    }
+}
+
+// 反則上がりの扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum FoulRule {
+    // 最下位にするだけで、出したカードは場に残る
+    #[default]
+    Demote,
+    // 最下位にして出したカードを無効にし、場を流して次のプレイヤーから出す
+    Void,
+}
+
+impl FoulRule {
+    pub const ALL: [FoulRule; 2] = [FoulRule::Demote, FoulRule::Void];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            FoulRule::Demote => "demote",
+            FoulRule::Void => "void",
+        }
+    }
+}
+
+impl FromStr for FoulRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FoulRule::ALL
+            .into_iter()
+            .find(|rule| rule.get_name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = FoulRule::ALL.iter().map(|r| r.get_name()).collect();
+                trf(
+                    "unknown_foul_rule",
+                    &[("value", &s), ("choices", &names.join(", "))],
+                )
+            })
+    }
+}
+
+// 場で適用するルール(省略した項目は従来のルールになる)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RuleSet {
+    pub seq: SeqRule,
+    // 階段に必要な最小の枚数(0なら階段を出せない)
+    pub min_seq: usize,
+    // 8を含む組み合わせで場を流す
+    pub eight_cut: bool,
+    // 同じスートが続くと以降はそのスートしか出せない
+    pub bind: bool,
+    // 4枚以上の同じ数字でカードの強さが逆転する
+    pub revolution: bool,
+    // Jを含む組み合わせで場が流れるまでカードの強さが逆転する
+    pub jack_back: bool,
+    // ジョーカー1枚には♠3を出せる
+    pub spade_three: bool,
+    // 前のラウンドの大富豪が最初に上がれなければ大貧民になる
+    pub capital_fall: bool,
+    // 5を出した枚数だけ次のプレイヤーを飛ばす
+    pub five_skip: bool,
+    // 8切りですぐに場を流さず、同じ枚数の8で返せる(全員がパスしたら場が流れる)
+    pub eight_return: bool,
+    // ジョーカーを含む組み合わせは場にカードがないときに出せない(手札が全てなくなる場合を除く)
+    pub no_joker_lead: bool,
+    // ジョーカー1枚で上がっても反則上がりにならない
+    pub joker_finish: bool,
+    // 縛りの間はジョーカーをどのスートとしても出せない
+    pub no_joker_bind: bool,
+    // 4枚の同じ数字とジョーカーの5枚で革命すると場も流れる(大革命)
+    pub super_revolution: bool,
+    // 残りのプレイヤーが2人になったら縛りをなくす(8切りはそのまま)
+    pub heads_up: bool,
+    pub lead: LeadRule,
+    pub lead_pass: LeadPassRule,
+    pub foul: FoulRule,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            seq: SeqRule::default(),
+            min_seq: MIN_SEQ,
+            eight_cut: true,
+            bind: true,
+            revolution: true,
+            jack_back: false,
+            spade_three: false,
+            capital_fall: false,
+            five_skip: false,
+            eight_return: false,
+            no_joker_lead: false,
+            joker_finish: false,
+            no_joker_bind: false,
+            super_revolution: false,
+            heads_up: false,
+            lead: LeadRule::default(),
+            lead_pass: LeadPassRule::default(),
+            foul: FoulRule::default(),
+        }
+    }
+}
+
+// メニューでオンとオフを切り替えるルールの名前のキー(get_togglesの順)
+pub const TOGGLE_KEYS: [&str; 14] = [
+    "eight_cut",
+    "bind",
+    "revolution",
+    "jack_back",
+    "spade_three",
+    "capital_fall",
+    "five_skip",
+    "seq_extreme",
+    "eight_return",
+    "no_joker_lead",
+    "joker_finish",
+    "no_joker_bind",
+    "super_revolution",
+    "heads_up",
+];
+
+impl RuleSet {
+    // len枚の階段を出せるか
+    pub fn is_seq_allowed(&self, len: usize) -> bool {
+        self.min_seq != 0 && len >= self.min_seq
+    }
+
+    pub fn get_toggles(&self) -> [bool; 14] {
+        [
+            self.eight_cut,
+            self.bind,
+            self.revolution,
+            self.jack_back,
+            self.spade_three,
+            self.capital_fall,
+            self.five_skip,
+            self.seq == SeqRule::Extreme,
+            self.eight_return,
+            self.no_joker_lead,
+            self.joker_finish,
+            self.no_joker_bind,
+            self.super_revolution,
+            self.heads_up,
+        ]
+    }
+
+    pub fn set_toggles(&mut self, toggles: [bool; 14]) {
+        self.eight_cut = toggles[0];
+        self.bind = toggles[1];
+        self.revolution = toggles[2];
+        self.jack_back = toggles[3];
+        self.spade_three = toggles[4];
+        self.capital_fall = toggles[5];
+        self.five_skip = toggles[6];
+        self.seq = match toggles[7] {
+            true => SeqRule::Extreme,
+            false => SeqRule::Pairwise,
+        };
+        self.eight_return = toggles[8];
+        self.no_joker_lead = toggles[9];
+        self.joker_finish = toggles[10];
+        self.no_joker_bind = toggles[11];
+        self.super_revolution = toggles[12];
+        self.heads_up = toggles[13];
+    }
+
+    // 遊べない組み合わせならエラーのキーを返し、遊べるなら効果のないルールの警告のキーを返す(キーはi18nの表)
+    pub fn validate(&self) -> Result<Vec<&'static str>, &'static str> {
+        if self.min_seq != 0 && !(MIN_SEQ..=RANKS.len()).contains(&self.min_seq) {
+            return Err("invalid_min_seq");
+        }
+        let warnings = [
+            (self.eight_return && !self.eight_cut, "eight_return_ignored"),
+            (
+                self.super_revolution && !self.revolution,
+                "super_revolution_ignored",
+            ),
+            (self.no_joker_bind && !self.bind, "no_joker_bind_ignored"),
+            (self.heads_up && !self.bind, "heads_up_ignored"),
+            (
+                self.seq == SeqRule::Extreme && self.min_seq == 0,
+                "seq_extreme_ignored",
+            ),
+        ];
+        Ok(warnings
+            .into_iter()
+            .filter(|(conflict, _)| *conflict)
+            .map(|(_, key)| key)
+            .collect())
+    }
+}
+
+// 名前を付けたルールの組み合わせ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Standard,
+    Kanto,
+    Kansai,
+    Tournament,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [
+        Preset::Standard,
+        Preset::Kanto,
+        Preset::Kansai,
+        Preset::Tournament,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Preset::Standard => "standard",
+            Preset::Kanto => "kanto",
+            Preset::Kansai => "kansai",
+            Preset::Tournament => "tournament",
+        }
+    }
+
+    pub fn get_rules(&self) -> RuleSet {
+        match self {
+            Preset::Standard => RuleSet::default(),
+            Preset::Kanto => RuleSet {
+                jack_back: true,
+                spade_three: true,
+                capital_fall: true,
+                ..RuleSet::default()
+            },
+            // 11バックと5スキップに加え、8切りを8で返せる
+            Preset::Kansai => RuleSet {
+                jack_back: true,
+                five_skip: true,
+                eight_return: true,
+                ..RuleSet::default()
+            },
+            // 大会で使われる公式ルールに近い組み合わせ(反則上がりは出したカードを無効にする)
+            Preset::Tournament => RuleSet {
+                seq: SeqRule::Extreme,
+                spade_three: true,
+                no_joker_lead: true,
+                heads_up: true,
+                foul: FoulRule::Void,
+                ..RuleSet::default()
+            },
+        }
+    }
+}
+
+// 組み込みのプリセットか、customに定義したプリセットのルール(同じ名前なら組み込みを優先する)
+pub fn find_preset(name: &str, custom: &BTreeMap<String, RuleSet>) -> Result<RuleSet, String> {
+    if let Ok(preset) = name.parse::<Preset>() {
+        return Ok(preset.get_rules());
+    }
+    custom.get(name).copied().ok_or_else(|| {
+        let names: Vec<&str> = Preset::ALL
+            .iter()
+            .map(|p| p.get_name())
+            .chain(
+                custom
+                    .keys()
+                    .filter(|k| k.parse::<Preset>().is_err())
+                    .map(|k| k.as_str()),
+            )
+            .collect();
+        trf(
+            "unknown_preset",
+            &[("value", &name), ("choices", &names.join(", "))],
+        )
+    })
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .into_iter()
+            .find(|preset| preset.get_name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Preset::ALL.iter().map(|p| p.get_name()).collect();
+                trf(
+                    "unknown_preset",
+                    &[("value", &s), ("choices", &names.join(", "))],
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        for rule in SeqRule::ALL {
+            assert_eq!(rule.get_name().parse::<SeqRule>(), Ok(rule));
+        }
+        assert!("official".parse::<SeqRule>().is_err());
+        for rule in LeadRule::ALL {
+            assert_eq!(rule.get_name().parse::<LeadRule>(), Ok(rule));
+        }
+        assert!("king".parse::<LeadRule>().is_err());
+        for rule in LeadPassRule::ALL {
+            assert_eq!(rule.get_name().parse::<LeadPassRule>(), Ok(rule));
+        }
+        assert!("ignore".parse::<LeadPassRule>().is_err());
+        assert_eq!(RuleSet::default().lead_pass, LeadPassRule::Reject);
+        for rule in FoulRule::ALL {
+            assert_eq!(rule.get_name().parse::<FoulRule>(), Ok(rule));
+        }
+        assert!("ignore".parse::<FoulRule>().is_err());
+        assert_eq!(RuleSet::default().foul, FoulRule::Demote);
+        assert_eq!(RuleSet::default().lead, LeadRule::Loser);
+        assert_eq!(RuleSet::default().seq, SeqRule::Pairwise);
+        for preset in Preset::ALL {
+            assert_eq!(preset.get_name().parse::<Preset>(), Ok(preset));
+        }
+        assert!("osaka".parse::<Preset>().is_err());
+    }
+
+    #[test]
+    fn test_find_preset() {
+        let house = RuleSet {
+            five_skip: true,
+            lead: LeadRule::Winner,
+            ..RuleSet::default()
+        };
+        let kanto = RuleSet {
+            bind: false,
+            ..RuleSet::default()
+        };
+        let custom = BTreeMap::from([("house".to_owned(), house), ("kanto".to_owned(), kanto)]);
+        let cases = [
+            ("standard", Ok(RuleSet::default())),
+            ("kansai", Ok(Preset::Kansai.get_rules())),
+            ("house", Ok(house)),
+            ("kanto", Ok(Preset::Kanto.get_rules())),
+            (
+                "osaka",
+                Err("不明なプリセット: osaka (standard, kanto, kansai, tournament, houseから選んでください)".to_owned()),
+            ),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(find_preset(name, &custom), expected, "{name}");
+        }
+        assert_eq!(
+            find_preset("house", &BTreeMap::new()),
+            Err(
+                "不明なプリセット: house (standard, kanto, kansai, tournamentから選んでください)"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn test_toggles() {
+        let rules = Preset::Kanto.get_rules();
+        assert_eq!(
+            rules.get_toggles(),
+            [
+                true, true, true, true, true, true, false, false, false, false, false, false,
+                false, false
+            ]
+        );
+        let mut toggles = rules.get_toggles();
+        toggles[0] = false;
+        toggles[7] = true;
+        toggles[8] = true;
+        toggles[10] = true;
+        toggles[13] = true;
+        let mut new_rules = rules;
+        new_rules.set_toggles(toggles);
+        assert!(!new_rules.eight_cut);
+        assert_eq!(new_rules.seq, SeqRule::Extreme);
+        assert!(new_rules.eight_return);
+        assert!(new_rules.joker_finish);
+        assert!(new_rules.heads_up);
+        assert!(!new_rules.no_joker_lead);
+        assert_eq!(new_rules.get_toggles(), toggles);
+    }
+
+    #[test]
+    fn test_validate() {
+        let cases = [
+            (RuleSet::default(), Ok(vec![])),
+            (
+                RuleSet {
+                    jack_back: true,
+                    revolution: false,
+                    ..RuleSet::default()
+                },
+                Ok(vec![]),
+            ),
+            (
+                RuleSet {
+                    min_seq: 0,
+                    ..RuleSet::default()
+                },
+                Ok(vec![]),
+            ),
+            (
+                RuleSet {
+                    eight_cut: false,
+                    eight_return: true,
+                    ..RuleSet::default()
+                },
+                Ok(vec!["eight_return_ignored"]),
+            ),
+            (
+                RuleSet {
+                    bind: false,
+                    no_joker_bind: true,
+                    heads_up: true,
+                    ..RuleSet::default()
+                },
+                Ok(vec!["no_joker_bind_ignored", "heads_up_ignored"]),
+            ),
+            (
+                RuleSet {
+                    revolution: false,
+                    super_revolution: true,
+                    seq: SeqRule::Extreme,
+                    min_seq: 0,
+                    ..RuleSet::default()
+                },
+                Ok(vec!["super_revolution_ignored", "seq_extreme_ignored"]),
+            ),
+            (
+                RuleSet {
+                    min_seq: 2,
+                    eight_cut: false,
+                    eight_return: true,
+                    ..RuleSet::default()
+                },
+                Err("invalid_min_seq"),
+            ),
+            (
+                RuleSet {
+                    min_seq: 14,
+                    ..RuleSet::default()
+                },
+                Err("invalid_min_seq"),
+            ),
+        ];
+        for (rules, expected) in cases {
+            assert_eq!(rules.validate(), expected, "{rules:?}");
+        }
+        // 組み込みのプリセットは警告なしで遊べる
+        for preset in Preset::ALL {
+            assert_eq!(preset.get_rules().validate(), Ok(vec![]), "{preset:?}");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::i18n::trf;
+use crate::state::GameState;
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+pub fn write_save(path: &Path, state: &GameState) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn write_save(_path: &Path, _state: &GameState) -> Result<(), String> {
+    Err(trf(
+        "feature_required",
+        &[("option", &"save"), ("feature", &"serde")],
+    ))
+}
+
+#[cfg(feature = "serde")]
+pub fn read_save(path: &Path) -> Result<GameState, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| trf("read_failed", &[("error", &e)]))?;
+    serde_json::from_str(&json).map_err(|e| trf("bad_format", &[("error", &e)]))
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn read_save(_path: &Path) -> Result<GameState, String> {
+    Err(trf(
+        "feature_required",
+        &[("option", &"resume"), ("feature", &"serde")],
+    ))
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::*;
+    use crate::card::{Card, Rank, Suit};
+    use crate::comb::Comb;
+    use crate::field::{Effect, Finish, TurnOutcome};
+    use crate::game::Game;
+    use crate::observer::Observers;
+    use crate::strategy::Strategy;
+
+    #[test]
+    fn test_serialize() {
+        for (value, expected) in [
+            (
+                serde_json::to_string(&Comb::Multi(vec![
+                    Card::Normal(Suit::Spade, Rank::Seven),
+                    Card::Joker,
+                ]))
+                .unwrap(),
+                r#"{"Multi":[{"Normal":["Spade","Seven"]},"Joker"]}"#,
+            ),
+            (
+                serde_json::to_string(&TurnOutcome {
+                    effects: vec![Effect::Revolution, Effect::CapitalFall(2)],
+                    finish: Some(Finish {
+                        idx: 0,
+                        place: 0,
+                        foul: false,
+                    }),
+                    cleared: false,
+                })
+                .unwrap(),
+                r#"{"effects":["revolution",{"capital_fall":2}],"finish":{"idx":0,"place":0,"foul":false},"cleared":false}"#,
+            ),
+            (
+                serde_json::to_string(&Strategy::Heuristic).unwrap(),
+                r#""heuristic""#,
+            ),
+        ] {
+            assert_eq!(value, expected);
+        }
+    }
+
+    #[test]
+    fn test_game_state() {
+        let players = [
+            Strategy::Min,
+            Strategy::Heuristic,
+            Strategy::Min,
+            Strategy::Min,
+        ]
+        .iter()
+        .map(|s| s.create_npc(s.get_name().to_owned(), 0))
+        .collect();
+        let mut game = Game::new(players, 0, 7);
+        for _ in 0..10 {
+            game.step(&mut Observers(vec![]));
+        }
+        let json = serde_json::to_string(&game.snapshot()).unwrap();
+        let state: GameState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.players[1].strategy, Some(Strategy::Heuristic));
+        let mut restored = Game::from_state(state, |_| unreachable!());
+        assert_eq!(serde_json::to_string(&restored.snapshot()).unwrap(), json);
+        // 復元したゲームは同じ進行になる
+        let mut observer = Observers(vec![]);
+        assert_eq!(
+            restored.play_round(&mut observer),
+            game.play_round(&mut observer)
+        );
+    }
+}
@@ -0,0 +1,440 @@
+use crate::card::{Card, Order};
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::game::Game;
+use crate::i18n::{tr, trf};
+use crate::moves::{check_move, get_legal_combs};
+use crate::observer::{Observer, Observers};
+use crate::options::ServeOptions;
+use crate::player::{take_from_hands, Action, Player};
+use crate::protocol::{ClientMessage, Connection, ServerMessage};
+use crate::render::{Renderer, Style};
+use crate::rules::RuleSet;
+use crate::strategy::Strategy;
+use crate::validator::Validator;
+use crate::{MAX_PLAYERS, NPC_NAMES, PLAYERS_COUNT};
+use rand::seq::SliceRandom;
+use session::{spawn_acceptor, SessionManager};
+use std::cell::RefCell;
+use std::net::TcpListener;
+use std::rc::Rc;
+#[cfg(feature = "http")]
+pub mod http;
+mod lobby;
+mod session;
+
+// リモートのクライアントが操作するプレイヤー
+pub struct NetworkPlayer {
+    name: String,
+    hands: Vec<Card>,
+    seat: usize,
+    session: Rc<RefCell<SessionManager>>,
+}
+
+impl NetworkPlayer {
+    pub fn new(name: String, seat: usize, session: Rc<RefCell<SessionManager>>) -> Self {
+        Self {
+            name,
+            hands: vec![],
+            seat,
+            session,
+        }
+    }
+
+    fn send(&mut self, message: &ServerMessage) {
+        self.session.borrow_mut().send(self.seat, message);
+    }
+
+    fn reject(&mut self, reason: &str) {
+        self.send(&ServerMessage::Reject {
+            message: reason.to_owned(),
+        });
+    }
+}
+
+impl Player for NetworkPlayer {
+    fn init(&mut self, hands: Vec<Card>) {
+        self.hands = hands;
+        self.send(&ServerMessage::Deal {
+            hands: self.hands.clone(),
+        });
+    }
+
+    fn count_hands(&self) -> usize {
+        self.hands.len()
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_hands(&self) -> &[Card] {
+        &self.hands
+    }
+
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+        take_from_hands(&mut self.hands, cards)
+    }
+
+    fn receive_cards(&mut self, cards: Vec<Card>) {
+        self.hands.extend(cards);
+    }
+
+    // 並べ替えた手札を送り直して交換の結果を知らせる
+    fn sort_hands(&mut self, order: Order) {
+        self.hands.sort_by(order.get_comparator());
+        self.send(&ServerMessage::Deal {
+            hands: self.hands.clone(),
+        });
+    }
+
+    fn play(&mut self, validator: &dyn Validator) -> Action {
+        self.session.borrow_mut().poll();
+        // やり直しても締め切りは延びない
+        let deadline = self.session.borrow().get_turn_deadline();
+        while self.session.borrow().is_connected(self.seat) {
+            self.send(&ServerMessage::TurnRequest {
+                view: validator.get_view(),
+                hands: self.hands.clone(),
+            });
+            let received = self.session.borrow_mut().receive(self.seat, deadline);
+            let Some(message) = received else {
+                break;
+            };
+            let comb = match message {
+                ClientMessage::Play { comb } => Some(comb),
+                ClientMessage::Pass => None,
+                _ => {
+                    self.reject(tr("already_started"));
+                    continue;
+                }
+            };
+            // 受け付けられない手は理由を送ってやり直してもらう
+            match check_move(validator, &self.hands, comb.as_ref()) {
+                Ok(hands_left) => {
+                    self.hands = hands_left;
+                    return Action::Move(comb);
+                }
+                Err(reason) => self.reject(reason),
+            }
+        }
+        // 持ち時間を過ぎたか接続が切れている間は自動で進める
+        let connected = self.session.borrow().is_connected(self.seat);
+        match connected {
+            true => self.reject(tr("turn_timed_out")),
+            false => self.session.borrow_mut().miss_turn(self.seat),
+        }
+        Action::Move(play_automatically(&mut self.hands, validator))
+    }
+
+    fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
+        (0..cards_count).map(|_| self.hands.remove(0)).collect()
+    }
+}
+
+// パスできればパスし、できなければ最も弱い手を出す
+fn play_automatically(hands: &mut Vec<Card>, validator: &dyn Validator) -> Option<Comb> {
+    if validator.get_prev_comb().is_some() {
+        return None;
+    }
+    let (comb, indices) = get_legal_combs(hands, validator).into_iter().next()?;
+    for i in indices.iter().rev() {
+        hands.remove(*i);
+    }
+    Some(comb)
+}
+
+// 全てのクライアントにゲームの進行を送る
+struct Broadcaster(Rc<RefCell<SessionManager>>);
+
+impl Observer for Broadcaster {
+    fn notify(&mut self, event: &GameEvent) {
+        self.0.borrow_mut().broadcast(event);
+    }
+}
+
+// クライアントが揃うのを待ち、空いた席をNPCで埋めて対戦する
+pub fn run_server(options: &ServeOptions, style: Style) -> Result<(), String> {
+    if options.ws && !cfg!(feature = "ws") {
+        return Err(trf(
+            "feature_required",
+            &[("option", &"--ws"), ("feature", &"ws")],
+        ));
+    }
+    let listener = TcpListener::bind(("0.0.0.0", options.port))
+        .map_err(|e| trf("bind_failed", &[("port", &options.port), ("error", &e)]))?;
+    if options.lobby {
+        println!("{}", trf("lobby_opened", &[("port", &options.port)]));
+        lobby::run_lobby(listener, options);
+        return Ok(());
+    }
+    println!(
+        "{}",
+        trf(
+            "waiting_clients",
+            &[("port", &options.port), ("count", &options.clients)]
+        )
+    );
+    let arrivals = spawn_acceptor(listener, options.ws);
+    let mut session = SessionManager::new(arrivals, options.reconnect_turns, 0)
+        .with_turn_timeout(options.turn_timeout);
+    let clients = session.wait_for_joins(options.clients, options.spectators);
+    let mut renderer = Renderer::new(style, 1.0, true);
+    let rules = RuleSet::default();
+    play_table(
+        session,
+        clients,
+        options.npc,
+        rules,
+        options.rounds,
+        &mut renderer,
+    );
+    Ok(())
+}
+
+// 参加したクライアントを席に着かせ、空いた席をNPCで埋めて対戦する
+fn play_table(
+    session: SessionManager,
+    clients: Vec<(String, Box<dyn Connection>)>,
+    npc: Strategy,
+    rules: RuleSet,
+    rounds: usize,
+    observer: &mut dyn Observer,
+) {
+    let session = Rc::new(RefCell::new(session.with_rules(rules)));
+    let mut seats: Vec<usize> = (0..PLAYERS_COUNT).collect();
+    seats.shuffle(&mut rand::thread_rng());
+    let mut players: Vec<Option<Box<dyn Player>>> = (0..PLAYERS_COUNT).map(|_| None).collect();
+    for ((name, connection), seat) in clients.into_iter().zip(seats) {
+        if let Err(e) = session.borrow_mut().seat(seat, connection) {
+            println!(
+                "{}",
+                trf("seat_notify_failed", &[("name", &name), ("error", &e)])
+            );
+        }
+        players[seat] = Some(Box::new(NetworkPlayer::new(name, seat, session.clone())));
+    }
+    let mut broadcaster = Broadcaster(session.clone());
+    let mut observers = Observers(vec![observer, &mut broadcaster]);
+    play_rounds(
+        fill_seats(players, npc),
+        rules,
+        rounds,
+        &mut observers,
+        |game, player_rank| seat_joins(game, &session, player_rank),
+    );
+}
+
+// ラウンドの間に参加したクライアントをNPCの席に着かせる(NPCの席がなければ席を増やす)
+fn seat_joins(
+    game: &mut Game,
+    session: &Rc<RefCell<SessionManager>>,
+    player_rank: &mut Vec<usize>,
+) {
+    let joins = session.borrow_mut().take_joins();
+    for (name, mut connection) in joins {
+        let players_count = game.get_names().len();
+        let humans = game.get_human_seats();
+        let npc_seat = (0..players_count).find(|seat| !humans.contains(seat));
+        if npc_seat.is_none() && players_count >= MAX_PLAYERS {
+            let _ = connection.send(&ServerMessage::Reject {
+                message: tr("no_seats").to_owned(),
+            });
+            continue;
+        }
+        let seat = npc_seat.unwrap_or(players_count);
+        if let Err(e) = session.borrow_mut().seat(seat, connection) {
+            println!(
+                "{}",
+                trf("seat_notify_failed", &[("name", &name), ("error", &e)])
+            );
+            continue;
+        }
+        println!("{}", trf("joins_next_round", &[("name", &name)]));
+        let player = Box::new(NetworkPlayer::new(name, seat, session.clone()));
+        match npc_seat {
+            Some(seat) => {
+                game.replace_player(seat, player);
+            }
+            None => {
+                if let Ok(rank) = game.add_player(player, player_rank) {
+                    *player_rank = rank;
+                }
+            }
+        }
+    }
+}
+
+// 空いた席をNPCで埋める
+fn fill_seats(players: Vec<Option<Box<dyn Player>>>, npc: Strategy) -> Vec<Box<dyn Player>> {
+    let mut npc_names = NPC_NAMES.iter();
+    players
+        .into_iter()
+        .map(|player| {
+            player.unwrap_or_else(|| {
+                let name = npc_names.next().map_or("Npc", |name| *name);
+                npc.create_npc(name.to_owned(), rand::random())
+            })
+        })
+        .collect()
+}
+
+// ラウンドの間にbetweenでプレイヤーを入れ替えられる
+fn play_rounds<F>(
+    players: Vec<Box<dyn Player>>,
+    rules: RuleSet,
+    rounds: usize,
+    observer: &mut dyn Observer,
+    mut between: F,
+) where
+    F: FnMut(&mut Game, &mut Vec<usize>),
+{
+    let mut game = Game::new(players, 0, rand::random()).with_rules(rules);
+    for round in 1..=rounds {
+        let mut player_rank = game.play_round(observer);
+        if round < rounds {
+            between(&mut game, &mut player_rank);
+            game.next_round(&player_rank, observer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+    use crate::field::Field;
+    use crate::protocol::{read_message, write_message};
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_seat_joins() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let session = SessionManager::new(spawn_acceptor(listener, false), 3, 0);
+        let session = Rc::new(RefCell::new(session));
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let join = ClientMessage::Join {
+                name: "Late".to_owned(),
+            };
+            write_message(&mut stream, &join).unwrap();
+            read_message::<_, ServerMessage>(&mut stream).unwrap()
+        });
+        let players = fill_seats((0..PLAYERS_COUNT).map(|_| None).collect(), Strategy::Min);
+        let mut game = Game::new(players, 0, 1);
+        let mut player_rank = vec![0, 1, 2, 3];
+        while game.get_human_seats().is_empty() {
+            seat_joins(&mut game, &session, &mut player_rank);
+            thread::sleep(Duration::from_millis(10));
+        }
+        // NPCの席を引き継ぎ、順位は変わらない
+        assert_eq!(game.get_human_seats(), [0]);
+        assert_eq!(game.get_names()[0], "Late");
+        assert_eq!(player_rank, [0, 1, 2, 3]);
+        assert!(matches!(
+            client.join().unwrap(),
+            ServerMessage::Welcome { seat: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn test_network_player_play() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        // 最初は場に出せないカードを送り、拒否されたらパスする
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let join = ClientMessage::Join {
+                name: "User".to_owned(),
+            };
+            write_message(&mut stream, &join).unwrap();
+            let mut received = vec![];
+            for reply in [
+                ClientMessage::Play {
+                    comb: Comb::Single(Card::Normal(Suit::Club, Rank::Four)),
+                },
+                ClientMessage::Pass,
+            ] {
+                loop {
+                    let message = read_message::<_, ServerMessage>(&mut stream).unwrap();
+                    received.push(message.clone());
+                    if matches!(message, ServerMessage::TurnRequest { .. }) {
+                        break;
+                    }
+                }
+                write_message(&mut stream, &reply).unwrap();
+            }
+            received
+        });
+        let mut session = SessionManager::new(spawn_acceptor(listener, false), 3, 0);
+        let (_, connection) = session.wait_for_joins(1, 0).pop().unwrap();
+        session.seat(0, connection).unwrap();
+        let session = Rc::new(RefCell::new(session));
+        let mut player = NetworkPlayer::new("User".to_owned(), 0, session);
+        player.init(vec![
+            Card::Normal(Suit::Club, Rank::Four),
+            Card::Normal(Suit::Club, Rank::Six),
+        ]);
+        let mut field = Field::new(2, 1);
+        field.set_players(vec!["User".to_owned(), "NpcA".to_owned()], vec![2, 4]);
+        field
+            .put(
+                Some(&Comb::Single(Card::Normal(Suit::Heart, Rank::Five))),
+                3,
+            )
+            .unwrap();
+        assert_eq!(player.play(&field), Action::Move(None));
+        assert_eq!(player.count_hands(), 2);
+        let received = client.join().unwrap();
+        assert!(matches!(
+            received[0],
+            ServerMessage::Welcome { seat: 0, .. }
+        ));
+        assert!(matches!(received[1], ServerMessage::Deal { .. }));
+        assert!(matches!(received[2], ServerMessage::TurnRequest { .. }));
+        assert!(matches!(received[3], ServerMessage::Reject { .. }));
+        assert!(matches!(received[4], ServerMessage::TurnRequest { .. }));
+        // 接続が切れた後は自動で場に出せる手を出す
+        field.put(None, 2).unwrap();
+        assert_eq!(
+            player.play(&field),
+            Action::Move(Some(Comb::Single(Card::Normal(Suit::Club, Rank::Four))))
+        );
+    }
+
+    #[test]
+    fn test_turn_timeout() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let join = ClientMessage::Join {
+            name: "Idle".to_owned(),
+        };
+        write_message(&mut stream, &join).unwrap();
+        let mut session = SessionManager::new(spawn_acceptor(listener, false), 3, 0)
+            .with_turn_timeout(Some(Duration::from_millis(50)));
+        let (_, connection) = session.wait_for_joins(1, 0).pop().unwrap();
+        session.seat(0, connection).unwrap();
+        let session = Rc::new(RefCell::new(session));
+        let mut player = NetworkPlayer::new("Idle".to_owned(), 0, session.clone());
+        player.init(vec![Card::Normal(Suit::Club, Rank::Four)]);
+        let mut field = Field::new(2, 1);
+        field.set_players(vec!["Idle".to_owned(), "NpcA".to_owned()], vec![1, 4]);
+        field
+            .put(
+                Some(&Comb::Single(Card::Normal(Suit::Heart, Rank::Five))),
+                3,
+            )
+            .unwrap();
+        // 手を送らないまま持ち時間を過ぎるとパスし、接続はそのまま残る
+        assert_eq!(player.play(&field), Action::Move(None));
+        assert!(session.borrow().is_connected(0));
+        let received: Vec<ServerMessage> =
+            (0..4).map(|_| read_message(&mut stream).unwrap()).collect();
+        assert!(matches!(received[2], ServerMessage::TurnRequest { .. }));
+        assert!(matches!(received[3], ServerMessage::Reject { .. }));
+    }
+}
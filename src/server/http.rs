@@ -0,0 +1,374 @@
+use crate::card::Card;
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::external::ExternalGame;
+use crate::i18n::{tr, trf};
+use crate::options::HttpOptions;
+use crate::rules::RuleSet;
+use crate::strategy::Strategy;
+use crate::view::GameView;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Response, Server};
+
+// 同時に進められるゲームの数
+const MAX_GAMES: usize = 64;
+// 1つのゲームで対戦できるラウンド数
+const MAX_ROUNDS: usize = 100;
+// 操作のないまま過ぎるとゲームを削除する時間
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+// POST /gamesで作るゲームの設定
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct NewGame {
+    name: String,
+    npc: Strategy,
+    rounds: usize,
+}
+
+impl Default for NewGame {
+    fn default() -> Self {
+        Self {
+            name: "User".to_owned(),
+            npc: Strategy::Min,
+            rounds: 1,
+        }
+    }
+}
+
+// POST /games/{id}/movesで送る手(combがnullならパス)
+#[derive(Debug, Deserialize)]
+struct Move {
+    comb: Option<Comb>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Turn {
+    view: GameView,
+    hands: Vec<Card>,
+}
+
+// GET /games/{id}で返す状態
+#[derive(Debug, Serialize)]
+struct GameState {
+    seat: usize,
+    turn: Option<Turn>,
+    // sinceより後のゲームの進行
+    events: Vec<GameEvent>,
+    // 次にsinceに指定する値
+    next: usize,
+    finished: bool,
+}
+
+// リクエストを受けるたびに人間の手番まで進めるゲーム
+struct HttpGame {
+    game: ExternalGame,
+    seat: usize,
+    // 全てのラウンドの人間の席から見える進行
+    events: Vec<GameEvent>,
+    // このラウンドの進行のうちeventsに写したものの数
+    copied: usize,
+    round: usize,
+    rounds: usize,
+    // 最後にリクエストを受けた時刻
+    accessed: Instant,
+}
+
+impl HttpGame {
+    // NPCとの対戦を始める
+    fn start(settings: NewGame, rules: RuleSet) -> Self {
+        let game =
+            ExternalGame::with_names(rand::random(), vec![settings.name], settings.npc, rules);
+        let mut http_game = Self {
+            seat: game.get_seats()[0],
+            game,
+            events: vec![],
+            copied: 0,
+            round: 1,
+            rounds: settings.rounds,
+            accessed: Instant::now(),
+        };
+        http_game.sync();
+        http_game
+    }
+
+    // 新しい進行を写し、ラウンドが終わっていれば次のラウンドを始める
+    fn sync(&mut self) {
+        loop {
+            let events = self.game.get_events(self.seat);
+            let len = events.len();
+            self.events.extend(events.into_iter().skip(self.copied));
+            self.copied = len;
+            if self.game.get_player_rank().is_none() || self.round >= self.rounds {
+                return;
+            }
+            self.round += 1;
+            self.copied = 0;
+            self.game.next_round();
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.game.get_player_rank().is_some() && self.round >= self.rounds
+    }
+
+    fn get_state(&self, since: usize) -> GameState {
+        let turn = (self.game.get_turn() == Some(self.seat)).then(|| Turn {
+            view: self.game.get_view(),
+            hands: self.game.get_hands(self.seat).to_vec(),
+        });
+        GameState {
+            seat: self.seat,
+            turn,
+            events: self.events.iter().skip(since).cloned().collect(),
+            next: self.events.len(),
+            finished: self.is_finished(),
+        }
+    }
+
+    // 手を出し、次の人間の手番までNPCの手番を進める
+    fn submit(&mut self, comb: Option<Comb>) -> Result<(), (u16, String)> {
+        if self.is_finished() {
+            return Err((409, tr("game_finished").to_owned()));
+        }
+        if self.game.get_turn().is_none() {
+            return Err((409, tr("not_your_turn").to_owned()));
+        }
+        self.game.play(comb).map_err(|message| (400, message))?;
+        self.sync();
+        Ok(())
+    }
+}
+
+// 作ったゲームをIDで管理する
+#[derive(Default)]
+struct Games {
+    games: HashMap<u64, HttpGame>,
+    next_id: u64,
+    // 作るゲームに適用するルール(GET /rulesで返す)
+    rules: RuleSet,
+}
+
+impl Games {
+    // リクエストを処理してステータスコードとJSONを返す
+    fn route(&mut self, method: &Method, url: &str, body: &str) -> (u16, String) {
+        self.expire(Instant::now());
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let result = match (method, &segments[..]) {
+            (Method::Get, ["rules"]) => Ok(to_json(&self.rules)),
+            (Method::Post, ["games"]) => self.create(body),
+            (Method::Get, ["games", id]) => self.get(id).map(|game| {
+                let since = get_query(query, "since").map_or(0, |v| v.parse().unwrap_or(0));
+                to_json(&game.get_state(since))
+            }),
+            (Method::Post, ["games", id, "moves"]) => self.get_mut(id).and_then(|game| {
+                let request = serde_json::from_str::<Move>(body)
+                    .map_err(|e| (400, trf("move_parse_failed", &[("error", &e)])))?;
+                game.submit(request.comb).map(|_| "{}".to_owned())
+            }),
+            (Method::Delete, ["games", id]) => match id.parse().ok() {
+                Some(id) if self.games.remove(&id).is_some() => Ok("{}".to_owned()),
+                _ => Err((404, trf("no_game", &[("id", id)]))),
+            },
+            _ => Err((404, trf("no_url", &[("url", &url)]))),
+        };
+        match result {
+            Ok(json) => (200, json),
+            Err((status, message)) => (status, to_json(&HashMap::from([("message", message)]))),
+        }
+    }
+
+    fn create(&mut self, body: &str) -> Result<String, (u16, String)> {
+        let body = if body.trim().is_empty() { "{}" } else { body };
+        let settings = serde_json::from_str::<NewGame>(body)
+            .map_err(|e| (400, trf("settings_parse_failed", &[("error", &e)])))?;
+        if settings.rounds == 0 || settings.name.trim().is_empty() {
+            return Err((400, tr("game_settings_required").to_owned()));
+        }
+        if settings.rounds > MAX_ROUNDS {
+            return Err((400, trf("too_many_rounds", &[("max", &MAX_ROUNDS)])));
+        }
+        if self.games.len() >= MAX_GAMES {
+            return Err((503, trf("too_many_games", &[("max", &MAX_GAMES)])));
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let game = HttpGame::start(settings, self.rules);
+        let json = to_json(&HashMap::from([("id", id), ("seat", game.seat as u64)]));
+        self.games.insert(id, game);
+        Ok(json)
+    }
+
+    // 状態を取得しても操作したことにする
+    fn get(&mut self, id: &str) -> Result<&HttpGame, (u16, String)> {
+        self.get_mut(id).map(|game| &*game)
+    }
+
+    fn get_mut(&mut self, id: &str) -> Result<&mut HttpGame, (u16, String)> {
+        let game = id
+            .parse()
+            .ok()
+            .and_then(|id| self.games.get_mut(&id))
+            .ok_or_else(|| (404, trf("no_game", &[("id", &id)])))?;
+        game.accessed = Instant::now();
+        Ok(game)
+    }
+
+    // IDLE_TIMEOUTの間リクエストのないゲームを削除する
+    fn expire(&mut self, now: Instant) {
+        self.games
+            .retain(|_, game| now.duration_since(game.accessed) < IDLE_TIMEOUT);
+    }
+}
+
+fn get_query<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+// ゲームの作成、状態の取得、手の送信を受け付けるHTTPサーバー
+pub fn run_http_server(options: &HttpOptions) -> Result<(), String> {
+    let server = Server::http(("0.0.0.0", options.port))
+        .map_err(|e| trf("bind_failed", &[("port", &options.port), ("error", &e)]))?;
+    println!("{}", trf("http_listening", &[("port", &options.port)]));
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let mut games = Games::default();
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, json) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => games.route(request.method(), request.url(), &body),
+            Err(e) => (400, to_json(&HashMap::from([("message", e.to_string())]))),
+        };
+        let response = Response::from_string(json)
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        let _ = request.respond(response);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::Value;
+
+    fn get_state(games: &mut Games, path: &str) -> Value {
+        let (status, json) = games.route(&Method::Get, path, "");
+        assert_eq!(status, 200);
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_route() {
+        let mut games = Games::default();
+        for (method, url, body, expected) in [
+            (Method::Post, "/games", r#"{"rounds":0}"#, 400),
+            (Method::Post, "/games", "{", 400),
+            (Method::Get, "/games/0", "", 404),
+            (Method::Get, "/games/x", "", 404),
+            (Method::Put, "/games", "", 404),
+            (Method::Delete, "/games/0", "", 404),
+        ] {
+            assert_eq!(games.route(&method, url, body).0, expected);
+        }
+        let (status, json) = games.route(&Method::Get, "/rules", "");
+        assert_eq!(status, 200);
+        let rules: RuleSet = serde_json::from_str(&json).unwrap();
+        assert_eq!(rules, RuleSet::default());
+        let (status, json) = games.route(&Method::Post, "/games", r#"{"name":"Bot"}"#);
+        assert_eq!(status, 200);
+        let created: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(created["id"], 0);
+        // 作った時点で人間の手番まで進み、出せない手を送ると断られる
+        let state = get_state(&mut games, "/games/0");
+        assert!(!state["turn"].is_null());
+        assert_eq!(state["seat"], created["seat"]);
+        assert_eq!(state["events"][0]["type"], "Start");
+        let move_url = "/games/0/moves";
+        assert_eq!(games.route(&Method::Post, move_url, "[]").0, 400);
+        let others = r#"{"comb":{"Multi":["Joker","Joker"]}}"#;
+        assert_eq!(games.route(&Method::Post, move_url, others).0, 400);
+        // 手札から1枚ずつ出すか、パスを続けるといずれ終わる
+        loop {
+            let state = get_state(&mut games, "/games/0");
+            if state["finished"] == true {
+                break;
+            }
+            let pass = games.route(&Method::Post, move_url, r#"{"comb":null}"#);
+            if pass.0 == 200 {
+                continue;
+            }
+            let card = &state["turn"]["hands"][0];
+            let single = format!(r#"{{"comb":{{"Single":{card}}}}}"#);
+            assert_eq!(games.route(&Method::Post, move_url, &single).0, 200);
+        }
+        let state = get_state(&mut games, "/games/0?since=1");
+        assert_eq!(
+            state["events"].as_array().unwrap().last().unwrap()["type"],
+            "End"
+        );
+        assert_eq!(games.route(&Method::Post, move_url, "{}").0, 409);
+        assert_eq!(games.route(&Method::Delete, "/games/0", "").0, 200);
+        assert_eq!(games.route(&Method::Get, "/games/0", "").0, 404);
+    }
+
+    #[test]
+    fn test_rounds() {
+        let mut games = Games::default();
+        assert_eq!(
+            games.route(&Method::Post, "/games", r#"{"rounds":2}"#).0,
+            200
+        );
+        // パスできなければ最初の1枚を出し、2ラウンド目の終わりまで進める
+        loop {
+            let state = get_state(&mut games, "/games/0");
+            if state["finished"] == true {
+                break;
+            }
+            if games
+                .route(&Method::Post, "/games/0/moves", r#"{"comb":null}"#)
+                .0
+                == 200
+            {
+                continue;
+            }
+            let card = &state["turn"]["hands"][0];
+            let single = format!(r#"{{"comb":{{"Single":{card}}}}}"#);
+            assert_eq!(games.route(&Method::Post, "/games/0/moves", &single).0, 200);
+        }
+        let state = get_state(&mut games, "/games/0");
+        let ends = state["events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|event| event["type"] == "End")
+            .count();
+        assert_eq!(ends, 2);
+        assert_eq!(state["next"], state["events"].as_array().unwrap().len());
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut games = Games::default();
+        let rounds = format!(r#"{{"rounds":{}}}"#, MAX_ROUNDS + 1);
+        assert_eq!(games.route(&Method::Post, "/games", &rounds).0, 400);
+        for _ in 0..MAX_GAMES {
+            assert_eq!(games.route(&Method::Post, "/games", "").0, 200);
+        }
+        assert_eq!(games.route(&Method::Post, "/games", "").0, 503);
+        // 操作のないまま過ぎたゲームは削除され、新しく作れる
+        games.expire(Instant::now() + IDLE_TIMEOUT);
+        assert!(games.games.is_empty());
+        assert_eq!(games.route(&Method::Post, "/games", "").0, 200);
+    }
+}
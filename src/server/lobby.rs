@@ -0,0 +1,475 @@
+use super::play_table;
+use super::session::{accept, get_token_id, Arrival, SessionManager};
+use crate::i18n::{tr, trf};
+use crate::observer::Observers;
+use crate::options::ServeOptions;
+use crate::protocol::{ClientMessage, Connection, ServerMessage, TableInfo, TableSettings};
+use crate::rules::{Preset, RuleSet};
+use crate::PLAYERS_COUNT;
+use std::collections::{BTreeMap, HashMap};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// 開始を待っているテーブル
+pub struct Table {
+    host: String,
+    settings: TableSettings,
+    rules: RuleSet,
+    // ホスト以外の参加者(ホストの接続はホストのスレッドが持つ)
+    guests: Vec<(String, Box<dyn Connection>)>,
+    spectators: Vec<Box<dyn Connection>>,
+}
+
+impl Table {
+    fn get_players(&self) -> Vec<String> {
+        let guests = self.guests.iter().map(|(name, _)| name.clone());
+        [self.host.clone()].into_iter().chain(guests).collect()
+    }
+}
+
+// テーブルの一覧と対戦中のゲームを管理する
+#[derive(Default)]
+pub struct Lobby {
+    tables: BTreeMap<String, Table>,
+    // テーブルの名前と再接続や観戦の要求を届ける先
+    games: HashMap<u64, (String, Sender<Arrival>)>,
+    next_id: u64,
+}
+
+impl Lobby {
+    pub fn get_tables(&self) -> Vec<TableInfo> {
+        self.tables
+            .keys()
+            .filter_map(|name| self.get_table(name))
+            .collect()
+    }
+
+    fn get_table(&self, name: &str) -> Option<TableInfo> {
+        let table = self.tables.get(name)?;
+        Some(TableInfo {
+            name: name.to_owned(),
+            players: table.get_players(),
+            settings: table.settings.clone(),
+            rules: table.rules,
+        })
+    }
+
+    pub fn create_table(
+        &mut self,
+        name: String,
+        host: String,
+        settings: TableSettings,
+    ) -> Result<TableInfo, String> {
+        if name.trim().is_empty() || host.trim().is_empty() {
+            return Err(tr("table_names_required").to_owned());
+        }
+        if self.tables.contains_key(&name) {
+            return Err(trf("table_exists", &[("name", &name)]));
+        }
+        // 設定ファイルはサーバーのものなので組み込みのプリセットだけを受け付ける
+        let rules = settings.preset.parse::<Preset>()?.get_rules();
+        let table = Table {
+            host,
+            settings,
+            rules,
+            guests: vec![],
+            spectators: vec![],
+        };
+        self.tables.insert(name.clone(), table);
+        Ok(self.get_table(&name).unwrap())
+    }
+
+    // 席に着けたら参加者全員に知らせ、着けなければ理由を送って接続を返す
+    pub fn join_table(
+        &mut self,
+        name: &str,
+        player: String,
+        mut connection: Box<dyn Connection>,
+    ) -> Option<Box<dyn Connection>> {
+        let reason = match self.tables.get(name) {
+            None => Some(trf("no_table", &[("name", &name)])),
+            Some(table) if table.guests.len() + 1 >= PLAYERS_COUNT => {
+                Some(trf("table_full", &[("name", &name)]))
+            }
+            Some(table) if table.get_players().contains(&player) => {
+                Some(trf("name_taken", &[("name", &player)]))
+            }
+            Some(_) => None,
+        };
+        if let Some(message) = reason {
+            let _ = connection.send(&ServerMessage::Reject { message });
+            return Some(connection);
+        }
+        let table = self.tables.get_mut(name).unwrap();
+        table.guests.push((player, connection));
+        let info = self.get_table(name).unwrap();
+        let table = self.tables.get_mut(name).unwrap();
+        // 接続が切れた参加者はテーブルから外す
+        table.guests.retain_mut(|(_, connection)| {
+            let message = ServerMessage::TableJoined {
+                table: info.clone(),
+            };
+            connection.send(&message).is_ok()
+        });
+        None
+    }
+
+    // 空いている席がある最初のテーブル
+    pub fn find_open_table(&self) -> Option<String> {
+        self.tables
+            .iter()
+            .find(|(_, table)| table.guests.len() + 1 < PLAYERS_COUNT)
+            .map(|(name, _)| name.clone())
+    }
+
+    // テーブルを一覧から外し、再接続を受け付けるゲームとして登録する
+    pub fn start(&mut self, name: &str) -> Option<(Table, u64, Receiver<Arrival>)> {
+        let table = self.tables.remove(name)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        let (sender, arrivals) = mpsc::channel();
+        self.games.insert(id, (name.to_owned(), sender));
+        Some((table, id, arrivals))
+    }
+
+    pub fn finish(&mut self, id: u64) {
+        self.games.remove(&id);
+    }
+
+    // ホストがいなくなったテーブルを片付ける
+    pub fn close_table(&mut self, name: &str) {
+        let Some(table) = self.tables.remove(name) else {
+            return;
+        };
+        for (_, mut connection) in table.guests {
+            let _ = connection.send(&ServerMessage::Reject {
+                message: trf("table_closed", &[("name", &name)]),
+            });
+        }
+    }
+
+    // 再接続の要求をトークンのゲームに届け、届けられなければ理由を送って接続を返す
+    pub fn rejoin(
+        &self,
+        token: String,
+        connection: Box<dyn Connection>,
+        addr: SocketAddr,
+    ) -> Option<Box<dyn Connection>> {
+        let sender = get_token_id(&token)
+            .and_then(|id| self.games.get(&id))
+            .map(|(_, sender)| sender);
+        let arrival = (ClientMessage::Rejoin { token }, connection, addr);
+        let (_, mut connection, _) = match sender {
+            Some(sender) => sender.send(arrival).err()?.0,
+            None => arrival,
+        };
+        let _ = connection.send(&ServerMessage::Reject {
+            message: tr("invalid_token").to_owned(),
+        });
+        Some(connection)
+    }
+
+    // 開始前のテーブルか対戦中のゲームに観戦者を加え、加えられなければ接続を返す
+    pub fn spectate(
+        &mut self,
+        name: &str,
+        mut connection: Box<dyn Connection>,
+        addr: SocketAddr,
+    ) -> Option<Box<dyn Connection>> {
+        if let Some(table) = self.tables.get_mut(name) {
+            table.spectators.push(connection);
+            return None;
+        }
+        let sender = self.games.values().find(|(table, _)| table == name);
+        let arrival = (
+            ClientMessage::Spectate {
+                table: Some(name.to_owned()),
+            },
+            connection,
+            addr,
+        );
+        (_, connection, _) = match sender {
+            Some((_, sender)) => sender.send(arrival).err()?.0,
+            None => arrival,
+        };
+        let _ = connection.send(&ServerMessage::Reject {
+            message: trf("no_table", &[("name", &name)]),
+        });
+        Some(connection)
+    }
+}
+
+// 接続ごとにスレッドを立ててロビーの要求を処理する
+pub fn run_lobby(listener: TcpListener, options: &ServeOptions) {
+    let lobby = Arc::new(Mutex::new(Lobby::default()));
+    for stream in listener.incoming().flatten() {
+        let Ok(addr) = stream.peer_addr() else {
+            continue;
+        };
+        let lobby = lobby.clone();
+        let (ws, rounds, reconnect_turns) = (options.ws, options.rounds, options.reconnect_turns);
+        let turn_timeout = options.turn_timeout;
+        thread::spawn(move || {
+            if let Ok(connection) = accept(stream, ws) {
+                handle_client(
+                    &lobby,
+                    connection,
+                    addr,
+                    rounds,
+                    reconnect_turns,
+                    turn_timeout,
+                );
+            }
+        });
+    }
+}
+
+fn handle_client(
+    lobby: &Mutex<Lobby>,
+    mut connection: Box<dyn Connection>,
+    addr: SocketAddr,
+    rounds: usize,
+    reconnect_turns: usize,
+    turn_timeout: Option<Duration>,
+) {
+    // ホストとして作ったテーブル
+    let mut hosting: Option<String> = None;
+    loop {
+        let Ok(message) = connection.receive() else {
+            if let Some(name) = hosting {
+                println!("{}", trf("host_left", &[("addr", &addr), ("name", &name)]));
+                lobby.lock().unwrap().close_table(&name);
+            }
+            return;
+        };
+        let mut lobby_guard = lobby.lock().unwrap();
+        let reply = match message {
+            ClientMessage::ListTables => ServerMessage::Tables {
+                tables: lobby_guard.get_tables(),
+            },
+            ClientMessage::CreateTable {
+                table,
+                name,
+                settings,
+            } if hosting.is_none() => match lobby_guard.create_table(table, name, settings) {
+                Ok(table) => {
+                    println!(
+                        "{}",
+                        trf("table_created", &[("addr", &addr), ("name", &table.name)])
+                    );
+                    hosting = Some(table.name.clone());
+                    ServerMessage::TableJoined { table }
+                }
+                Err(message) => ServerMessage::Reject { message },
+            },
+            ClientMessage::JoinTable { table, name } if hosting.is_none() => {
+                match lobby_guard.join_table(&table, name, connection) {
+                    Some(returned) => {
+                        connection = returned;
+                        continue;
+                    }
+                    None => return,
+                }
+            }
+            // テーブルを指定しなければ空いているテーブルに着く
+            ClientMessage::Join { name } if hosting.is_none() => {
+                match lobby_guard.find_open_table() {
+                    Some(table) => match lobby_guard.join_table(&table, name, connection) {
+                        Some(returned) => {
+                            connection = returned;
+                            continue;
+                        }
+                        None => return,
+                    },
+                    None => ServerMessage::Reject {
+                        message: tr("no_open_table").to_owned(),
+                    },
+                }
+            }
+            ClientMessage::Rejoin { token } => match lobby_guard.rejoin(token, connection, addr) {
+                Some(returned) => {
+                    connection = returned;
+                    continue;
+                }
+                None => return,
+            },
+            ClientMessage::Spectate { table: Some(table) } if hosting.is_none() => {
+                match lobby_guard.spectate(&table, connection, addr) {
+                    Some(returned) => {
+                        connection = returned;
+                        continue;
+                    }
+                    None => return,
+                }
+            }
+            ClientMessage::GetRules => match hosting
+                .as_deref()
+                .and_then(|name| lobby_guard.get_table(name))
+            {
+                Some(table) => ServerMessage::Rules { rules: table.rules },
+                None => ServerMessage::Reject {
+                    message: tr("not_at_table").to_owned(),
+                },
+            },
+            ClientMessage::Spectate { table: None } => ServerMessage::Reject {
+                message: tr("spectate_table_required").to_owned(),
+            },
+            ClientMessage::StartGame => {
+                let started = hosting.as_deref().and_then(|name| lobby_guard.start(name));
+                match started {
+                    Some((table, id, arrivals)) => {
+                        drop(lobby_guard);
+                        let name = hosting.unwrap();
+                        println!("{}", trf("table_started", &[("name", &name)]));
+                        let mut clients = vec![(table.host, connection)];
+                        clients.extend(table.guests);
+                        let mut session = SessionManager::new(arrivals, reconnect_turns, id)
+                            .with_turn_timeout(turn_timeout);
+                        for spectator in table.spectators {
+                            session.add_spectator(spectator);
+                        }
+                        let mut observers = Observers(vec![]);
+                        let npc = table.settings.npc;
+                        play_table(session, clients, npc, table.rules, rounds, &mut observers);
+                        lobby.lock().unwrap().finish(id);
+                        println!("{}", trf("table_finished", &[("name", &name)]));
+                        return;
+                    }
+                    None => ServerMessage::Reject {
+                        message: tr("host_only").to_owned(),
+                    },
+                }
+            }
+            ClientMessage::CreateTable { .. }
+            | ClientMessage::JoinTable { .. }
+            | ClientMessage::Join { .. }
+            | ClientMessage::Spectate { .. } => ServerMessage::Reject {
+                message: tr("already_seated").to_owned(),
+            },
+            ClientMessage::Play { .. } | ClientMessage::Pass | ClientMessage::Chat { .. } => {
+                ServerMessage::Reject {
+                    message: tr("not_started").to_owned(),
+                }
+            }
+        };
+        drop(lobby_guard);
+        if connection.send(&reply).is_err() {
+            if let Some(name) = hosting {
+                lobby.lock().unwrap().close_table(&name);
+            }
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Strategy;
+    use std::io;
+
+    // 送られたメッセージを記録する接続
+    struct Recorder(Arc<Mutex<Vec<ServerMessage>>>);
+
+    impl Connection for Recorder {
+        fn send(&mut self, message: &ServerMessage) -> io::Result<()> {
+            self.0.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+
+        fn receive(&mut self) -> io::Result<ClientMessage> {
+            Err(io::ErrorKind::UnexpectedEof.into())
+        }
+
+        fn try_receive(&mut self) -> io::Result<Option<ClientMessage>> {
+            Ok(None)
+        }
+    }
+
+    fn create_recorder() -> (Box<dyn Connection>, Arc<Mutex<Vec<ServerMessage>>>) {
+        let sent = Arc::new(Mutex::new(vec![]));
+        (Box::new(Recorder(sent.clone())), sent)
+    }
+
+    #[test]
+    fn test_lobby() {
+        let settings = TableSettings {
+            preset: "standard".to_owned(),
+            npc: Strategy::Heuristic,
+        };
+        let mut lobby = Lobby::default();
+        assert_eq!(lobby.find_open_table(), None);
+        let info = lobby
+            .create_table("Room".to_owned(), "Alice".to_owned(), settings.clone())
+            .unwrap();
+        assert_eq!(info.players, vec!["Alice".to_owned()]);
+        assert_eq!(info.rules, RuleSet::default());
+        let settings = TableSettings {
+            preset: "kansai".to_owned(),
+            npc: Strategy::Min,
+        };
+        let info = lobby
+            .create_table("Hall".to_owned(), "Alice".to_owned(), settings)
+            .unwrap();
+        assert_eq!(info.rules, Preset::Kansai.get_rules());
+        lobby.close_table("Hall");
+        for (name, preset) in [("Room", "standard"), ("Other", "local"), ("", "standard")] {
+            let settings = TableSettings {
+                preset: preset.to_owned(),
+                npc: Strategy::Min,
+            };
+            assert!(lobby
+                .create_table(name.to_owned(), "Bob".to_owned(), settings)
+                .is_err());
+        }
+        // 3人が参加すると満席になる
+        let mut sent = vec![];
+        for name in ["Bob", "Carol", "Dave"] {
+            let (connection, messages) = create_recorder();
+            assert!(lobby
+                .join_table("Room", name.to_owned(), connection)
+                .is_none());
+            sent.push(messages);
+        }
+        let last = sent[0].lock().unwrap().last().cloned();
+        let Some(ServerMessage::TableJoined { table }) = last else {
+            panic!("参加者に知らされませんでした");
+        };
+        assert_eq!(table.players, vec!["Alice", "Bob", "Carol", "Dave"]);
+        assert_eq!(lobby.find_open_table(), None);
+        for name in ["Room", "Hall"] {
+            let (connection, messages) = create_recorder();
+            assert!(lobby
+                .join_table(name, "Eve".to_owned(), connection)
+                .is_some());
+            assert!(matches!(
+                messages.lock().unwrap()[0],
+                ServerMessage::Reject { .. }
+            ));
+        }
+        assert_eq!(lobby.get_tables(), vec![table]);
+        let addr = "127.0.0.1:7777".parse().unwrap();
+        let (connection, _) = create_recorder();
+        assert!(lobby.spectate("Room", connection, addr).is_none());
+        // 始まったテーブルは一覧から外れ、トークンで再接続を届けられる
+        let (table, id, arrivals) = lobby.start("Room").unwrap();
+        assert_eq!(table.spectators.len(), 1);
+        assert!(lobby.get_tables().is_empty());
+        let (connection, _) = create_recorder();
+        let token = format!("{id}-0123456789abcdef");
+        assert!(lobby.rejoin(token.clone(), connection, addr).is_none());
+        assert!(arrivals.try_recv().is_ok());
+        // 対戦中のゲームも観戦できる
+        let (connection, _) = create_recorder();
+        assert!(lobby.spectate("Room", connection, addr).is_none());
+        assert!(arrivals.try_recv().is_ok());
+        lobby.finish(id);
+        let (connection, _) = create_recorder();
+        assert!(lobby.rejoin(token, connection, addr).is_some());
+        let (connection, _) = create_recorder();
+        assert!(lobby.spectate("Room", connection, addr).is_some());
+    }
+}
@@ -0,0 +1,608 @@
+use crate::event::GameEvent;
+use crate::i18n::{tr, trf};
+use crate::protocol::{ClientMessage, Connection, ServerMessage};
+use crate::rules::RuleSet;
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 手番の手を待つ間に他の席を確かめる間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+// CHAT_WINDOWの間に送れるチャットの数
+const CHAT_LIMIT: usize = 3;
+const CHAT_WINDOW: Duration = Duration::from_secs(10);
+const MAX_CHAT_LEN: usize = 200;
+
+// 受け付けた接続と最初のメッセージ
+pub type Arrival = (ClientMessage, Box<dyn Connection>, SocketAddr);
+
+// 席ごとの接続と再接続用のトークンを管理する
+pub struct SessionManager {
+    // トークンの先頭に付けてどのゲームの席かを区別する
+    id: u64,
+    arrivals: Receiver<Arrival>,
+    connections: Vec<Option<Box<dyn Connection>>>,
+    // 再接続を待っている席のトークン
+    tokens: HashMap<String, usize>,
+    // 接続が切れてから自動で進めた手番の数
+    missed_turns: Vec<usize>,
+    max_missed_turns: usize,
+    names: Vec<String>,
+    // 席ごとに直近のチャットを送った時刻
+    chat_times: Vec<Vec<Instant>>,
+    // 公開された進行だけを受け取る観戦者
+    spectators: Vec<Box<dyn Connection>>,
+    // ゲームの途中に参加を求め、次のラウンドを待っているクライアント
+    joins: Vec<(String, Box<dyn Connection>)>,
+    // GetRulesに返すテーブルのルール
+    rules: RuleSet,
+    // 手番の持ち時間(Noneなら制限しない)
+    turn_timeout: Option<Duration>,
+}
+
+impl SessionManager {
+    pub fn new(arrivals: Receiver<Arrival>, max_missed_turns: usize, id: u64) -> Self {
+        Self {
+            id,
+            arrivals,
+            connections: vec![],
+            tokens: HashMap::new(),
+            missed_turns: vec![],
+            max_missed_turns,
+            names: vec![],
+            chat_times: vec![],
+            spectators: vec![],
+            joins: vec![],
+            rules: RuleSet::default(),
+            turn_timeout: None,
+        }
+    }
+
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn with_turn_timeout(mut self, turn_timeout: Option<Duration>) -> Self {
+        self.turn_timeout = turn_timeout;
+        self
+    }
+
+    // 今から始まる手番の締め切り
+    pub fn get_turn_deadline(&self) -> Option<Instant> {
+        self.turn_timeout.map(|timeout| Instant::now() + timeout)
+    }
+
+    // 指定した人数のプレイヤーと観戦者が参加するまで待つ
+    pub fn wait_for_joins(
+        &mut self,
+        count: usize,
+        spectators: usize,
+    ) -> Vec<(String, Box<dyn Connection>)> {
+        let mut clients = vec![];
+        while clients.len() < count || self.spectators.len() < spectators {
+            let Ok((message, mut connection, addr)) = self.arrivals.recv() else {
+                break;
+            };
+            match message {
+                ClientMessage::Join { name } if clients.len() < count => {
+                    println!(
+                        "{}",
+                        trf("client_joined", &[("name", &name), ("addr", &addr)])
+                    );
+                    clients.push((name, connection));
+                }
+                ClientMessage::Spectate { .. } => {
+                    println!("{}", trf("spectating", &[("addr", &addr)]));
+                    self.add_spectator(connection);
+                }
+                _ => {
+                    println!("{}", trf("bad_join_from", &[("addr", &addr)]));
+                    let _ = connection.send(&ServerMessage::Reject {
+                        message: tr("bad_join").to_owned(),
+                    });
+                }
+            }
+        }
+        clients
+    }
+
+    // 席に着いた接続にトークンを渡す
+    pub fn seat(&mut self, seat: usize, mut connection: Box<dyn Connection>) -> io::Result<()> {
+        if self.connections.len() <= seat {
+            self.connections.resize_with(seat + 1, || None);
+            self.missed_turns.resize(seat + 1, 0);
+            self.chat_times.resize(seat + 1, vec![]);
+        }
+        // トークンは1回だけ使える
+        self.tokens.retain(|_, s| *s != seat);
+        let token = format!("{}-{:016x}", self.id, rand::random::<u64>());
+        connection.send(&ServerMessage::Welcome {
+            seat,
+            token: token.clone(),
+        })?;
+        self.tokens.insert(token, seat);
+        self.connections[seat] = Some(connection);
+        Ok(())
+    }
+
+    pub fn is_connected(&self, seat: usize) -> bool {
+        self.connections.get(seat).is_some_and(Option::is_some)
+    }
+
+    pub fn send(&mut self, seat: usize, message: &ServerMessage) {
+        let Some(connection) = self.connections.get_mut(seat).and_then(Option::as_mut) else {
+            return;
+        };
+        if connection.send(message).is_err() {
+            self.disconnect(seat);
+        }
+    }
+
+    // 手番の席から手が届くまで待つ(その間も他の席のチャットを中継する)
+    // 締め切りを過ぎるか接続が切れたらNoneを返す
+    pub fn receive(&mut self, seat: usize, deadline: Option<Instant>) -> Option<ClientMessage> {
+        while self.is_connected(seat) && deadline.is_none_or(|deadline| Instant::now() < deadline) {
+            if let Some(message) = self.poll_messages(Some(seat)) {
+                return Some(message);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        None
+    }
+
+    fn try_receive(&mut self, seat: usize) -> Option<ClientMessage> {
+        let connection = self.connections.get_mut(seat)?.as_mut()?;
+        match connection.try_receive() {
+            Ok(message) => message,
+            Err(_) => {
+                self.disconnect(seat);
+                None
+            }
+        }
+    }
+
+    // 全ての席と観戦者にチャットを中継する
+    fn chat(&mut self, seat: usize, text: &str) {
+        let now = Instant::now();
+        let times = &mut self.chat_times[seat];
+        times.retain(|time| now.duration_since(*time) < CHAT_WINDOW);
+        if times.len() >= CHAT_LIMIT {
+            self.send(
+                seat,
+                &ServerMessage::Reject {
+                    message: tr("chat_flood").to_owned(),
+                },
+            );
+            return;
+        }
+        let text: String = text.trim().chars().take(MAX_CHAT_LEN).collect();
+        if text.is_empty() {
+            return;
+        }
+        times.push(now);
+        let message = ServerMessage::Chat {
+            name: self.get_name(seat),
+            text,
+        };
+        for seat in 0..self.connections.len() {
+            self.send(seat, &message);
+        }
+        self.send_to_spectators(&message);
+    }
+
+    fn send_to_spectators(&mut self, message: &ServerMessage) {
+        // 接続が切れた観戦者は外す
+        self.spectators
+            .retain_mut(|connection| connection.send(message).is_ok());
+    }
+
+    fn disconnect(&mut self, seat: usize) {
+        if self.connections[seat].take().is_some() {
+            self.missed_turns[seat] = 0;
+            println!(
+                "{}",
+                trf("connection_lost", &[("name", &self.get_name(seat))])
+            );
+        }
+    }
+
+    fn get_name(&self, seat: usize) -> String {
+        self.names
+            .get(seat)
+            .cloned()
+            .unwrap_or_else(|| format!("P{seat}"))
+    }
+
+    // 接続が切れた席の手番を自動で進めたことを記録する
+    pub fn miss_turn(&mut self, seat: usize) {
+        self.missed_turns[seat] += 1;
+        if self.missed_turns[seat] == self.max_missed_turns {
+            // これ以降は再接続できない
+            self.tokens.retain(|_, s| *s != seat);
+            println!("{}", trf("substitute", &[("name", &self.get_name(seat))]));
+        }
+    }
+
+    // 観戦者を加え、ゲームの途中なら名前を知らせる
+    pub fn add_spectator(&mut self, mut connection: Box<dyn Connection>) {
+        if !self.names.is_empty() {
+            let start = GameEvent::Start {
+                names: self.names.clone(),
+                hands: vec![vec![]; self.names.len()],
+            };
+            if connection
+                .send(&ServerMessage::Event { event: start })
+                .is_err()
+            {
+                return;
+            }
+        }
+        self.spectators.push(connection);
+    }
+
+    // 届いた要求とチャットを処理する
+    pub fn poll(&mut self) {
+        self.poll_messages(None);
+    }
+
+    // 届いた要求とチャットを処理し、手番の席から届いた手を返す
+    fn poll_messages(&mut self, turn: Option<usize>) -> Option<ClientMessage> {
+        self.poll_arrivals();
+        for seat in 0..self.connections.len() {
+            while let Some(message) = self.try_receive(seat) {
+                match message {
+                    ClientMessage::Chat { text } => self.chat(seat, &text),
+                    ClientMessage::GetRules => {
+                        let rules = self.rules;
+                        self.send(seat, &ServerMessage::Rules { rules });
+                    }
+                    message if Some(seat) == turn => return Some(message),
+                    _ => self.send(
+                        seat,
+                        &ServerMessage::Reject {
+                            message: tr("not_your_turn").to_owned(),
+                        },
+                    ),
+                }
+            }
+        }
+        None
+    }
+
+    // ラウンドの間に席に着かせる、途中から参加を求めたクライアント
+    pub fn take_joins(&mut self) -> Vec<(String, Box<dyn Connection>)> {
+        self.poll_arrivals();
+        std::mem::take(&mut self.joins)
+    }
+
+    // 届いた再接続、観戦と途中からの参加の要求を処理する
+    fn poll_arrivals(&mut self) {
+        while let Ok((message, mut connection, addr)) = self.arrivals.try_recv() {
+            match message {
+                ClientMessage::Spectate { .. } => {
+                    println!("{}", trf("spectating", &[("addr", &addr)]));
+                    self.add_spectator(connection);
+                    continue;
+                }
+                ClientMessage::Join { name } => {
+                    println!(
+                        "{}",
+                        trf("waiting_next_round", &[("name", &name), ("addr", &addr)])
+                    );
+                    self.joins.push((name, connection));
+                    continue;
+                }
+                _ => {}
+            }
+            let seat = match &message {
+                ClientMessage::Rejoin { token } => self.tokens.get(token).copied(),
+                _ => None,
+            };
+            let Some(seat) = seat.filter(|seat| !self.is_connected(*seat)) else {
+                println!("{}", trf("refused", &[("addr", &addr)]));
+                let _ = connection.send(&ServerMessage::Reject {
+                    message: tr("cannot_join").to_owned(),
+                });
+                continue;
+            };
+            // 名前を知らせ直す(手札は次の手番で送る)
+            let start = GameEvent::Start {
+                names: self.names.clone(),
+                hands: vec![vec![]; self.names.len()],
+            };
+            let result = self.seat(seat, connection).and_then(|_| {
+                let connection = self.connections[seat].as_mut().unwrap();
+                connection.send(&ServerMessage::Event { event: start })
+            });
+            match result {
+                Ok(()) => {
+                    let name = self.get_name(seat);
+                    println!("{}", trf("rejoined", &[("name", &name), ("addr", &addr)]));
+                }
+                Err(_) => self.disconnect(seat),
+            }
+        }
+    }
+
+    // 全ての接続にゲームの進行を送る
+    pub fn broadcast(&mut self, event: &GameEvent) {
+        if let GameEvent::Start { names, .. } = event {
+            self.names = names.clone();
+        }
+        self.poll();
+        for seat in 0..self.connections.len() {
+            let event = event.censor(Some(seat));
+            self.send(seat, &ServerMessage::Event { event });
+        }
+        self.send_to_spectators(&ServerMessage::Event {
+            event: event.censor(None),
+        });
+    }
+}
+
+// 別のスレッドで接続を受け付け続ける
+pub fn spawn_acceptor(listener: TcpListener, ws: bool) -> Receiver<Arrival> {
+    let (sender, arrivals) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let Ok(addr) = stream.peer_addr() else {
+                continue;
+            };
+            let Ok(mut connection) = accept(stream, ws) else {
+                continue;
+            };
+            if let Ok(message) = connection.receive() {
+                if sender.send((message, connection, addr)).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    arrivals
+}
+
+// トークンからゲームを取得する
+pub fn get_token_id(token: &str) -> Option<u64> {
+    token.split_once('-')?.0.parse().ok()
+}
+
+// TCPかWebSocketで接続を受け付ける
+#[cfg(feature = "ws")]
+pub fn accept(stream: TcpStream, ws: bool) -> io::Result<Box<dyn Connection>> {
+    if ws {
+        let socket = tungstenite::accept(stream).map_err(io::Error::other)?;
+        return Ok(Box::new(socket));
+    }
+    Ok(Box::new(stream))
+}
+
+#[cfg(not(feature = "ws"))]
+pub fn accept(stream: TcpStream, _ws: bool) -> io::Result<Box<dyn Connection>> {
+    Ok(Box::new(stream))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::Card;
+    use crate::protocol::{read_message, write_message};
+    use std::time::Duration;
+
+    fn connect(addr: SocketAddr, message: &ClientMessage) -> TcpStream {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        write_message(&mut stream, message).unwrap();
+        stream
+    }
+
+    // 要求が処理されて返事が届くまで待つ
+    fn wait_for_reply(session: &mut SessionManager, stream: &mut TcpStream) -> ServerMessage {
+        loop {
+            session.poll();
+            if let Ok(message) = read_message(stream) {
+                return message;
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejoin() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut session = SessionManager::new(spawn_acceptor(listener, false), 2, 5);
+        let mut client = connect(
+            addr,
+            &ClientMessage::Join {
+                name: "User".to_owned(),
+            },
+        );
+        let (_, connection) = session.wait_for_joins(1, 0).pop().unwrap();
+        session.seat(1, connection).unwrap();
+        let ServerMessage::Welcome { seat: 1, token } = wait_for_reply(&mut session, &mut client)
+        else {
+            panic!("トークンを受け取れませんでした");
+        };
+        assert_eq!(get_token_id(&token), Some(5));
+        // 接続が切れたら受信できない
+        drop(client);
+        assert_eq!(session.receive(1, None), None);
+        assert!(!session.is_connected(1));
+        // 間違ったトークンでは再接続できない
+        let mut other = connect(
+            addr,
+            &ClientMessage::Rejoin {
+                token: "0".to_owned(),
+            },
+        );
+        assert!(matches!(
+            wait_for_reply(&mut session, &mut other),
+            ServerMessage::Reject { .. }
+        ));
+        let mut client = connect(addr, &ClientMessage::Rejoin { token });
+        let ServerMessage::Welcome { seat: 1, token } = wait_for_reply(&mut session, &mut client)
+        else {
+            panic!("再接続できませんでした");
+        };
+        assert!(session.is_connected(1));
+        // 自動で進めた手番が上限に達したら再接続できない
+        drop(client);
+        assert_eq!(session.receive(1, None), None);
+        session.miss_turn(1);
+        session.miss_turn(1);
+        let mut client = connect(addr, &ClientMessage::Rejoin { token });
+        assert!(matches!(
+            wait_for_reply(&mut session, &mut client),
+            ServerMessage::Reject { .. }
+        ));
+        assert!(!session.is_connected(1));
+    }
+
+    #[test]
+    fn test_chat() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut session = SessionManager::new(spawn_acceptor(listener, false), 2, 0);
+        let join = ClientMessage::Join {
+            name: "User".to_owned(),
+        };
+        let mut clients = vec![connect(addr, &join), connect(addr, &join)];
+        for (seat, (_, connection)) in session.wait_for_joins(2, 0).into_iter().enumerate() {
+            session.seat(seat, connection).unwrap();
+        }
+        for client in &mut clients {
+            wait_for_reply(&mut session, client);
+        }
+        // 上限を超えたチャットは送った本人に断られる
+        for i in 0..=CHAT_LIMIT {
+            let chat = ClientMessage::Chat {
+                text: format!(" {i} "),
+            };
+            write_message(&mut clients[0], &chat).unwrap();
+        }
+        for i in 0..CHAT_LIMIT {
+            let expected = ServerMessage::Chat {
+                name: "P0".to_owned(),
+                text: i.to_string(),
+            };
+            assert_eq!(wait_for_reply(&mut session, &mut clients[1]), expected);
+            assert_eq!(wait_for_reply(&mut session, &mut clients[0]), expected);
+        }
+        assert!(matches!(
+            wait_for_reply(&mut session, &mut clients[0]),
+            ServerMessage::Reject { .. }
+        ));
+        // 手番でない席から届いた手は断る
+        write_message(&mut clients[1], &ClientMessage::Pass).unwrap();
+        assert!(matches!(
+            wait_for_reply(&mut session, &mut clients[1]),
+            ServerMessage::Reject { .. }
+        ));
+        // ルールはどの席からでも求められる
+        let rules = RuleSet {
+            jack_back: true,
+            ..RuleSet::default()
+        };
+        session = session.with_rules(rules);
+        write_message(&mut clients[1], &ClientMessage::GetRules).unwrap();
+        assert_eq!(
+            wait_for_reply(&mut session, &mut clients[1]),
+            ServerMessage::Rules { rules }
+        );
+    }
+
+    #[test]
+    fn test_take_joins() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut session = SessionManager::new(spawn_acceptor(listener, false), 2, 0);
+        let join = ClientMessage::Join {
+            name: "Late".to_owned(),
+        };
+        let mut client = connect(addr, &join);
+        // ゲームの途中に届いた参加の要求は次のラウンドまで待たせる
+        let mut joins = loop {
+            let joins = session.take_joins();
+            if !joins.is_empty() {
+                break joins;
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        let (name, connection) = joins.pop().unwrap();
+        assert_eq!(name, "Late");
+        assert!(session.take_joins().is_empty());
+        session.seat(2, connection).unwrap();
+        assert!(matches!(
+            wait_for_reply(&mut session, &mut client),
+            ServerMessage::Welcome { seat: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_spectate() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut session = SessionManager::new(spawn_acceptor(listener, false), 2, 0);
+        let mut spectator = connect(addr, &ClientMessage::Spectate { table: None });
+        assert!(session.wait_for_joins(0, 1).is_empty());
+        let names = vec!["P0".to_owned(), "P1".to_owned()];
+        session.broadcast(&GameEvent::Start {
+            names: names.clone(),
+            hands: vec![vec![Card::Joker], vec![]],
+        });
+        session.broadcast(&GameEvent::End {
+            player_rank: vec![0, 1],
+        });
+        // 観戦者には誰の手札も送らない
+        for expected in [
+            GameEvent::Start {
+                names,
+                hands: vec![vec![], vec![]],
+            },
+            GameEvent::End {
+                player_rank: vec![0, 1],
+            },
+        ] {
+            assert_eq!(
+                wait_for_reply(&mut session, &mut spectator),
+                ServerMessage::Event { event: expected }
+            );
+        }
+    }
+
+    #[cfg(feature = "ws")]
+    #[test]
+    fn test_accept_ws() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let stream = TcpStream::connect(addr).unwrap();
+            let (mut socket, _) = tungstenite::client(format!("ws://{addr}/"), stream).unwrap();
+            let join = tungstenite::Message::text(r#"{"type":"Join","name":"Web"}"#);
+            socket.send(join).unwrap();
+            socket.read().unwrap()
+        });
+        let (stream, _) = listener.accept().unwrap();
+        let mut connection = accept(stream, true).unwrap();
+        assert_eq!(
+            connection.receive().unwrap(),
+            ClientMessage::Join {
+                name: "Web".to_owned()
+            }
+        );
+        let reject = ServerMessage::Reject {
+            message: String::new(),
+        };
+        connection.send(&reject).unwrap();
+        let message = client.join().unwrap();
+        assert_eq!(
+            message.to_text().unwrap(),
+            r#"{"type":"Reject","message":""}"#
+        );
+    }
+}
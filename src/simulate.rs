@@ -0,0 +1,186 @@
+use crate::dataset::{record_samples, set_places, Sample};
+use crate::event::GameEvent;
+use crate::field::Effect;
+use crate::game::Game;
+use crate::i18n::{tr, trf};
+use crate::observer::{Observer, Observers};
+use crate::player::Player;
+use crate::record::GameRecord;
+use crate::strategy::Strategy;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+pub struct SimulateOptions {
+    pub games: usize,
+    pub seats: Vec<Strategy>,
+    pub seed: u64,
+    // ゲーム毎の結果を書き出すCSVファイル
+    pub output: Option<PathBuf>,
+    // 1手ごとの学習データを書き出すnpzファイル
+    pub dataset: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub games: usize,
+    // 席ごとの順位の回数
+    pub places: Vec<Vec<usize>>,
+    pub turns: usize,
+    pub revs: usize,
+    pub binds: usize,
+    pub eights: usize,
+    pub records: Vec<GameRecord>,
+    pub samples: Vec<Sample>,
+}
+
+impl Summary {
+    pub fn get_lines(&self, seats: &[Strategy]) -> Vec<String> {
+        let games = self.games.max(1) as f64;
+        let places: String = (1..=seats.len())
+            .map(|p| format!("{:>7}", trf("tui_place", &[("place", &p)])))
+            .collect();
+        let mut lines = vec![trf("simulate_header", &[("places", &places)])];
+        for (i, (strategy, places)) in seats.iter().zip(&self.places).enumerate() {
+            let average = places
+                .iter()
+                .enumerate()
+                .map(|(p, count)| (p + 1) * count)
+                .sum::<usize>() as f64
+                / games;
+            lines.push(format!(
+                "{i:>2}  {:<10} {}  {average:>8.2}",
+                strategy.get_name(),
+                places
+                    .iter()
+                    .map(|count| format!("{:>7.1}%", *count as f64 * 100.0 / games))
+                    .collect::<String>()
+            ));
+        }
+        let turns = format!("{:.1}", self.turns as f64 / games);
+        lines.push(trf("average_turns", &[("turns", &turns)]));
+        for (key, count) in [
+            ("revolution", self.revs),
+            ("bind", self.binds),
+            ("eight_cut", self.eights),
+        ] {
+            let count = format!("{:.2}", count as f64 / games);
+            lines.push(trf("per_game", &[("name", &tr(key)), ("count", &count)]));
+        }
+        lines
+    }
+}
+
+impl Observer for Summary {
+    fn notify(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Turn { outcome, .. } => {
+                self.turns += 1;
+                self.revs += usize::from(
+                    outcome.contains(Effect::Revolution)
+                        || outcome.contains(Effect::SuperRevolution),
+                );
+                self.binds += usize::from(outcome.contains(Effect::Bind));
+                self.eights += usize::from(outcome.contains(Effect::EightCut));
+            }
+            GameEvent::End { player_rank } => {
+                self.games += 1;
+                for (place, idx) in player_rank.iter().enumerate() {
+                    self.places[*idx][place] += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// 毎ゲーム新しくカードを配り、開始するプレイヤーを順番に変えて対戦する
+pub fn simulate(options: &SimulateOptions) -> Summary {
+    let seats_count = options.seats.len();
+    let mut summary = Summary {
+        places: vec![vec![0; seats_count]; seats_count],
+        ..Default::default()
+    };
+    for game in 0..options.games {
+        let seed = options.seed.wrapping_add(game as u64);
+        let mut record = GameRecord::new(seed, options.seats.clone());
+        let mut observers = Observers(vec![&mut summary, &mut record]);
+        let mut players = create_npcs(&options.seats, seed);
+        let samples = Rc::new(RefCell::new(vec![]));
+        if options.dataset.is_some() {
+            players = record_samples(players, &samples);
+        }
+        let player_rank = Game::new(players, game % seats_count, seed).play_round(&mut observers);
+        summary.records.push(record);
+        let mut samples = samples.take();
+        set_places(&mut samples, &player_rank);
+        summary.samples.extend(samples);
+    }
+    summary
+}
+
+// 各席の戦略のNPCで1ゲーム対戦して順位を取得する
+pub fn play_game(
+    seats: &[Strategy],
+    start_idx: usize,
+    seed: u64,
+    observer: &mut dyn Observer,
+) -> Vec<usize> {
+    let mut game = Game::new(create_npcs(seats, seed), start_idx, seed);
+    game.play_round(observer)
+}
+
+fn create_npcs(seats: &[Strategy], seed: u64) -> Vec<Box<dyn Player>> {
+    seats
+        .iter()
+        .enumerate()
+        .map(|(i, strategy)| {
+            let name = format!("{}{i}", strategy.get_name());
+            strategy.create_npc(name, seed.wrapping_mul(31).wrapping_add(i as u64))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_simulate() {
+        let options = SimulateOptions {
+            games: 8,
+            seats: vec![
+                Strategy::Min,
+                Strategy::Min,
+                Strategy::Heuristic,
+                Strategy::Min,
+            ],
+            seed: 1,
+            output: None,
+            dataset: None,
+        };
+        let summary = simulate(&options);
+        assert!(summary.samples.is_empty());
+        assert_eq!(summary.games, 8);
+        for places in &summary.places {
+            assert_eq!(places.iter().sum::<usize>(), 8);
+        }
+        assert!(summary.turns > 0);
+        assert_eq!(summary.records.len(), 8);
+        assert_eq!(
+            summary.records.iter().map(|r| r.turns).sum::<usize>(),
+            summary.turns
+        );
+        // 同じシードなら同じ結果になる
+        assert_eq!(simulate(&options), summary);
+        // 学習データを記録しても結果は変わらない
+        let options = SimulateOptions {
+            dataset: Some(PathBuf::from("dataset.npz")),
+            ..options
+        };
+        let with_samples = simulate(&options);
+        assert_eq!(with_samples.records, summary.records);
+        assert_eq!(with_samples.samples.len(), summary.turns);
+        assert!(with_samples.samples.iter().all(|s| s.place < 4));
+    }
+}
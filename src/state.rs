@@ -0,0 +1,45 @@
+use crate::card::Card;
+use crate::field::Field;
+use crate::strategy::Strategy;
+use crate::validator::Validator;
+use crate::view::GameView;
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlayerState {
+    pub name: String,
+    // 人間のプレイヤーはNone
+    pub strategy: Option<Strategy>,
+    pub hands: Vec<Card>,
+}
+
+// ゲームを途中から再開するために必要な全ての状態
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameState {
+    pub players: Vec<PlayerState>,
+    pub field: Field,
+    pub rng: ChaCha8Rng,
+    // 現在のラウンドで最初に手番になったプレイヤーの席
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dealer: usize,
+}
+
+impl GameState {
+    // 手番、場のカード、縛り、革命、席毎の順位などの場の状態
+    pub fn get_view(&self) -> GameView {
+        self.field.get_view()
+    }
+
+    pub fn get_hands(&self, idx: usize) -> &[Card] {
+        &self.players[idx].hands
+    }
+
+    // 順位が決まったプレイヤーの席を順位の良い順に並べる
+    pub fn get_standings(&self) -> Vec<usize> {
+        self.field.get_player_rank()
+    }
+}
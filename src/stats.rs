@@ -0,0 +1,205 @@
+use crate::event::GameEvent;
+use crate::i18n::{tr, trf};
+use crate::observer::Observer;
+use crate::rating::{update_ratings, INITIAL_RATING};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub rounds: usize,
+    pub wins: usize,
+    // 順位の合計(平均順位の計算に使う)
+    pub total_place: usize,
+    pub fouls: usize,
+    // 現在の大富豪の連続回数
+    pub streak: usize,
+    pub longest_streak: usize,
+    #[serde(default = "get_initial_rating")]
+    pub rating: f64,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        Self {
+            rounds: 0,
+            wins: 0,
+            total_place: 0,
+            fouls: 0,
+            streak: 0,
+            longest_streak: 0,
+            rating: INITIAL_RATING,
+        }
+    }
+}
+
+fn get_initial_rating() -> f64 {
+    INITIAL_RATING
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub players: BTreeMap<String, PlayerStats>,
+}
+
+impl Stats {
+    // ラウンドの結果を記録して、席順にレーティングの変化(変化前, 変化後)を返す
+    pub fn record_round(
+        &mut self,
+        names: &[String],
+        player_rank: &[usize],
+        fouls: &[usize],
+    ) -> Vec<(f64, f64)> {
+        let ratings: Vec<f64> = names
+            .iter()
+            .map(|name| self.players.get(name).map_or(INITIAL_RATING, |s| s.rating))
+            .collect();
+        let new_ratings = update_ratings(&ratings, player_rank);
+        for (name, rating) in names.iter().zip(&new_ratings) {
+            self.players.entry(name.clone()).or_default().rating = *rating;
+        }
+        for (place, idx) in player_rank.iter().enumerate() {
+            let stats = self.players.entry(names[*idx].clone()).or_default();
+            stats.rounds += 1;
+            stats.total_place += place + 1;
+            if place == 0 {
+                stats.wins += 1;
+                stats.streak += 1;
+                stats.longest_streak = stats.longest_streak.max(stats.streak);
+            } else {
+                stats.streak = 0;
+            }
+        }
+        for idx in fouls {
+            self.players.entry(names[*idx].clone()).or_default().fouls += 1;
+        }
+        ratings.into_iter().zip(new_ratings).collect()
+    }
+
+    pub fn get_lines(&self) -> Vec<String> {
+        let mut lines = vec![tr("stats_header").to_owned()];
+        for (name, stats) in &self.players {
+            let average = stats.total_place as f64 / stats.rounds.max(1) as f64;
+            lines.push(format!(
+                "{name:<10} {:>9} {:>4} {:>9.2} {:>11} {:>15} {:>13.0}",
+                stats.rounds, stats.wins, average, stats.fouls, stats.longest_streak, stats.rating
+            ));
+        }
+        lines
+    }
+}
+
+// ユーザーの設定ディレクトリの統計ファイルのパス
+pub fn get_stats_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("daifugo").join("stats.toml"))
+}
+
+pub fn load_stats(path: &Path) -> Result<Stats, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => toml::from_str(&text).map_err(|e| e.to_string()),
+        Err(_) => Ok(Stats::default()),
+    }
+}
+
+pub fn save_stats(path: &Path, stats: &Stats) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = toml::to_string(stats).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+// ラウンドが終わる度に統計を更新してファイルに保存する
+pub struct StatsRecorder {
+    path: PathBuf,
+    names: Vec<String>,
+    fouls: Vec<usize>,
+}
+
+impl StatsRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            names: vec![],
+            fouls: vec![],
+        }
+    }
+}
+
+impl Observer for StatsRecorder {
+    fn notify(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Start { names, .. } => {
+                self.names = names.clone();
+                self.fouls.clear();
+            }
+            GameEvent::Turn { idx, outcome, .. }
+                if outcome.finish.is_some_and(|finish| finish.foul) =>
+            {
+                self.fouls.push(*idx);
+            }
+            GameEvent::End { player_rank } => {
+                let result = load_stats(&self.path).and_then(|mut stats| {
+                    let changes = stats.record_round(&self.names, player_rank, &self.fouls);
+                    save_stats(&self.path, &stats).map(|_| changes)
+                });
+                match result {
+                    Ok(changes) => {
+                        println!("{}", tr("rating"));
+                        for line in get_rating_lines(&self.names, &changes) {
+                            println!("{line}");
+                        }
+                    }
+                    Err(e) => eprintln!("{}", trf("stats_save_failed", &[("error", &e)])),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn get_rating_lines(names: &[String], changes: &[(f64, f64)]) -> Vec<String> {
+    names
+        .iter()
+        .zip(changes)
+        .map(|(name, (old, new))| {
+            let (old, new) = (old.round() as i64, new.round() as i64);
+            format!("{name}: {old} → {new} ({:+})", new - old)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_record_round() {
+        let names = ["User", "NpcA", "NpcB", "NpcC"].map(String::from);
+        let mut stats = Stats::default();
+        for (player_rank, fouls) in [
+            (vec![0, 1, 2, 3], vec![]),
+            (vec![0, 2, 1, 3], vec![3]),
+            (vec![1, 0, 2, 3], vec![]),
+            (vec![0, 1, 2, 3], vec![]),
+        ] {
+            stats.record_round(&names, &player_rank, &fouls);
+        }
+        let user = &stats.players["User"];
+        assert_eq!(
+            (user.rounds, user.wins, user.total_place, user.fouls),
+            (4, 3, 5, 0)
+        );
+        assert_eq!((user.streak, user.longest_streak), (1, 2));
+        assert!(user.rating > INITIAL_RATING);
+        let changes = stats.record_round(&names, &[3, 2, 1, 0], &[]);
+        assert!(changes[0].1 < changes[0].0);
+        assert!(changes[3].1 > changes[3].0);
+        assert_eq!(stats.players["NpcC"].fouls, 1);
+        // TOMLに変換して元に戻せる
+        let text = toml::to_string(&stats).unwrap();
+        assert_eq!(toml::from_str::<Stats>(&text).unwrap(), stats);
+    }
+}
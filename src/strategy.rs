@@ -0,0 +1,67 @@
+use crate::heuristic::{HeuristicNpc, Weights};
+use crate::i18n::trf;
+use crate::mcts::MctsNpc;
+use crate::npc::MinNpc;
+use crate::player::Player;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Strategy {
+    Min,
+    Heuristic,
+    Mcts,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [Strategy::Min, Strategy::Heuristic, Strategy::Mcts];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Strategy::Min => "min",
+            Strategy::Heuristic => "heuristic",
+            Strategy::Mcts => "mcts",
+        }
+    }
+
+    pub fn create_npc(&self, name: String, seed: u64) -> Box<dyn Player> {
+        match self {
+            Strategy::Min => Box::new(MinNpc::new(name)),
+            Strategy::Heuristic => Box::new(HeuristicNpc::new(name, Weights::default())),
+            Strategy::Mcts => Box::new(MctsNpc::new(name, seed)),
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Strategy::ALL
+            .into_iter()
+            .find(|strategy| strategy.get_name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Strategy::ALL.iter().map(|s| s.get_name()).collect();
+                trf(
+                    "unknown_strategy",
+                    &[("value", &s), ("choices", &names.join(", "))],
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        for strategy in Strategy::ALL {
+            assert_eq!(strategy.get_name().parse::<Strategy>(), Ok(strategy));
+        }
+        assert!("max".parse::<Strategy>().is_err());
+    }
+}
@@ -2,10 +2,81 @@ use crate::{
     card::{Card, Suit},
     comb::Comb,
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
 
+// 1つの組み合わせに含まれるジョーカー以外のカードの最大の枚数(13枚の階段)
+const MAX_SUITS: usize = 13;
+
+// 組み合わせのカードのスートを順に並べたもの(手番毎に確保し直さないように配列に持つ)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(from = "Vec<Suit>", into = "Vec<Suit>")
+)]
+pub struct SuitList {
+    suits: [Suit; MAX_SUITS],
+    len: usize,
+}
+
+impl SuitList {
+    fn new() -> Self {
+        SuitList {
+            suits: [Suit::Club; MAX_SUITS],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, suit: Suit) {
+        if self.len < MAX_SUITS {
+            self.suits[self.len] = suit;
+            self.len += 1;
+        }
+    }
+
+    // ジョーカー以外のカードのスート
+    fn from_cards(cards: &[Card]) -> Self {
+        let mut list = SuitList::new();
+        for card in cards {
+            if let Card::Normal(s, _) = card {
+                list.push(*s);
+            }
+        }
+        list
+    }
+}
+
+impl Deref for SuitList {
+    type Target = [Suit];
+
+    fn deref(&self) -> &Self::Target {
+        &self.suits[..self.len]
+    }
+}
+
+impl From<Vec<Suit>> for SuitList {
+    fn from(suits: Vec<Suit>) -> Self {
+        let mut list = SuitList::new();
+        for suit in suits {
+            list.push(suit);
+        }
+        list
+    }
+}
+
+impl From<SuitList> for Vec<Suit> {
+    fn from(list: SuitList) -> Self {
+        list.to_vec()
+    }
+}
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SuitBinder {
-    suits: Option<Vec<Suit>>,
-    prev_suits: Option<Vec<Suit>>,
+    suits: Option<SuitList>,
+    prev_suits: Option<SuitList>,
 }
 
 impl SuitBinder {
@@ -16,6 +87,20 @@ impl SuitBinder {
         }
     }
 
+    pub fn get_bind_suits(&self) -> Option<&[Suit]> {
+        self.suits.as_deref()
+    }
+
+    // 次に同じスートが続けば縛りになる直前の組み合わせのスート
+    pub fn get_prev_suits(&self) -> Option<&[Suit]> {
+        self.prev_suits.as_deref()
+    }
+
+    pub fn set_bind_suits(&mut self, suits: Option<Vec<Suit>>) {
+        self.suits = suits.map(SuitList::from);
+        self.prev_suits = None;
+    }
+
     pub fn is_activate(&self) -> bool {
         self.suits.is_some()
     }
@@ -27,16 +112,18 @@ impl SuitBinder {
                     self.suits = self.prev_suits.take();
                 }
                 _ => {
-                    self.prev_suits = Some(vec![*s]);
+                    let mut suits = SuitList::new();
+                    suits.push(*s);
+                    self.prev_suits = Some(suits);
                 }
             },
             Comb::Multi(cards) | Comb::Seq(cards) if !cards.contains(&Card::Joker) => {
                 match &self.prev_suits {
-                    Some(suits) if suits == &get_suits(cards) => {
+                    Some(suits) if *suits == SuitList::from_cards(cards) => {
                         self.suits = self.prev_suits.take();
                     }
                     _ => {
-                        self.prev_suits = Some(get_suits(cards));
+                        self.prev_suits = Some(SuitList::from_cards(cards));
                     }
                 }
             }
@@ -64,10 +151,13 @@ impl SuitBinder {
                 }
                 Comb::Multi(cards) => {
                     (cards.len() == suits.len())
-                        && cards.iter().zip(suits).all(|(card, suit)| match card {
-                            Card::Normal(s, _) => s == suit,
-                            Card::Joker => true,
-                        })
+                        && cards
+                            .iter()
+                            .zip(suits.iter())
+                            .all(|(card, suit)| match card {
+                                Card::Normal(s, _) => s == suit,
+                                Card::Joker => true,
+                            })
                 }
                 Comb::Seq(cards) => {
                     (cards.len() == suits.len())
@@ -82,16 +172,6 @@ impl SuitBinder {
     }
 }
 
-fn get_suits(cards: &[Card]) -> Vec<Suit> {
-    cards
-        .iter()
-        .filter_map(|card| match card {
-            Card::Normal(s, _) => Some(*s),
-            Card::Joker => None,
-        })
-        .collect()
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -99,7 +179,7 @@ mod test {
 
     fn create_suit_binder(suits: Vec<Suit>) -> SuitBinder {
         let mut binder = SuitBinder::new();
-        binder.suits = Some(suits);
+        binder.suits = Some(SuitList::from(suits));
         binder
     }
 
@@ -222,11 +302,21 @@ mod test {
             for comb in combs {
                 binder.push(&comb);
             }
-            assert_eq!(binder.suits, expected_suits);
-            assert_eq!(binder.prev_suits, expected_prev_suits);
+            assert_eq!(binder.suits.map(Vec::from), expected_suits);
+            assert_eq!(binder.prev_suits.map(Vec::from), expected_prev_suits);
         }
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_suit_list_serde() {
+        // 保存したゲームとの互換性のためにスートの配列として保存する
+        let suits = SuitList::from(vec![Suit::Heart, Suit::Spade]);
+        let json = serde_json::to_string(&suits).unwrap();
+        assert_eq!(json, "[\"Heart\",\"Spade\"]");
+        assert_eq!(serde_json::from_str::<SuitList>(&json).unwrap(), suits);
+    }
+
     #[test]
     fn test_is_valid() {
         // ♣︎縛り
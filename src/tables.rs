@@ -0,0 +1,110 @@
+// 1つの数字のスートの組み合わせ(4ビット)の数
+const SUITS_MASKS: usize = 1 << 4;
+// 1つのスートの数字の組み合わせ(13ビット)の数
+const RANKS_MASKS: usize = 1 << 13;
+// 4つのスートから2枚以上を選ぶ組の最大の数(4C2)
+const MAX_SETS: usize = 6;
+
+// スートのマスク毎に、枚数別に選べるスートの組を下位のビットを優先する順に並べた表(空きは0)
+pub static SUIT_SETS: [[[u8; MAX_SETS]; 5]; SUITS_MASKS] = build_suit_sets();
+
+// 数字のマスク毎に、各数字から上に連続して持っている数字の枚数を並べた表
+pub static RUNS: [[u8; 13]; RANKS_MASKS] = build_runs();
+
+const fn build_suit_sets() -> [[[u8; MAX_SETS]; 5]; SUITS_MASKS] {
+    let mut table = [[[0; MAX_SETS]; 5]; SUITS_MASKS];
+    let mut suits = 0;
+    while suits < SUITS_MASKS {
+        let mut len = 1;
+        while len <= 4 {
+            // 下位のビットを優先する順はビットを反転した値の降順になる
+            let mut count = 0;
+            let mut set = SUITS_MASKS;
+            while set > 1 {
+                set -= 1;
+                let reversed = (set as u8).reverse_bits() >> 4;
+                if reversed & !(suits as u8) == 0 && reversed.count_ones() as usize == len {
+                    if count < MAX_SETS {
+                        table[suits][len][count] = reversed;
+                    }
+                    count += 1;
+                }
+            }
+            len += 1;
+        }
+        suits += 1;
+    }
+    table
+}
+
+const fn build_runs() -> [[u8; 13]; RANKS_MASKS] {
+    let mut table = [[0; 13]; RANKS_MASKS];
+    let mut ranks = 0;
+    while ranks < RANKS_MASKS {
+        let mut low = 13;
+        let mut run = 0;
+        while low > 0 {
+            low -= 1;
+            run = match ranks & (1 << low) != 0 {
+                true => run + 1,
+                false => 0,
+            };
+            table[ranks][low] = run;
+        }
+        ranks += 1;
+    }
+    table
+}
+
+// スートのマスクから選べるlen枚のスートの組(5枚以上の組はない)
+pub fn get_suit_sets(suits: u8, len: usize) -> impl Iterator<Item = u8> {
+    SUIT_SETS[suits as usize]
+        .get(len)
+        .into_iter()
+        .flatten()
+        .copied()
+        .take_while(|set| *set != 0)
+}
+
+// 数字のマスクでlowの数字から始まるlen枚の階段を作れるか
+pub fn has_run(ranks: u16, low: usize, len: usize) -> bool {
+    RUNS[ranks as usize][low] as usize >= len
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_suit_sets() {
+        for (suits, len, expected) in [
+            (
+                0b1111,
+                2,
+                vec![0b0011, 0b0101, 0b1001, 0b0110, 0b1010, 0b1100],
+            ),
+            (0b1111, 4, vec![0b1111]),
+            (0b1011, 3, vec![0b1011]),
+            (0b1010, 2, vec![0b1010]),
+            (0b1000, 2, vec![]),
+            (0b1111, 5, vec![]),
+            (0b0000, 1, vec![]),
+        ] {
+            assert_eq!(get_suit_sets(suits, len).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_has_run() {
+        for (ranks, low, len, expected) in [
+            (0b0111, 0, 3, true),
+            (0b0111, 1, 3, false),
+            (0b1110_1110, 1, 3, true),
+            (0b1110_1110, 5, 3, true),
+            (0b1110_1110, 5, 4, false),
+            (0x1fff, 0, 13, true),
+        ] {
+            assert_eq!(has_run(ranks, low, len), expected);
+        }
+    }
+}
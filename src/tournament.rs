@@ -0,0 +1,406 @@
+use crate::game::Game;
+use crate::i18n::{tr, trf};
+use crate::names;
+use crate::observer::Observers;
+use crate::pc::Pc;
+use crate::player::Player;
+use crate::rating::{update_ratings, INITIAL_RATING};
+use crate::render::{Renderer, Style};
+use crate::strategy::Strategy;
+use crate::{MAX_PLAYERS, MIN_PLAYERS};
+use itertools::Itertools;
+use std::str::FromStr;
+
+// ラウンド毎に参加者をテーブルに振り分ける方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pairing {
+    // 並びを1つずつずらして毎回違う相手と対戦する
+    #[default]
+    Rotate,
+    // それまでの成績が近い参加者を同じテーブルに座らせる(スイス式)
+    Swiss,
+}
+
+impl Pairing {
+    pub const ALL: [Pairing; 2] = [Pairing::Rotate, Pairing::Swiss];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Pairing::Rotate => "rotate",
+            Pairing::Swiss => "swiss",
+        }
+    }
+}
+
+impl FromStr for Pairing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pairing::ALL
+            .into_iter()
+            .find(|pairing| pairing.get_name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Pairing::ALL.iter().map(|p| p.get_name()).collect();
+                trf(
+                    "unknown_pairing",
+                    &[("value", &s), ("choices", &names.join(", "))],
+                )
+            })
+    }
+}
+
+pub struct TournamentOptions {
+    // 参加者の人数(人間のプレイヤーを含む)
+    pub players: usize,
+    pub tables: usize,
+    pub rounds: usize,
+    pub pairing: Pairing,
+    // 人間のプレイヤーの人数
+    pub humans: usize,
+    // 人間以外の参加者を等分して先頭から順に割り当てるNPCの戦略
+    pub npcs: Vec<Strategy>,
+    pub seed: u64,
+    pub color: bool,
+    pub ascii: bool,
+}
+
+impl TournamentOptions {
+    // 1つのテーブルに座る人数(参加者をテーブルに均等に分けられなければエラー)
+    pub fn get_table_size(&self) -> Result<usize, String> {
+        if self.humans > self.players {
+            return Err(trf("too_many_humans", &[("players", &self.players)]));
+        }
+        if self.tables == 0 || !self.players.is_multiple_of(self.tables) {
+            return Err(trf(
+                "uneven_tables",
+                &[("players", &self.players), ("tables", &self.tables)],
+            ));
+        }
+        let size = self.players / self.tables;
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&size) {
+            return Err(trf(
+                "invalid_table_size",
+                &[
+                    ("min", &MIN_PLAYERS),
+                    ("max", &MAX_PLAYERS),
+                    ("size", &size),
+                ],
+            ));
+        }
+        Ok(size)
+    }
+}
+
+// 参加者毎の通算成績
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    // 順位に応じた得点の合計(大富豪が最も多く、大貧民は0点)
+    pub points: usize,
+    // ラウンド毎の順位(0が大富豪)
+    pub places: Vec<usize>,
+    pub rating: f64,
+}
+
+impl Standing {
+    pub fn get_wins(&self) -> usize {
+        self.places.iter().filter(|place| **place == 0).count()
+    }
+
+    pub fn get_average_place(&self) -> f64 {
+        self.places.iter().sum::<usize>() as f64 / self.places.len().max(1) as f64 + 1.0
+    }
+}
+
+// 1つのテーブルの結果(roundとtableは0から数える)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableResult {
+    pub round: usize,
+    pub table: usize,
+    // 上がった順に並べた名前
+    pub ranking: Vec<String>,
+}
+
+pub struct Tournament {
+    standings: Vec<Standing>,
+}
+
+impl Tournament {
+    pub fn new(names: Vec<String>) -> Self {
+        Self {
+            standings: names
+                .into_iter()
+                .map(|name| Standing {
+                    name,
+                    points: 0,
+                    places: vec![],
+                    rating: INITIAL_RATING,
+                })
+                .collect(),
+        }
+    }
+
+    // テーブルの結果を加える(seatsは席に座った参加者、player_rankは上がった順の席)
+    pub fn add_result(&mut self, seats: &[usize], player_rank: &[usize]) {
+        let ratings: Vec<f64> = seats.iter().map(|p| self.standings[*p].rating).collect();
+        let ratings = update_ratings(&ratings, player_rank);
+        for (seat, p) in seats.iter().enumerate() {
+            let standing = &mut self.standings[*p];
+            let place = player_rank
+                .iter()
+                .position(|idx| *idx == seat)
+                .unwrap_or(seats.len() - 1);
+            standing.points += seats.len() - 1 - place;
+            standing.places.push(place);
+            standing.rating = ratings[seat];
+        }
+    }
+
+    // 得点の多い順(同点なら平均順位の良い順)に並べた参加者の番号
+    pub fn get_order(&self) -> Vec<usize> {
+        (0..self.standings.len())
+            .sorted_by(|a, b| {
+                let (a, b) = (&self.standings[*a], &self.standings[*b]);
+                b.points
+                    .cmp(&a.points)
+                    .then(a.get_average_place().total_cmp(&b.get_average_place()))
+            })
+            .collect()
+    }
+
+    pub fn get_leaderboard(&self) -> Vec<&Standing> {
+        self.get_order()
+            .into_iter()
+            .map(|p| &self.standings[p])
+            .collect()
+    }
+
+    pub fn get_lines(&self) -> Vec<String> {
+        let mut lines = vec![tr("tournament_header").to_owned()];
+        for (rank, standing) in self.get_leaderboard().into_iter().enumerate() {
+            lines.push(format!(
+                "{:>4}  {:<10} {:>6} {:>4} {:>9.2} {:>13.1}",
+                rank + 1,
+                standing.name,
+                standing.points,
+                standing.get_wins(),
+                standing.get_average_place(),
+                standing.rating
+            ));
+        }
+        lines
+    }
+}
+
+// ラウンド毎に各テーブルに座る参加者を求める
+// 最初の参加者を固定して残りを1つずつずらし(サークル方式)、並んだ順にテーブルへ交互に割り振る
+pub fn get_tables(players: usize, tables: usize, round: usize) -> Vec<Vec<usize>> {
+    let mut order = vec![0; players];
+    for p in 1..players {
+        order[1 + (p - 1 + round) % (players - 1)] = p;
+    }
+    (0..tables)
+        .map(|table| order.iter().copied().skip(table).step_by(tables).collect())
+        .collect()
+}
+
+// スイス式で各テーブルに座る参加者を求める(orderは成績の良い順に並べた参加者)
+pub fn get_swiss_tables(order: &[usize], tables: usize) -> Vec<Vec<usize>> {
+    order
+        .chunks(order.len() / tables)
+        .map(<[usize]>::to_vec)
+        .collect()
+}
+
+// 参加者を作る(人間のプレイヤーが先)
+// NPCは戦略毎にまとめて並べ、最初のラウンドから違う戦略と同じテーブルに座るようにする
+fn create_entrants(options: &TournamentOptions, style: Style) -> Vec<Box<dyn Player>> {
+    let hotseat = options.humans > 1;
+    let users = names::get_user_names(&["User".to_owned()], options.humans)
+        .into_iter()
+        .map(|name| Box::new(Pc::new(name, style, hotseat, None)) as Box<dyn Player>);
+    let npcs_count = options.players - options.humans;
+    let npcs = (options.humans..options.players).map(|i| {
+        let strategy = options.npcs[(i - options.humans) * options.npcs.len() / npcs_count];
+        let name = format!("{}{i}", strategy.get_name());
+        strategy.create_npc(name, options.seed.wrapping_mul(31).wrapping_add(i as u64))
+    });
+    users.chain(npcs).collect()
+}
+
+// 全てのラウンドを対戦し、最終的な成績を返す
+// 人間のプレイヤーが座るテーブルだけを画面に表示し、テーブル毎の結果はon_tableに渡す
+pub fn run_tournament(
+    options: &TournamentOptions,
+    on_table: &mut dyn FnMut(&TableResult),
+) -> Tournament {
+    let style = Style::new(options.color, options.ascii);
+    let mut entrants: Vec<Option<Box<dyn Player>>> = create_entrants(options, style)
+        .into_iter()
+        .map(Some)
+        .collect();
+    let mut tournament = Tournament::new(
+        entrants
+            .iter()
+            .flatten()
+            .map(|p| p.get_name().to_owned())
+            .collect(),
+    );
+    for round in 0..options.rounds {
+        // スイス式でもまだ成績がない最初のラウンドは並んだ順に振り分ける
+        let seatings = match options.pairing {
+            Pairing::Swiss if round > 0 => {
+                get_swiss_tables(&tournament.get_order(), options.tables)
+            }
+            _ => get_tables(options.players, options.tables, round),
+        };
+        for (table, seats) in seatings.into_iter().enumerate() {
+            let players: Vec<Box<dyn Player>> =
+                seats.iter().filter_map(|p| entrants[*p].take()).collect();
+            let seed = options
+                .seed
+                .wrapping_add((round * options.tables + table) as u64);
+            let mut game = Game::new(players, round % seats.len(), seed);
+            let player_rank = match seats.iter().any(|p| *p < options.humans) {
+                true => {
+                    let mut renderer = Renderer::new(style, 1.0, false);
+                    game.play_round(&mut renderer)
+                }
+                false => game.play_round(&mut Observers(vec![])),
+            };
+            if game.is_quit() {
+                return tournament;
+            }
+            on_table(&TableResult {
+                round,
+                table,
+                ranking: player_rank
+                    .iter()
+                    .map(|idx| game.get_names()[*idx].clone())
+                    .collect(),
+            });
+            tournament.add_result(&seats, &player_rank);
+            for (p, player) in seats.iter().zip(game.into_players()) {
+                entrants[*p] = Some(player);
+            }
+        }
+    }
+    tournament
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_table_size() {
+        for (players, tables, humans, expected) in [
+            (8, 2, 1, Ok(4)),
+            (10, 2, 0, Ok(5)),
+            (8, 3, 0, Err(())),
+            (8, 8, 0, Err(())),
+            (18, 2, 0, Err(())),
+            (4, 1, 5, Err(())),
+        ] {
+            let options = TournamentOptions {
+                players,
+                tables,
+                rounds: 1,
+                pairing: Pairing::Rotate,
+                humans,
+                npcs: vec![Strategy::Min],
+                seed: 0,
+                color: false,
+                ascii: true,
+            };
+            assert_eq!(options.get_table_size().map_err(|_| ()), expected);
+        }
+    }
+
+    #[test]
+    fn test_get_tables() {
+        assert_eq!(
+            get_tables(8, 2, 0),
+            vec![vec![0, 2, 4, 6], vec![1, 3, 5, 7]]
+        );
+        assert_eq!(
+            get_tables(8, 2, 1),
+            vec![vec![0, 1, 3, 5], vec![7, 2, 4, 6]]
+        );
+        // どのラウンドでも全員がちょうど1つのテーブルに座る
+        for round in 0..10 {
+            let tables = get_tables(9, 3, round);
+            assert!(tables.iter().all(|seats| seats.len() == 3));
+            let mut all: Vec<usize> = tables.concat();
+            all.sort();
+            assert_eq!(all, (0..9).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_get_swiss_tables() {
+        assert_eq!(
+            get_swiss_tables(&[5, 0, 3, 1, 4, 2], 2),
+            vec![vec![5, 0, 3], vec![1, 4, 2]]
+        );
+        assert_eq!(get_swiss_tables(&[1, 0], 1), vec![vec![1, 0]]);
+    }
+
+    #[test]
+    fn test_parse_pairing() {
+        for pairing in Pairing::ALL {
+            assert_eq!(pairing.get_name().parse::<Pairing>(), Ok(pairing));
+        }
+        assert!("knockout".parse::<Pairing>().is_err());
+    }
+
+    #[test]
+    fn test_add_result() {
+        let names = ["A", "B", "C", "D", "E"].map(String::from).to_vec();
+        let mut tournament = Tournament::new(names);
+        tournament.add_result(&[4, 1, 2], &[2, 0, 1]);
+        tournament.add_result(&[0, 3], &[1, 0]);
+        let leaderboard: Vec<(&str, usize)> = tournament
+            .get_leaderboard()
+            .iter()
+            .map(|s| (s.name.as_str(), s.points))
+            .collect();
+        assert_eq!(
+            leaderboard,
+            vec![("C", 2), ("D", 1), ("E", 1), ("A", 0), ("B", 0)]
+        );
+        let standings = tournament.get_leaderboard();
+        assert_eq!(standings[0].get_wins(), 1);
+        assert!(standings[0].rating > INITIAL_RATING);
+        assert_eq!(standings[4].get_average_place(), 3.0);
+        assert_eq!(tournament.get_order(), vec![2, 3, 4, 0, 1]);
+    }
+
+    #[test]
+    fn test_run_tournament() {
+        for pairing in Pairing::ALL {
+            let options = TournamentOptions {
+                players: 6,
+                tables: 2,
+                rounds: 3,
+                pairing,
+                humans: 0,
+                npcs: vec![Strategy::Min, Strategy::Heuristic],
+                seed: 1,
+                color: false,
+                ascii: true,
+            };
+            let mut results = vec![];
+            let tournament = run_tournament(&options, &mut |result| results.push(result.clone()));
+            assert_eq!(results.len(), 3 * 2);
+            assert!(results.iter().all(|result| result.ranking.len() == 3));
+            let leaderboard = tournament.get_leaderboard();
+            assert_eq!(leaderboard.len(), 6);
+            assert!(leaderboard.iter().all(|s| s.places.len() == 3));
+            // 1ラウンドの得点の合計はテーブル毎に0+1+2
+            let points: usize = leaderboard.iter().map(|s| s.points).sum();
+            assert_eq!(points, 3 * 2 * 3);
+            assert_eq!(tournament.get_lines().len(), 7);
+        }
+    }
+}
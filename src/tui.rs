@@ -0,0 +1,513 @@
+use crate::card::{Card, Order, Suit};
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::game::Game;
+use crate::i18n::{tr, trf};
+use crate::observer::Observer;
+use crate::pc::conver_to_comb;
+use crate::player::{take_from_hands, Action, Player};
+use crate::render;
+use crate::validator::Validator;
+use crate::view::GameView;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::cell::RefCell;
+use std::process;
+use std::rc::Rc;
+use std::time::Instant;
+
+// 画面に表示する内容
+struct Board {
+    style: render::Style,
+    names: Vec<String>,
+    hands_counts: Vec<usize>,
+    results: Vec<Option<String>>,
+    plays: Vec<Line<'static>>,
+    view: GameView,
+    hands: Vec<Card>,
+    cursor: usize,
+    selected: Vec<bool>,
+    message: String,
+}
+
+struct State {
+    terminal: DefaultTerminal,
+    board: Board,
+    speed: f64,
+}
+
+impl State {
+    fn draw(&mut self) {
+        let board = &self.board;
+        if self.terminal.draw(|frame| draw(frame, board)).is_err() {
+            quit();
+        }
+    }
+
+    fn wait(&mut self) {
+        // 待ち時間の間に速度変更と一時停止のキーを受け付ける
+        let deadline = Instant::now() + render::get_delay(self.speed);
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !event::poll(remaining).unwrap_or(false) {
+                break;
+            }
+            match read_key() {
+                KeyCode::Char(c @ ('+' | '-')) => {
+                    self.speed = render::change_speed(self.speed, &c.to_string());
+                    self.board.message = trf("speed", &[("speed", &self.speed)]);
+                    self.draw();
+                }
+                KeyCode::Char(' ') => {
+                    self.board.message = tr("tui_paused").to_owned();
+                    self.draw();
+                    while read_key() != KeyCode::Char(' ') {}
+                    self.board.message.clear();
+                    self.draw();
+                }
+                KeyCode::Char('q') | KeyCode::Esc => quit(),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Tui {
+    state: Rc<RefCell<State>>,
+}
+
+impl Tui {
+    pub fn new(style: render::Style, speed: f64) -> Self {
+        let board = Board {
+            style,
+            names: vec![],
+            hands_counts: vec![],
+            results: vec![],
+            plays: vec![],
+            view: GameView::default(),
+            hands: vec![],
+            cursor: 0,
+            selected: vec![],
+            message: String::new(),
+        };
+        let state = State {
+            terminal: ratatui::init(),
+            board,
+            speed,
+        };
+        Self {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    pub fn create_player(&self, name: String) -> TuiPlayer {
+        TuiPlayer {
+            name,
+            hands: vec![],
+            tui: self.clone(),
+        }
+    }
+
+    // roundsを指定すればそのラウンド数だけ遊んで終わる
+    pub fn run(&self, mut game: Game, rounds: Option<usize>) {
+        let mut observer = self.clone();
+        for round in 1.. {
+            let player_rank = game.play_round(&mut observer);
+            let again = match rounds {
+                Some(rounds) => round < rounds,
+                None => self.confirm(tr("play_again")),
+            };
+            if !again {
+                break;
+            }
+            game.next_round(&player_rank, &mut observer);
+        }
+        ratatui::restore();
+    }
+
+    fn set_hands(&self, hands: &[Card]) {
+        let board = &mut self.state.borrow_mut().board;
+        board.hands = hands.to_vec();
+        board.cursor = 0;
+        board.selected = vec![false; hands.len()];
+    }
+
+    fn set_message(&self, message: &str) {
+        self.state.borrow_mut().board.message = message.to_owned();
+    }
+
+    fn confirm(&self, message: &str) -> bool {
+        self.set_message(message);
+        let mut state = self.state.borrow_mut();
+        loop {
+            state.draw();
+            match read_key() {
+                KeyCode::Char('y') => return true,
+                KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => return false,
+                _ => {}
+            }
+        }
+    }
+
+    // メッセージを表示して何かキーが押されるまで待つ
+    fn acknowledge(&self, message: &str) {
+        self.set_message(message);
+        let mut state = self.state.borrow_mut();
+        state.draw();
+        read_key();
+    }
+
+    // 手札からカードを選択する、パスした場合はNoneを返す
+    fn select(&self, view: GameView, can_pass: bool) -> Option<Vec<usize>> {
+        let mut state = self.state.borrow_mut();
+        state.board.view = view;
+        loop {
+            state.draw();
+            let board = &mut state.board;
+            match read_key() {
+                KeyCode::Left | KeyCode::Char('h') => {
+                    board.cursor = board.cursor.saturating_sub(1);
+                }
+                KeyCode::Right | KeyCode::Char('l') if board.cursor + 1 < board.hands.len() => {
+                    board.cursor += 1;
+                }
+                KeyCode::Char(' ') => {
+                    if let Some(selected) = board.selected.get_mut(board.cursor) {
+                        *selected = !*selected;
+                    }
+                }
+                KeyCode::Enter => {
+                    let indices: Vec<usize> = (0..board.selected.len())
+                        .filter(|i| board.selected[*i])
+                        .collect();
+                    if !indices.is_empty() {
+                        return Some(indices);
+                    }
+                }
+                KeyCode::Char('p') => {
+                    if can_pass {
+                        return None;
+                    }
+                    board.message = tr("cannot_pass").to_owned();
+                }
+                KeyCode::Char('q') | KeyCode::Esc => quit(),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Observer for Tui {
+    fn notify(&mut self, event: &GameEvent) {
+        let mut state = self.state.borrow_mut();
+        let board = &mut state.board;
+        match event {
+            GameEvent::Start { names, hands } => {
+                board.names = names.clone();
+                board.hands_counts = hands.iter().map(Vec::len).collect();
+                board.results = vec![None; names.len()];
+                board.plays.clear();
+                board.view = GameView::default();
+                board.message.clear();
+            }
+            GameEvent::Turn {
+                idx,
+                comb,
+                hands_count,
+                outcome,
+            } => {
+                board.hands_counts[*idx] = *hands_count;
+                let name = board.names[*idx].clone();
+                let line = match comb {
+                    Some(comb) => {
+                        let mut spans = vec![Span::raw(format!("{name}: "))];
+                        spans.extend(comb_spans(&board.style, comb));
+                        Line::from(spans)
+                    }
+                    None => Line::from(format!("{name}: {}", tr("pass"))).dim(),
+                };
+                board.plays.push(line);
+                for effect in &outcome.effects {
+                    let text = render::get_effect_text(effect);
+                    board.plays.push(Line::from(text).bold().yellow());
+                }
+                if let Some(finish) = outcome.finish {
+                    let key = if finish.foul { "tui_foul" } else { "tui_out" };
+                    board.results[*idx] = Some(tr(key).to_owned());
+                }
+                if let Some(fallen) = outcome.get_fallen() {
+                    board.results[fallen] = Some(tr("capital_fall").to_owned());
+                }
+                state.draw();
+                state.wait();
+            }
+            GameEvent::Lead { idx } => {
+                let line = render::get_lead_line(&board.names[*idx]);
+                board.plays.push(Line::from(line).dim());
+            }
+            GameEvent::End { player_rank } => {
+                for (i, idx) in player_rank.iter().enumerate() {
+                    board.results[*idx] = Some(trf("tui_place", &[("place", &(i + 1))]));
+                }
+                board.plays.push(Line::from(tr("results")).bold());
+                state.draw();
+            }
+            GameEvent::Tribute { from, to, cards } => {
+                let text = get_exchange_text("tribute", &board.names, *from, *to, cards.len());
+                board.plays.push(Line::from(text));
+            }
+            GameEvent::Return { from, to, cards } => {
+                let text = get_exchange_text("return", &board.names, *from, *to, cards.len());
+                board.plays.push(Line::from(text));
+            }
+            GameEvent::Reject { error, .. } => {
+                board.message = tr(error.get_key()).to_owned();
+            }
+            GameEvent::Exchange => {
+                board.plays.push(Line::from(tr("exchange")));
+            }
+            // TUIのプレイヤーは保存も中断もしない(qキーですぐに終了する)
+            GameEvent::Reason { .. }
+            | GameEvent::Quit { .. }
+            | GameEvent::Substitute { .. }
+            | GameEvent::Undo { .. }
+            | GameEvent::Save { .. } => {}
+        }
+    }
+}
+
+pub struct TuiPlayer {
+    name: String,
+    hands: Vec<Card>,
+    tui: Tui,
+}
+
+impl TuiPlayer {
+    fn remove_hands(&mut self, indices: &[usize]) {
+        // 手札からカードを除く
+        for i in indices.iter().rev() {
+            self.hands.remove(*i);
+        }
+        self.tui.set_hands(&self.hands);
+    }
+}
+
+impl Player for TuiPlayer {
+    fn init(&mut self, hands: Vec<Card>) {
+        self.hands = hands;
+        self.tui.set_hands(&self.hands);
+    }
+
+    fn count_hands(&self) -> usize {
+        self.hands.len()
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_hands(&self) -> &[Card] {
+        &self.hands
+    }
+
+    fn take_cards(&mut self, cards: &[Card]) -> Vec<Card> {
+        take_from_hands(&mut self.hands, cards)
+    }
+
+    fn receive_cards(&mut self, cards: Vec<Card>) {
+        self.hands.extend(cards);
+    }
+
+    fn sort_hands(&mut self, order: Order) {
+        self.hands.sort_by(order.get_comparator());
+        self.tui.set_hands(&self.hands);
+    }
+
+    fn play(&mut self, validator: &dyn Validator) -> Action {
+        let view = validator.get_view();
+        let can_pass = validator.can_pass();
+        self.tui.set_hands(&self.hands);
+        self.tui.set_message(tr("your_turn"));
+        loop {
+            let Some(indices) = self.tui.select(view.clone(), can_pass) else {
+                return Action::Move(None);
+            };
+            let cards = indices.iter().map(|i| self.hands[*i]).collect();
+            match conver_to_comb(cards, view.rules.min_seq) {
+                Ok(comb) if validator.is_valid(&comb) => {
+                    self.remove_hands(&indices);
+                    self.tui.set_message("");
+                    return Action::Move(Some(comb));
+                }
+                _ => self.tui.set_message(tr("invalid_comb")),
+            }
+        }
+    }
+
+    fn confirm_tribute(&mut self, cards: &[Card]) {
+        self.tui.set_hands(&self.hands);
+        let style = self.tui.state.borrow().board.style;
+        let cards: Vec<String> = cards.iter().map(|card| style.card(card)).collect();
+        let cards = cards.join(" ");
+        self.tui
+            .acknowledge(&trf("tui_confirm_tribute", &[("cards", &cards)]));
+        self.tui.set_message("");
+    }
+
+    fn get_needless_cards(&mut self, cards_count: usize) -> Vec<Card> {
+        self.tui.set_hands(&self.hands);
+        self.tui
+            .set_message(&trf("tui_needless_cards", &[("count", &cards_count)]));
+        loop {
+            let indices = self
+                .tui
+                .select(GameView::default(), false)
+                .unwrap_or_default();
+            if indices.len() == cards_count {
+                let cards = indices.iter().map(|i| self.hands[*i]).collect();
+                self.remove_hands(&indices);
+                self.tui.set_message("");
+                return cards;
+            }
+        }
+    }
+}
+
+fn get_exchange_text(
+    key: &'static str,
+    names: &[String],
+    from: usize,
+    to: usize,
+    count: usize,
+) -> String {
+    trf(
+        key,
+        &[
+            ("from", &names[from]),
+            ("to", &names[to]),
+            ("count", &count),
+        ],
+    )
+}
+
+fn read_key() -> KeyCode {
+    loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => return key.code,
+            Ok(_) => {}
+            Err(_) => quit(),
+        }
+    }
+}
+
+fn quit() -> ! {
+    ratatui::restore();
+    process::exit(0);
+}
+
+fn card_span(style: &render::Style, card: &Card) -> Span<'static> {
+    let text = style.card_text(card);
+    match card {
+        Card::Normal(Suit::Heart | Suit::Diamond, _) => Span::styled(text, Style::new().red()),
+        Card::Normal(_, _) => Span::raw(text),
+        Card::Joker => Span::styled(text, Style::new().magenta()),
+    }
+}
+
+fn comb_spans(style: &render::Style, comb: &Comb) -> Vec<Span<'static>> {
+    let cards = match comb {
+        Comb::Single(card) => std::slice::from_ref(card),
+        Comb::Multi(cards) | Comb::Seq(cards) => cards.as_slice(),
+    };
+    cards
+        .iter()
+        .enumerate()
+        .flat_map(|(i, card)| {
+            let sep = Span::raw(if i == 0 { "" } else { " " });
+            [sep, card_span(style, card)]
+        })
+        .collect()
+}
+
+fn draw(frame: &mut Frame, board: &Board) {
+    let [top, hand, message] = Layout::vertical([
+        Constraint::Min(8),
+        Constraint::Length(4),
+        Constraint::Length(3),
+    ])
+    .areas(frame.area());
+    let [field, sidebar] =
+        Layout::horizontal([Constraint::Min(30), Constraint::Length(28)]).areas(top);
+    // 場のパネル
+    let view = &board.view;
+    let bind = match &view.bind_suits {
+        Some(suits) => suits.iter().map(|s| board.style.suit_text(s)).collect(),
+        None => vec![tr("none").to_owned()],
+    };
+    let rev = tr(if view.is_rev { "yes" } else { "none" });
+    let title = trf("tui_field", &[("bind", &bind.join(" ")), ("rev", &rev)]);
+    let rows = field.height.saturating_sub(2) as usize;
+    let plays: Vec<Line> = board
+        .plays
+        .iter()
+        .skip(board.plays.len().saturating_sub(rows))
+        .cloned()
+        .collect();
+    frame.render_widget(
+        Paragraph::new(plays).block(Block::bordered().title(title)),
+        field,
+    );
+    // 順位のパネル
+    let standings: Vec<Line> = board
+        .names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let count = board.hands_counts.get(i).copied().unwrap_or_default();
+            match board.results.get(i).cloned().flatten() {
+                Some(result) => Line::from(format!("{name} [{count:2}] {result}")).dim(),
+                None => Line::from(format!("{name} [{count:2}]")),
+            }
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(standings).block(Block::bordered().title(tr("tui_players"))),
+        sidebar,
+    );
+    // 手札のパネル
+    let cards: Vec<Span> = board
+        .hands
+        .iter()
+        .enumerate()
+        .flat_map(|(i, card)| {
+            let mut span = card_span(&board.style, card);
+            if board.selected.get(i) == Some(&true) {
+                span = span.bold().underlined().on_dark_gray();
+            }
+            if i == board.cursor {
+                span = span.reversed();
+            }
+            [span, Span::raw(" ")]
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(Line::from(cards))
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(tr("tui_hands"))),
+        hand,
+    );
+    // メッセージ
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(board.message.clone()).fg(Color::Cyan),
+            Line::from(tr("tui_help")).dim(),
+        ])
+        .block(Block::bordered()),
+        message,
+    );
+}
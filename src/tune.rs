@@ -0,0 +1,188 @@
+use crate::game::Game;
+use crate::heuristic::{HeuristicNpc, Weights};
+use crate::observer::Observers;
+use crate::player::Player;
+use crate::PLAYERS_COUNT;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::cmp::Ordering;
+
+// 突然変異で重みに加える値の幅
+const MUTATION: f64 = 0.5;
+
+pub struct TuneOptions {
+    pub generations: usize,
+    // 1世代の個体数
+    pub population: usize,
+    // 1つの個体を評価するゲーム数
+    pub games: usize,
+    pub seed: u64,
+    // 最良の重みを設定ファイルに書き込む
+    pub write: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generation {
+    pub best: Weights,
+    // 最良の個体の平均得点(1位を1、最下位を0とする)
+    pub fitness: f64,
+}
+
+impl From<&Generation> for String {
+    fn from(generation: &Generation) -> Self {
+        let w = &generation.best;
+        format!(
+            "{:.3}  count={:.2} strength={:.2} joker={:.2} split={:.2} pass={:.2} bind={:.2} eight={:.2}",
+            generation.fitness,
+            w.count,
+            w.strength,
+            w.joker,
+            w.split,
+            w.pass,
+            w.bind,
+            w.eight
+        )
+    }
+}
+
+impl From<&Weights> for [f64; 7] {
+    fn from(w: &Weights) -> Self {
+        [
+            w.count, w.strength, w.joker, w.split, w.pass, w.bind, w.eight,
+        ]
+    }
+}
+
+impl From<[f64; 7]> for Weights {
+    fn from([count, strength, joker, split, pass, bind, eight]: [f64; 7]) -> Self {
+        Self {
+            count,
+            strength,
+            joker,
+            split,
+            pass,
+            bind,
+            eight,
+        }
+    }
+}
+
+// 既定の重みのNPCと2人ずつ交互に座って対戦させ、重みを進化させる
+pub fn run_tune(options: &TuneOptions, on_generation: &mut dyn FnMut(&Generation)) -> Generation {
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    let mut population: Vec<Weights> = (0..options.population)
+        .map(|i| match i {
+            0 => Weights::default(),
+            _ => mutate(&Weights::default(), &mut rng),
+        })
+        .collect();
+    let mut best: Option<Generation> = None;
+    for generation in 0..options.generations {
+        // 同じ世代の個体は同じ配り方で評価する
+        let seed = options
+            .seed
+            .wrapping_add((generation * options.games) as u64);
+        let mut ranked: Vec<(f64, Weights)> = population
+            .iter()
+            .map(|weights| (evaluate(weights, options.games, seed), *weights))
+            .collect();
+        ranked.sort_by(|(f1, _), (f2, _)| f2.partial_cmp(f1).unwrap_or(Ordering::Equal));
+        let current = Generation {
+            best: ranked[0].1,
+            fitness: ranked[0].0,
+        };
+        on_generation(&current);
+        if best
+            .as_ref()
+            .is_none_or(|best| current.fitness > best.fitness)
+        {
+            best = Some(current);
+        }
+        // 上位半分を残し、残りを交叉と突然変異で作る
+        let elites: Vec<Weights> = ranked
+            .into_iter()
+            .take(options.population.div_ceil(2))
+            .map(|(_, weights)| weights)
+            .collect();
+        population = elites.clone();
+        while population.len() < options.population {
+            let first = elites.choose(&mut rng).unwrap();
+            let second = elites.choose(&mut rng).unwrap();
+            let child = crossover(first, second, &mut rng);
+            population.push(mutate(&child, &mut rng));
+        }
+    }
+    best.unwrap_or(Generation {
+        best: Weights::default(),
+        fitness: 0.0,
+    })
+}
+
+// 重みのNPCが席を変えながらgamesゲーム対戦したときの平均得点
+fn evaluate(weights: &Weights, games: usize, seed: u64) -> f64 {
+    let mut total = 0.0;
+    for game in 0..games {
+        let players: Vec<Box<dyn Player>> = (0..PLAYERS_COUNT)
+            .map(|i| {
+                let weights = match i % 2 {
+                    0 => *weights,
+                    _ => Weights::default(),
+                };
+                Box::new(HeuristicNpc::new(format!("heuristic{i}"), weights)) as Box<dyn Player>
+            })
+            .collect();
+        let seed = seed.wrapping_add(game as u64);
+        let player_rank =
+            Game::new(players, game % PLAYERS_COUNT, seed).play_round(&mut Observers(vec![]));
+        total += player_rank
+            .iter()
+            .enumerate()
+            .filter(|(_, idx)| *idx % 2 == 0)
+            .map(|(place, _)| (PLAYERS_COUNT - 1 - place) as f64 / (PLAYERS_COUNT - 1) as f64)
+            .sum::<f64>()
+            / 2.0;
+    }
+    total / games.max(1) as f64
+}
+
+// 重みを1つずつ2つの親のどちらかから受け継ぐ
+fn crossover(first: &Weights, second: &Weights, rng: &mut ChaCha8Rng) -> Weights {
+    let (first, second) = (<[f64; 7]>::from(first), <[f64; 7]>::from(second));
+    Weights::from(std::array::from_fn(|i| match rng.gen_bool(0.5) {
+        true => first[i],
+        false => second[i],
+    }))
+}
+
+fn mutate(weights: &Weights, rng: &mut ChaCha8Rng) -> Weights {
+    Weights::from(<[f64; 7]>::from(weights).map(|w| w + rng.gen_range(-MUTATION..MUTATION)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_run_tune() {
+        let weights = Weights::default();
+        assert_eq!(Weights::from(<[f64; 7]>::from(&weights)), weights);
+        assert!((0.0..=1.0).contains(&evaluate(&weights, 4, 0)));
+        let options = TuneOptions {
+            generations: 2,
+            population: 3,
+            games: 2,
+            seed: 0,
+            write: false,
+        };
+        let mut generations = vec![];
+        let best = run_tune(&options, &mut |generation| {
+            generations.push(generation.clone())
+        });
+        assert_eq!(generations.len(), 2);
+        assert!(generations.iter().all(|g| (0.0..=1.0).contains(&g.fitness)));
+        assert!(generations.contains(&best));
+        // 同じシードなら同じ結果になる
+        assert_eq!(run_tune(&options, &mut |_| {}), best);
+    }
+}
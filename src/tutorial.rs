@@ -0,0 +1,216 @@
+use crate::card::{cmp_order, create_deck, parse_cards, Card};
+use crate::comb::Comb;
+use crate::event::GameEvent;
+use crate::field::{Effect, Field};
+use crate::game::Game;
+use crate::i18n::{tr, trf};
+use crate::input::get_input;
+use crate::observer::{Observer, Observers};
+use crate::pc::Pc;
+use crate::player::Player;
+use crate::render::{Renderer, Style};
+use crate::state::{GameState, PlayerState};
+use crate::strategy::Strategy;
+use crate::{NPC_NAMES, PLAYERS_COUNT};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// 説明する組み合わせやルールが揃った人間のプレイヤーの手札
+// (4のペア、6の革命、♥9から♥Jの階段、8切り)
+const USER_HANDS: &str = "♣3 ♦4 ♥4 ♣6 ♦6 ♥6 ♠6 ♣8 ♥9 ♥10 ♥J ♠Q ♦K";
+
+// 決まった配り方(残りのカードは順にNPCに配る)
+pub fn get_tutorial_deals() -> Vec<Vec<Card>> {
+    let user = parse_cards(USER_HANDS).unwrap();
+    let mut deals = vec![vec![]; PLAYERS_COUNT];
+    deals[0] = user.clone();
+    create_deck()
+        .into_iter()
+        .filter(|card| !user.contains(card))
+        .enumerate()
+        .for_each(|(i, card)| deals[1 + i % (PLAYERS_COUNT - 1)].push(card));
+    deals.iter_mut().for_each(|d| d.sort_by(cmp_order));
+    deals
+}
+
+// 人間のプレイヤーが最初の手番になるゲーム(NPCは最も弱いカードから出す)
+pub fn create_tutorial_game<F>(create_user: F) -> Game
+where
+    F: FnMut(String) -> Box<dyn Player>,
+{
+    let deals = get_tutorial_deals();
+    let names: Vec<String> = ["User"]
+        .iter()
+        .chain(&NPC_NAMES)
+        .map(|name| name.to_string())
+        .collect();
+    let mut field = Field::new(PLAYERS_COUNT, 0);
+    field.set_players(names.clone(), deals.iter().map(Vec::len).collect());
+    let players = names
+        .into_iter()
+        .zip(deals)
+        .enumerate()
+        .map(|(i, (name, hands))| PlayerState {
+            name,
+            strategy: (i > 0).then_some(Strategy::Min),
+            hands,
+        })
+        .collect();
+    let state = GameState {
+        players,
+        field,
+        rng: ChaCha8Rng::seed_from_u64(0),
+        dealer: 0,
+    };
+    Game::from_state(state, create_user)
+}
+
+// 場で初めて起きたことを説明する
+pub struct Guide {
+    style: Style,
+    shown: Vec<&'static str>,
+}
+
+impl Guide {
+    pub fn new(style: Style) -> Self {
+        Self {
+            style,
+            shown: vec![],
+        }
+    }
+
+    // まだ説明していないことの説明のキー
+    pub fn get_tips(&mut self, event: &GameEvent) -> Vec<&'static str> {
+        let keys = match event {
+            GameEvent::Start { .. } => vec!["tutorial_intro", "tutorial_hands"],
+            GameEvent::Turn { comb, outcome, .. } => {
+                let mut keys = vec![match comb {
+                    None => "tutorial_pass",
+                    Some(Comb::Single(_)) => "tutorial_single",
+                    Some(Comb::Multi(_)) => "tutorial_multi",
+                    Some(Comb::Seq(_)) => "tutorial_seq",
+                }];
+                for effect in &outcome.effects {
+                    match effect {
+                        Effect::EightCut => keys.push("tutorial_eight"),
+                        Effect::Bind => keys.push("tutorial_bind"),
+                        Effect::Revolution | Effect::SuperRevolution => keys.push("tutorial_rev"),
+                        _ => {}
+                    }
+                }
+                if let Some(finish) = outcome.finish {
+                    keys.push(if finish.foul {
+                        "tutorial_foul"
+                    } else {
+                        "tutorial_out"
+                    });
+                }
+                keys
+            }
+            GameEvent::Lead { .. } => vec!["tutorial_lead"],
+            GameEvent::End { .. } => vec!["tutorial_end"],
+            _ => vec![],
+        };
+        let keys: Vec<&'static str> = keys
+            .into_iter()
+            .filter(|key| !self.shown.contains(key))
+            .collect();
+        self.shown.extend(&keys);
+        keys
+    }
+}
+
+impl Observer for Guide {
+    fn notify(&mut self, event: &GameEvent) {
+        let tips = self.get_tips(event);
+        for key in &tips {
+            println!("{}", self.style.bold(tr("tutorial_tip")));
+            println!("{}", tr(key));
+        }
+        // 説明を読んでから進める
+        if !tips.is_empty() && !matches!(event, GameEvent::End { .. }) {
+            get_input(self.style.dim(tr("tutorial_continue")));
+        }
+    }
+}
+
+// 決まった配り方で1ラウンドを遊びながらルールを説明する
+pub fn run_tutorial(style: Style) {
+    let mut game = create_tutorial_game(|name| Box::new(Pc::new(name, style, false, None)));
+    let mut renderer = Renderer::new(style, 1.0, false);
+    let mut guide = Guide::new(style);
+    game.play_round(&mut Observers(vec![&mut renderer, &mut guide]));
+    if game.is_quit() {
+        return;
+    }
+    println!("{}", trf("tutorial_next", &[("command", &"daifugo")]));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::field::{Finish, TurnOutcome};
+    use crate::npc::MinNpc;
+
+    #[test]
+    fn test_get_tutorial_deals() {
+        let deals = get_tutorial_deals();
+        assert_eq!(
+            deals.iter().map(Vec::len).collect::<Vec<_>>(),
+            [13, 14, 13, 13]
+        );
+        let mut cards: Vec<Card> = deals.concat();
+        cards.sort_by(cmp_order);
+        let mut deck = create_deck();
+        deck.sort_by(cmp_order);
+        assert_eq!(cards, deck);
+        assert_eq!(deals[0], parse_cards(USER_HANDS).unwrap());
+    }
+
+    #[test]
+    fn test_get_tips() {
+        let mut guide = Guide::new(Style::default());
+        let pass = GameEvent::Turn {
+            idx: 1,
+            comb: None,
+            hands_count: 5,
+            outcome: TurnOutcome::default(),
+        };
+        let eight = GameEvent::Turn {
+            idx: 0,
+            comb: Some(Comb::Single(parse_cards("♣8").unwrap()[0])),
+            hands_count: 0,
+            outcome: TurnOutcome {
+                effects: vec![Effect::EightCut],
+                finish: Some(Finish {
+                    idx: 0,
+                    place: 3,
+                    foul: true,
+                }),
+                cleared: false,
+            },
+        };
+        for (event, expected) in [
+            (&pass, vec!["tutorial_pass"]),
+            // 同じことは2回説明しない
+            (&pass, vec![]),
+            (
+                &eight,
+                vec!["tutorial_single", "tutorial_eight", "tutorial_foul"],
+            ),
+            (&GameEvent::Lead { idx: 0 }, vec!["tutorial_lead"]),
+            (&GameEvent::Exchange, vec![]),
+        ] {
+            assert_eq!(guide.get_tips(event), expected);
+        }
+    }
+
+    #[test]
+    fn test_create_tutorial_game() {
+        let mut game = create_tutorial_game(|name| Box::new(MinNpc::new(name)));
+        assert_eq!(game.get_names()[0], "User");
+        assert_eq!(game.get_field().get_idx(), 0);
+        let player_rank = game.play_round(&mut Observers(vec![]));
+        assert_eq!(player_rank.len(), PLAYERS_COUNT);
+    }
+}
@@ -1,6 +1,24 @@
 use crate::comb::Comb;
+use crate::rules::RuleSet;
+use crate::view::GameView;
 
 pub trait Validator {
     fn get_prev_comb(&self) -> Option<&Comb>;
     fn is_valid(&self, comb: &Comb) -> bool;
+    fn get_view(&self) -> GameView;
+
+    // パスできるか(場にカードがなければできない)
+    fn can_pass(&self) -> bool {
+        self.get_prev_comb().is_some()
+    }
+
+    // 場で適用されているルール
+    fn get_rules(&self) -> RuleSet {
+        self.get_view().rules
+    }
+
+    // このラウンドで場に出された組み合わせと出したプレイヤーの席(わからなければ空)
+    fn get_history(&self) -> &[(usize, Comb)] {
+        &[]
+    }
 }
@@ -0,0 +1,221 @@
+use crate::card::{cmp_order, Card};
+use crate::game::{
+    get_cut_winners, get_deals, get_diamond_holder, get_exchange_pairs, get_strongest_cards,
+};
+use crate::i18n::{tr, trf};
+use crate::kifu::{cards_to_kifu, Kifu};
+use crate::replay::get_snapshots;
+use crate::rules::LeadRule;
+use itertools::Itertools;
+
+// 棋譜をエンジンで再生し直して、記録された順位や配られたカードが同じになるかを確かめる
+// (シードが書かれていれば配られたカードと交換も確かめる)
+pub fn verify_kifu(kifu: &Kifu) -> Result<Vec<String>, String> {
+    let players_counts: Vec<usize> = kifu.rounds.iter().map(|round| round.names.len()).collect();
+    let deals = kifu.seed.map(|seed| get_deals(seed, &players_counts));
+    let cuts = kifu.seed.map(|seed| get_cut_winners(seed, &players_counts));
+    let mut lines = vec![];
+    let mut dealer = None;
+    for (r, round) in kifu.rounds.iter().enumerate() {
+        let error = |mes: String| {
+            trf(
+                "verify_round_error",
+                &[("round", &(r + 1)), ("error", &mes)],
+            )
+        };
+        let snapshots = get_snapshots(round).map_err(error)?;
+        // 最初に手番になったプレイヤーがルールの通りか
+        let start_idx = round.moves.first().map(|(idx, _)| *idx);
+        // プレイヤーが入れ替わったラウンドは前のラウンドの順位から親と交換を確かめられない
+        let prev = r.checked_sub(1).map(|p| &kifu.rounds[p]);
+        let changed = prev.is_some_and(|prev| prev.names != round.names);
+        let prev_rank = prev.map(|prev| &prev.player_rank).filter(|_| !changed);
+        if let (Some(start_idx), Some(prev_dealer), Some(prev_rank)) =
+            (start_idx, dealer, prev_rank)
+        {
+            let expected = match round.rules.lead {
+                LeadRule::Loser => prev_rank.last().copied(),
+                LeadRule::Rotate => Some((prev_dealer + 1) % round.names.len()),
+                LeadRule::Winner => prev_rank.first().copied(),
+                LeadRule::Diamond => get_diamond_holder(round.deals.iter().map(Vec::as_slice))
+                    .or(prev_rank.last().copied()),
+                // シードがなければ山札を引いた結果を確かめられない
+                LeadRule::Cut => cuts.as_ref().map(|cuts| cuts[r]),
+            };
+            if expected.is_some_and(|expected| expected != start_idx) {
+                return Err(error(trf("wrong_start", &[("seat", &start_idx)])));
+            }
+        }
+        dealer = start_idx;
+        if let Some(deals) = deals.as_ref().filter(|_| !changed) {
+            let expected = match prev_rank {
+                Some(prev_rank) => get_exchanged_hands(&deals[r], &round.deals, prev_rank),
+                None => Some(deals[r].clone()),
+            };
+            check_deals(expected.as_deref(), &round.deals).map_err(error)?;
+        }
+        // 中断したラウンドには順位が書かれない
+        let last = &snapshots[snapshots.len() - 1];
+        if !round.player_rank.is_empty() && last.player_rank != round.player_rank {
+            let rank = |rank: &[usize]| rank.iter().map(|idx| format!("P{idx}")).join(" ");
+            return Err(error(trf(
+                "rank_mismatch",
+                &[
+                    ("kifu", &rank(&round.player_rank)),
+                    ("replay", &rank(&last.player_rank)),
+                ],
+            )));
+        }
+        lines.push(trf(
+            "round_verified",
+            &[("round", &(r + 1)), ("count", &round.moves.len())],
+        ));
+    }
+    lines.push(match kifu.seed {
+        Some(seed) => trf("verified_with_seed", &[("seed", &seed)]),
+        None => tr("verified_without_seed").to_owned(),
+    });
+    Ok(lines)
+}
+
+fn check_deals(expected: Option<&[Vec<Card>]>, recorded: &[Vec<Card>]) -> Result<(), String> {
+    let Some(expected) = expected else {
+        return Err(tr("exchange_mismatch").to_owned());
+    };
+    for (i, (expected, recorded)) in expected.iter().zip(recorded).enumerate() {
+        if get_sorted(expected) != get_sorted(recorded) {
+            return Err(trf(
+                "deal_mismatch",
+                &[
+                    ("seat", &i),
+                    ("kifu", &cards_to_kifu(&get_sorted(recorded))),
+                    ("replay", &cards_to_kifu(&get_sorted(expected))),
+                ],
+            ));
+        }
+    }
+    Ok(())
+}
+
+// 配られたカードから献上した後の手札を求める(返礼は棋譜の手札から逆算する)
+fn get_exchanged_hands(
+    dealt: &[Vec<Card>],
+    recorded: &[Vec<Card>],
+    prev_rank: &[usize],
+) -> Option<Vec<Vec<Card>>> {
+    if prev_rank.len() != dealt.len() || recorded.len() != dealt.len() {
+        return None;
+    }
+    let mut hands = dealt.to_vec();
+    for (winner, loser, count) in get_exchange_pairs(prev_rank) {
+        let tribute = get_strongest_cards(&dealt[loser], count);
+        let kept = subtract(&dealt[loser], &tribute)?;
+        let returns = subtract(&recorded[loser], &kept)?;
+        if returns.len() != count {
+            return None;
+        }
+        hands[winner] = subtract(&[dealt[winner].clone(), tribute].concat(), &returns)?;
+        hands[loser] = [kept, returns].concat();
+    }
+    Some(hands)
+}
+
+// cardsからremovedを1枚ずつ除く(含まれていないカードがあればNone)
+fn subtract(cards: &[Card], removed: &[Card]) -> Option<Vec<Card>> {
+    let mut rest = cards.to_vec();
+    for card in removed {
+        let pos = rest.iter().position(|c| c == card)?;
+        rest.remove(pos);
+    }
+    Some(rest)
+}
+
+fn get_sorted(cards: &[Card]) -> Vec<Card> {
+    let mut cards = cards.to_vec();
+    cards.sort_by(cmp_order);
+    cards
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::Game;
+    use crate::kifu::{parse_kifu, KifuWriter};
+    use crate::npc::MinNpc;
+    use crate::player::Player;
+    use crate::rules::{Preset, RuleSet};
+
+    fn record_kifu(seed: u64, rounds: usize, lead: LeadRule) -> String {
+        let players: Vec<Box<dyn Player>> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>)
+            .collect();
+        let rules = RuleSet {
+            lead,
+            ..Preset::Kanto.get_rules()
+        };
+        let mut game = Game::new(players, 0, seed).with_rules(rules);
+        let mut buf = vec![];
+        let mut writer = KifuWriter::new(&mut buf)
+            .with_seed(Some(seed))
+            .with_rules(rules);
+        for round in 1..=rounds {
+            let player_rank = game.play_round(&mut writer);
+            if round < rounds {
+                game.next_round(&player_rank, &mut writer);
+            }
+        }
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_verify_kifu() {
+        let text = record_kifu(3, 3, LeadRule::Loser);
+        let lines = verify_kifu(&parse_kifu(&text).unwrap()).unwrap();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3], "シード3で配られたカードと全ての手を再現しました");
+        // シードがなければ手だけを確かめる
+        let without_seed = text.replace("seed 3\n", "");
+        assert!(verify_kifu(&parse_kifu(&without_seed).unwrap()).is_ok());
+        // 違うシードでは配られたカードが一致しない
+        let other_seed = text.replace("seed 3\n", "seed 4\n");
+        let result = verify_kifu(&parse_kifu(&other_seed).unwrap());
+        assert!(result
+            .unwrap_err()
+            .starts_with("ラウンド1: P0に配られたカードが一致しません"));
+        // 書き換えた順位
+        let rank_line = text.lines().find(|line| line.starts_with("rank:")).unwrap();
+        let mut rank: Vec<&str> = rank_line["rank: ".len()..].split(' ').collect();
+        rank.swap(0, 1);
+        let tampered = text.replacen(rank_line, &format!("rank: {}", rank.join(" ")), 1);
+        let result = verify_kifu(&parse_kifu(&tampered).unwrap());
+        assert!(result.unwrap_err().starts_with("ラウンド1: "));
+        // 2ラウンド目以降の親のルール
+        for lead in LeadRule::ALL {
+            let text = record_kifu(7, 3, lead);
+            assert!(verify_kifu(&parse_kifu(&text).unwrap()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_get_exchanged_hands() {
+        let dealt = get_deals(5, &[4]).remove(0);
+        // 大貧民(P3)は最も強い2枚を大富豪(P0)に渡し、最も弱い2枚を受け取る
+        let mut recorded = dealt.clone();
+        let tribute = get_strongest_cards(&dealt[3], 2);
+        let returns = dealt[0][..2].to_vec();
+        recorded[3] = [subtract(&dealt[3], &tribute).unwrap(), returns.clone()].concat();
+        recorded[0] = [subtract(&dealt[0], &returns).unwrap(), tribute].concat();
+        // 貧民(P2)と富豪(P1)は1枚ずつ交換する
+        let tribute = get_strongest_cards(&dealt[2], 1);
+        let returns = dealt[1][..1].to_vec();
+        recorded[2] = [subtract(&dealt[2], &tribute).unwrap(), returns.clone()].concat();
+        recorded[1] = [subtract(&dealt[1], &returns).unwrap(), tribute].concat();
+        let hands = get_exchanged_hands(&dealt, &recorded, &[0, 1, 2, 3]).unwrap();
+        assert!(check_deals(Some(&hands), &recorded).is_ok());
+        // 最も強いカードを渡していない
+        recorded.swap(0, 3);
+        let hands = get_exchanged_hands(&dealt, &recorded, &[0, 1, 2, 3]);
+        assert!(hands.is_none_or(|hands| check_deals(Some(&hands), &recorded).is_err()));
+    }
+}
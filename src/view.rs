@@ -0,0 +1,44 @@
+use crate::card::Suit;
+use crate::comb::Comb;
+use crate::rules::RuleSet;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// プレイヤーに公開される場の状態
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameView {
+    pub idx: usize,
+    pub prev_comb: Option<Comb>,
+    pub bind_suits: Option<Vec<Suit>>,
+    pub is_rev: bool,
+    // 11バックで場が流れるまでカードの強さが逆転しているか
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub is_jack_back: bool,
+    pub players: Vec<(String, usize)>,
+    pub pass_count: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rules: RuleSet,
+    // 場のカードを出したプレイヤーの席
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lead: Option<usize>,
+    // 8返しで場の8を返せる状態か
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pending_eight: bool,
+    // 場のカードに対してパスしたプレイヤーの席(パスした順)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub passed: Vec<usize>,
+    // 席毎の順位(0が1位、まだ上がっていなければNone)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub places: Vec<Option<usize>>,
+}
+
+impl GameView {
+    // 場のカードにまだ出せるプレイヤーの席(パスしたプレイヤー、場のカードを出したプレイヤー、上がったプレイヤーを除く)
+    pub fn get_responders(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|idx| self.players[*idx].1 > 0)
+            .filter(|idx| !self.passed.contains(idx) && self.lead != Some(*idx))
+            .collect()
+    }
+}
@@ -0,0 +1,38 @@
+use crate::external::ExternalGame;
+use crate::strategy::Strategy;
+use wasm_bindgen::prelude::*;
+
+// ブラウザで人間1人とNPC3人が対戦するゲーム
+#[wasm_bindgen]
+pub struct WasmGame(ExternalGame);
+
+#[wasm_bindgen]
+impl WasmGame {
+    // 人間の席から出せる手の一覧(nullはパス)
+    pub fn legal_moves(&mut self) -> String {
+        serde_json::to_string(&self.0.get_legal_moves()).unwrap()
+    }
+
+    // 手(nullならパス)を出し、次の人間の手番までNPCの手番を進める
+    pub fn play(&mut self, comb_json: &str) -> Result<(), JsError> {
+        let comb = serde_json::from_str(comb_json)?;
+        self.0.play(comb).map_err(|message| JsError::new(&message))
+    }
+
+    pub fn state_json(&mut self) -> String {
+        let seat = self.0.get_seats()[0];
+        self.0.get_state_json(seat)
+    }
+
+    // ラウンドが終わっていればカードを配り直して次のラウンドを始める
+    pub fn next_round(&mut self) -> bool {
+        self.0.next_round()
+    }
+}
+
+// seedで配り方とNPCを決めてゲームを始める(npcはmin、heuristic、mcts)
+#[wasm_bindgen]
+pub fn new_game(seed: u32, npc: &str) -> Result<WasmGame, JsError> {
+    let npc = npc.parse::<Strategy>().map_err(|e| JsError::new(&e))?;
+    Ok(WasmGame(ExternalGame::new(seed.into(), 1, npc)))
+}
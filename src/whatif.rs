@@ -0,0 +1,150 @@
+use crate::game::Game;
+use crate::i18n::{tr, trf};
+use crate::input::get_input;
+use crate::kifu::{parse_kifu, Kifu};
+use crate::pc::Pc;
+use crate::player::Player;
+use crate::render::{Renderer, Style};
+use crate::replay::get_state;
+use crate::strategy::Strategy;
+use std::fs;
+use std::path::PathBuf;
+
+pub struct WhatIfOptions {
+    pub path: PathBuf,
+    // 遊び直すラウンド(1から数える)
+    pub round: usize,
+    // 棋譜の通りに進める手の数(0ならラウンドの最初から)
+    pub turn: usize,
+    // 人間のプレイヤーが操作する席
+    pub seat: usize,
+    // それ以外の席のNPCの戦略
+    pub npc: Strategy,
+    pub color: bool,
+    pub ascii: bool,
+}
+
+// 棋譜の途中の場面からゲームを作る
+// NPCの乱数は棋譜のシードから決めるので、同じ場面で同じ手を選べばNPCも同じ手を選ぶ
+pub fn create_game<F>(kifu: &Kifu, options: &WhatIfOptions, create_user: F) -> Result<Game, String>
+where
+    F: FnMut(String) -> Box<dyn Player>,
+{
+    let round = options
+        .round
+        .checked_sub(1)
+        .and_then(|r| kifu.rounds.get(r))
+        .ok_or_else(|| {
+            trf(
+                "no_round",
+                &[("round", &options.round), ("count", &kifu.rounds.len())],
+            )
+        })?;
+    let seed = kifu
+        .seed
+        .unwrap_or_default()
+        .wrapping_add(options.round as u64);
+    let state = get_state(round, options.turn, options.seat, options.npc, seed)?;
+    let mut game = Game::from_state(state, create_user);
+    game.reseed_players();
+    Ok(game)
+}
+
+// 棋譜の途中の場面から違う手を選んで遊び直す(何度でも同じ場面からやり直せる)
+pub fn run_whatif(options: &WhatIfOptions) -> Result<(), String> {
+    let text =
+        fs::read_to_string(&options.path).map_err(|e| trf("kifu_read_failed", &[("error", &e)]))?;
+    let kifu = parse_kifu(&text)?;
+    let style = Style::new(options.color, options.ascii);
+    let recorded = options
+        .round
+        .checked_sub(1)
+        .and_then(|r| kifu.rounds.get(r))
+        .map(|round| round.player_rank.clone())
+        .unwrap_or_default();
+    loop {
+        let mut game = create_game(&kifu, options, |name| {
+            Box::new(Pc::new(name, style, false, None))
+        })?;
+        println!(
+            "{}",
+            trf(
+                "whatif_start",
+                &[("round", &options.round), ("turn", &(options.turn + 1))]
+            )
+        );
+        let mut renderer = Renderer::new(style, 1.0, false);
+        let player_rank = game.play_round(&mut renderer);
+        if game.is_quit() {
+            return Ok(());
+        }
+        // 棋譜に順位が書かれていれば操作した席の順位を比べる
+        let place = |rank: &[usize]| rank.iter().position(|idx| *idx == options.seat);
+        let before = place(&recorded);
+        if let (Some(before), Some(after)) = (before, place(&player_rank)) {
+            println!(
+                "{}",
+                trf(
+                    "whatif_place",
+                    &[
+                        ("seat", &options.seat),
+                        ("before", &(before + 1)),
+                        ("after", &(after + 1)),
+                    ]
+                )
+            );
+        }
+        if get_input(tr("whatif_again").to_owned()) != "y" {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::kifu::KifuWriter;
+    use crate::npc::MinNpc;
+    use crate::observer::Observers;
+
+    fn record_kifu(seed: u64) -> Kifu {
+        let players: Vec<Box<dyn Player>> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|name| Box::new(MinNpc::new(name.to_string())) as Box<dyn Player>)
+            .collect();
+        let mut game = Game::new(players, 0, seed);
+        let mut buf = vec![];
+        let mut writer = KifuWriter::new(&mut buf).with_seed(Some(seed));
+        game.play_round(&mut writer);
+        parse_kifu(&String::from_utf8(buf).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_create_game() {
+        let kifu = record_kifu(5);
+        let mut options = WhatIfOptions {
+            path: PathBuf::new(),
+            round: 1,
+            turn: 6,
+            seat: 2,
+            npc: Strategy::Mcts,
+            color: false,
+            ascii: true,
+        };
+        let play = |options: &WhatIfOptions| {
+            let mut game = create_game(&kifu, options, |name| {
+                Box::new(MinNpc::new(name)) as Box<dyn Player>
+            })
+            .unwrap();
+            assert_eq!(game.get_field().get_idx(), kifu.rounds[0].moves[6].0);
+            game.play_round(&mut Observers(vec![]))
+        };
+        // 同じ場面から同じ手を選べば同じ結果になる
+        assert_eq!(play(&options), play(&options));
+        options.round = 2;
+        assert!(create_game(&kifu, &options, |_| unreachable!()).is_err());
+        options.round = 1;
+        options.turn = 1000;
+        assert!(create_game(&kifu, &options, |_| unreachable!()).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::bitcards::HandMask;
+use crate::card::{Card, SUITS};
+use crate::comb::Comb;
+use crate::view::GameView;
+use crate::PLAYERS_COUNT;
+
+// HandMaskのビットの数(52枚のカードと2枚分のジョーカー)
+const CARD_BITS: usize = 54;
+// 手札の枚数の最大(15以上は15とみなす)
+const MAX_COUNT: usize = 15;
+
+// splitmix64で作る乱数の列
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+struct ZobristKeys {
+    // 手番のプレイヤーの手札のカード
+    hands: [u64; CARD_BITS],
+    // 場のカードと組み合わせの種類
+    field: [u64; CARD_BITS],
+    kinds: [u64; 3],
+    binds: [u64; 4],
+    rev: u64,
+    // 席毎の手札の枚数
+    counts: [[u64; MAX_COUNT + 1]; PLAYERS_COUNT],
+    turns: [u64; PLAYERS_COUNT],
+    pass: u64,
+}
+
+static KEYS: ZobristKeys = build_keys();
+
+const fn build_keys() -> ZobristKeys {
+    let mut keys = ZobristKeys {
+        hands: [0; CARD_BITS],
+        field: [0; CARD_BITS],
+        kinds: [0; 3],
+        binds: [0; 4],
+        rev: 0,
+        counts: [[0; MAX_COUNT + 1]; PLAYERS_COUNT],
+        turns: [0; PLAYERS_COUNT],
+        pass: 0,
+    };
+    let mut state = 0x6461_6966_7567_6f00;
+    let mut i = 0;
+    while i < CARD_BITS {
+        (state, keys.hands[i]) = splitmix64(state);
+        (state, keys.field[i]) = splitmix64(state);
+        i += 1;
+    }
+    i = 0;
+    while i < 4 {
+        (state, keys.binds[i]) = splitmix64(state);
+        if i < 3 {
+            (state, keys.kinds[i]) = splitmix64(state);
+        }
+        i += 1;
+    }
+    i = 0;
+    while i < PLAYERS_COUNT {
+        (state, keys.turns[i]) = splitmix64(state);
+        let mut count = 0;
+        while count <= MAX_COUNT {
+            (state, keys.counts[i][count]) = splitmix64(state);
+            count += 1;
+        }
+        i += 1;
+    }
+    (state, keys.rev) = splitmix64(state);
+    (_, keys.pass) = splitmix64(state);
+    keys
+}
+
+fn hash_mask(keys: &[u64; CARD_BITS], mask: &HandMask) -> u64 {
+    let mut bits = mask.get_bits();
+    let mut hash = 0;
+    while bits != 0 {
+        hash ^= keys[bits.trailing_zeros() as usize];
+        bits &= bits - 1;
+    }
+    hash
+}
+
+fn hash_comb(comb: Option<&Comb>) -> u64 {
+    match comb {
+        Some(Comb::Single(card)) => {
+            KEYS.kinds[0] ^ hash_mask(&KEYS.field, &HandMask::from(&[*card][..]))
+        }
+        Some(Comb::Multi(cards)) => {
+            KEYS.kinds[1] ^ hash_mask(&KEYS.field, &HandMask::from(&cards[..]))
+        }
+        Some(Comb::Seq(cards)) => {
+            KEYS.kinds[2] ^ hash_mask(&KEYS.field, &HandMask::from(&cards[..]))
+        }
+        None => 0,
+    }
+}
+
+fn hash_count(idx: usize, count: usize) -> u64 {
+    KEYS.counts[idx % PLAYERS_COUNT][count.min(MAX_COUNT)]
+}
+
+// 手番のプレイヤーから見た状態(手札、場のカード、縛り、革命、各席の枚数、手番)のハッシュ
+pub fn hash_state(view: &GameView, hands: &[Card]) -> u64 {
+    let mut hash =
+        hash_mask(&KEYS.hands, &HandMask::from(hands)) ^ hash_comb(view.prev_comb.as_ref());
+    let bind_suits = view.bind_suits.as_deref().unwrap_or_default();
+    for (i, suit) in SUITS.iter().enumerate() {
+        if bind_suits.contains(suit) {
+            hash ^= KEYS.binds[i];
+        }
+    }
+    if view.is_rev {
+        hash ^= KEYS.rev;
+    }
+    for (idx, (_, count)) in view.players.iter().enumerate() {
+        hash ^= hash_count(idx, *count);
+    }
+    hash ^ KEYS.turns[view.idx % PLAYERS_COUNT]
+}
+
+// 手番のプレイヤーが手(Noneならパス)を打った後の状態のハッシュを差分で求める
+pub fn hash_move(hash: u64, view: &GameView, hands: &[Card], comb: Option<&Comb>) -> u64 {
+    let Some(comb) = comb else {
+        return hash ^ KEYS.pass;
+    };
+    let cards = match comb {
+        Comb::Single(card) => HandMask::from(&[*card][..]),
+        Comb::Multi(cards) | Comb::Seq(cards) => HandMask::from(&cards[..]),
+    };
+    let count = hands.len();
+    let mut hash = hash
+        ^ hash_mask(&KEYS.hands, &cards)
+        ^ hash_comb(view.prev_comb.as_ref())
+        ^ hash_comb(Some(comb))
+        ^ hash_count(view.idx, count)
+        ^ hash_count(view.idx, count - cards.count());
+    // 4枚以上の同じ数字で革命が起こる
+    if view.rules.revolution && matches!(comb, Comb::Multi(cards) if cards.len() >= 4) {
+        hash ^= KEYS.rev;
+    }
+    hash
+}
+
+// 状態のハッシュ毎のプレイアウトの回数と報酬の合計
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Entry {
+    pub visits: usize,
+    pub rewards: f64,
+}
+
+// ハッシュの下位のビットで位置を決め、衝突したら新しい評価で上書きする置換表
+pub struct TranspositionTable {
+    entries: Vec<Option<(u64, Entry)>>,
+}
+
+impl TranspositionTable {
+    // sizeは2の累乗に切り上げる(0なら何も記録しない)
+    pub fn new(size: usize) -> Self {
+        let size = match size {
+            0 => 0,
+            size => size.next_power_of_two(),
+        };
+        Self {
+            entries: vec![None; size],
+        }
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, hash: u64) -> Option<Entry> {
+        let slot = self.get_slot(hash)?;
+        match self.entries[slot] {
+            Some((key, entry)) if key == hash => Some(entry),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&mut self, hash: u64, entry: Entry) {
+        if let Some(slot) = self.get_slot(hash) {
+            self.entries[slot] = Some((hash, entry));
+        }
+    }
+
+    fn get_slot(&self, hash: u64) -> Option<usize> {
+        match self.entries.len() {
+            0 => None,
+            len => Some(hash as usize & (len - 1)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{Rank, Suit};
+
+    #[test]
+    fn test_hash_move() {
+        let hands = vec![
+            Card::Normal(Suit::Club, Rank::Four),
+            Card::Normal(Suit::Diamond, Rank::Four),
+            Card::Normal(Suit::Heart, Rank::Four),
+            Card::Normal(Suit::Spade, Rank::Four),
+            Card::Normal(Suit::Spade, Rank::King),
+        ];
+        let view = GameView {
+            idx: 1,
+            prev_comb: Some(Comb::Single(Card::Normal(Suit::Club, Rank::Three))),
+            players: vec![("A".to_owned(), 3), ("B".to_owned(), 5)],
+            ..Default::default()
+        };
+        let hash = hash_state(&view, &hands);
+        for comb in [
+            Comb::Single(Card::Normal(Suit::Spade, Rank::King)),
+            Comb::Multi(hands[..4].to_vec()),
+        ] {
+            // 差分で求めたハッシュは打った後の状態から求めたハッシュと一致する
+            let hands_left = crate::moves::remove_cards(&hands, &comb).unwrap();
+            let after = GameView {
+                prev_comb: Some(comb.clone()),
+                is_rev: matches!(comb, Comb::Multi(_)),
+                players: vec![("A".to_owned(), 3), ("B".to_owned(), hands_left.len())],
+                ..view.clone()
+            };
+            assert_eq!(
+                hash_move(hash, &view, &hands, Some(&comb)),
+                hash_state(&after, &hands_left)
+            );
+        }
+        assert_ne!(hash_move(hash, &view, &hands, None), hash);
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        for (size, expected) in [(0, 0), (5, 8), (16, 16)] {
+            let mut table = TranspositionTable::new(size);
+            assert_eq!(table.get_size(), expected);
+            let entry = Entry {
+                visits: 3,
+                rewards: 1.5,
+            };
+            table.insert(42, entry);
+            assert_eq!(table.get(42), (size > 0).then_some(entry));
+            assert_eq!(table.get(42 + 64), None);
+        }
+    }
+}